@@ -16,11 +16,11 @@ fi
 
 # Run Rust tests first
 echo "🧪 Running Rust tests..."
-cargo test
+cargo test --workspace
 
 # Build WASM module
 echo "🔧 Building WASM module for development..."
-wasm-pack build --target web --dev
+wasm-pack build --target web --out-name parser_core --dev
 
 echo "✅ WASM build complete!"
 
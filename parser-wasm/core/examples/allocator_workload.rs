@@ -0,0 +1,55 @@
+//! Allocation-heavy workload used to compare allocators: schema validation
+//! with many errors, then an edit. Run it once with and once without the
+//! `wee_alloc` feature:
+//!
+//! ```text
+//! cargo run --release -p parser-core --example allocator_workload
+//! cargo run --release -p parser-core --features wee_alloc --example allocator_workload
+//! ```
+
+use parser_core::native::{self, SchemaValidationOptions};
+
+const SCHEMA: &str = r#"{
+    "type": "object",
+    "properties": {
+        "items": {
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "integer" },
+                    "name": { "type": "string", "minLength": 1 },
+                    "tags": { "type": "array", "items": { "type": "string" } }
+                },
+                "required": ["id", "name"]
+            }
+        }
+    }
+}"#;
+
+fn main() {
+    let items: Vec<String> = (0..2_000)
+        .map(|i| format!(r#"{{ "id": "{i}", "name": "", "tags": ["a", "b", {i}] }}"#))
+        .collect();
+    let json = format!(r#"{{ "items": [{}] }}"#, items.join(","));
+    let path = ["items".to_string(), "0".to_string(), "id".to_string()];
+
+    let started = std::time::Instant::now();
+    for _ in 0..5 {
+        let outcome = native::validate_schema(&json, SCHEMA, &SchemaValidationOptions::default())
+            .expect("the schema compiles");
+        assert!(!outcome.valid);
+        // a fresh parse each round, as the first edit of a file would have
+        native::clear_parse_cache();
+        native::update_value("json", &json, &path, "0").expect("the path exists");
+    }
+    println!(
+        "allocator={} elapsed={:?}",
+        if cfg!(feature = "wee_alloc") {
+            "wee_alloc"
+        } else {
+            "system"
+        },
+        started.elapsed()
+    );
+}
@@ -0,0 +1,237 @@
+//! Shared driver for the fuzz targets.
+//!
+//! Runs every native entry point over one input. Failures are expected and
+//! ignored; a panic, which is an unrecoverable trap in the wasm build, is
+//! what libFuzzer reports.
+
+use parser_core::native::{
+    self, CommentSpacing, EnvFile, LookupOptions, MixedContent, Occurrence, OrganizeEnvOptions,
+    UpdateOptions,
+};
+
+const SCHEMA: &str =
+    r##"{"type": "object", "properties": {"a": {"$ref": "#"}}, "required": ["a"]}"##;
+/// Reaches every coercion, through each keyword that types a variable.
+const ENV_SCHEMA: &str = r#"{"properties": {"A": {"type": ["integer", "boolean"]}}, "patternProperties": {"^[B-M]": {"type": "array"}, "_N$": {"type": "number"}}, "additionalProperties": {"anyOf": [{"type": "object"}, {"type": "string"}]}}"#;
+
+/// The first line of `data` is a dotted path (`split_path`), the rest is the
+/// content; a short path keeps the fuzzer's mutations meaningful.
+pub fn exercise(file_type: &str, data: &[u8]) {
+    let _ = native::decode_bytes(data);
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let (path_line, content) = text.split_once('\n').unwrap_or(("", text));
+    let path = native::split_path(path_line);
+    let _ = native::to_pointer(&path);
+    let _ = native::from_pointer(path_line);
+    let options = LookupOptions {
+        mixed_content: match data.len() % 3 {
+            0 => MixedContent::First,
+            1 => MixedContent::Concat,
+            _ => MixedContent::Error,
+        },
+        occurrence: match data.len() % 4 {
+            0 => Occurrence::First,
+            1 => Occurrence::Last,
+            n => Occurrence::Nth(n),
+        },
+        lenient: data.len() % 5 == 0,
+        raw_text: data.len() % 3 == 0,
+    };
+    let update = UpdateOptions {
+        lookup: options,
+        comment_spacing: match data.len() / 3 % 3 {
+            0 => CommentSpacing::Preserve,
+            1 => CommentSpacing::Align,
+            _ => CommentSpacing::Single,
+        },
+        skip_validation: data.len() % 2 == 0,
+    };
+
+    let _ = native::validate(file_type, content);
+    let _ = native::validate_multi(file_type, content, Some(10));
+    let _ = native::find_value_spans(file_type, content, std::slice::from_ref(&path));
+    let _ = native::find_value_span_with(file_type, content, &path, &options);
+    let _ = native::get_value_with(file_type, content, &path, true, &options);
+    if let Ok(updated) =
+        native::update_value_with(file_type, content, &path, "v\"<&]]>\n$x", &update)
+    {
+        let keys = native::DiffOptions {
+            key_attributes: vec!["id".into(), path_line.into()],
+        };
+        let _ = native::diff_with(file_type, content, &updated, &keys);
+    }
+    let _ = native::diff(file_type, path_line, content);
+    let _ = native::semantic_hash(file_type, content);
+    for ignore_key_order in [false, true] {
+        let opts = native::EqualsOptions { ignore_key_order };
+        let _ = native::equals(file_type, content, content, &opts);
+        let _ = native::equals(file_type, path_line, content, &opts);
+    }
+    let braces = native::TemplateOptions {
+        syntax: native::PlaceholderSyntax::Braces,
+    };
+    for opts in [native::TemplateOptions::default(), braces] {
+        if let Ok(found) = native::list_placeholders(file_type, content, &opts) {
+            let variables = found
+                .into_iter()
+                .map(|placeholder| (placeholder.name, path_line.to_string()))
+                .collect();
+            let _ = native::render(file_type, content, &variables, &opts);
+        }
+    }
+    // Debug quoting is close enough to JSON; a mismatch only rejects the script
+    let step_path = format!("{path_line:?}");
+    let script = format!(
+        r#"[{{"op":"changeType","path":{step_path},"type":"string"}},{{"op":"move","from":{step_path},"to":["moved"]}},{{"op":"setDefault","path":{step_path},"value":{{"a":[1]}}}},{{"op":"rename","path":{step_path},"to":"R"}},{{"op":"delete","path":{step_path}}}]"#
+    );
+    let _ = native::migrate(file_type, content, &script);
+    let environment = |env: &str, content: &str| native::EnvironmentFile {
+        env: env.into(),
+        content: content.into(),
+    };
+    let _ = native::env_matrix(
+        file_type,
+        &[environment("a", content), environment("b", path_line)],
+    );
+    let copy = |name: &str| native::ConfigFile {
+        name: name.into(),
+        file_type: file_type.into(),
+        content: content.into(),
+    };
+    let _ = native::find_duplicates(&[copy("a"), copy("b")]);
+    let all_rules = native::LintConfig {
+        key_naming: Some((native::Severity::Warning, native::KeyCase::Snake)),
+        max_line_length: Some((native::Severity::Warning, 8)),
+        header_comment: Some((native::Severity::Error, Some("x".into()))),
+        no_tabs: Some(native::Severity::Warning),
+        no_empty_values: Some(native::Severity::Error),
+        eol_last: Some(native::Severity::Warning),
+        no_trailing_spaces: Some(native::Severity::Warning),
+        no_mixed_spaces_and_tabs: Some((native::Severity::Warning, 3)),
+        sort_keys: Some((native::Severity::Warning, native::KeyOrder::Alphabetical)),
+        no_boolean_strings: None,
+    };
+    let _ = native::lint(file_type, content, &all_rules);
+    if let Ok(found) = native::scan_secrets(file_type, content) {
+        let paths: Vec<_> = found.into_iter().map(|finding| finding.path).collect();
+        let _ = native::redact(file_type, content, &paths, native::REDACTED);
+    }
+    let reverse = |item: &native::SensitiveValue| Ok(item.value.chars().rev().collect());
+    if let Ok(encrypted) =
+        native::encrypt_values(file_type, content, std::slice::from_ref(&path), reverse)
+    {
+        let _ = native::decrypt_values(file_type, &encrypted, reverse);
+    }
+    for to_type in ["json", "xml", "env"] {
+        let opts = native::ConvertOptions {
+            infer_types: data.len() % 2 == 0,
+            ..Default::default()
+        };
+        if let Ok(converted) = native::convert(file_type, to_type, content, &opts) {
+            let _ = native::convert(to_type, file_type, &converted, &opts);
+        }
+    }
+    for unit in [native::ColumnUnit::Utf16, native::ColumnUnit::Byte] {
+        if let Ok(mut result) = native::validate_multi(file_type, content, Some(10)) {
+            native::apply_column_unit(&mut result, content, unit);
+            let _ = result.group_by_line();
+        }
+    }
+    let offset = data.len() % (content.len() + 1);
+    if let Ok(Some(found)) = native::path_at_offset(file_type, content, offset) {
+        let ancestors = found.ancestors.iter().map(|a| (&a.path, a.occurrence));
+        for (path, occurrence) in ancestors.chain([(&found.path, found.occurrence)]) {
+            let lookup = LookupOptions {
+                occurrence: occurrence.map_or(Occurrence::First, Occurrence::Nth),
+                ..options
+            };
+            let _ = native::find_value_span_with(file_type, content, path, &lookup);
+        }
+    }
+    let _ = native::fuzzy_find(file_type, content, path_line);
+    match file_type {
+        "json" => {
+            let _ = native::format(file_type, content);
+            let _ = native::overlay(
+                content,
+                &[path_line.into(), content.into()],
+                &native::OverlayOptions {
+                    arrays: native::ArrayStrategy::MergeByKey("id".into()),
+                },
+            );
+            let _ = native::suggest_keys(content, &path);
+            if let Ok(value) = serde_json::from_str(path_line) {
+                let _ = native::find_item(content, &path, "id", &value);
+            }
+            let _ = native::validate_schema(content, SCHEMA, &Default::default());
+            if native::register_schema("fuzz", content).is_ok() {
+                let _ = native::unused_keys(content, "fuzz");
+                let _ = native::schema_docs_markdown("fuzz");
+                for file_type in ["json", "xml", "env"] {
+                    let opts = native::InstantiateOptions {
+                        file_type: file_type.into(),
+                        include_optional: true,
+                        ..Default::default()
+                    };
+                    let _ = native::instantiate_schema("fuzz", &opts);
+                }
+            }
+        }
+        "xml" => {
+            let _ = native::xml_instructions(content);
+            let _ = native::xml_comments(content, &path);
+            let _ = native::xml_attributes(content, &path);
+        }
+        _ => {
+            if let Ok(resolved) = native::resolve_env(content, true) {
+                if let Some(entry) = resolved.entries.first() {
+                    let others = [EnvFile {
+                        name: ".env.local".into(),
+                        content: content.into(),
+                    }];
+                    let _ = native::rename_env_key(content, &entry.key, "RENAMED", &others);
+                }
+            }
+            let _ = native::organize_env(content, &OrganizeEnvOptions::default());
+            let _ = native::validate_env_schema(content, ENV_SCHEMA, &Default::default());
+            let _ = native::merge_env(&[
+                EnvFile {
+                    name: ".env".into(),
+                    content: content.into(),
+                },
+                EnvFile {
+                    name: ".env.local".into(),
+                    content: path_line.into(),
+                },
+            ]);
+        }
+    }
+
+    // the handle: streamed in, then queried and edited
+    let Ok(mut doc) = native::Document::new(file_type, "") else {
+        return;
+    };
+    let mut rest = content;
+    while !rest.is_empty() {
+        let mut cut = rest.len().min(1 + data.len() % 16);
+        while !rest.is_char_boundary(cut) {
+            cut += 1;
+        }
+        let _ = doc.feed(&rest[..cut]);
+        rest = &rest[cut..];
+    }
+    let _ = doc.finish();
+    let _ = doc.next_tokens(8);
+    let _ = doc.find_value_span_with(&path, &options);
+    let before = doc.snapshot();
+    let _ = doc.update_value_with(&path, "1", &update);
+    let _ = doc.diff_since(before);
+    let _ = doc.restore(before);
+    let _ = doc.update_value_with(&path, "1", &update);
+    let len = doc.content().len();
+    let (line, column) = doc.offset_to_position(len / 2);
+    let _ = doc.position_to_offset(line, column + 1);
+    let _ = doc.position_to_offset(usize::MAX, usize::MAX);
+}
@@ -0,0 +1,89 @@
+//! Array items looked up by a field.
+//!
+//! Lists of objects are usually edited by identity (the service named
+//! `web`, the user with id 7), not by position. [`find`] scans the array in
+//! the JSON tree for the first object whose field holds a value, so the
+//! caller gets the item's index and span without exporting the array.
+//! Values compare as `JSON.parse` would read them: strings decoded, numbers
+//! by value (`8080` is `8080.0`), objects regardless of key order.
+
+use serde::Serialize;
+use serde_json::Value;
+use tsify::Tsify;
+
+use crate::json_tree::{JsonNode, JsonValue};
+use crate::semantic_hash::canonical_number;
+use crate::Span;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct FoundItem {
+    pub index: usize,
+    /// The array path with the item's index appended.
+    pub path: Vec<String>,
+    /// The whole item, braces included.
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+/// The first object item of the array at `array_path` whose `field` equals
+/// `value`; a repeated field is read at its first occurrence.
+pub(crate) fn find(
+    content: &str,
+    root: &JsonNode,
+    array_path: &[String],
+    field: &str,
+    value: &Value,
+) -> Result<Option<FoundItem>, String> {
+    let mut array = root;
+    for (i, segment) in array_path.iter().enumerate() {
+        array = array
+            .child(segment)
+            .map(|(_, next)| next)
+            .ok_or_else(|| format!("Path not found: {}", array_path[..=i].join("/")))?;
+    }
+    let JsonValue::Array(items) = &array.value else {
+        return Err(format!(
+            "Path does not resolve to an array: {}",
+            array_path.join("/")
+        ));
+    };
+    let found = items.iter().position(|item| {
+        let JsonValue::Object(members) = &item.value else {
+            return false;
+        };
+        members
+            .iter()
+            .find(|member| member.name == field)
+            .is_some_and(|member| {
+                let span = member.value.span;
+                serde_json::from_str::<Value>(&content[span.start..span.end])
+                    .is_ok_and(|held| same_value(&held, value))
+            })
+    });
+    Ok(found.map(|index| {
+        let mut path = array_path.to_vec();
+        path.push(index.to_string());
+        FoundItem {
+            index,
+            path,
+            span: items[index].span,
+        }
+    }))
+}
+
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => {
+            canonical_number(&x.to_string()) == canonical_number(&y.to_string())
+        }
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| same_value(x, y))
+        }
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .all(|(key, x)| y.get(key).is_some_and(|y| same_value(x, y)))
+        }
+        _ => a == b,
+    }
+}
@@ -0,0 +1,271 @@
+//! `konficurator-cli`: the editor's checks and edits for files on disk.
+//!
+//! Runs natively or under a WASI runtime (`--target wasm32-wasip1`, with the
+//! working directory preopened). Exit status is 0 when every file passed, 1
+//! when a file is invalid or could not be read, edited or written, and 2 for
+//! usage errors.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use parser_core::native::{
+    self, DecodedText, DetailedError, MultiValidationResult, ValidationResult,
+};
+use serde::Serialize;
+
+const USAGE: &str = "\
+Usage: konficurator-cli <command> [options] <file>...
+
+Commands:
+  validate <file>...              Report the first syntax error per file
+  validate-multi <file>...        Report up to --max-errors errors per file
+  update <file> <path> <value>    Replace one value, keeping every other byte
+  format <file>...                Pretty-print JSON files
+
+Options:
+  --type <json|xml|config|env>    File type (default: from the file name)
+  --max-errors <n>                Error cap for validate-multi (default 3, max 10)
+  --write                         update/format: rewrite the file in place
+  --json                          Print results as JSON, one object per file
+  -h, --help                      Show this help
+
+Paths are dot-separated (`server.port`, `items.0`, `a\\.b.c` for a dot
+inside a key) or a JSON array of segments (`[\"a.b\", \"c\"]`).";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Validate,
+    ValidateMulti,
+    Update,
+    Format,
+}
+
+#[derive(Debug)]
+struct Args {
+    command: Command,
+    file_type: Option<String>,
+    max_errors: Option<u32>,
+    write: bool,
+    json: bool,
+    operands: Vec<String>,
+}
+
+fn parse_args(mut raw: impl Iterator<Item = String>) -> Result<Args, String> {
+    let command = match raw.next().as_deref() {
+        Some("validate") => Command::Validate,
+        Some("validate-multi") => Command::ValidateMulti,
+        Some("update") => Command::Update,
+        Some("format") => Command::Format,
+        Some(other) => return Err(format!("unknown command '{other}'")),
+        None => return Err("missing command".into()),
+    };
+    let mut args = Args {
+        command,
+        file_type: None,
+        max_errors: None,
+        write: false,
+        json: false,
+        operands: Vec::new(),
+    };
+    while let Some(arg) = raw.next() {
+        match arg.as_str() {
+            "--type" => args.file_type = Some(raw.next().ok_or("--type needs a value")?),
+            "--max-errors" => {
+                let value = raw.next().ok_or("--max-errors needs a value")?;
+                let cap = value
+                    .parse()
+                    .map_err(|_| format!("invalid --max-errors '{value}'"))?;
+                args.max_errors = Some(cap);
+            }
+            "--write" => args.write = true,
+            "--json" => args.json = true,
+            "--" => args.operands.extend(raw.by_ref()),
+            flag if flag.starts_with("--") => return Err(format!("unknown option '{flag}'")),
+            _ => args.operands.push(arg),
+        }
+    }
+    let expected = match args.command {
+        Command::Update => args.operands.len() == 3,
+        _ => !args.operands.is_empty(),
+    };
+    if !expected {
+        return Err("wrong number of arguments".into());
+    }
+    Ok(args)
+}
+
+/// File type from `--type` or the file name: `.json`, `.xml`, `.config`,
+/// and `.env` / `.env.*`.
+fn file_type_for(args: &Args, file: &str) -> Result<String, String> {
+    if let Some(ty) = &args.file_type {
+        return Ok(ty.clone());
+    }
+    let name = Path::new(file)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(file);
+    if name == ".env" || name.starts_with(".env.") || name.ends_with(".env") {
+        return Ok("env".into());
+    }
+    match Path::new(name).extension().and_then(|ext| ext.to_str()) {
+        Some("json") => Ok("json".into()),
+        Some("xml") | Some("config") => Ok("xml".into()),
+        _ => Err("cannot infer the file type, pass --type".into()),
+    }
+}
+
+/// Accepts `a.b.0` (`\.` for a dot inside a key) or a JSON array of strings.
+fn parse_path(raw: &str) -> Result<Vec<String>, String> {
+    if raw.trim_start().starts_with('[') {
+        return serde_json::from_str(raw).map_err(|e| format!("invalid path '{raw}': {e}"));
+    }
+    Ok(native::split_path(raw))
+}
+
+#[derive(Serialize)]
+struct FileReport<'a, T: Serialize> {
+    file: &'a str,
+    #[serde(flatten)]
+    result: T,
+}
+
+/// What `update` and `format` did to a file: its new content, or with
+/// `--write` whether it was rewritten.
+#[derive(Serialize)]
+struct Edit<'a> {
+    changed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    written: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct FileError<'a> {
+    file: &'a str,
+    error: String,
+}
+
+fn print_error(args: &Args, file: &str, error: String) {
+    if args.json {
+        println!("{}", to_json(&FileError { file, error }));
+    } else {
+        eprintln!("{file}: {error}");
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn print_detailed(file: &str, error: &DetailedError) {
+    match error.code {
+        Some(code) => println!(
+            "{file}:{}:{}: {} [{code}]",
+            error.line, error.column, error.message
+        ),
+        None => println!("{file}:{}:{}: {}", error.line, error.column, error.message),
+    }
+}
+
+fn validate(args: &Args, file: &str, ty: &str, content: &str) -> Result<bool, String> {
+    let result: ValidationResult = native::validate(ty, content).map_err(|e| e.to_string())?;
+    let valid = result.valid;
+    if args.json {
+        println!("{}", to_json(&FileReport { file, result }));
+    } else if let Some(message) = &result.message {
+        match (result.line, result.column) {
+            (Some(line), Some(column)) => println!("{file}:{line}:{column}: {message}"),
+            _ => println!("{file}: {message}"),
+        }
+    }
+    Ok(valid)
+}
+
+fn validate_multi(args: &Args, file: &str, ty: &str, content: &str) -> Result<bool, String> {
+    let result: MultiValidationResult =
+        native::validate_multi(ty, content, args.max_errors).map_err(|e| e.to_string())?;
+    let valid = result.valid;
+    if args.json {
+        println!("{}", to_json(&FileReport { file, result }));
+    } else {
+        for error in &result.errors {
+            print_detailed(file, error);
+        }
+    }
+    Ok(valid)
+}
+
+/// Prints the new content, or writes it back with `--write` in the encoding
+/// the file was read in.
+fn emit(args: &Args, file: &str, read: &DecodedText, updated: String) -> Result<bool, String> {
+    let changed = updated != read.content;
+    if args.write && changed {
+        let bytes = native::encode_text(&updated, read.encoding, read.bom);
+        std::fs::write(file, bytes).map_err(|e| format!("cannot write: {e}"))?;
+    }
+    if args.json {
+        let result = Edit {
+            changed,
+            written: args.write.then_some(changed),
+            content: (!args.write).then_some(updated.as_str()),
+        };
+        println!("{}", to_json(&FileReport { file, result }));
+    } else if !args.write {
+        print!("{updated}");
+    }
+    Ok(true)
+}
+
+fn run_file(args: &Args, file: &str) -> Result<bool, String> {
+    let ty = file_type_for(args, file)?;
+    let bytes = std::fs::read(file).map_err(|e| format!("cannot read: {e}"))?;
+    let read = native::decode_bytes(&bytes).map_err(|e| format!("cannot read: {e}"))?;
+    let content = read.content.as_str();
+    match args.command {
+        Command::Validate => validate(args, file, &ty, content),
+        Command::ValidateMulti => validate_multi(args, file, &ty, content),
+        Command::Update => {
+            let path = parse_path(&args.operands[1])?;
+            let updated = native::update_value(&ty, content, &path, &args.operands[2])
+                .map_err(|e| e.to_string())?;
+            emit(args, file, &read, updated)
+        }
+        Command::Format => {
+            let formatted = native::format(&ty, content).map_err(|e| e.to_string())?;
+            emit(args, file, &read, formatted)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let mut raw = std::env::args().skip(1).peekable();
+    if matches!(raw.peek().map(String::as_str), None | Some("-h" | "--help")) {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    let args = match parse_args(raw) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("konficurator-cli: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let files: &[String] = match args.command {
+        Command::Update => &args.operands[..1],
+        _ => &args.operands,
+    };
+
+    let mut status = ExitCode::SUCCESS;
+    for file in files {
+        match run_file(&args, file) {
+            Ok(true) => {}
+            Ok(false) => status = ExitCode::from(1),
+            Err(e) => {
+                print_error(&args, file, e);
+                status = ExitCode::from(1);
+            }
+        }
+    }
+    status
+}
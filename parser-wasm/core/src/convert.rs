@@ -0,0 +1,501 @@
+//! Conversion between formats.
+//!
+//! Every format is read into one neutral [`Value`] and written back out, so
+//! each pair of formats goes through the same two steps. The mapping is
+//! fixed, so converting the same content always gives the same output:
+//!
+//! - JSON ↔ XML: an object member is a child element, an array repeats its
+//!   element, `@name` keys are attributes and `#text` is the text next to
+//!   child elements. A self-closing element with no attributes is `null`.
+//! - JSON ↔ ENV: nested keys are joined with a separator (`SERVER__PORT`)
+//!   and array items use their index (`HOSTS__0`).
+//!
+//! Output is built fresh: nothing of the source's layout carries over.
+
+use std::collections::HashMap;
+
+#[cfg(feature = "env")]
+use crate::env_parser::EnvDocument;
+use crate::escape_json_string;
+use crate::json_lexer::Kind;
+use crate::json_parser::decode_json_string;
+#[cfg(feature = "env")]
+use crate::json_tree::MAX_TREE_DEPTH;
+use crate::json_tree::{JsonNode, JsonValue};
+#[cfg(feature = "xml")]
+use crate::xml_tree::XmlElement;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvertOptions {
+    /// Joins nested keys into ENV names, and splits them when reading ENV.
+    /// An empty separator keeps every ENV key at the top level.
+    pub separator: String,
+    /// Root element for JSON that is not an object with a single key.
+    pub root: String,
+    /// Read ENV values and XML text that spell a JSON number or boolean as
+    /// one; otherwise they stay strings.
+    pub infer_types: bool,
+    /// Upper-case ENV names when writing ENV (`SERVER__PORT`).
+    pub uppercase: bool,
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        Self {
+            separator: "__".into(),
+            root: "root".into(),
+            infer_types: false,
+            uppercase: false,
+        }
+    }
+}
+
+/// A format-neutral value: what each reader produces and each writer takes.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    /// The number's JSON text, kept as written.
+    Number(String),
+    String(String),
+    Array(Vec<Value>),
+    /// Members in document order, each key once.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// `text` as a string, or with `infer` as the number or boolean it spells.
+    #[cfg(any(feature = "xml", feature = "env"))]
+    fn scalar(text: &str, infer: bool) -> Self {
+        if infer {
+            match text {
+                "true" => return Value::Bool(true),
+                "false" => return Value::Bool(false),
+                _ => {}
+            }
+            if text.trim() == text
+                && matches!(
+                    serde_json::from_str::<serde_json::Value>(text),
+                    Ok(serde_json::Value::Number(_))
+                )
+            {
+                return Value::Number(text.to_string());
+            }
+        }
+        Value::String(text.to_string())
+    }
+
+    /// A scalar's text as written into XML or ENV; containers have none.
+    fn text(&self) -> Option<&str> {
+        match self {
+            Value::Null => Some(""),
+            Value::Bool(true) => Some("true"),
+            Value::Bool(false) => Some("false"),
+            Value::Number(text) | Value::String(text) => Some(text),
+            Value::Array(_) | Value::Object(_) => None,
+        }
+    }
+}
+
+/// Collects object members; a repeated key keeps its first position and its
+/// last value, as `JSON.parse` does.
+#[derive(Default)]
+struct Members {
+    members: Vec<(String, Value)>,
+    index: HashMap<String, usize>,
+}
+
+impl Members {
+    fn insert(&mut self, key: String, value: Value) {
+        match self.index.get(&key) {
+            Some(&i) => self.members[i].1 = value,
+            None => {
+                self.index.insert(key.clone(), self.members.len());
+                self.members.push((key, value));
+            }
+        }
+    }
+
+    fn finish(self) -> Value {
+        Value::Object(self.members)
+    }
+}
+
+// ───────────────────────── JSON ─────────────────────────
+
+pub(crate) fn from_json(content: &str, node: &JsonNode) -> Value {
+    match &node.value {
+        JsonValue::Object(members) => {
+            let mut out = Members::default();
+            for member in members {
+                out.insert(member.name.clone(), from_json(content, &member.value));
+            }
+            out.finish()
+        }
+        JsonValue::Array(items) => {
+            Value::Array(items.iter().map(|item| from_json(content, item)).collect())
+        }
+        JsonValue::Scalar(kind) => {
+            let raw = &content[node.span.start..node.span.end];
+            match kind {
+                Kind::StringLit => {
+                    Value::String(decode_json_string(raw).unwrap_or_else(|| raw.to_string()))
+                }
+                Kind::True => Value::Bool(true),
+                Kind::False => Value::Bool(false),
+                Kind::Null => Value::Null,
+                _ => Value::Number(raw.to_string()),
+            }
+        }
+    }
+}
+
+/// Pretty-printed with two-space indentation and a trailing newline.
+pub(crate) fn to_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_json(&mut out, value, 0);
+    out.push('\n');
+    out
+}
+
+fn write_json(out: &mut String, value: &Value, depth: usize) {
+    let newline = |out: &mut String, depth: usize| {
+        out.push('\n');
+        out.push_str(&"  ".repeat(depth));
+    };
+    match value {
+        Value::Array(items) if !items.is_empty() => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, depth + 1);
+                write_json(out, item, depth + 1);
+            }
+            newline(out, depth);
+            out.push(']');
+        }
+        Value::Object(members) if !members.is_empty() => {
+            out.push('{');
+            for (i, (key, value)) in members.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, depth + 1);
+                out.push_str(&format!("\"{}\": ", escape_json_string(key)));
+                write_json(out, value, depth + 1);
+            }
+            newline(out, depth);
+            out.push('}');
+        }
+        Value::Array(_) => out.push_str("[]"),
+        Value::Object(_) => out.push_str("{}"),
+        Value::Null => out.push_str("null"),
+        Value::String(text) => out.push_str(&format!("\"{}\"", escape_json_string(text))),
+        Value::Bool(_) | Value::Number(_) => out.push_str(value.text().unwrap_or_default()),
+    }
+}
+
+// ───────────────────────── XML ─────────────────────────
+
+/// `{ root-name: value }` for the root element.
+#[cfg(feature = "xml")]
+pub(crate) fn from_xml(content: &str, root: &XmlElement, infer: bool) -> Value {
+    Value::Object(vec![(
+        root.name.clone(),
+        element_value(content, root, infer),
+    )])
+}
+
+#[cfg(feature = "xml")]
+fn element_value(content: &str, element: &XmlElement, infer: bool) -> Value {
+    if element.attributes.is_empty() && element.children.is_empty() {
+        if !element.text.is_empty() {
+            return Value::scalar(&element.text_value(), infer);
+        }
+        let self_closing = content[element.span.start..element.span.end].ends_with("/>");
+        return if self_closing {
+            Value::Null
+        } else {
+            Value::String(String::new())
+        };
+    }
+    let mut members = Members::default();
+    for attr in &element.attributes {
+        members.insert(format!("@{}", attr.name), Value::scalar(&attr.value, infer));
+    }
+    if !element.text.is_empty() {
+        members.insert("#text".into(), Value::scalar(&element.text_value(), infer));
+    }
+    // children grouped by name, in order of first appearance
+    let mut groups: Vec<(&str, Vec<Value>)> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for child in &element.children {
+        let value = element_value(content, child, infer);
+        match index.get(child.name.as_str()) {
+            Some(&i) => groups[i].1.push(value),
+            None => {
+                index.insert(&child.name, groups.len());
+                groups.push((&child.name, vec![value]));
+            }
+        }
+    }
+    for (name, mut values) in groups {
+        let value = if values.len() == 1 {
+            values.pop().unwrap_or(Value::Null)
+        } else {
+            Value::Array(values)
+        };
+        members.insert(name.to_string(), value);
+    }
+    members.finish()
+}
+
+/// An XML document with a declaration and two-space indentation. An object
+/// with a single key names the root element; anything else is wrapped in
+/// `root`.
+#[cfg(feature = "xml")]
+pub(crate) fn to_xml(value: &Value, root: &str) -> Result<String, String> {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    match value {
+        Value::Object(members) if members.len() == 1 && !is_special(&members[0].0) => {
+            let (name, value) = &members[0];
+            match value {
+                Value::Array(_) => {
+                    write_element(&mut out, root, &Value::Object(members.clone()), 0)?
+                }
+                _ => write_element(&mut out, name, value, 0)?,
+            }
+        }
+        _ => write_element(&mut out, root, value, 0)?,
+    }
+    Ok(out)
+}
+
+/// `@name` attributes and `#text`, which are not child elements.
+#[cfg(feature = "xml")]
+fn is_special(key: &str) -> bool {
+    key.starts_with('@') || key == "#text"
+}
+
+#[cfg(feature = "xml")]
+fn write_element(out: &mut String, name: &str, value: &Value, depth: usize) -> Result<(), String> {
+    use crate::escape_xml_string;
+
+    if !is_xml_name(name) {
+        return Err(format!("'{name}' is not a valid XML element name"));
+    }
+    let indent = "  ".repeat(depth);
+    let mut attributes = String::new();
+    let mut text = None;
+    let mut children: Vec<(&str, &Value)> = Vec::new();
+    match value {
+        Value::Object(members) => {
+            for (key, value) in members {
+                if let Some(attr) = key.strip_prefix('@') {
+                    if !is_xml_name(attr) {
+                        return Err(format!("'{attr}' is not a valid XML attribute name"));
+                    }
+                    let value = value
+                        .text()
+                        .ok_or_else(|| format!("Attribute '{key}' must be a scalar"))?;
+                    attributes.push_str(&format!(" {attr}=\"{}\"", escape_xml_string(value)));
+                } else if key == "#text" {
+                    text = Some(value.text().ok_or("'#text' must be a scalar")?);
+                } else {
+                    children.push((key, value));
+                }
+            }
+        }
+        // an array nested in an array has no name of its own
+        Value::Array(items) => children.extend(items.iter().map(|item| ("item", item))),
+        Value::Null => {
+            out.push_str(&format!("{indent}<{name}/>\n"));
+            return Ok(());
+        }
+        scalar => text = scalar.text(),
+    }
+    let text = text.map(escape_xml_string).unwrap_or_default();
+    if children.is_empty() {
+        out.push_str(&format!("{indent}<{name}{attributes}>{text}</{name}>\n"));
+        return Ok(());
+    }
+    out.push_str(&format!("{indent}<{name}{attributes}>\n"));
+    if !text.is_empty() {
+        out.push_str(&format!("{indent}  {text}\n"));
+    }
+    for (key, value) in children {
+        match value {
+            Value::Array(items) => {
+                for item in items {
+                    write_element(out, key, item, depth + 1)?;
+                }
+            }
+            _ => write_element(out, key, value, depth + 1)?,
+        }
+    }
+    out.push_str(&format!("{indent}</{name}>\n"));
+    Ok(())
+}
+
+/// A letter or `_`, then letters, digits, `-`, `_`, `.` or a namespace `:`.
+#[cfg(feature = "xml")]
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+// ───────────────────────── ENV ─────────────────────────
+
+/// The nested object `separator`-joined keys describe. Objects whose keys
+/// are exactly `0`..`n` become arrays.
+#[cfg(feature = "env")]
+pub(crate) fn from_env(
+    content: &str,
+    doc: &EnvDocument,
+    separator: &str,
+    infer: bool,
+) -> Result<Value, String> {
+    let mut root = Node::default();
+    for entry in doc.entries() {
+        let raw = &content[entry.value_span.start..entry.value_span.end];
+        let value = Value::scalar(&crate::env_parser::decode_value(raw), infer);
+        let segments: Vec<&str> = if separator.is_empty() {
+            vec![entry.key.as_str()]
+        } else {
+            entry.key.split(separator).collect()
+        };
+        if segments.len() > MAX_TREE_DEPTH {
+            return Err(format!("Keys nest deeper than {MAX_TREE_DEPTH} levels"));
+        }
+        let conflict = || format!("ENV key '{}' conflicts with another key", entry.key);
+        let mut node = &mut root;
+        for segment in &segments {
+            if node.value.is_some() {
+                return Err(conflict());
+            }
+            node = node.child(segment);
+        }
+        if node.value.is_some() || !node.children.is_empty() {
+            return Err(conflict());
+        }
+        node.value = Some(value);
+    }
+    Ok(root.finish())
+}
+
+#[cfg(feature = "env")]
+#[derive(Default)]
+struct Node {
+    value: Option<Value>,
+    children: Vec<(String, Node)>,
+    index: HashMap<String, usize>,
+}
+
+#[cfg(feature = "env")]
+impl Node {
+    fn child(&mut self, key: &str) -> &mut Node {
+        let i = match self.index.get(key) {
+            Some(&i) => i,
+            None => {
+                self.index.insert(key.to_string(), self.children.len());
+                self.children.push((key.to_string(), Node::default()));
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[i].1
+    }
+
+    fn finish(self) -> Value {
+        if let Some(value) = self.value {
+            return value;
+        }
+        let is_array = !self.children.is_empty()
+            && (0..self.children.len()).all(|i| self.index.contains_key(&i.to_string()));
+        if is_array {
+            let mut items: Vec<Option<Value>> = vec![None; self.children.len()];
+            for (key, child) in self.children {
+                if let Ok(i) = key.parse::<usize>() {
+                    items[i] = Some(child.finish());
+                }
+            }
+            return Value::Array(items.into_iter().flatten().collect());
+        }
+        Value::Object(
+            self.children
+                .into_iter()
+                .map(|(key, child)| (key, child.finish()))
+                .collect(),
+        )
+    }
+}
+
+/// `KEY=value` lines, nested keys joined with `opts.separator`, in
+/// document order with array items by index, so the same input always
+/// gives the same lines. Values are quoted as `update_value` would write
+/// them.
+#[cfg(feature = "env")]
+pub(crate) fn to_env(value: &Value, opts: &ConvertOptions) -> Result<String, String> {
+    let Value::Object(members) = value else {
+        return Err("ENV output needs an object at the top level".into());
+    };
+    let mut lines = Vec::new();
+    for (key, value) in members {
+        flatten(key.clone(), value, &opts.separator, &mut lines)?;
+    }
+    if opts.uppercase {
+        for (key, _) in &mut lines {
+            *key = key.to_uppercase();
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    let mut out = String::new();
+    let env = crate::document::FileType::Env.format();
+    for (key, text) in lines {
+        if key.is_empty()
+            || key.starts_with('#')
+            || key.contains(|c: char| c.is_whitespace() || c == '=')
+        {
+            return Err(format!("'{key}' cannot be an ENV key"));
+        }
+        if !seen.insert(key.clone()) {
+            return Err(format!(
+                "More than one value flattens to the ENV key '{key}'"
+            ));
+        }
+        out.push_str(&key);
+        out.push('=');
+        out.push_str(&env.format_value(&text));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(feature = "env")]
+fn flatten(
+    key: String,
+    value: &Value,
+    separator: &str,
+    out: &mut Vec<(String, String)>,
+) -> Result<(), String> {
+    match value {
+        Value::Object(members) if !members.is_empty() => {
+            for (child, value) in members {
+                flatten(format!("{key}{separator}{child}"), value, separator, out)?;
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (i, item) in items.iter().enumerate() {
+                flatten(format!("{key}{separator}{i}"), item, separator, out)?;
+            }
+        }
+        Value::Object(_) => out.push((key, "{}".into())),
+        Value::Array(_) => out.push((key, "[]".into())),
+        scalar => out.push((key, scalar.text().unwrap_or_default().to_string())),
+    }
+    Ok(())
+}
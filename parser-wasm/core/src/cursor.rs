@@ -0,0 +1,352 @@
+//! The value under a cursor.
+//!
+//! An editor's hover, breadcrumbs and context actions start from a byte
+//! offset; `native::path_at_offset` turns it into the innermost value around
+//! it, as a path with the spans of its key and value. A value's end counts as inside
+//! it, so a cursor right after `8080` or a closing quote is still on that
+//! value. JSON members run from the key to the end of the value and array
+//! items are matched by index; XML elements run from `<` to the end of the
+//! end tag, attributes from their name to the closing quote, and text
+//! belongs to its element; an ENV entry runs from `export` or the key to
+//! the end of the value. The containers passed on the way down are kept as
+//! the value's ancestors, for breadcrumbs and "select enclosing block".
+//!
+//! Lookups resolve a path to the first value it matches, so a path through
+//! a repeated JSON key or XML element has to say which one it means. A JSON
+//! value gets the `occurrence` a lookup asks for; an XML element gets an
+//! attribute predicate (`item[@id="2"]`) from the first of its attributes
+//! that no earlier match shares, and is flagged `ambiguous` when it has
+//! none.
+
+use serde::Serialize;
+use tsify::Tsify;
+
+#[cfg(feature = "env")]
+use crate::env_parser::EnvDocument;
+use crate::json_tree::{JsonNode, JsonValue};
+use crate::paths::escape_segment;
+#[cfg(feature = "xml")]
+use crate::xml_parser::XmlAttribute;
+#[cfg(feature = "xml")]
+use crate::xml_tree::XmlElement;
+use crate::Span;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+pub struct PathAtOffset {
+    /// Resolves with `find_value_span` to this value, given `occurrence`,
+    /// unless `ambiguous` is set. Lookups do not reach the JSON root, an
+    /// object or array that is an array item, or an XML element without
+    /// text of its own (a root holding only elements, say): their paths
+    /// name them but fail to resolve.
+    pub path: Vec<String>,
+    /// Which match of `path` this is, 0-based in document order, when a
+    /// repeated JSON key makes it match several; pass it as
+    /// `LookupOptions::occurrence` (`Nth`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub occurrence: Option<usize>,
+    /// `path` resolves to an earlier element: one repeated among its
+    /// siblings with no attribute that tells it apart.
+    pub ambiguous: bool,
+    /// The member's key, the element or attribute name, or the variable
+    /// name; absent for an array item and the JSON root.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub key_span: Option<Span>,
+    /// The value as written: a JSON value with its quotes or brackets, an
+    /// attribute value inside its quotes, an element's first text (the
+    /// whole element when it has none), an ENV value with its quotes.
+    pub value_span: Span,
+    /// The objects, arrays and elements around the value, outermost first;
+    /// empty for ENV.
+    pub ancestors: Vec<Ancestor>,
+}
+
+/// A container around the value at an offset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct Ancestor {
+    /// A prefix of the value's path.
+    pub path: Vec<String>,
+    /// As for [`PathAtOffset`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub occurrence: Option<usize>,
+    pub ambiguous: bool,
+    pub kind: AncestorKind,
+    /// Brackets included; `<` to the end of the end tag for an element.
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Tsify)]
+#[serde(rename_all = "lowercase")]
+pub enum AncestorKind {
+    Object,
+    Array,
+    Element,
+}
+
+fn contains(span: Span, offset: usize) -> bool {
+    span.start <= offset && offset <= span.end
+}
+
+pub(crate) fn json_path_at(root: &JsonNode, offset: usize) -> Option<PathAtOffset> {
+    if !contains(root.span, offset) {
+        return None;
+    }
+    let mut found = PathAtOffset {
+        path: Vec::new(),
+        occurrence: None,
+        ambiguous: false,
+        key_span: None,
+        value_span: root.span,
+        ancestors: Vec::new(),
+    };
+    let mut steps = Vec::new();
+    let mut node = root;
+    loop {
+        let (kind, next) = match &node.value {
+            JsonValue::Object(members) => (
+                AncestorKind::Object,
+                members.iter().find_map(|member| {
+                    let span = Span::new(member.key_span.start, member.value.span.end);
+                    contains(span, offset).then_some((
+                        JsonStep::Key(&member.name),
+                        Some(member.key_span),
+                        &member.value,
+                    ))
+                }),
+            ),
+            JsonValue::Array(items) => (
+                AncestorKind::Array,
+                items
+                    .iter()
+                    .enumerate()
+                    .find(|(_, item)| contains(item.span, offset))
+                    .map(|(i, item)| (JsonStep::Index(i), None, item)),
+            ),
+            JsonValue::Scalar(_) => return Some(found),
+        };
+        let Some((step, key_span, child)) = next else {
+            return Some(found);
+        };
+        found.ancestors.push(Ancestor {
+            path: found.path.clone(),
+            occurrence: found.occurrence,
+            ambiguous: false,
+            kind,
+            span: node.span,
+        });
+        found.path.push(match step {
+            JsonStep::Key(name) => escape_segment(name),
+            JsonStep::Index(i) => i.to_string(),
+        });
+        steps.push(step);
+        found.occurrence = json_occurrence(root, &steps, child);
+        found.key_span = key_span;
+        found.value_span = child.span;
+        node = child;
+    }
+}
+
+/// A JSON path segment as the tree names it, key unescaped.
+enum JsonStep<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Which of the values `steps` matches is `target`, when it matches more
+/// than one.
+fn json_occurrence(root: &JsonNode, steps: &[JsonStep], target: &JsonNode) -> Option<usize> {
+    fn collect<'t>(node: &'t JsonNode, steps: &[JsonStep], out: &mut Vec<&'t JsonNode>) {
+        let Some((step, rest)) = steps.split_first() else {
+            out.push(node);
+            return;
+        };
+        match (&node.value, step) {
+            (JsonValue::Object(members), JsonStep::Key(name)) => {
+                for member in members.iter().filter(|member| member.name == *name) {
+                    collect(&member.value, rest, out);
+                }
+            }
+            (JsonValue::Array(items), JsonStep::Index(i)) => {
+                if let Some(item) = items.get(*i) {
+                    collect(item, rest, out);
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut matches = Vec::new();
+    collect(root, steps, &mut matches);
+    if matches.len() < 2 {
+        return None;
+    }
+    matches.iter().position(|node| std::ptr::eq(*node, target))
+}
+
+#[cfg(feature = "xml")]
+pub(crate) fn xml_path_at(root: &XmlElement, offset: usize) -> Option<PathAtOffset> {
+    if !contains(root.span, offset) {
+        return None;
+    }
+    let mut path = vec![root.name.clone()];
+    let mut steps = vec![XmlStep {
+        name: &root.name,
+        predicate: None,
+    }];
+    let mut ambiguous = false;
+    let mut ancestors = Vec::new();
+    let mut element = root;
+    loop {
+        let ancestor = Ancestor {
+            path: path.clone(),
+            occurrence: None,
+            ambiguous,
+            kind: AncestorKind::Element,
+            span: element.span,
+        };
+        // from the name to the closing quote
+        let attribute = element
+            .attributes
+            .iter()
+            .zip(&element.attribute_names)
+            .find(|(attribute, name)| {
+                contains(Span::new(name.start, attribute.span.end + 1), offset)
+            });
+        if let Some((attribute, name)) = attribute {
+            ancestors.push(ancestor);
+            path.push(format!("@{}", attribute.name));
+            return Some(PathAtOffset {
+                path,
+                occurrence: None,
+                ambiguous,
+                key_span: Some(*name),
+                value_span: attribute.span,
+                ancestors,
+            });
+        }
+        match element
+            .children
+            .iter()
+            .find(|child| contains(child.span, offset))
+        {
+            Some(child) => {
+                ancestors.push(ancestor);
+                let (segment, step) = xml_step(root, &mut steps, child);
+                path.push(segment);
+                ambiguous = step.is_none();
+                element = child;
+            }
+            None => {
+                return Some(PathAtOffset {
+                    path,
+                    occurrence: None,
+                    ambiguous,
+                    key_span: Some(element.name_span),
+                    value_span: element.value_span(),
+                    ancestors,
+                })
+            }
+        }
+    }
+}
+
+/// An XML path step: an element name and at most one attribute predicate.
+#[cfg(feature = "xml")]
+#[derive(Clone, Copy)]
+struct XmlStep<'a> {
+    name: &'a str,
+    predicate: Option<&'a XmlAttribute>,
+}
+
+#[cfg(feature = "xml")]
+impl XmlStep<'_> {
+    fn matches(&self, element: &XmlElement) -> bool {
+        element.name == self.name
+            && self.predicate.is_none_or(|want| {
+                element
+                    .attributes
+                    .iter()
+                    .any(|attr| attr.name == want.name && attr.value == want.value)
+            })
+    }
+}
+
+/// The first element in document order that `steps` match from the root.
+#[cfg(feature = "xml")]
+fn first_xml_match<'t>(element: &'t XmlElement, steps: &[XmlStep]) -> Option<&'t XmlElement> {
+    let (step, rest) = steps.split_first()?;
+    if !step.matches(element) {
+        return None;
+    }
+    if rest.is_empty() {
+        return Some(element);
+    }
+    element
+        .children
+        .iter()
+        .find_map(|child| first_xml_match(child, rest))
+}
+
+/// Pushes the step to `child` and returns its path segment: the plain name
+/// when that already resolves to `child`, else the name with the first
+/// attribute predicate that does. `None` in place of the step means neither
+/// does and the plain name was kept.
+#[cfg(feature = "xml")]
+fn xml_step<'t>(
+    root: &XmlElement,
+    steps: &mut Vec<XmlStep<'t>>,
+    child: &'t XmlElement,
+) -> (String, Option<XmlStep<'t>>) {
+    let plain = XmlStep {
+        name: &child.name,
+        predicate: None,
+    };
+    let candidates = std::iter::once(plain).chain(
+        child
+            .attributes
+            .iter()
+            // a value with both quotes has no predicate form
+            .filter(|attr| !(attr.value.contains('"') && attr.value.contains('\'')))
+            .map(|attr| XmlStep {
+                name: &child.name,
+                predicate: Some(attr),
+            }),
+    );
+    for step in candidates {
+        steps.push(step);
+        if first_xml_match(root, steps).is_some_and(|found| std::ptr::eq(found, child)) {
+            let segment = match step.predicate {
+                Some(attr) => {
+                    let quote = if attr.value.contains('"') { '\'' } else { '"' };
+                    format!(
+                        "{}[@{}={quote}{}{quote}]",
+                        child.name, attr.name, attr.value
+                    )
+                }
+                None => child.name.clone(),
+            };
+            return (segment, Some(step));
+        }
+        steps.pop();
+    }
+    steps.push(plain);
+    (child.name.clone(), None)
+}
+
+#[cfg(feature = "env")]
+pub(crate) fn env_path_at(doc: &EnvDocument, offset: usize) -> Option<PathAtOffset> {
+    doc.entries().iter().find_map(|entry| {
+        let start = entry.export.unwrap_or(entry.key_span).start;
+        contains(Span::new(start, entry.value_span.end), offset).then(|| PathAtOffset {
+            path: vec![escape_segment(&entry.key)],
+            occurrence: None,
+            ambiguous: false,
+            key_span: Some(entry.key_span),
+            value_span: entry.value_span,
+            ancestors: Vec::new(),
+        })
+    })
+}
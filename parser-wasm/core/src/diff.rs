@@ -0,0 +1,545 @@
+//! Structural diff between two versions of a document.
+//!
+//! Reports what a user would call a change, not which bytes moved: JSON
+//! objects are compared key by key and arrays index by index, and values
+//! that differ only in spelling (`1.0` / `1`, `"\u0041"` / `"A"`, whitespace
+//! inside a container) are equal. XML elements are matched by name, or by a
+//! key attribute ([`DiffOptions::key_attributes`]) among repeated siblings,
+//! and compared by attributes, text and children. ENV files are compared
+//! key by key, on the values as the application reads them, with quoting
+//! and `export` changes reported separately. Each entry carries the
+//! value text and span on the side(s) it exists in, so a host can highlight
+//! it in both versions.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use tsify::Tsify;
+
+#[cfg(feature = "env")]
+use crate::env_parser::{self, Entry, EnvDocument};
+use crate::json_lexer::Kind;
+use crate::json_parser::decode_json_string;
+use crate::json_tree::{JsonNode, JsonValue, Member};
+use crate::paths::escape_segment;
+#[cfg(feature = "xml")]
+use crate::xml_tree::XmlElement;
+use crate::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Tsify)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// Only in the new version.
+    Added,
+    /// Only in the old version.
+    Removed,
+    /// In both, with different values.
+    Changed,
+    /// An XML element in both, at another position among its siblings. Its
+    /// content is compared as usual.
+    Moved,
+    /// An ENV value written with other quotes (`"x"`, `'x'`, `x`); reported
+    /// next to `Changed` when the value differs too.
+    Quoting,
+    /// An ENV assignment that gained or lost its `export` prefix. The sides
+    /// cover the whole assignment.
+    Export,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Attributes that identify an XML element among siblings of the same
+    /// name (`id`, `name`), tried in order. Siblings without one are paired
+    /// by position.
+    pub key_attributes: Vec<String>,
+}
+
+/// How `equals` compares two documents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EqualsOptions {
+    /// JSON members, XML sibling elements and ENV entries in another order
+    /// are still equal.
+    pub ignore_key_order: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct Equality {
+    pub equal: bool,
+    /// Where the documents first differ, in the first one's order; a
+    /// reordering points at the first key out of place.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub path: Option<Vec<String>>,
+}
+
+/// A value as it appears in one version: its text as written and its span.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct DiffSide {
+    pub value: String,
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct DiffEntry {
+    pub kind: ChangeKind,
+    /// Resolves with `find_value_span` in whichever version has the value.
+    pub path: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub old: Option<DiffSide>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub new: Option<DiffSide>,
+}
+
+fn entry(
+    kind: ChangeKind,
+    path: &[String],
+    (old, a): (&str, Option<Span>),
+    (new, b): (&str, Option<Span>),
+) -> DiffEntry {
+    let side = |content: &str, span: Span| DiffSide {
+        value: content[span.start..span.end].to_string(),
+        span,
+    };
+    DiffEntry {
+        kind,
+        path: path.to_vec(),
+        old: a.map(|span| side(old, span)),
+        new: b.map(|span| side(new, span)),
+    }
+}
+
+/// Differences from `old` (parsed as `old_tree`) to `new`, in the old
+/// document's order; keys added to an object follow that object's other
+/// entries.
+pub(crate) fn json(
+    old: &str,
+    old_tree: &JsonNode,
+    new: &str,
+    new_tree: &JsonNode,
+) -> Vec<DiffEntry> {
+    let mut diff = JsonDiff {
+        old,
+        new,
+        path: Vec::new(),
+        out: Vec::new(),
+    };
+    diff.node(old_tree, new_tree);
+    diff.out
+}
+
+struct JsonDiff<'a> {
+    old: &'a str,
+    new: &'a str,
+    path: Vec<String>,
+    out: Vec<DiffEntry>,
+}
+
+impl JsonDiff<'_> {
+    fn node(&mut self, a: &JsonNode, b: &JsonNode) {
+        if self.old[a.span.start..a.span.end] == self.new[b.span.start..b.span.end] {
+            return;
+        }
+        match (&a.value, &b.value) {
+            (JsonValue::Object(old), JsonValue::Object(new)) => self.members(old, new),
+            (JsonValue::Array(old), JsonValue::Array(new)) => {
+                for i in 0..old.len().max(new.len()) {
+                    self.path.push(i.to_string());
+                    match (old.get(i), new.get(i)) {
+                        (Some(a), Some(b)) => self.node(a, b),
+                        (Some(a), None) => self.push(ChangeKind::Removed, Some(a), None),
+                        (None, Some(b)) => self.push(ChangeKind::Added, None, Some(b)),
+                        (None, None) => {}
+                    }
+                    self.path.pop();
+                }
+            }
+            (JsonValue::Scalar(ka), JsonValue::Scalar(kb))
+                if same_scalar(
+                    *ka,
+                    &self.old[a.span.start..a.span.end],
+                    *kb,
+                    &self.new[b.span.start..b.span.end],
+                ) => {}
+            _ => self.push(ChangeKind::Changed, Some(a), Some(b)),
+        }
+    }
+
+    /// A repeated key is compared through its first occurrence, the one a
+    /// path resolves to by default.
+    fn members(&mut self, old: &[Member], new: &[Member]) {
+        let mut by_name: HashMap<&str, &JsonNode> = HashMap::new();
+        for member in new {
+            by_name.entry(member.name.as_str()).or_insert(&member.value);
+        }
+        let mut seen = HashSet::new();
+        for member in old {
+            if !seen.insert(member.name.as_str()) {
+                continue;
+            }
+            self.path.push(escape_segment(&member.name));
+            match by_name.get(member.name.as_str()) {
+                Some(b) => self.node(&member.value, b),
+                None => self.push(ChangeKind::Removed, Some(&member.value), None),
+            }
+            self.path.pop();
+        }
+        for member in new {
+            if seen.insert(member.name.as_str()) {
+                self.path.push(escape_segment(&member.name));
+                self.push(ChangeKind::Added, None, Some(&member.value));
+                self.path.pop();
+            }
+        }
+    }
+
+    fn push(&mut self, kind: ChangeKind, a: Option<&JsonNode>, b: Option<&JsonNode>) {
+        self.out.push(entry(
+            kind,
+            &self.path,
+            (self.old, a.map(|a| a.span)),
+            (self.new, b.map(|b| b.span)),
+        ));
+    }
+}
+
+/// The first member of `a` that is out of place in `b`, for two trees
+/// [`json`] found no differences between.
+pub(crate) fn json_key_order(a: &JsonNode, b: &JsonNode) -> Option<Vec<String>> {
+    let mut path = Vec::new();
+    key_order(a, b, &mut path).then_some(path)
+}
+
+fn key_order(a: &JsonNode, b: &JsonNode, path: &mut Vec<String>) -> bool {
+    match (&a.value, &b.value) {
+        (JsonValue::Object(old), JsonValue::Object(new)) => {
+            for ((name, a), (other, b)) in first_members(old).into_iter().zip(first_members(new)) {
+                path.push(escape_segment(name));
+                if name != other || key_order(a, b, path) {
+                    return true;
+                }
+                path.pop();
+            }
+            false
+        }
+        (JsonValue::Array(old), JsonValue::Array(new)) => {
+            for (i, (a, b)) in old.iter().zip(new).enumerate() {
+                path.push(i.to_string());
+                if key_order(a, b, path) {
+                    return true;
+                }
+                path.pop();
+            }
+            false
+        }
+        _ => false,
+    }
+}
+
+/// Each key's first member, the one [`json`] compares.
+fn first_members(members: &[Member]) -> Vec<(&str, &JsonNode)> {
+    let mut seen = HashSet::new();
+    members
+        .iter()
+        .filter(|m| seen.insert(m.name.as_str()))
+        .map(|m| (m.name.as_str(), &m.value))
+        .collect()
+}
+
+/// Whether two scalar tokens hold the same value: strings after decoding,
+/// numbers by value, literals by kind.
+pub(crate) fn same_scalar(ka: Kind, a: &str, kb: Kind, b: &str) -> bool {
+    if ka != kb {
+        return false;
+    }
+    match ka {
+        Kind::StringLit => a == b || decode_json_string(a) == decode_json_string(b),
+        Kind::NumberLit => {
+            if let (Ok(x), Ok(y)) = (a.parse::<i128>(), b.parse::<i128>()) {
+                return x == y;
+            }
+            match (a.parse::<f64>(), b.parse::<f64>()) {
+                (Ok(x), Ok(y)) => x.is_finite() && x == y,
+                _ => a == b,
+            }
+        }
+        _ => true,
+    }
+}
+
+/// Differences from `old` (rooted at `old_root`) to `new`. Entries for an
+/// element come in the order attributes, text, children; children follow
+/// the old document's order, with added ones last.
+#[cfg(feature = "xml")]
+pub(crate) fn xml(
+    old: &str,
+    old_root: &XmlElement,
+    new: &str,
+    new_root: &XmlElement,
+    opts: &DiffOptions,
+) -> Vec<DiffEntry> {
+    let mut diff = XmlDiff {
+        old,
+        new,
+        keys: &opts.key_attributes,
+        path: Vec::new(),
+        out: Vec::new(),
+    };
+    if old_root.name == new_root.name {
+        diff.path.push(old_root.name.clone());
+        diff.element(old_root, new_root);
+    } else {
+        diff.push(
+            ChangeKind::Changed,
+            Some(old_root.span),
+            Some(new_root.span),
+        );
+    }
+    diff.out
+}
+
+/// What pairs an XML element with its counterpart: its name, the first key
+/// attribute it has, and how many siblings before it share both.
+#[cfg(feature = "xml")]
+#[derive(PartialEq, Eq, Hash)]
+struct Identity<'e> {
+    name: &'e str,
+    key: Option<(&'e str, &'e str)>,
+    occurrence: usize,
+}
+
+#[cfg(feature = "xml")]
+struct XmlDiff<'a> {
+    old: &'a str,
+    new: &'a str,
+    keys: &'a [String],
+    path: Vec<String>,
+    out: Vec<DiffEntry>,
+}
+
+#[cfg(feature = "xml")]
+impl XmlDiff<'_> {
+    fn element(&mut self, a: &XmlElement, b: &XmlElement) {
+        if self.old[a.span.start..a.span.end] == self.new[b.span.start..b.span.end] {
+            return;
+        }
+        for attr in &a.attributes {
+            let other = b.attributes.iter().find(|o| o.name == attr.name);
+            if other.is_some_and(|o| o.value == attr.value) {
+                continue;
+            }
+            let kind = match other {
+                Some(_) => ChangeKind::Changed,
+                None => ChangeKind::Removed,
+            };
+            self.path.push(format!("@{}", attr.name));
+            self.push(kind, Some(attr.span), other.map(|o| o.span));
+            self.path.pop();
+        }
+        for attr in &b.attributes {
+            if !a.attributes.iter().any(|o| o.name == attr.name) {
+                self.path.push(format!("@{}", attr.name));
+                self.push(ChangeKind::Added, None, Some(attr.span));
+                self.path.pop();
+            }
+        }
+        let (ta, tb) = (a.text_span(), b.text_span());
+        if (ta.is_some() || tb.is_some()) && a.text_value() != b.text_value() {
+            let kind = match (ta, tb) {
+                (Some(_), Some(_)) => ChangeKind::Changed,
+                (Some(_), None) => ChangeKind::Removed,
+                _ => ChangeKind::Added,
+            };
+            self.push(kind, ta, tb);
+        }
+        self.children(&a.children, &b.children);
+    }
+
+    fn children(&mut self, old: &[XmlElement], new: &[XmlElement]) {
+        let old_ids = self.identities(old);
+        let new_ids = self.identities(new);
+        let by_id: HashMap<&Identity, usize> =
+            new_ids.iter().enumerate().map(|(j, id)| (id, j)).collect();
+        let matched: Vec<Option<usize>> = old_ids.iter().map(|id| by_id.get(id).copied()).collect();
+        let in_place = in_order(&matched);
+        for (i, a) in old.iter().enumerate() {
+            self.path.push(segment(&old_ids[i]));
+            match matched[i] {
+                Some(j) => {
+                    if !in_place[i] {
+                        self.push(ChangeKind::Moved, Some(a.span), Some(new[j].span));
+                    }
+                    self.element(a, &new[j]);
+                }
+                None => self.push(ChangeKind::Removed, Some(a.span), None),
+            }
+            self.path.pop();
+        }
+        let kept: HashSet<usize> = matched.iter().flatten().copied().collect();
+        for (j, b) in new.iter().enumerate() {
+            if !kept.contains(&j) {
+                self.path.push(segment(&new_ids[j]));
+                self.push(ChangeKind::Added, None, Some(b.span));
+                self.path.pop();
+            }
+        }
+    }
+
+    fn identities<'e>(&self, children: &'e [XmlElement]) -> Vec<Identity<'e>> {
+        // siblings seen so far per identity, counted under occurrence 0
+        let mut seen: HashMap<Identity, usize> = HashMap::new();
+        children
+            .iter()
+            .map(|child| {
+                let key = self
+                    .keys
+                    .iter()
+                    .find_map(|key| child.attributes.iter().find(|attr| attr.name == *key))
+                    .map(|attr| (attr.name.as_str(), attr.value.as_str()));
+                let first = Identity {
+                    name: &child.name,
+                    key,
+                    occurrence: 0,
+                };
+                let count = seen.entry(first).or_default();
+                *count += 1;
+                Identity {
+                    name: &child.name,
+                    key,
+                    occurrence: *count - 1,
+                }
+            })
+            .collect()
+    }
+
+    fn push(&mut self, kind: ChangeKind, a: Option<Span>, b: Option<Span>) {
+        self.out
+            .push(entry(kind, &self.path, (self.old, a), (self.new, b)));
+    }
+}
+
+/// The path segment of an element: `name[@key="value"]` when it has a key
+/// attribute, so the path resolves to it; otherwise the plain name, which
+/// resolves to the first sibling of that name.
+#[cfg(feature = "xml")]
+fn segment(id: &Identity) -> String {
+    match id.key {
+        Some((attr, value)) if !(value.contains('"') && value.contains('\'')) => {
+            let quote = if value.contains('"') { '\'' } else { '"' };
+            format!("{}[@{attr}={quote}{value}{quote}]", id.name)
+        }
+        _ => id.name.to_string(),
+    }
+}
+
+/// For each old child, whether it stays in order: the matched ones that
+/// form a longest run of increasing new positions. The other matched
+/// children are the ones that moved.
+#[cfg(feature = "xml")]
+fn in_order(positions: &[Option<usize>]) -> Vec<bool> {
+    // (new position, old index) ending the best run of each length
+    let mut tails: Vec<(usize, usize)> = Vec::new();
+    let mut prev: Vec<Option<usize>> = vec![None; positions.len()];
+    for (i, pos) in positions.iter().enumerate() {
+        let Some(pos) = *pos else {
+            continue;
+        };
+        let len = tails.partition_point(|&(tail, _)| tail < pos);
+        prev[i] = len.checked_sub(1).map(|k| tails[k].1);
+        if len == tails.len() {
+            tails.push((pos, i));
+        } else {
+            tails[len] = (pos, i);
+        }
+    }
+    let mut keep = vec![false; positions.len()];
+    let mut at = tails.last().map(|&(_, i)| i);
+    while let Some(i) = at {
+        keep[i] = true;
+        at = prev[i];
+    }
+    keep
+}
+
+/// The first key of `a` whose entry is out of place in `b`, for two files
+/// with the same keys.
+#[cfg(feature = "env")]
+pub(crate) fn env_key_order(a: &EnvDocument, b: &EnvDocument) -> Option<Vec<String>> {
+    a.entries()
+        .iter()
+        .zip(b.entries())
+        .find(|(a, b)| a.key != b.key)
+        .map(|(a, _)| vec![escape_segment(&a.key)])
+}
+
+/// Differences from `old` (parsed as `old_doc`) to `new`: changed and
+/// removed keys in the old file's order, then added keys in the new one's.
+/// Keys are unique in a valid ENV file, so order and spacing never matter.
+#[cfg(feature = "env")]
+pub(crate) fn env(
+    old: &str,
+    old_doc: &EnvDocument,
+    new: &str,
+    new_doc: &EnvDocument,
+) -> Vec<DiffEntry> {
+    let by_key = |doc| -> HashMap<&str, &Entry> {
+        EnvDocument::entries(doc)
+            .iter()
+            .map(|e| (e.key.as_str(), e))
+            .collect()
+    };
+    let (old_keys, new_keys) = (by_key(old_doc), by_key(new_doc));
+    let mut out = Vec::new();
+    for a in old_doc.entries() {
+        let path = [escape_segment(&a.key)];
+        let Some(b) = new_keys.get(a.key.as_str()) else {
+            out.push(entry(
+                ChangeKind::Removed,
+                &path,
+                (old, Some(a.value_span)),
+                (new, None),
+            ));
+            continue;
+        };
+        let value =
+            |content: &str, span: Span| env_parser::decode_value(&content[span.start..span.end]);
+        let pairs = [
+            (
+                ChangeKind::Changed,
+                value(old, a.value_span) != value(new, b.value_span),
+                a.value_span,
+                b.value_span,
+            ),
+            (
+                ChangeKind::Quoting,
+                a.quote != b.quote,
+                a.value_span,
+                b.value_span,
+            ),
+            (
+                ChangeKind::Export,
+                a.export.is_some() != b.export.is_some(),
+                a.assignment_span(),
+                b.assignment_span(),
+            ),
+        ];
+        for (kind, differs, sa, sb) in pairs {
+            if differs {
+                out.push(entry(kind, &path, (old, Some(sa)), (new, Some(sb))));
+            }
+        }
+    }
+    for b in new_doc.entries() {
+        if !old_keys.contains_key(b.key.as_str()) {
+            out.push(entry(
+                ChangeKind::Added,
+                &[escape_segment(&b.key)],
+                (old, None),
+                (new, Some(b.value_span)),
+            ));
+        }
+    }
+    out
+}
@@ -0,0 +1,892 @@
+//! Parse-once document handle.
+//!
+//! `Document` owns a copy of the content and lazily caches the expensive parts
+//! of the pipeline (JSON tokens, parsed ENV entries, syntax checks, resolved
+//! spans) so that validating and then editing the same content does not lex it
+//! again. Any edit replaces the content and drops the cache.
+
+use std::cell::{Cell, OnceCell, RefCell};
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tsify::Tsify;
+#[cfg(feature = "xml")]
+use xmlparser::Tokenizer;
+
+#[cfg(feature = "env")]
+use crate::env_parser::{self, EnvDocument};
+#[cfg(feature = "env")]
+use crate::env_resolve::{self, ResolvedEnv};
+use crate::json_grammar::Grammar;
+use crate::json_lexer::{self, Lexer, Token};
+use crate::json_parser;
+use crate::json_tree::{self, JsonNode, JsonValue};
+#[cfg(feature = "xml")]
+use crate::limits::TokenBudget;
+use crate::limits::{self, LimitExceeded};
+use crate::line_index::LineIndex;
+use crate::multi_validation::{MultiValidationOptions, MultiValidationResult};
+use crate::schema::{self, SchemaOutcome, SchemaValidationOptions};
+#[cfg(feature = "xml")]
+use crate::xml_error_summary;
+#[cfg(feature = "xml")]
+use crate::xml_parser;
+#[cfg(feature = "xml")]
+use crate::xml_tree::{self, XmlElement};
+use crate::{multi_validation_result, validation_summary, Span, ValidationResult};
+
+/// Registry key of a format; names and handlers are registered in
+/// `formats.rs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum FileType {
+    Json,
+    #[cfg(feature = "xml")]
+    Xml,
+    #[cfg(feature = "env")]
+    Env,
+}
+
+pub(crate) fn unsupported_file_type(raw: &str) -> String {
+    format!("Unsupported file type: {}", raw.to_lowercase())
+}
+
+#[derive(Default)]
+struct ParseCache {
+    syntax: OnceCell<Result<(), String>>,
+    summary: OnceCell<ValidationResult>,
+    json_tokens: OnceCell<Result<Vec<Token>, String>>,
+    lenient_tokens: OnceCell<Result<Vec<Token>, String>>,
+    #[cfg(feature = "env")]
+    env_doc: OnceCell<Result<EnvDocument, String>>,
+    spans: RefCell<HashMap<(Vec<String>, LookupOptions), Span>>,
+    line_index: OnceCell<LineIndex>,
+    token_cursor: Cell<TokenCursor>,
+    pending_scan: RefCell<Option<PendingScan>>,
+}
+
+/// Position of the `next_tokens` stream: the byte offset to resume lexing
+/// from, and the token index for when the full token vector is cached.
+#[derive(Debug, Default, Clone, Copy)]
+struct TokenCursor {
+    offset: usize,
+    index: usize,
+    done: bool,
+}
+
+/// JSON tokens lexed by earlier [`Document::validate_step`] calls, the
+/// offset to resume from, and the grammar check of the tokens so far.
+struct PendingScan {
+    tokens: Vec<Token>,
+    offset: usize,
+    grammar: Grammar,
+}
+
+impl PendingScan {
+    fn new(tokens: Vec<Token>) -> Self {
+        Self {
+            tokens,
+            offset: 0,
+            grammar: Grammar::default(),
+        }
+    }
+
+    fn push(&mut self, content: &str, token: Token) {
+        self.grammar.feed(content, token);
+        self.tokens.push(token);
+    }
+}
+
+/// Where [`Document::scan_json`] stopped.
+enum ScanEnd {
+    Paused,
+    Finished,
+    /// The lexer rejected the token starting at the offset.
+    Failed(String, usize),
+}
+
+/// Result of [`Document::validate_step`].
+pub(crate) enum ValidationStep<'a> {
+    Done(&'a ValidationResult),
+    Pending { processed: usize },
+}
+
+/// Result of [`Document::validate_in_chunks`].
+pub(crate) enum ChunkedValidation<'a> {
+    Done(&'a ValidationResult),
+    Cancelled { processed: usize },
+}
+
+const DEFAULT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Fires the progress callback each time the scan crosses a chunk boundary.
+struct ChunkProgress<F> {
+    chunk: usize,
+    next: usize,
+    total: usize,
+    callback: F,
+}
+
+impl<F: FnMut(usize, usize) -> bool> ChunkProgress<F> {
+    fn new(chunk: usize, total: usize, callback: F) -> Self {
+        let chunk = if chunk == 0 {
+            DEFAULT_CHUNK_BYTES
+        } else {
+            chunk
+        };
+        Self {
+            chunk,
+            next: chunk,
+            total,
+            callback,
+        }
+    }
+
+    /// Returns `false` when the callback asked to stop.
+    fn advance(&mut self, offset: usize) -> bool {
+        if offset < self.next || offset >= self.total {
+            return true;
+        }
+        self.next = (offset / self.chunk + 1) * self.chunk;
+        (self.callback)(offset, self.total)
+    }
+
+    fn finish(mut self) {
+        let _ = (self.callback)(self.total, self.total);
+    }
+}
+
+/// Retained JSON token storage. Tokens are `Copy`, so the previous content
+/// version's vector can be cleared and refilled in place: a bump arena whose
+/// reset is O(1) and whose allocation survives edits. Buffers above
+/// `MAX_RETAINED_TOKENS` are released rather than kept for the handle's life.
+#[derive(Default)]
+struct TokenArena {
+    buf: Cell<Vec<Token>>,
+}
+
+const MAX_RETAINED_TOKENS: usize = 256 * 1024;
+
+impl TokenArena {
+    fn take(&self) -> Vec<Token> {
+        let mut buf = self.buf.take();
+        buf.clear();
+        buf
+    }
+
+    fn recycle(&self, buf: Vec<Token>) {
+        if buf.capacity() <= MAX_RETAINED_TOKENS {
+            self.buf.set(buf);
+        }
+    }
+}
+
+#[derive(Serialize, Tsify)]
+pub struct TokenBatch {
+    pub tokens: Vec<Token>,
+    pub done: bool,
+}
+
+/// Which text an XML element with both text and child elements resolves to,
+/// as in `<msg>Hello <b>world</b>!</msg>`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MixedContent {
+    /// The first direct text node (`Hello `).
+    #[default]
+    First,
+    /// From the first to the last non-blank direct text node, inline
+    /// children included (`Hello <b>world</b>!`); an update replaces them.
+    Concat,
+    /// Fail on an element that has both non-blank text and child elements.
+    Error,
+}
+
+impl MixedContent {
+    /// `"first"`, `"concat"` or `"error"`.
+    pub fn parse(label: &str) -> Option<Self> {
+        match label {
+            "first" => Some(MixedContent::First),
+            "concat" => Some(MixedContent::Concat),
+            "error" => Some(MixedContent::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Which match a JSON path resolves to when an object repeats a key, as in
+/// `{ "port": 80, "port": 8080 }`. Most parsers keep the last one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Occurrence {
+    #[default]
+    First,
+    Last,
+    /// 0-based, in document order.
+    Nth(usize),
+}
+
+impl Occurrence {
+    /// `"first"` or `"last"`; [`Occurrence::Nth`] has no label.
+    pub fn parse(label: &str) -> Option<Self> {
+        match label {
+            "first" => Some(Occurrence::First),
+            "last" => Some(Occurrence::Last),
+            _ => None,
+        }
+    }
+}
+
+/// Per-call options for resolving a path. Spans are cached per path and
+/// options, so lookups with different options do not see each other's
+/// results.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LookupOptions {
+    /// XML only.
+    pub mixed_content: MixedContent,
+    /// JSON only.
+    pub occurrence: Occurrence,
+    /// JSON only: also read `NaN`, `Infinity` and `-Infinity`, so that
+    /// slightly broken files can be opened and repaired. Validation still
+    /// reports them.
+    pub lenient: bool,
+    /// XML only: keep the line breaks and indentation around an element's
+    /// text in its span. By default they are left out, so that an update of
+    /// `<host>\n  localhost\n</host>` replaces `localhost` alone.
+    pub raw_text: bool,
+}
+
+/// Gap left before the `# comment` that follows an ENV value once an update
+/// changes the value's width. The comment itself is never touched, and a
+/// non-empty value always stays at least one space away from it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommentSpacing {
+    /// The gap as written; the comment moves with the end of the value.
+    #[default]
+    Preserve,
+    /// Widen or narrow the gap so the `#` keeps its column, which keeps a
+    /// block of aligned comments aligned. A gap holding a tab is kept.
+    Align,
+    /// A single space.
+    Single,
+}
+
+impl CommentSpacing {
+    /// `"preserve"`, `"align"` or `"single"`.
+    pub fn parse(label: &str) -> Option<Self> {
+        match label {
+            "preserve" => Some(CommentSpacing::Preserve),
+            "align" => Some(CommentSpacing::Align),
+            "single" => Some(CommentSpacing::Single),
+            _ => None,
+        }
+    }
+}
+
+/// Per-call options for an update: how to find the value, and how to lay
+/// out the bytes after it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateOptions {
+    pub lookup: LookupOptions,
+    /// ENV only.
+    pub comment_spacing: CommentSpacing,
+    /// Go straight to span resolution, without the structural check, for
+    /// callers that have just validated the same content. Content that does
+    /// not even tokenize still fails.
+    pub skip_validation: bool,
+}
+
+impl From<LookupOptions> for UpdateOptions {
+    fn from(lookup: LookupOptions) -> Self {
+        Self {
+            lookup,
+            ..Self::default()
+        }
+    }
+}
+
+pub struct Document {
+    file_type: FileType,
+    content: String,
+    cache: ParseCache,
+    arena: TokenArena,
+}
+
+impl Document {
+    #[cfg(all(test, feature = "xml", feature = "env"))]
+    pub(crate) fn open(file_type: &str, content: &str) -> Result<Self, String> {
+        let ty = FileType::parse(file_type).ok_or_else(|| unsupported_file_type(file_type))?;
+        Ok(Self::with_type(ty, content.to_string()))
+    }
+
+    pub(crate) fn with_type(file_type: FileType, content: String) -> Self {
+        Self {
+            file_type,
+            content,
+            cache: ParseCache::default(),
+            arena: TokenArena::default(),
+        }
+    }
+
+    pub(crate) fn file_type(&self) -> FileType {
+        self.file_type
+    }
+
+    pub(crate) fn content_str(&self) -> &str {
+        &self.content
+    }
+
+    pub(crate) fn replace_content(&mut self, content: String) {
+        self.content = content;
+        self.reset_cache();
+    }
+
+    /// Drops cached results, handing the token vector back to the arena.
+    fn reset_cache(&mut self) {
+        let cache = std::mem::take(&mut self.cache);
+        if let Some(Ok(tokens)) = cache.json_tokens.into_inner() {
+            self.arena.recycle(tokens);
+        } else if let Some(scan) = cache.pending_scan.into_inner() {
+            self.arena.recycle(scan.tokens);
+        }
+    }
+
+    /// Structural check used before edits (same rules as the byte-preserving
+    /// parsers' `validate_syntax`).
+    pub(crate) fn check_syntax(&self) -> Result<(), String> {
+        self.cache
+            .syntax
+            .get_or_init(|| match self.file_type {
+                FileType::Json => {
+                    let tokens = self.json_tokens()?;
+                    json_lexer::validate(tokens)
+                }
+                #[cfg(feature = "xml")]
+                FileType::Xml => self.file_type.format().validate_syntax(&self.content),
+                #[cfg(feature = "env")]
+                FileType::Env => self.env_doc().map(|_| ()),
+            })
+            .clone()
+    }
+
+    pub(crate) fn summary(&self) -> &ValidationResult {
+        self.cache
+            .summary
+            .get_or_init(|| validation_summary(self.file_type, &self.content))
+    }
+
+    pub(crate) fn line_index(&self) -> &LineIndex {
+        self.cache
+            .line_index
+            .get_or_init(|| LineIndex::new(&self.content))
+    }
+
+    pub(crate) fn span_for(&self, path: &[String], opts: &LookupOptions) -> Result<Span, String> {
+        let key = (path.to_vec(), *opts);
+        if let Some(span) = self.cache.spans.borrow().get(&key) {
+            return Ok(*span);
+        }
+        let span = match self.file_type {
+            FileType::Json => json_parser::find_occurrence(
+                if opts.lenient {
+                    self.lenient_json_tokens()?
+                } else {
+                    self.json_tokens()?
+                },
+                &self.content,
+                path,
+                opts.occurrence,
+            )?,
+            #[cfg(feature = "xml")]
+            FileType::Xml => xml_parser::lookup(&self.content, path, opts, false)?,
+            #[cfg(feature = "env")]
+            FileType::Env => env_parser::find_value_span_in_doc(self.env_doc()?, path)?,
+        };
+        self.cache.spans.borrow_mut().insert(key, span);
+        Ok(span)
+    }
+
+    /// Text of the value at `path`: as written, or with `decode` as a user
+    /// edits it (the format's `decode_value`, undoing `format_value`).
+    pub(crate) fn value_for(
+        &self,
+        path: &[String],
+        decode: bool,
+        opts: &LookupOptions,
+    ) -> Result<String, String> {
+        let span = self.checked_span_for(path, opts)?;
+        Ok(if decode {
+            self.file_type.format().decode_value_at(&self.content, span)
+        } else {
+            self.content[span.start..span.end].to_string()
+        })
+    }
+
+    /// Resolves many paths against one parse. The outer error is a parse
+    /// failure; each entry is that path's lookup result.
+    pub(crate) fn spans_for(
+        &self,
+        paths: &[Vec<String>],
+    ) -> Result<Vec<Result<Span, String>>, String> {
+        let opts = LookupOptions::default();
+        match self.file_type {
+            // no reusable token vector: resolve all uncached paths in one scan
+            #[cfg(feature = "xml")]
+            FileType::Xml => {
+                let uncached: Vec<Vec<String>> = {
+                    let cache = self.cache.spans.borrow();
+                    paths
+                        .iter()
+                        .filter(|p| !cache.contains_key(&(p.to_vec(), opts)))
+                        .cloned()
+                        .collect()
+                };
+                let mut scanned = HashMap::new();
+                if !uncached.is_empty() {
+                    let found = xml_parser::find_value_spans(&self.content, &uncached)?;
+                    let mut cache = self.cache.spans.borrow_mut();
+                    for (path, span) in uncached.into_iter().zip(found) {
+                        if let Ok(span) = span {
+                            cache.insert((path.clone(), opts), span);
+                        }
+                        scanned.insert(path, span);
+                    }
+                }
+                let cache = self.cache.spans.borrow();
+                return Ok(paths
+                    .iter()
+                    .map(|path| match cache.get(&(path.clone(), opts)) {
+                        Some(span) => Ok(*span),
+                        None => scanned[path].clone(),
+                    })
+                    .collect());
+            }
+            // surface a lex/parse failure once rather than per path
+            FileType::Json => self.json_tokens().map(|_| ())?,
+            #[cfg(feature = "env")]
+            FileType::Env => self.env_doc().map(|_| ())?,
+        }
+        Ok(paths
+            .iter()
+            .map(|path| self.span_for(path, &opts))
+            .collect())
+    }
+
+    /// Syntax check plus span lookup. XML has no reusable token vector, so an
+    /// uncached XML lookup validates and resolves in one tokenizer pass.
+    fn checked_span_for(&self, path: &[String], opts: &LookupOptions) -> Result<Span, String> {
+        #[cfg(feature = "xml")]
+        if self.file_type == FileType::Xml && self.cache.syntax.get().is_none() {
+            let key = (path.to_vec(), *opts);
+            if !self.cache.spans.borrow().contains_key(&key) {
+                // syntax errors take precedence over lookup errors in the scan
+                let span = xml_parser::lookup(&self.content, path, opts, true)?;
+                let _ = self.cache.syntax.set(Ok(()));
+                self.cache.spans.borrow_mut().insert(key, span);
+                return Ok(span);
+            }
+        }
+        if opts.lenient && self.file_type == FileType::Json {
+            json_lexer::validate(self.lenient_json_tokens()?)?;
+        } else {
+            self.check_syntax()?;
+        }
+        self.span_for(path, opts)
+    }
+
+    /// Validates, locates and replaces the value at `path`, keeping the new
+    /// content on the handle. Returns the updated content.
+    pub(crate) fn apply_update(
+        &mut self,
+        path: &[String],
+        new_val: &str,
+        opts: &UpdateOptions,
+    ) -> Result<String, String> {
+        let updated = self.updated_content(path, new_val, opts)?;
+        self.replace_content(updated.clone());
+        Ok(updated)
+    }
+
+    /// Content with the value at `path` replaced; the handle is unchanged.
+    pub(crate) fn updated_content(
+        &self,
+        path: &[String],
+        new_val: &str,
+        opts: &UpdateOptions,
+    ) -> Result<String, String> {
+        let span = if opts.skip_validation {
+            self.span_for(path, &opts.lookup)?
+        } else {
+            self.checked_span_for(path, &opts.lookup)?
+        };
+        let format = self.file_type.format();
+        let fragment = format.format_value_at(&self.content, span, new_val);
+        #[cfg(feature = "env")]
+        if self.file_type == FileType::Env {
+            return Ok(env_parser::replace_before_comment(
+                &self.content,
+                span,
+                &fragment,
+                opts.comment_spacing,
+            ));
+        }
+        Ok(format.replace_value(&self.content, span, &fragment))
+    }
+
+    /// Validates in `chunk_bytes` slices, calling `progress(processed, total)`
+    /// between slices; returning `false` cancels and discards partial work.
+    /// JSON tokens produced along the way are kept for later lookups. ENV
+    /// content is line-oriented and cheap, so it is checked in one step.
+    pub(crate) fn validate_in_chunks(
+        &self,
+        chunk_bytes: usize,
+        progress: impl FnMut(usize, usize) -> bool,
+    ) -> ChunkedValidation<'_> {
+        if let Some(summary) = self.cache.summary.get() {
+            return ChunkedValidation::Done(summary);
+        }
+        let mut tracker = ChunkProgress::new(chunk_bytes, self.content.len(), progress);
+        match self.file_type {
+            FileType::Json => {
+                let mut scan = PendingScan::new(self.arena.take());
+                let end = self.scan_json(&mut scan, |offset, _| !tracker.advance(offset));
+                if let ScanEnd::Paused = end {
+                    self.arena.recycle(scan.tokens);
+                    return ChunkedValidation::Cancelled {
+                        processed: scan.offset,
+                    };
+                }
+                self.finish_json_scan(scan, end);
+            }
+            #[cfg(feature = "xml")]
+            FileType::Xml => {
+                let mut failure = None;
+                let mut budget = TokenBudget::new();
+                for token in Tokenizer::from(self.content.as_str()) {
+                    if budget.spend().is_err() {
+                        break;
+                    }
+                    match token {
+                        Ok(token) => {
+                            let end = token.span().end();
+                            if !tracker.advance(end) {
+                                return ChunkedValidation::Cancelled { processed: end };
+                            }
+                        }
+                        Err(e) => {
+                            failure = Some(xml_error_summary(&self.content, &e));
+                            break;
+                        }
+                    }
+                }
+                let _ = self
+                    .cache
+                    .summary
+                    .set(failure.unwrap_or_else(ValidationResult::success));
+            }
+            #[cfg(feature = "env")]
+            FileType::Env => {}
+        }
+        tracker.finish();
+        ChunkedValidation::Done(self.summary())
+    }
+
+    /// Advances validation by about `chunk_bytes` (64 KiB when 0) and returns,
+    /// so an async caller can yield between calls. JSON lexing resumes where
+    /// the previous step stopped and the tokens are kept for later lookups;
+    /// XML and ENV are checked in a single step.
+    pub(crate) fn validate_step(&self, chunk_bytes: usize) -> ValidationStep<'_> {
+        if let Some(summary) = self.cache.summary.get() {
+            return ValidationStep::Done(summary);
+        }
+        if self.file_type == FileType::Json {
+            if let Some(Ok(tokens)) = self.cache.json_tokens.get() {
+                // lexed by an earlier lookup; only the grammar is left
+                let mut grammar = Grammar::default();
+                for &token in tokens {
+                    grammar.feed(&self.content, token);
+                }
+                let _ = self.cache.summary.set(grammar.finish(&self.content));
+            } else {
+                let chunk = if chunk_bytes == 0 {
+                    DEFAULT_CHUNK_BYTES
+                } else {
+                    chunk_bytes
+                };
+                let mut scan = self
+                    .cache
+                    .pending_scan
+                    .take()
+                    .unwrap_or_else(|| PendingScan::new(self.arena.take()));
+                let stop = scan.offset.saturating_add(chunk);
+                let end = self.scan_json(&mut scan, |offset, finished| offset >= stop && !finished);
+                if let ScanEnd::Paused = end {
+                    let processed = scan.offset;
+                    self.cache.pending_scan.replace(Some(scan));
+                    return ValidationStep::Pending { processed };
+                }
+                self.finish_json_scan(scan, end);
+            }
+        }
+        ValidationStep::Done(self.summary())
+    }
+
+    /// Lexes on from `scan`, feeding each token to its grammar, until the
+    /// input ends, the lexer fails, or `pause(offset, finished)` asks to stop
+    /// after a token.
+    fn scan_json(
+        &self,
+        scan: &mut PendingScan,
+        mut pause: impl FnMut(usize, bool) -> bool,
+    ) -> ScanEnd {
+        let mut lexer = Lexer::resume(&self.content, scan.offset, scan.tokens.len());
+        loop {
+            let before = lexer.offset();
+            match lexer.next() {
+                None => return ScanEnd::Finished,
+                Some(Err(e)) => {
+                    let rest = &self.content[before..];
+                    let skipped = rest.len()
+                        - rest
+                            .trim_start_matches(|c: char| c.is_ascii_whitespace())
+                            .len();
+                    let start = before + skipped;
+                    return ScanEnd::Failed(e, start);
+                }
+                Some(Ok(token)) => {
+                    scan.push(&self.content, token);
+                    scan.offset = lexer.offset();
+                    if pause(scan.offset, lexer.is_finished()) {
+                        return ScanEnd::Paused;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Caches the tokens of a completed scan and the summary its grammar
+    /// gives, so no parse of the whole content is needed.
+    fn finish_json_scan(&self, scan: PendingScan, end: ScanEnd) {
+        let summary = match end {
+            ScanEnd::Failed(e, start) => {
+                self.arena.recycle(scan.tokens);
+                let summary = scan.grammar.lex_failure(&self.content, &e, start);
+                let _ = self.cache.json_tokens.set(Err(e));
+                summary
+            }
+            _ => {
+                let summary = scan.grammar.finish(&self.content);
+                let _ = self.cache.json_tokens.set(Ok(scan.tokens));
+                summary
+            }
+        };
+        let _ = self.cache.summary.set(summary);
+    }
+
+    /// Appends a chunk of streamed input. JSON is lexed as chunks arrive, up
+    /// to the last token that later input cannot extend, and the tokens carry
+    /// over to [`Document::validate_step`], so finishing only lexes the tail.
+    /// XML and ENV chunks are buffered and parsed when validated.
+    pub(crate) fn feed(&mut self, chunk: &str) {
+        let scan = self.cache.pending_scan.take();
+        self.content.push_str(chunk);
+        self.reset_cache();
+        if self.file_type != FileType::Json {
+            return;
+        }
+        let mut scan = scan.unwrap_or_else(|| PendingScan::new(self.arena.take()));
+        let mut lexer = Lexer::resume(&self.content, scan.offset, scan.tokens.len());
+        // a token touching the end may continue in the next chunk, and an
+        // error may be a token cut in two: both are retried on the next feed
+        while let Some(Ok(token)) = lexer.next() {
+            if token.span.end >= self.content.len() {
+                break;
+            }
+            scan.push(&self.content, token);
+            scan.offset = lexer.offset();
+        }
+        self.cache.pending_scan.replace(Some(scan));
+    }
+
+    /// Runs an entry point under the global limits. An overrun may have left
+    /// truncated results in the cache, so the cache is dropped.
+    pub(crate) fn limited<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> T,
+    ) -> Result<T, LimitExceeded> {
+        let len = self.content.len();
+        limits::guard(len, || f(self)).inspect_err(|_| self.reset_cache())
+    }
+
+    /// Returns up to `max` further tokens without lexing the rest of the
+    /// document. Served from the cached token vector when one exists.
+    pub(crate) fn token_batch(&self, max: usize) -> Result<TokenBatch, String> {
+        if self.file_type != FileType::Json {
+            return Err("Streaming tokenization is only supported for JSON documents".into());
+        }
+        let mut cursor = self.cache.token_cursor.get();
+        if cursor.done {
+            return Ok(TokenBatch {
+                tokens: Vec::new(),
+                done: true,
+            });
+        }
+
+        let tokens = if let Some(Ok(all)) = self.cache.json_tokens.get() {
+            let end = cursor.index.saturating_add(max).min(all.len());
+            let batch = all[cursor.index..end].to_vec();
+            cursor.done = end == all.len();
+            batch
+        } else {
+            let mut lexer = Lexer::at(&self.content, cursor.offset);
+            let batch = lexer.by_ref().take(max).collect::<Result<Vec<_>, _>>();
+            cursor.offset = lexer.offset();
+            cursor.done = lexer.is_finished();
+            match batch {
+                Ok(batch) => batch,
+                Err(e) => {
+                    cursor.done = true;
+                    self.cache.token_cursor.set(cursor);
+                    return Err(e);
+                }
+            }
+        };
+        cursor.index += tokens.len();
+        self.cache.token_cursor.set(cursor);
+        Ok(TokenBatch {
+            tokens,
+            done: cursor.done,
+        })
+    }
+
+    fn json_tokens(&self) -> Result<&Vec<Token>, String> {
+        self.cache
+            .json_tokens
+            .get_or_init(|| self.lex_json())
+            .as_ref()
+            .map_err(|e| e.clone())
+    }
+
+    /// The span-annotated tree of a JSON document.
+    pub(crate) fn json_tree(&self) -> Result<JsonNode, String> {
+        json_tree::build(self.json_tokens()?, &self.content)
+    }
+
+    /// The element tree of an XML document.
+    #[cfg(feature = "xml")]
+    pub(crate) fn xml_tree(&self) -> Result<XmlElement, String> {
+        xml_tree::build(&self.content)
+    }
+
+    /// Tokens for lenient lookups. Lenient lexing only accepts more, so
+    /// strict tokens are reused when they lexed.
+    fn lenient_json_tokens(&self) -> Result<&Vec<Token>, String> {
+        if let Some(Ok(tokens)) = self.cache.json_tokens.get() {
+            return Ok(tokens);
+        }
+        self.cache
+            .lenient_tokens
+            .get_or_init(|| Lexer::new(&self.content).lenient().collect())
+            .as_ref()
+            .map_err(|e| e.clone())
+    }
+
+    fn lex_json(&self) -> Result<Vec<Token>, String> {
+        let mut tokens = self.arena.take();
+        for token in Lexer::new(&self.content) {
+            match token {
+                Ok(token) => tokens.push(token),
+                Err(e) => {
+                    self.arena.recycle(tokens);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(tokens)
+    }
+
+    pub(crate) fn multi_result(
+        &self,
+        cap: usize,
+        opts: &MultiValidationOptions,
+    ) -> MultiValidationResult {
+        let index = Some(self.line_index());
+        let result = multi_validation_result(self.file_type, &self.content, cap, index, opts);
+        let declared = result.valid.then(|| self.declared_schema()).flatten();
+        match declared {
+            Some(schema_id) => {
+                let opts = SchemaValidationOptions {
+                    max_errors: cap,
+                    report_deprecated: false,
+                    ..Default::default()
+                };
+                result.with_schema(&self.schema_outcome(&schema_id, &opts.capped()))
+            }
+            None => result,
+        }
+        .with_limit(cap)
+    }
+
+    /// The root `$schema` of a JSON document, when a schema is registered
+    /// under that id.
+    fn declared_schema(&self) -> Option<String> {
+        if self.file_type != FileType::Json {
+            return None;
+        }
+        let root = self.json_tree().ok()?;
+        let JsonValue::Object(members) = &root.value else {
+            return None;
+        };
+        let value = &members.iter().find(|m| m.name == "$schema")?.value;
+        let id: String =
+            serde_json::from_str(&self.content[value.span.start..value.span.end]).ok()?;
+        schema::get_cached_schema(&id).map(|_| id)
+    }
+
+    pub(crate) fn schema_outcome(
+        &self,
+        schema_id: &str,
+        opts: &SchemaValidationOptions,
+    ) -> SchemaOutcome {
+        schema::profiled(opts, || {
+            schema::validate_registered_indexed(&self.content, schema_id, opts, self.line_index())
+        })
+    }
+
+    pub(crate) fn rewind_tokens(&self) {
+        self.cache.token_cursor.set(TokenCursor::default());
+    }
+
+    /// 1-based line and character column for a byte offset.
+    pub(crate) fn position_of(&self, offset: usize) -> (usize, usize) {
+        self.line_index().line_col_chars(&self.content, offset)
+    }
+
+    /// Byte offset for a 1-based line and character column.
+    pub(crate) fn offset_of(&self, line: usize, column: usize) -> usize {
+        self.line_index().offset(&self.content, line, column)
+    }
+
+    #[cfg(all(test, feature = "xml", feature = "env"))]
+    pub(crate) fn cached_tokens(&self) -> Option<&[Token]> {
+        match self.cache.json_tokens.get() {
+            Some(Ok(tokens)) => Some(tokens),
+            _ => None,
+        }
+    }
+
+    /// `${VAR}` / `$VAR` references resolved against this ENV file's keys.
+    #[cfg(feature = "env")]
+    pub(crate) fn resolve_env(&self, expand: bool) -> Result<ResolvedEnv, String> {
+        let env = self.env_doc()?;
+        Ok(env_resolve::resolve(&self.content, env, expand, |offset| {
+            self.position_of(offset)
+        }))
+    }
+
+    #[cfg(feature = "env")]
+    pub(crate) fn env_doc(&self) -> Result<&EnvDocument, String> {
+        self.cache
+            .env_doc
+            .get_or_init(|| EnvDocument::parse(&self.content))
+            .as_ref()
+            .map_err(|e| e.clone())
+    }
+}
@@ -0,0 +1,121 @@
+//! Values repeated across documents.
+//!
+//! Every string value (the `template` value sites) is keyed by the name it
+//! sits under and its decoded text, so `"url": "postgres://db"` in JSON,
+//! `<db url="postgres://db"/>` in XML and `URL=postgres://db` in ENV would
+//! have to share the name `url` exactly to match. Pairs found in at least
+//! two of the documents form a cluster; repeats within one document alone
+//! are left to the format's own tooling.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tsify::Tsify;
+
+use crate::document::Document;
+use crate::paths::{parse, Segment};
+use crate::template::Site;
+use crate::Span;
+
+/// One document of a [`find_duplicates`](crate::native::find_duplicates)
+/// call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigFile {
+    pub name: String,
+    /// `"json"`, `"xml"` or `"env"`.
+    pub file_type: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct DuplicateOccurrence {
+    pub file: String,
+    pub path: Vec<String>,
+    pub line: usize,
+    pub column: usize,
+    /// The value, between any quotes.
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+/// A key/value pair set in more than one document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct DuplicateCluster {
+    /// The name the value sits under: the last key of its path, without
+    /// an XML attribute's `@`.
+    pub key: String,
+    /// The decoded value.
+    pub value: String,
+    /// Every place it is set, in file order and then document order.
+    pub occurrences: Vec<DuplicateOccurrence>,
+}
+
+#[derive(Default)]
+pub(crate) struct Finder {
+    clusters: Vec<DuplicateCluster>,
+    /// Index of each file the cluster's occurrences come from, in order.
+    files: Vec<Vec<usize>>,
+    index: HashMap<(String, String), usize>,
+}
+
+impl Finder {
+    /// Adds the non-empty values of file number `file`.
+    pub(crate) fn add(&mut self, file: usize, name: &str, doc: &Document, sites: &[Site]) {
+        let content = doc.content_str();
+        for site in sites {
+            let Some(key) = key_of(&site.path) else {
+                continue;
+            };
+            let value = site.decoded(content);
+            if value.is_empty() {
+                continue;
+            }
+            let (line, column) = doc.position_of(site.span.start);
+            let occurrence = DuplicateOccurrence {
+                file: name.to_string(),
+                path: site.path.clone(),
+                line,
+                column,
+                span: site.span,
+            };
+            let at = *self
+                .index
+                .entry((key.to_string(), value.clone()))
+                .or_insert_with(|| {
+                    self.clusters.push(DuplicateCluster {
+                        key: key.to_string(),
+                        value,
+                        occurrences: Vec::new(),
+                    });
+                    self.files.push(Vec::new());
+                    self.clusters.len() - 1
+                });
+            self.clusters[at].occurrences.push(occurrence);
+            if self.files[at].last() != Some(&file) {
+                self.files[at].push(file);
+            }
+        }
+    }
+
+    /// The clusters spanning two or more files, in order of first
+    /// occurrence.
+    pub(crate) fn finish(self) -> Vec<DuplicateCluster> {
+        self.clusters
+            .into_iter()
+            .zip(self.files)
+            .filter(|(_, files)| files.len() > 1)
+            .map(|(cluster, _)| cluster)
+            .collect()
+    }
+}
+
+/// The last key of `path`, skipping array indexes; `None` for a value
+/// that only sits in arrays.
+fn key_of(path: &[String]) -> Option<&str> {
+    path.iter().rev().find_map(|segment| match parse(segment) {
+        Segment::Key(key) => Some(key),
+        Segment::Index(_) => None,
+        Segment::Plain(key) if key.bytes().all(|b| b.is_ascii_digit()) => None,
+        Segment::Plain(key) => Some(key.strip_prefix('@').unwrap_or(key)),
+    })
+}
@@ -0,0 +1,150 @@
+//! Byte input: BOM sniffing and UTF-16 transcoding.
+//!
+//! Every parser works on `&str`, so files read as bytes are decoded here
+//! first. The detected encoding and BOM travel with the text so a save can
+//! re-encode the edited content exactly as it was read.
+
+use serde::Serialize;
+use tsify::Tsify;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Tsify)]
+pub enum TextEncoding {
+    #[serde(rename = "utf-8")]
+    Utf8,
+    #[serde(rename = "utf-16le")]
+    Utf16Le,
+    #[serde(rename = "utf-16be")]
+    Utf16Be,
+}
+
+impl TextEncoding {
+    pub fn label(self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "utf-8",
+            TextEncoding::Utf16Le => "utf-16le",
+            TextEncoding::Utf16Be => "utf-16be",
+        }
+    }
+
+    /// Accepts the WHATWG labels `decode_bytes` reports, case-insensitively,
+    /// plus `utf8` and `utf-16` (little-endian, as browsers read it).
+    pub fn parse(label: &str) -> Option<Self> {
+        match label.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Some(TextEncoding::Utf8),
+            "utf-16le" | "utf-16" => Some(TextEncoding::Utf16Le),
+            "utf-16be" => Some(TextEncoding::Utf16Be),
+            _ => None,
+        }
+    }
+
+    fn bom(self) -> &'static [u8] {
+        match self {
+            TextEncoding::Utf8 => b"\xEF\xBB\xBF",
+            TextEncoding::Utf16Le => b"\xFF\xFE",
+            TextEncoding::Utf16Be => b"\xFE\xFF",
+        }
+    }
+}
+
+/// Text decoded from bytes, with what is needed to write it back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct DecodedText {
+    /// The text without its BOM.
+    pub content: String,
+    pub encoding: TextEncoding,
+    pub bom: bool,
+}
+
+/// Detects the encoding from a BOM, or without one from the NUL byte pattern
+/// of a leading ASCII character (config files start with `{`, `<`, `#` or a
+/// letter), and decodes. Anything else must be valid UTF-8.
+pub(crate) fn decode(bytes: &[u8]) -> Result<DecodedText, String> {
+    let sniffed = [
+        TextEncoding::Utf8,
+        TextEncoding::Utf16Le,
+        TextEncoding::Utf16Be,
+    ]
+    .into_iter()
+    .find(|encoding| bytes.starts_with(encoding.bom()));
+    let (encoding, bom) = match sniffed {
+        Some(encoding) => (encoding, true),
+        None => (sniff_utf16(bytes).unwrap_or(TextEncoding::Utf8), false),
+    };
+    let body = if bom {
+        &bytes[encoding.bom().len()..]
+    } else {
+        bytes
+    };
+    let content = match encoding {
+        TextEncoding::Utf8 => String::from_utf8(body.to_vec()).map_err(|e| {
+            format!(
+                "Content is not valid UTF-8 (byte {})",
+                bytes.len() - body.len() + e.utf8_error().valid_up_to()
+            )
+        })?,
+        TextEncoding::Utf16Le | TextEncoding::Utf16Be => decode_utf16(body, encoding)?,
+    };
+    Ok(DecodedText {
+        content,
+        encoding,
+        bom,
+    })
+}
+
+fn sniff_utf16(bytes: &[u8]) -> Option<TextEncoding> {
+    match bytes {
+        [lo, 0, ..] if *lo != 0 && bytes.len().is_multiple_of(2) => Some(TextEncoding::Utf16Le),
+        [0, hi, ..] if *hi != 0 && bytes.len().is_multiple_of(2) => Some(TextEncoding::Utf16Be),
+        _ => None,
+    }
+}
+
+fn decode_utf16(body: &[u8], encoding: TextEncoding) -> Result<String, String> {
+    if !body.len().is_multiple_of(2) {
+        return Err(format!(
+            "Content is not valid {}: odd byte length",
+            encoding.label().to_uppercase()
+        ));
+    }
+    let units = body.chunks_exact(2).map(|pair| match encoding {
+        TextEncoding::Utf16Be => u16::from_be_bytes([pair[0], pair[1]]),
+        _ => u16::from_le_bytes([pair[0], pair[1]]),
+    });
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|e| {
+            format!(
+                "Content is not valid {}: unpaired surrogate {:#06x}",
+                encoding.label().to_uppercase(),
+                e.unpaired_surrogate()
+            )
+        })
+}
+
+/// Byte length of a leading U+FEFF in `content`, which parsers skip. It stays
+/// in the string, so spans index the content as given and edits keep it.
+pub(crate) fn bom_len(content: &str) -> usize {
+    if content.starts_with('\u{FEFF}') {
+        '\u{FEFF}'.len_utf8()
+    } else {
+        0
+    }
+}
+
+/// `content` in `encoding`, prefixed with the BOM when `bom` is set.
+pub(crate) fn encode(content: &str, encoding: TextEncoding, bom: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(content.len() + 3);
+    if bom {
+        out.extend_from_slice(encoding.bom());
+    }
+    match encoding {
+        TextEncoding::Utf8 => out.extend_from_slice(content.as_bytes()),
+        TextEncoding::Utf16Le => content
+            .encode_utf16()
+            .for_each(|unit| out.extend_from_slice(&unit.to_le_bytes())),
+        TextEncoding::Utf16Be => content
+            .encode_utf16()
+            .for_each(|unit| out.extend_from_slice(&unit.to_be_bytes())),
+    }
+    out
+}
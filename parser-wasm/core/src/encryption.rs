@@ -0,0 +1,34 @@
+//! Values stored encrypted in place.
+//!
+//! An encrypted value is written as `ENC[<ciphertext>]`, so the file stays
+//! valid, can be committed, and shows which values are protected. The
+//! cipher belongs to the host: this module only finds the values to hand
+//! over and recognises the marker when they come back.
+
+use serde::Serialize;
+use tsify::Tsify;
+
+/// Marks an encrypted value: `ENC[` … `]`.
+pub const ENCRYPTED_PREFIX: &str = "ENC[";
+const ENCRYPTED_SUFFIX: &str = "]";
+
+/// A value handed to or returned by the host's cipher.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct SensitiveValue {
+    pub path: Vec<String>,
+    /// Plain text on the way in, ciphertext (without the marker) on the way
+    /// out, or the other way round when decrypting.
+    pub value: String,
+}
+
+/// `ciphertext` as it is stored.
+pub fn seal(ciphertext: &str) -> String {
+    format!("{ENCRYPTED_PREFIX}{ciphertext}{ENCRYPTED_SUFFIX}")
+}
+
+/// The ciphertext of a stored value, when it is one.
+pub fn unseal(value: &str) -> Option<&str> {
+    value
+        .strip_prefix(ENCRYPTED_PREFIX)?
+        .strip_suffix(ENCRYPTED_SUFFIX)
+}
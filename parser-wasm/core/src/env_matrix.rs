@@ -0,0 +1,137 @@
+//! Per-environment comparison of one config.
+//!
+//! Each environment's file is read into `convert`'s neutral value and
+//! flattened to its leaves: scalars and empty containers, keyed by path.
+//! A row per path lists the value in every environment, so a host can show
+//! the config as a table with one column per environment.
+
+use serde::Serialize;
+use tsify::Tsify;
+
+use crate::convert::Value;
+use crate::paths::escape_segment;
+use crate::semantic_hash::canonical_number;
+
+/// The config of one environment, e.g. `production`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvironmentFile {
+    pub env: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct MatrixRow {
+    pub path: Vec<String>,
+    /// One per environment, in the order of `envs`; `null` where the path
+    /// is missing. Strings are decoded, other values are JSON text.
+    pub values: Vec<Option<String>>,
+    /// Environments without the path.
+    pub missing: Vec<String>,
+    /// Set in every environment, to the same value.
+    pub identical: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct EnvMatrix {
+    pub envs: Vec<String>,
+    /// Paths in the order they are first seen, environment by environment.
+    pub rows: Vec<MatrixRow>,
+}
+
+/// A leaf's value: strings decoded, the rest as JSON text, which keeps
+/// `"1"` and `1` apart. Numbers compare by value.
+enum Cell {
+    Text(String),
+    Number(String),
+    Json(String),
+}
+
+impl Cell {
+    fn shown(&self) -> String {
+        match self {
+            Cell::Text(text) | Cell::Number(text) | Cell::Json(text) => text.clone(),
+        }
+    }
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Cell::Text(a), Cell::Text(b)) | (Cell::Json(a), Cell::Json(b)) => a == b,
+            (Cell::Number(a), Cell::Number(b)) => canonical_number(a) == canonical_number(b),
+            _ => false,
+        }
+    }
+}
+
+/// `values` in the order of `envs`. With `xml`, an element's `#text`
+/// member is addressed by the element's own path, as lookups do.
+pub(crate) fn build(envs: Vec<String>, values: &[Value], xml: bool) -> EnvMatrix {
+    let mut rows: Vec<(Vec<String>, Vec<Option<Cell>>)> = Vec::new();
+    let mut index = std::collections::HashMap::new();
+    for (column, value) in values.iter().enumerate() {
+        let mut leaves = Vec::new();
+        flatten(value, xml, &mut Vec::new(), &mut leaves);
+        for (path, cell) in leaves {
+            let row = *index.entry(path.clone()).or_insert_with(|| {
+                rows.push((path, (0..values.len()).map(|_| None).collect()));
+                rows.len() - 1
+            });
+            rows[row].1[column] = Some(cell);
+        }
+    }
+    let rows = rows
+        .into_iter()
+        .map(|(path, cells)| {
+            let missing = cells
+                .iter()
+                .zip(&envs)
+                .filter(|(cell, _)| cell.is_none())
+                .map(|(_, env)| env.clone())
+                .collect::<Vec<_>>();
+            let identical = missing.is_empty() && cells.windows(2).all(|pair| pair[0] == pair[1]);
+            MatrixRow {
+                path,
+                values: cells
+                    .iter()
+                    .map(|cell| cell.as_ref().map(Cell::shown))
+                    .collect(),
+                missing,
+                identical,
+            }
+        })
+        .collect();
+    EnvMatrix { envs, rows }
+}
+
+fn flatten(value: &Value, xml: bool, path: &mut Vec<String>, out: &mut Vec<(Vec<String>, Cell)>) {
+    let leaf = match value {
+        Value::Object(members) if !members.is_empty() => {
+            for (key, value) in members {
+                if xml && key == "#text" {
+                    flatten(value, xml, path, out);
+                    continue;
+                }
+                path.push(escape_segment(key));
+                flatten(value, xml, path, out);
+                path.pop();
+            }
+            return;
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (i, item) in items.iter().enumerate() {
+                path.push(i.to_string());
+                flatten(item, xml, path, out);
+                path.pop();
+            }
+            return;
+        }
+        Value::Object(_) => Cell::Json("{}".into()),
+        Value::Array(_) => Cell::Json("[]".into()),
+        Value::Null => Cell::Json("null".into()),
+        Value::Bool(b) => Cell::Json(b.to_string()),
+        Value::Number(text) => Cell::Number(text.clone()),
+        Value::String(text) => Cell::Text(text.clone()),
+    };
+    out.push((path.clone(), leaf));
+}
@@ -0,0 +1,101 @@
+//! Layered `.env` files.
+//!
+//! Layers are applied in the order given, so a later file overrides an
+//! earlier one the way dotenv tooling stacks `.env`, `.env.local` and
+//! `.env.production`. Each key keeps the definition that won plus the ones it
+//! shadowed, which answers "why is this value X".
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tsify::Tsify;
+
+use crate::env_parser::{self, EnvDocument};
+use crate::Span;
+
+/// One layer of a merge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvFile {
+    pub name: String,
+    pub content: String,
+}
+
+/// Where a key is set: the file, its 1-based line, and the value span in
+/// that file.
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct EnvDefinition {
+    pub file: String,
+    pub line: usize,
+    /// The value span's text, quotes included.
+    pub raw: String,
+    /// Quotes removed and escapes decoded.
+    pub value: String,
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct MergedEntry {
+    pub key: String,
+    /// The definition that won.
+    #[serde(flatten)]
+    pub definition: EnvDefinition,
+    /// Earlier definitions it overrides, first file first.
+    pub overridden: Vec<EnvDefinition>,
+}
+
+/// Effective entries in order of first definition.
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct MergedEnv {
+    pub entries: Vec<MergedEntry>,
+}
+
+#[derive(Default)]
+pub(crate) struct Merge {
+    entries: Vec<MergedEntry>,
+    index: HashMap<String, usize>,
+}
+
+impl Merge {
+    /// Applies `doc`, parsed from `content`, over the layers so far.
+    /// `position` maps a byte offset to its line and column.
+    pub(crate) fn layer(
+        &mut self,
+        file: &str,
+        content: &str,
+        doc: &EnvDocument,
+        position: impl Fn(usize) -> (usize, usize),
+    ) {
+        for entry in doc.entries() {
+            let raw = &content[entry.value_span.start..entry.value_span.end];
+            let definition = EnvDefinition {
+                file: file.to_string(),
+                line: position(entry.value_span.start).0,
+                raw: raw.to_string(),
+                value: env_parser::decode_value(raw),
+                span: entry.value_span,
+            };
+            match self.index.get(&entry.key) {
+                Some(&i) => {
+                    let merged = &mut self.entries[i];
+                    let shadowed = std::mem::replace(&mut merged.definition, definition);
+                    merged.overridden.push(shadowed);
+                }
+                None => {
+                    self.index.insert(entry.key.clone(), self.entries.len());
+                    self.entries.push(MergedEntry {
+                        key: entry.key.clone(),
+                        definition,
+                        overridden: Vec::new(),
+                    });
+                }
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> MergedEnv {
+        MergedEnv {
+            entries: self.entries,
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Reordering `.env` entries.
+//!
+//! Entries are moved as whole lines: the assignment line (or lines, for a
+//! multi-line quoted value) with its trailing comment, plus the comment
+//! lines written above it since the previous entry. Blank lines between
+//! entries are dropped and put back between groups. A header at the top of
+//! the file (comments followed by a blank line before the first entry) and
+//! whatever follows the last entry stay where they are. Lines end where
+//! the lexer ends them: at `\n`, `\r\n` or a lone `\r`.
+
+use crate::encoding::bom_len;
+use crate::env_parser::EnvDocument;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrganizeEnvOptions {
+    /// Gather keys sharing the text up to their first `_` (`DB_`, `REDIS_`)
+    /// into groups; otherwise sort every key as one list.
+    pub group_by_prefix: bool,
+    /// Separate groups with a blank line.
+    pub blank_line_between_groups: bool,
+}
+
+impl Default for OrganizeEnvOptions {
+    fn default() -> Self {
+        Self {
+            group_by_prefix: true,
+            blank_line_between_groups: true,
+        }
+    }
+}
+
+/// An entry with the lines that move along with it.
+struct Block<'a> {
+    key: &'a str,
+    /// Comment lines above the entry, each with its line ending.
+    comments: Vec<&'a str>,
+    /// From the start of the assignment's first line through the end of its
+    /// last line.
+    lines: &'a str,
+}
+
+pub(crate) fn organize(content: &str, doc: &EnvDocument, opts: &OrganizeEnvOptions) -> String {
+    let entries = doc.entries();
+    let Some(last) = entries.last() else {
+        return content.to_string();
+    };
+    let eol = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+
+    // the byte order mark stays first
+    let bom = bom_len(content);
+    let mut blocks = Vec::with_capacity(entries.len());
+    let mut header = bom;
+    let mut cursor = bom;
+    for (i, entry) in entries.iter().enumerate() {
+        let start = line_start(content, entry.assignment_span().start).max(bom);
+        let end = line_end(content, entry.value_span.end);
+        let gap = &content[cursor..start];
+        let mut comments: Vec<&str> = lines(gap)
+            .filter(|line| line.trim_start().starts_with('#'))
+            .collect();
+        if i == 0 {
+            // comments directly above the first entry are its own; anything
+            // before them is the file's header
+            let attached = lines(gap)
+                .rev()
+                .take_while(|line| line.trim_start().starts_with('#'))
+                .count();
+            comments.drain(..comments.len() - attached);
+            header = start - comments.iter().map(|line| line.len()).sum::<usize>();
+        }
+        blocks.push(Block {
+            key: &entry.key,
+            comments,
+            lines: &content[start..end],
+        });
+        cursor = end;
+    }
+    let trailer = &content[line_end(content, last.value_span.end)..];
+
+    let prefix = |key: &str| -> String {
+        match key.find('_') {
+            Some(i) if opts.group_by_prefix => key[..=i].to_string(),
+            _ => String::new(),
+        }
+    };
+    blocks.sort_by_cached_key(|block| (prefix(block.key), block.key));
+
+    let mut out = String::with_capacity(content.len() + eol.len() * blocks.len());
+    out.push_str(&content[..header]);
+    let mut group: Option<String> = None;
+    for block in &blocks {
+        let own = prefix(block.key);
+        if opts.blank_line_between_groups && group.as_ref().is_some_and(|g| *g != own) {
+            out.push_str(eol);
+        }
+        group = Some(own);
+        for line in &block.comments {
+            out.push_str(line);
+        }
+        out.push_str(block.lines);
+        if !block.lines.ends_with(['\r', '\n']) {
+            out.push_str(eol);
+        }
+    }
+    if trailer.is_empty() && !content.ends_with(['\r', '\n']) {
+        out.truncate(out.trim_end_matches(['\r', '\n']).len());
+    }
+    out.push_str(trailer);
+    out
+}
+
+/// Start of the line holding `offset`; the lexer ends lines at `\n`, `\r\n`
+/// and a lone `\r`.
+pub(crate) fn line_start(content: &str, offset: usize) -> usize {
+    content[..offset].rfind(['\r', '\n']).map_or(0, |i| i + 1)
+}
+
+/// Past the line ending of the line holding `offset`.
+pub(crate) fn line_end(content: &str, offset: usize) -> usize {
+    content[offset..]
+        .find(['\r', '\n'])
+        .map_or(content.len(), |i| {
+            offset + i + eol_len(&content[offset + i..])
+        })
+}
+
+fn eol_len(rest: &str) -> usize {
+    if rest.starts_with("\r\n") {
+        2
+    } else {
+        1
+    }
+}
+
+/// The lines of `text`, each with its line ending.
+fn lines(text: &str) -> impl DoubleEndedIterator<Item = &str> {
+    let mut ends = Vec::new();
+    let mut at = 0;
+    while at < text.len() {
+        let end = line_end(text, at);
+        ends.push((at, end));
+        at = end;
+    }
+    ends.into_iter().map(move |(start, end)| &text[start..end])
+}
@@ -0,0 +1,473 @@
+//---------------------------------------------------------
+// env_parser.rs  (no external crates, browser–WASM ready)
+//---------------------------------------------------------
+
+use crate::document::CommentSpacing;
+use crate::line_index::{self, ColumnUnit};
+use crate::paths;
+use crate::{BytePreservingParser, Span};
+
+// Move Quote definition above mod lexer so it's visible to the whole file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quote {
+    Single,
+    Double,
+}
+impl Quote {
+    pub fn as_byte(self) -> u8 {
+        match self {
+            Quote::Single => b'\'',
+            Quote::Double => b'"',
+        }
+    }
+}
+
+// Make struct Line<'a> public so it can be used in mod lexer
+#[allow(dead_code)]
+pub struct Line<'a> {
+    pub bytes: &'a [u8],
+    pub eol_len: usize, // 0, 1 or 2
+}
+
+// ───────────────────────── 1. LEXER ─────────────────────────
+mod lexer {
+
+    use super::Line;
+    use super::{Quote, Span};
+    use crate::encoding::bom_len;
+    use crate::limits::TokenBudget;
+
+    /// Parsed line → (optional) key/value spans + quote info.
+    #[derive(Debug)]
+    pub struct EntryRaw {
+        /// The `export` keyword, when the line has one.
+        pub export: Option<Span>,
+        pub key_span: Span,
+        pub value_span: Span,
+        pub quote: Option<Quote>,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct LexError {
+        pub msg: String,
+        /// Byte offset of the error in the buffer.
+        pub offset: usize,
+    }
+
+    /// The line at the start of `bytes`, EOL (`\n`, `\r\n` or `\r`) included.
+    fn next_line(bytes: &[u8]) -> Line<'_> {
+        let idx = bytes
+            .iter()
+            .position(|&b| b == b'\n' || b == b'\r')
+            .unwrap_or(bytes.len());
+        let rest = &bytes[idx..];
+        let eol_len = if rest.starts_with(b"\r\n") {
+            2
+        } else if rest.is_empty() {
+            0
+        } else {
+            1
+        };
+        Line {
+            bytes: &bytes[..idx + eol_len],
+            eol_len,
+        }
+    }
+
+    /// Index of the quote closing a value whose body starts at `from`, which
+    /// may lie on a later line. Double quotes honour backslash escapes.
+    fn closing_quote(bytes: &[u8], from: usize, quote: Quote) -> Option<usize> {
+        let mut j = from;
+        while j < bytes.len() {
+            match bytes[j] {
+                b if b == quote.as_byte() => return Some(j),
+                // `\"` and `\\` inside double quotes are escapes
+                b'\\' if quote == Quote::Double => j += 2,
+                _ => j += 1,
+            }
+        }
+        None
+    }
+
+    /// Core tokenisation logic – returns Vec of raw entries; ignores comments/blank lines.
+    ///
+    /// A quoted value runs to its closing quote even across line breaks, so
+    /// one entry can cover several physical lines.
+    pub fn lex_with_pos(buf: &str) -> Result<Vec<EntryRaw>, LexError> {
+        let bytes = buf.as_bytes();
+        let mut offset = bom_len(buf); // running byte offset in the original buffer
+        let mut out = Vec::<EntryRaw>::new();
+        let mut budget = TokenBudget::new();
+
+        while offset < bytes.len() {
+            let line = next_line(&bytes[offset..]);
+            let slice = line.bytes; // still contains EOL
+            let trimmed = trim_ws(slice);
+
+            // count leading whitespace to compute accurate offsets
+            let mut lead_ws = 0usize;
+            while lead_ws < slice.len() && is_space(slice[lead_ws]) {
+                lead_ws += 1;
+            }
+
+            if trimmed.is_empty() || trimmed[0] == b'#' {
+                // blank / comment
+                offset += slice.len();
+                continue;
+            }
+
+            // optional leading "export"
+            let mut idx = 0;
+            let export = starts_with_kw(trimmed, b"export");
+            if export {
+                idx += b"export".len();
+                skip_spaces(trimmed, &mut idx);
+            }
+
+            // parse key
+            let key_start = idx;
+            while idx < trimmed.len() && !trimmed[idx].is_ascii_whitespace() && trimmed[idx] != b'='
+            {
+                idx += 1;
+            }
+            let key_end = idx;
+            skip_spaces(trimmed, &mut idx);
+
+            // '='
+            if idx >= trimmed.len() || trimmed[idx] != b'=' {
+                return Err(LexError {
+                    msg: "missing '=' separator".into(),
+                    offset: offset + lead_ws + idx,
+                });
+            }
+            idx += 1; // past '='
+                      // capture value (leading spaces allowed)
+            skip_spaces(trimmed, &mut idx);
+
+            // determine quoting
+            let (quote, val_body_start) = match trimmed.get(idx) {
+                Some(b'"') => (Some(super::Quote::Double), idx + 1),
+                Some(b'\'') => (Some(super::Quote::Single), idx + 1),
+                _ => (None, idx),
+            };
+
+            // byte offset of `trimmed` in the buffer
+            let base = offset + (trimmed.as_ptr() as usize - slice.as_ptr() as usize);
+            // where the next entry's line starts
+            let mut next_offset = offset + slice.len();
+
+            // locate end of value (before in-line comment / EOL)
+            let val_end;
+
+            // For quoted values, find the closing quote first
+            if let Some(q) = quote {
+                let Some(close) = closing_quote(bytes, base + val_body_start, q) else {
+                    return Err(LexError {
+                        msg: "unterminated quoted value".into(),
+                        offset: base + idx,
+                    });
+                };
+                if close >= next_offset {
+                    // multi-line value: resume after the line holding the quote
+                    next_offset = close + 1 + next_line(&bytes[close + 1..]).bytes.len();
+                }
+                val_end = close + 1 - base; // include the closing quote
+            } else {
+                // For unquoted values, find end considering comments
+                let mut j = trimmed.len();
+                if let Some(pos) = memchr::memchr(b'#', &trimmed[val_body_start..]) {
+                    j = val_body_start + pos;
+                }
+                // Strip trailing spaces before comment
+                while j > val_body_start && is_space(trimmed[j - 1]) {
+                    j -= 1;
+                }
+                val_end = j;
+            }
+
+            let key_global = Span::new(base + key_start, base + key_end);
+            // For quoted values, include the quotes in the span
+            let (val_span_start, val_span_end) = if quote.is_some() {
+                (val_body_start - 1, val_end) // include opening and closing quotes
+            } else {
+                (val_body_start, val_end)
+            };
+            let val_global = Span::new(base + val_span_start, base + val_span_end);
+
+            budget.spend().map_err(|msg| LexError {
+                msg,
+                offset: offset + lead_ws,
+            })?;
+            out.push(EntryRaw {
+                export: export.then(|| Span::new(base, base + b"export".len())),
+                key_span: key_global,
+                value_span: val_global,
+                quote,
+            });
+
+            offset = next_offset;
+        }
+        Ok(out)
+    }
+
+    // Backward-compatible wrapper that drops position info
+    pub fn lex(buf: &str) -> Result<Vec<EntryRaw>, String> {
+        match lex_with_pos(buf) {
+            Ok(v) => Ok(v),
+            Err(e) => Err(e.msg),
+        }
+    }
+
+    // ───── helpers ─────
+    #[inline]
+    fn is_space(b: u8) -> bool {
+        b == b' ' || b == b'\t'
+    }
+    #[inline]
+    fn trim_ws(mut s: &[u8]) -> &[u8] {
+        while !s.is_empty() && is_space(s[0]) {
+            s = &s[1..];
+        }
+        while !s.is_empty()
+            && (is_space(s[s.len() - 1]) || s[s.len() - 1] == b'\n' || s[s.len() - 1] == b'\r')
+        {
+            s = &s[..s.len() - 1];
+        }
+        s
+    }
+    #[inline]
+    fn skip_spaces(buf: &[u8], idx: &mut usize) {
+        while *idx < buf.len() && is_space(buf[*idx]) {
+            *idx += 1;
+        }
+    }
+    #[inline]
+    fn starts_with_kw(buf: &[u8], kw: &[u8]) -> bool {
+        buf.len() >= kw.len()
+            && &buf[..kw.len()] == kw
+            && (buf.get(kw.len()).is_none_or(|c| is_space(*c)))
+    }
+}
+use lexer::lex;
+
+// ───────────────────────── 2. MODEL ─────────────────────────
+#[derive(Debug)]
+pub(crate) struct Entry {
+    pub(crate) key: String,
+    pub(crate) export: Option<Span>,
+    pub(crate) key_span: Span,
+    pub(crate) value_span: Span,
+    pub(crate) quote: Option<Quote>,
+}
+
+#[derive(Debug)]
+pub(crate) struct EnvDocument {
+    entries: Vec<Entry>,
+}
+
+impl EnvDocument {
+    pub(crate) fn parse(buf: &str) -> Result<Self, String> {
+        let raw = lex(buf)?;
+        let mut entries = Vec::with_capacity(raw.len());
+        let mut seen = std::collections::HashSet::new();
+
+        for r in raw {
+            let key = &buf[r.key_span.start..r.key_span.end];
+            let key_str = key.trim().to_owned();
+            if !seen.insert(key_str.clone()) {
+                return Err(format!("duplicate key '{}'", key_str));
+            }
+            entries.push(Entry {
+                key: key_str,
+                export: r.export,
+                key_span: r.key_span,
+                value_span: r.value_span,
+                quote: r.quote,
+            });
+        }
+        Ok(Self { entries })
+    }
+
+    pub(crate) fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.key == key)
+    }
+}
+
+impl Entry {
+    /// The assignment as written: `export` (if present) through the end of
+    /// the value, without a trailing comment.
+    pub(crate) fn assignment_span(&self) -> Span {
+        let start = self
+            .export
+            .map_or(self.key_span.start, |export| export.start);
+        Span::new(start, self.value_span.end)
+    }
+}
+
+/// `content` with `fragment` spliced over the value at `span`. A `# comment`
+/// after the value on its line is kept byte for byte; `spacing` decides the
+/// gap before it, and a non-empty value never ends up touching the `#`.
+pub(crate) fn replace_before_comment(
+    content: &str,
+    span: Span,
+    fragment: &str,
+    spacing: CommentSpacing,
+) -> String {
+    let rest = &content[span.end..];
+    let after = rest.trim_start_matches([' ', '\t']);
+    let gap = &rest[..rest.len() - after.len()];
+    let (end, gap) = if after.starts_with('#') {
+        // width of the last line of a value, in characters
+        let width = |text: &str| text.rsplit('\n').next().map_or(0, |l| l.chars().count());
+        let old = &content[span.start..span.end];
+        let gap = match spacing {
+            CommentSpacing::Align if !gap.contains('\t') && !fragment.contains('\n') => {
+                let column = width(old) + gap.len();
+                " ".repeat(column.saturating_sub(width(fragment)).max(1))
+            }
+            CommentSpacing::Single => " ".into(),
+            _ if gap.is_empty() && !fragment.is_empty() => " ".into(),
+            _ => gap.to_string(),
+        };
+        (span.end + rest.len() - after.len(), gap)
+    } else {
+        (span.end, String::new())
+    };
+    let mut out = String::with_capacity(content.len() + fragment.len() + gap.len());
+    out.push_str(&content[..span.start]);
+    out.push_str(fragment);
+    out.push_str(&gap);
+    out.push_str(&content[end..]);
+    out
+}
+
+/// A value span's text as the application reads it: double quotes removed
+/// and `\"`, `\\`, `\n`, `\r`, `\t` unescaped, single quotes removed with
+/// the body kept literally, unquoted values as written.
+pub(crate) fn decode_value(raw: &str) -> String {
+    if let Some(body) = raw.strip_prefix('\'').and_then(|r| r.strip_suffix('\'')) {
+        return body.to_string();
+    }
+    match raw.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
+        Some(body) => unescape(body),
+        None => raw.to_string(),
+    }
+}
+
+/// The body of a double-quoted value with its escapes decoded.
+pub(crate) fn unescape(body: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some(other @ ('"' | '\\')) => out.push(other),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+// ───────────────────────── 3. PUBLIC PARSER ─────────────────────────
+pub struct EnvParser;
+impl Default for EnvParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EnvParser {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl BytePreservingParser for EnvParser {
+    fn validate_syntax(&self, content: &str) -> Result<(), String> {
+        // full parse catches duplicates / missing '=' / unterminated quotes
+        EnvDocument::parse(content).map(|_| ())
+    }
+
+    fn find_value_span(&self, content: &str, path: &[String]) -> Result<Span, String> {
+        if path.len() != 1 {
+            return Err("ENV path must contain exactly one key".into());
+        }
+        let doc = EnvDocument::parse(content)?;
+        find_value_span_in_doc(&doc, path)
+    }
+
+    fn validate_and_find_span(&self, content: &str, path: &[String]) -> Result<Span, String> {
+        // the ENV parse is the validation, so one parse covers both
+        self.find_value_span(content, path)
+    }
+}
+
+pub(crate) fn find_value_span_in_doc(doc: &EnvDocument, path: &[String]) -> Result<Span, String> {
+    if path.len() != 1 {
+        return Err("ENV path must contain exactly one key".into());
+    }
+    let key = paths::literal(&path[0]);
+    match doc.get(key) {
+        Some(entry) => Ok(entry.value_span),
+        None => Err(format!("key '{}' not found", key)),
+    }
+}
+
+// Positional validation for ENV, returning first error with line/column
+#[derive(Debug, Clone)]
+pub struct PosError {
+    pub msg: String,
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+}
+
+impl PosError {
+    fn at(content: &str, offset: usize, msg: String) -> Self {
+        let (line, column) = line_index::line_col_at(content, offset, ColumnUnit::Char);
+        Self {
+            msg,
+            line,
+            column,
+            offset,
+        }
+    }
+}
+
+pub fn validate_with_pos(content: &str) -> Result<(), PosError> {
+    // First stage: lexical errors (missing '=', unterminated quotes) with line/column
+    let raw = match lexer::lex_with_pos(content) {
+        Ok(v) => v,
+        Err(e) => return Err(PosError::at(content, e.offset, e.msg)),
+    };
+
+    // Second stage: duplicate key detection with position of the second occurrence
+    let mut seen = std::collections::HashSet::new();
+    for r in &raw {
+        let key = &content[r.key_span.start..r.key_span.end];
+        let key_trim = key.trim();
+        if !seen.insert(key_trim.to_owned()) {
+            return Err(PosError::at(
+                content,
+                r.key_span.start,
+                format!("duplicate key '{}'", key_trim),
+            ));
+        }
+    }
+
+    Ok(())
+}
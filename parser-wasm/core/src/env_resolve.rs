@@ -0,0 +1,385 @@
+//! `${VAR}` / `$VAR` interpolation for ENV files.
+//!
+//! References resolve against the file's own keys, in any order, the way
+//! dotenv-expand and docker-compose read them: single-quoted values are
+//! literal and `\$` is a literal dollar. A reference to an undefined key, or
+//! one that leads back to the entry being expanded, expands to nothing and
+//! is reported as a diagnostic.
+//!
+//! The same scanner finds the references a key rename has to follow.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::Serialize;
+use tsify::Tsify;
+
+use crate::env_parser::{self, EnvDocument, Quote};
+use crate::multi_validation::DetailedError;
+use crate::Span;
+
+/// Every entry's value plus the reference graph between keys.
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct ResolvedEnv {
+    pub entries: Vec<ResolvedEntry>,
+    /// Keys each entry references, in order of first use.
+    #[tsify(type = "Record<string, string[]>")]
+    pub dependencies: BTreeMap<String, Vec<String>>,
+    /// `env.undefined_reference` and `env.reference_cycle`, in file order.
+    pub diagnostics: Vec<DetailedError>,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct ResolvedEntry {
+    pub key: String,
+    /// The value span's text, quotes included.
+    pub raw: String,
+    /// Quotes removed and escapes decoded; references expanded when asked.
+    pub value: String,
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+pub enum RenameKind {
+    /// The key of an entry that defines the variable.
+    Key,
+    /// A `${NAME}` or `$NAME` reference to it.
+    Reference,
+}
+
+/// One replacement of a rename, located in the content before it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct RenameEdit {
+    pub kind: RenameKind,
+    pub line: usize,
+    pub column: usize,
+    #[serde(flatten)]
+    pub span: Span,
+    /// What replaces the span.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct RenamedEnvFile {
+    pub name: String,
+    pub content: String,
+    pub edits: Vec<RenameEdit>,
+}
+
+/// A renamed file plus the other files of the set, in the order given.
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct RenamedEnv {
+    pub content: String,
+    pub edits: Vec<RenameEdit>,
+    pub files: Vec<RenamedEnvFile>,
+}
+
+enum Piece<'a> {
+    /// Decoded text.
+    Text(String),
+    Ref {
+        name: &'a str,
+        span: Span,
+    },
+}
+
+/// Resolves `doc`'s entries. `position` maps a byte offset to the line and
+/// column diagnostics report.
+pub(crate) fn resolve(
+    content: &str,
+    doc: &EnvDocument,
+    expand: bool,
+    position: impl Fn(usize) -> (usize, usize),
+) -> ResolvedEnv {
+    let entries = doc.entries();
+    let index: HashMap<&str, usize> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| (entry.key.as_str(), i))
+        .collect();
+    let pieces: Vec<Vec<Piece>> = entries
+        .iter()
+        .map(|entry| split(content, entry.value_span, entry.quote))
+        .collect();
+    // per entry: (piece index, referenced entry) for each reference
+    let edges: Vec<Vec<(usize, Option<usize>)>> = pieces
+        .iter()
+        .map(|pieces| {
+            pieces
+                .iter()
+                .enumerate()
+                .filter_map(|(i, piece)| match piece {
+                    Piece::Ref { name, .. } => Some((i, index.get(name).copied())),
+                    Piece::Text(_) => None,
+                })
+                .collect()
+        })
+        .collect();
+
+    let (order, cyclic) = topological_order(&edges);
+    let mut values: Vec<String> = vec![String::new(); entries.len()];
+    for &node in &order {
+        let mut value = String::new();
+        for (i, piece) in pieces[node].iter().enumerate() {
+            match piece {
+                Piece::Text(text) => value.push_str(text),
+                Piece::Ref { name, .. } => {
+                    if let Some(&target) = index.get(name) {
+                        if !cyclic.contains(&(node, i)) {
+                            let resolved = values[target].clone();
+                            value.push_str(&resolved);
+                        }
+                    }
+                }
+            }
+        }
+        values[node] = value;
+    }
+
+    let mut diagnostics = Vec::new();
+    let mut dependencies = BTreeMap::new();
+    for (node, entry) in entries.iter().enumerate() {
+        let mut refs: Vec<String> = Vec::new();
+        for (i, piece) in pieces[node].iter().enumerate() {
+            let Piece::Ref { name, span } = piece else {
+                continue;
+            };
+            let problem = if !index.contains_key(name) {
+                Some((
+                    "env.undefined_reference",
+                    format!("'{}' references undefined variable '{name}'", entry.key),
+                ))
+            } else if cyclic.contains(&(node, i)) {
+                Some((
+                    "env.reference_cycle",
+                    format!(
+                        "'{}' references '{name}', which refers back to it",
+                        entry.key
+                    ),
+                ))
+            } else {
+                None
+            };
+            if let Some((code, message)) = problem {
+                let (line, column) = position(span.start);
+                diagnostics.push(DetailedError {
+                    message,
+                    code: Some(code),
+                    line,
+                    column,
+                    span: *span,
+                    sub_errors: Vec::new(),
+                    related: Vec::new(),
+                });
+            }
+            if !refs.iter().any(|r| r == name) {
+                refs.push(name.to_string());
+            }
+        }
+        dependencies.insert(entry.key.clone(), refs);
+    }
+
+    let entries = entries
+        .iter()
+        .zip(values)
+        .map(|(entry, value)| {
+            let raw = &content[entry.value_span.start..entry.value_span.end];
+            ResolvedEntry {
+                key: entry.key.clone(),
+                raw: raw.to_string(),
+                value: if expand {
+                    value
+                } else {
+                    env_parser::decode_value(raw)
+                },
+                span: entry.value_span,
+            }
+        })
+        .collect();
+    ResolvedEnv {
+        entries,
+        dependencies,
+        diagnostics,
+    }
+}
+
+/// `content` with every definition of `old` renamed to `new` and every
+/// reference to it rewritten in the same style, plus those edits in order.
+/// Single-quoted values and `\$` escapes are literal, so they are left.
+pub(crate) fn rename(
+    content: &str,
+    doc: &EnvDocument,
+    old: &str,
+    new: &str,
+    position: impl Fn(usize) -> (usize, usize),
+) -> (String, Vec<RenameEdit>) {
+    let mut edits = Vec::new();
+    let mut edit = |kind, span: Span, text: String| {
+        let (line, column) = position(span.start);
+        edits.push(RenameEdit {
+            kind,
+            line,
+            column,
+            span,
+            text,
+        });
+    };
+    for entry in doc.entries() {
+        if entry.key == old {
+            edit(RenameKind::Key, entry.key_span, new.to_string());
+        }
+        for piece in split(content, entry.value_span, entry.quote) {
+            let Piece::Ref { name, span } = piece else {
+                continue;
+            };
+            if name != old {
+                continue;
+            }
+            let text = if content[span.start..].starts_with("${") {
+                format!("${{{new}}}")
+            } else {
+                format!("${new}")
+            };
+            edit(RenameKind::Reference, span, text);
+        }
+    }
+    let mut out = String::with_capacity(content.len());
+    let mut at = 0;
+    for edit in &edits {
+        out.push_str(&content[at..edit.span.start]);
+        out.push_str(&edit.text);
+        at = edit.span.end;
+    }
+    out.push_str(&content[at..]);
+    (out, edits)
+}
+
+/// Post-order over the reference graph, so every entry comes after the
+/// entries it references, plus the `(entry, piece)` references that close a
+/// cycle. Iterative: a long reference chain must not exhaust the stack.
+fn topological_order(
+    edges: &[Vec<(usize, Option<usize>)>],
+) -> (Vec<usize>, HashSet<(usize, usize)>) {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        New,
+        Open,
+        Done,
+    }
+    let mut state = vec![State::New; edges.len()];
+    let mut order = Vec::with_capacity(edges.len());
+    let mut cyclic = HashSet::new();
+    for root in 0..edges.len() {
+        if state[root] != State::New {
+            continue;
+        }
+        state[root] = State::Open;
+        let mut stack = vec![(root, 0)];
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            let Some(&(piece, target)) = edges[node].get(*next) else {
+                state[node] = State::Done;
+                order.push(node);
+                stack.pop();
+                continue;
+            };
+            *next += 1;
+            match target.map(|t| (t, state[t])) {
+                Some((t, State::New)) => {
+                    state[t] = State::Open;
+                    stack.push((t, 0));
+                }
+                Some((_, State::Open)) => {
+                    cyclic.insert((node, piece));
+                }
+                _ => {}
+            }
+        }
+    }
+    (order, cyclic)
+}
+
+/// Splits a value span into decoded text and references.
+fn split(content: &str, span: Span, quote: Option<Quote>) -> Vec<Piece<'_>> {
+    let raw = &content[span.start..span.end];
+    match quote {
+        Some(Quote::Single) => vec![Piece::Text(raw[1..raw.len() - 1].to_string())],
+        Some(Quote::Double) => scan(&raw[1..raw.len() - 1], span.start + 1, true),
+        None => scan(raw, span.start, false),
+    }
+}
+
+/// `escapes`: the body is double-quoted, so backslash escapes are decoded
+/// and an escaped backslash does not escape a following `$`.
+fn scan(body: &str, base: usize, escapes: bool) -> Vec<Piece<'_>> {
+    let bytes = body.as_bytes();
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut literal_start = 0;
+    let flush = |text: &mut String, literal: &str| {
+        if escapes {
+            text.push_str(&env_parser::unescape(literal));
+        } else {
+            text.push_str(literal);
+        }
+    };
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if bytes.get(i + 1) == Some(&b'$') => {
+                flush(&mut text, &body[literal_start..i]);
+                text.push('$');
+                i += 2;
+                literal_start = i;
+            }
+            b'\\' if escapes => i += 2,
+            b'$' => match reference_at(&body[i..]) {
+                Some((name, len)) => {
+                    flush(&mut text, &body[literal_start..i]);
+                    if !text.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    }
+                    pieces.push(Piece::Ref {
+                        name,
+                        span: Span::new(base + i, base + i + len),
+                    });
+                    i += len;
+                    literal_start = i;
+                }
+                None => i += 1,
+            },
+            _ => i += 1,
+        }
+    }
+    flush(&mut text, &body[literal_start..]);
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+    pieces
+}
+
+/// The variable name and byte length of a `${NAME}` or `$NAME` reference at
+/// the start of `text`, which begins with `$`.
+fn reference_at(text: &str) -> Option<(&str, usize)> {
+    let rest = &text[1..];
+    if let Some(braced) = rest.strip_prefix('{') {
+        let close = braced.find('}')?;
+        let name = &braced[..close];
+        return is_name(name).then_some((name, close + 3));
+    }
+    let len = rest
+        .bytes()
+        .enumerate()
+        .take_while(|&(i, b)| b == b'_' || b.is_ascii_alphabetic() || (i > 0 && b.is_ascii_digit()))
+        .count();
+    (len > 0).then(|| (&rest[..len], len + 1))
+}
+
+pub(crate) fn is_name(name: &str) -> bool {
+    let mut bytes = name.bytes();
+    bytes
+        .next()
+        .is_some_and(|b| b == b'_' || b.is_ascii_alphabetic())
+        && bytes.all(|b| b == b'_' || b.is_ascii_alphanumeric())
+}
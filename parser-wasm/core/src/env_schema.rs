@@ -0,0 +1,168 @@
+//! JSON Schema validation of `.env` content.
+//!
+//! ENV values are always strings, so the file is validated as an object of
+//! its variables after each value is coerced to a type the schema declares
+//! for it, through `properties`, a matching `patternProperties` pattern or
+//! `additionalProperties`: `integer`, `number`, `boolean` (`true` /
+//! `false`), or `object` / `array` written as JSON. A value that spells none of the declared
+//! types stays a string for the schema to reject. Errors point at the value
+//! of the variable their instance path starts with; errors about the file
+//! as a whole, such as a missing `required` variable, have no position.
+
+use std::collections::HashMap;
+
+use jsonschema::JSONSchema;
+use serde_json::{Map, Number, Value};
+
+use crate::document::FileType;
+use crate::env_parser::{decode_value, EnvDocument};
+use crate::parse_cache;
+use crate::schema::{
+    self, in_place_subschemas, schema_issue_outcome, RegisteredSchema, SchemaOutcome,
+    SchemaValidationOptions,
+};
+use crate::Span;
+
+/// Validates `content` against inline schema text.
+pub(crate) fn validate_source(
+    content: &str,
+    schema: &str,
+    opts: &SchemaValidationOptions,
+) -> SchemaOutcome {
+    match schema::compile_source(schema, opts) {
+        Ok(compiled) => validate(content, &compiled, opts),
+        Err(outcome) => outcome,
+    }
+}
+
+/// Validates `content` against the schema registered as `schema_id`.
+pub(crate) fn validate_registered(
+    content: &str,
+    schema_id: &str,
+    opts: &SchemaValidationOptions,
+) -> SchemaOutcome {
+    match schema::registered_for(schema_id, opts) {
+        Some(registered) => validate(content, &registered, opts),
+        None => schema_issue_outcome(format!("Schema '{schema_id}' is not registered")),
+    }
+}
+
+fn validate(
+    content: &str,
+    schema: &RegisteredSchema,
+    opts: &SchemaValidationOptions,
+) -> SchemaOutcome {
+    parse_cache::with_document(FileType::Env, content, |doc| {
+        let summary = doc.summary();
+        if let Some(message) = &summary.message {
+            return schema::syntax_outcome(message.clone(), summary.span, content, opts);
+        }
+        match doc.env_doc() {
+            Ok(env) => validate_env(content, env, schema, opts),
+            Err(message) => schema::syntax_outcome(message, None, content, opts),
+        }
+    })
+}
+
+fn validate_env(
+    content: &str,
+    env: &EnvDocument,
+    schema: &RegisteredSchema,
+    opts: &SchemaValidationOptions,
+) -> SchemaOutcome {
+    let mut instance = Map::new();
+    let mut spans: HashMap<&str, Span> = HashMap::new();
+    let mut patterns = Patterns::default();
+    for entry in env.entries() {
+        let span = entry.value_span;
+        let text = decode_value(&content[span.start..span.end]);
+        let value = coerce(
+            text,
+            &declared_types(&schema.value, &entry.key, &mut patterns),
+        );
+        instance.insert(entry.key.clone(), value);
+        spans.insert(&entry.key, span);
+    }
+    let span_of = |pointer: &str| {
+        let segment = pointer.strip_prefix('/')?.split('/').next()?;
+        spans
+            .get(segment.replace("~1", "/").replace("~0", "~").as_str())
+            .copied()
+    };
+    schema::validate_mapped(schema, &Value::Object(instance), content, opts, &span_of)
+}
+
+/// The `type`s the schema gives the variable `key`, in the order written:
+/// those of its `properties` entry and of the `patternProperties` it
+/// matches, or of `additionalProperties` when neither names it.
+fn declared_types<'a>(root: &'a Value, key: &str, patterns: &mut Patterns<'a>) -> Vec<&'a str> {
+    let mut members = Vec::new();
+    for schema in in_place_subschemas(root, &[root]) {
+        let named = members.len();
+        members.extend(schema.get("properties").and_then(|p| p.get(key)));
+        if let Some(Value::Object(by_pattern)) = schema.get("patternProperties") {
+            for (pattern, member) in by_pattern {
+                if patterns.matches(pattern, key) {
+                    members.push(member);
+                }
+            }
+        }
+        if members.len() == named {
+            members.extend(schema.get("additionalProperties").filter(|s| s.is_object()));
+        }
+    }
+    let mut types = Vec::new();
+    for schema in in_place_subschemas(root, &members) {
+        match schema.get("type") {
+            Some(Value::String(ty)) => types.push(ty.as_str()),
+            Some(Value::Array(list)) => types.extend(list.iter().filter_map(Value::as_str)),
+            _ => {}
+        }
+    }
+    types
+}
+
+/// Compiled `patternProperties` patterns; `None` for one that does not
+/// compile, which is taken to match.
+#[derive(Default)]
+struct Patterns<'a>(HashMap<&'a str, Option<JSONSchema>>);
+
+impl<'a> Patterns<'a> {
+    fn matches(&mut self, pattern: &'a str, key: &str) -> bool {
+        let compiled = self.0.entry(pattern).or_insert_with(|| {
+            JSONSchema::compile(&serde_json::json!({ "pattern": pattern })).ok()
+        });
+        compiled
+            .as_ref()
+            .is_none_or(|schema| schema.is_valid(&Value::String(key.to_string())))
+    }
+}
+
+/// `text` as the first of `types` it spells, otherwise as a string.
+fn coerce(text: String, types: &[&str]) -> Value {
+    for ty in types {
+        let coerced = match *ty {
+            "integer" => text.parse::<i64>().ok().map(Value::from),
+            "number" => match text.parse::<i64>() {
+                Ok(n) => Some(Value::from(n)),
+                Err(_) => text
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(Number::from_f64)
+                    .map(Value::Number),
+            },
+            "boolean" => match text.as_str() {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            "object" => serde_json::from_str(&text).ok().filter(Value::is_object),
+            "array" => serde_json::from_str(&text).ok().filter(Value::is_array),
+            _ => None,
+        };
+        if let Some(value) = coerced {
+            return value;
+        }
+    }
+    Value::String(text)
+}
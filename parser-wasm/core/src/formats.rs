@@ -0,0 +1,282 @@
+//! Format registry.
+//!
+//! Every file format is one [`Registration`]: the names `file_type`
+//! arguments accept, and a handler implementing [`BytePreservingParser`] for
+//! lookups and edits plus [`Validator`] for whole-document checks. The
+//! stateless entry points and `Document` dispatch through it, so a new format
+//! is added here rather than in each entry point. `Document` still
+//! special-cases JSON and ENV where it caches their tokens and entries.
+
+#[cfg(feature = "xml")]
+use xmlparser::{Error as XmlError, Tokenizer};
+
+use serde::de::IgnoredAny;
+
+use crate::document::FileType;
+use crate::encoding::bom_len;
+#[cfg(feature = "env")]
+use crate::env_parser;
+use crate::json_parser::decode_json_string;
+#[cfg(feature = "xml")]
+use crate::limits::TokenBudget;
+use crate::line_index::{self, ColumnUnit, LineIndex};
+#[cfg(feature = "xml")]
+use crate::multi_validation::validate_xml_multi;
+#[cfg(feature = "env")]
+use crate::multi_validation::DetailedError;
+use crate::multi_validation::{
+    infer_json_span, validate_json_multi, MultiValidationOptions, MultiValidationResult,
+};
+#[cfg(feature = "xml")]
+use crate::xml_parser;
+#[cfg(feature = "env")]
+use crate::{escape_env_string, EnvParser};
+use crate::{
+    escape_json_string, is_json_literal, BytePreservingParser, JsonParser, Span, ValidationResult,
+};
+#[cfg(feature = "xml")]
+use crate::{escape_xml_string, xml_error_summary, XmlParser};
+
+/// Whole-document checks and value rendering: the half of a format that
+/// [`BytePreservingParser`] does not cover.
+pub(crate) trait Validator {
+    /// First error, behind `validate`.
+    fn summary(&self, content: &str) -> ValidationResult;
+    /// Up to `cap` diagnostics, behind `validate_multi`.
+    fn diagnostics(
+        &self,
+        content: &str,
+        cap: usize,
+        index: Option<&LineIndex>,
+        opts: &MultiValidationOptions,
+    ) -> MultiValidationResult;
+    /// Renders `new_val` as a fragment that can be spliced into a value span
+    /// (quoting and escaping as needed).
+    fn format_value(&self, new_val: &str) -> String;
+    /// Inverse of [`Validator::format_value`]: the text of a value span as
+    /// the user edits it.
+    fn decode_value(&self, raw: &str) -> String;
+
+    /// [`Validator::format_value`] for the value at `span` of `content`.
+    /// Formats whose values can sit in a different lexical context (XML
+    /// CDATA sections) override this and the decode counterpart.
+    fn format_value_at(&self, _content: &str, _span: Span, new_val: &str) -> String {
+        self.format_value(new_val)
+    }
+
+    /// [`Validator::decode_value`] for the value at `span` of `content`.
+    fn decode_value_at(&self, content: &str, span: Span) -> String {
+        self.decode_value(&content[span.start..span.end])
+    }
+}
+
+pub(crate) trait Format: BytePreservingParser + Validator + Sync {}
+
+impl<T: BytePreservingParser + Validator + Sync> Format for T {}
+
+pub(crate) struct Registration {
+    pub(crate) file_type: FileType,
+    /// Lowercase `file_type` names; the first is the one `capabilities()`
+    /// reports.
+    pub(crate) names: &'static [&'static str],
+    pub(crate) format: &'static dyn Format,
+}
+
+static FORMATS: &[Registration] = &[
+    Registration {
+        file_type: FileType::Json,
+        names: &["json"],
+        format: &JsonParser,
+    },
+    #[cfg(feature = "xml")]
+    Registration {
+        file_type: FileType::Xml,
+        names: &["xml", "config"],
+        format: &XmlParser,
+    },
+    #[cfg(feature = "env")]
+    Registration {
+        file_type: FileType::Env,
+        names: &["env"],
+        format: &EnvParser,
+    },
+];
+
+pub(crate) fn registrations() -> &'static [Registration] {
+    FORMATS
+}
+
+impl FileType {
+    pub(crate) fn parse(raw: &str) -> Option<Self> {
+        let name = raw.to_lowercase();
+        FORMATS
+            .iter()
+            .find(|reg| reg.names.contains(&name.as_str()))
+            .map(|reg| reg.file_type)
+    }
+
+    pub(crate) fn format(self) -> &'static dyn Format {
+        FORMATS
+            .iter()
+            .find(|reg| reg.file_type == self)
+            .map(|reg| reg.format)
+            .expect("every FileType is registered")
+    }
+}
+
+#[cfg(all(test, feature = "xml", feature = "env"))]
+thread_local! {
+    /// Whole-document `serde_json` passes made by `JsonParser::summary`.
+    pub(crate) static JSON_SUMMARY_PARSES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+impl Validator for JsonParser {
+    fn summary(&self, content: &str) -> ValidationResult {
+        #[cfg(all(test, feature = "xml", feature = "env"))]
+        JSON_SUMMARY_PARSES.with(|n| n.set(n.get() + 1));
+        match serde_json::from_str::<IgnoredAny>(&content[bom_len(content)..]) {
+            Ok(_) => ValidationResult::success(),
+            Err(e) => {
+                // serde_json counts columns in bytes; report characters
+                let start = line_index::offset_at(content, e.line(), e.column(), ColumnUnit::Byte);
+                let span = infer_json_span(content, start);
+                let (line, column) = line_index::line_col_at(content, span.start, ColumnUnit::Char);
+                ValidationResult::failure(e.to_string(), line, column, span)
+            }
+        }
+    }
+
+    fn diagnostics(
+        &self,
+        content: &str,
+        cap: usize,
+        index: Option<&LineIndex>,
+        _opts: &MultiValidationOptions,
+    ) -> MultiValidationResult {
+        validate_json_multi(content, cap, index)
+    }
+
+    fn format_value(&self, new_val: &str) -> String {
+        if is_json_literal(new_val) {
+            new_val.to_string()
+        } else {
+            format!("\"{}\"", escape_json_string(new_val))
+        }
+    }
+
+    fn decode_value(&self, raw: &str) -> String {
+        decode_json_string(raw).unwrap_or_else(|| raw.to_string())
+    }
+}
+
+#[cfg(feature = "xml")]
+impl Validator for XmlParser {
+    fn summary(&self, content: &str) -> ValidationResult {
+        // Iterate tokens and stop at first error to get precise position
+        let mut err: Option<XmlError> = None;
+        let mut budget = TokenBudget::new();
+        for tok in Tokenizer::from(content) {
+            if let Err(e) = tok {
+                err = Some(e);
+                break;
+            }
+            if budget.spend().is_err() {
+                break;
+            }
+        }
+        match err {
+            Some(e) => xml_error_summary(content, &e),
+            None => ValidationResult::success(),
+        }
+    }
+
+    fn diagnostics(
+        &self,
+        content: &str,
+        cap: usize,
+        index: Option<&LineIndex>,
+        opts: &MultiValidationOptions,
+    ) -> MultiValidationResult {
+        validate_xml_multi(content, cap, index, opts.xml_resync)
+    }
+
+    fn format_value(&self, new_val: &str) -> String {
+        escape_xml_string(new_val)
+    }
+
+    fn decode_value(&self, raw: &str) -> String {
+        xml_parser::decode_entities(raw)
+    }
+
+    fn format_value_at(&self, content: &str, span: Span, new_val: &str) -> String {
+        if xml_parser::in_cdata(content, span) {
+            xml_parser::escape_cdata(new_val)
+        } else if xml_parser::bare_instruction(content, span) {
+            // `<?target?>` gains content: keep it apart from the target
+            format!(" {}", self.format_value(new_val))
+        } else {
+            self.format_value(new_val)
+        }
+    }
+
+    fn decode_value_at(&self, content: &str, span: Span) -> String {
+        let raw = &content[span.start..span.end];
+        if xml_parser::in_cdata(content, span) {
+            raw.to_string()
+        } else {
+            self.decode_value(raw)
+        }
+    }
+}
+
+#[cfg(feature = "env")]
+impl Validator for EnvParser {
+    fn summary(&self, content: &str) -> ValidationResult {
+        match env_parser::validate_with_pos(content) {
+            Ok(_) => ValidationResult::success(),
+            Err(e) => {
+                ValidationResult::failure(e.msg, e.line, e.column, Span::new(e.offset, e.offset))
+            }
+        }
+    }
+
+    fn diagnostics(
+        &self,
+        content: &str,
+        _cap: usize,
+        _index: Option<&LineIndex>,
+        _opts: &MultiValidationOptions,
+    ) -> MultiValidationResult {
+        match env_parser::validate_with_pos(content) {
+            Ok(_) => MultiValidationResult::success(),
+            Err(e) => {
+                let summary = DetailedError {
+                    message: e.msg,
+                    code: None,
+                    line: e.line,
+                    column: e.column,
+                    span: Span::new(e.offset, e.offset),
+                    sub_errors: Vec::new(),
+                    related: Vec::new(),
+                };
+                MultiValidationResult::single(summary)
+            }
+        }
+    }
+
+    fn format_value(&self, new_val: &str) -> String {
+        // an opening quote would start a quoted value, which then runs on
+        // past the line (and a trailing comment) looking for its close
+        let needs_quotes =
+            new_val.contains([' ', '#', '\n', '\r', '\t']) || new_val.starts_with(['"', '\'']);
+        if needs_quotes {
+            format!("\"{}\"", escape_env_string(new_val))
+        } else {
+            new_val.to_string()
+        }
+    }
+
+    fn decode_value(&self, raw: &str) -> String {
+        env_parser::decode_value(raw)
+    }
+}
@@ -0,0 +1,224 @@
+//! Skeleton documents from a registered schema.
+//!
+//! Each schema node gives its value by the first of `const`, `default`,
+//! the first `examples` entry and the first `enum` option that it has.
+//! Failing those, objects get their required properties (all of them with
+//! `include_optional`), arrays get `minItems` items and scalars an empty
+//! value of their `type`, raised to `minimum` for numbers. Local `$ref`s are
+//! followed, `allOf` branches are merged and `anyOf`/`oneOf` take their
+//! first branch.
+//!
+//! Registered schemas are kept as parsed values, which do not remember key
+//! order, so required properties come first in `required` order and the
+//! others follow alphabetically.
+
+use serde_json::Value as Json;
+
+use crate::convert::{ConvertOptions, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstantiateOptions {
+    /// Format of the document, `json` by default.
+    pub file_type: String,
+    /// Also fill in properties the schema does not require.
+    pub include_optional: bool,
+    /// How the skeleton is written as XML or ENV.
+    pub convert: ConvertOptions,
+}
+
+impl Default for InstantiateOptions {
+    fn default() -> Self {
+        Self {
+            file_type: "json".into(),
+            include_optional: false,
+            convert: ConvertOptions::default(),
+        }
+    }
+}
+
+/// Nesting past which a (typically recursive) schema gives `null`.
+const MAX_INSTANCE_DEPTH: usize = 32;
+
+pub(crate) fn instance(root: &Json, include_optional: bool) -> Value {
+    Builder {
+        root,
+        include_optional,
+    }
+    .value(root, 0)
+}
+
+struct Builder<'a> {
+    root: &'a Json,
+    include_optional: bool,
+}
+
+impl<'a> Builder<'a> {
+    fn value(&self, schema: &'a Json, depth: usize) -> Value {
+        let schema = self.deref(schema);
+        if depth > MAX_INSTANCE_DEPTH {
+            return Value::Null;
+        }
+        let Json::Object(keywords) = schema else {
+            return Value::Null;
+        };
+        let given = keywords
+            .get("const")
+            .or_else(|| keywords.get("default"))
+            .or_else(|| keywords.get("examples").and_then(|e| e.get(0)))
+            .or_else(|| keywords.get("enum").and_then(|e| e.get(0)));
+        if let Some(value) = given {
+            return from_json(value);
+        }
+        if let Some(Json::Array(branches)) = keywords.get("allOf") {
+            let mut merged = Vec::new();
+            for branch in branches {
+                match self.value(branch, depth + 1) {
+                    Value::Object(members) => merge(&mut merged, members),
+                    Value::Null => {}
+                    other => return other,
+                }
+            }
+            if let Value::Object(own) = self.object(keywords, depth) {
+                merge(&mut merged, own);
+            }
+            return Value::Object(merged);
+        }
+        let first_branch = ["anyOf", "oneOf"]
+            .iter()
+            .find_map(|keyword| keywords.get(*keyword)?.get(0));
+        if let Some(branch) = first_branch {
+            return self.value(branch, depth + 1);
+        }
+        let ty = match keywords.get("type") {
+            Some(Json::String(ty)) => Some(ty.as_str()),
+            Some(Json::Array(types)) => types
+                .iter()
+                .filter_map(Json::as_str)
+                .find(|ty| *ty != "null")
+                .or(types.first().and_then(Json::as_str)),
+            _ => None,
+        };
+        let ty = ty.or_else(|| {
+            if keywords.contains_key("properties") || keywords.contains_key("required") {
+                Some("object")
+            } else if keywords.contains_key("items") {
+                Some("array")
+            } else {
+                None
+            }
+        });
+        match ty {
+            Some("object") => self.object(keywords, depth),
+            Some("array") => {
+                let count = keywords.get("minItems").and_then(Json::as_u64).unwrap_or(0);
+                let items = match keywords.get("items") {
+                    Some(items @ Json::Object(_)) => Some(items),
+                    _ => None,
+                };
+                let items = match items {
+                    Some(items) if count > 0 => (0..count.min(MAX_INSTANCE_DEPTH as u64))
+                        .map(|_| self.value(items, depth + 1))
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                Value::Array(items)
+            }
+            Some("string") => Value::String(String::new()),
+            Some(ty @ ("number" | "integer")) => {
+                let bound = |keyword| keywords.get(keyword).and_then(Json::as_f64);
+                let least = match (bound("minimum"), bound("exclusiveMinimum")) {
+                    (Some(min), _) if ty == "integer" => min.ceil(),
+                    (Some(min), _) => min,
+                    (None, Some(min)) if ty == "integer" => min.floor() + 1.0,
+                    (None, Some(min)) => min + 1.0,
+                    (None, None) => 0.0,
+                };
+                Value::Number(format!("{least}"))
+            }
+            Some("boolean") => Value::Bool(false),
+            _ => Value::Null,
+        }
+    }
+
+    fn object(&self, keywords: &'a serde_json::Map<String, Json>, depth: usize) -> Value {
+        let properties = keywords.get("properties").and_then(Json::as_object);
+        let required: Vec<&str> = keywords
+            .get("required")
+            .and_then(Json::as_array)
+            .map(|names| names.iter().filter_map(Json::as_str).collect())
+            .unwrap_or_default();
+        let mut names: Vec<&str> = Vec::new();
+        for name in &required {
+            if !names.contains(name) {
+                names.push(name);
+            }
+        }
+        if self.include_optional {
+            for name in properties.into_iter().flat_map(|p| p.keys()) {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+        }
+        let members = names
+            .into_iter()
+            .map(|name| {
+                let value = match properties.and_then(|p| p.get(name)) {
+                    Some(schema) => self.value(schema, depth + 1),
+                    None => Value::Null,
+                };
+                (name.to_string(), value)
+            })
+            .collect();
+        Value::Object(members)
+    }
+
+    /// Follows local `$ref`s (`#/definitions/…`) to the schema they name.
+    fn deref(&self, schema: &'a Json) -> &'a Json {
+        let mut current = schema;
+        for _ in 0..MAX_INSTANCE_DEPTH {
+            let target = current
+                .get("$ref")
+                .and_then(Json::as_str)
+                .and_then(|target| target.strip_prefix('#'))
+                .and_then(|pointer| {
+                    if pointer.is_empty() {
+                        Some(self.root)
+                    } else {
+                        self.root.pointer(pointer)
+                    }
+                });
+            match target {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        current
+    }
+}
+
+/// Members of `extra` added to `into`, replacing any of the same name.
+fn merge(into: &mut Vec<(String, Value)>, extra: Vec<(String, Value)>) {
+    for (name, value) in extra {
+        match into.iter_mut().find(|(key, _)| *key == name) {
+            Some(slot) => slot.1 = value,
+            None => into.push((name, value)),
+        }
+    }
+}
+
+fn from_json(value: &Json) -> Value {
+    match value {
+        Json::Null => Value::Null,
+        Json::Bool(b) => Value::Bool(*b),
+        Json::Number(n) => Value::Number(n.to_string()),
+        Json::String(s) => Value::String(s.clone()),
+        Json::Array(items) => Value::Array(items.iter().map(from_json).collect()),
+        Json::Object(members) => Value::Object(
+            members
+                .iter()
+                .map(|(key, value)| (key.clone(), from_json(value)))
+                .collect(),
+        ),
+    }
+}
@@ -0,0 +1,84 @@
+//! Token-based JSON pretty printer for `format`.
+//!
+//! Re-indents from the lexer's token stream instead of a `serde_json::Value`,
+//! so key order, duplicate keys and the literal text of every string and
+//! number (escapes, exponents, `1.0`) come out exactly as written.
+
+use crate::encoding::bom_len;
+use crate::json_lexer::{Kind, Lexer, Token};
+
+/// Deepest nesting `format_json` re-indents. Indentation grows with the
+/// square of the depth, so a few kilobytes of `[[[[...` would otherwise
+/// expand into gigabytes of output.
+pub(crate) const MAX_FORMAT_DEPTH: usize = 1000;
+
+pub(crate) enum FormatError {
+    Syntax(String),
+    /// The content nests deeper than [`MAX_FORMAT_DEPTH`].
+    TooDeep,
+}
+
+/// Pretty-prints `content` with `indent` per level and a trailing newline.
+/// Empty containers stay on one line. The caller checks syntax first.
+pub(crate) fn format_json(content: &str, indent: &str) -> Result<String, FormatError> {
+    let tokens = Lexer::new(content)
+        .collect::<Result<Vec<Token>, String>>()
+        .map_err(FormatError::Syntax)?;
+    let mut depth = 0usize;
+    for token in &tokens {
+        match token.kind {
+            Kind::LBrace | Kind::LBrack => {
+                depth += 1;
+                if depth > MAX_FORMAT_DEPTH {
+                    return Err(FormatError::TooDeep);
+                }
+            }
+            Kind::RBrace | Kind::RBrack => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    let mut out = String::with_capacity(content.len() + content.len() / 4);
+    out.push_str(&content[..bom_len(content)]);
+    let mut depth = 0usize;
+    let newline = |out: &mut String, depth: usize| {
+        out.push('\n');
+        for _ in 0..depth {
+            out.push_str(indent);
+        }
+    };
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        let text = &content[token.span.start..token.span.end];
+        match token.kind {
+            Kind::LBrace | Kind::LBrack => {
+                out.push_str(text);
+                match tokens.get(i + 1) {
+                    Some(next) if matches!(next.kind, Kind::RBrace | Kind::RBrack) => {
+                        out.push_str(&content[next.span.start..next.span.end]);
+                        i += 1;
+                    }
+                    _ => {
+                        depth += 1;
+                        newline(&mut out, depth);
+                    }
+                }
+            }
+            Kind::RBrace | Kind::RBrack => {
+                depth = depth.saturating_sub(1);
+                newline(&mut out, depth);
+                out.push_str(text);
+            }
+            Kind::Comma => {
+                out.push(',');
+                newline(&mut out, depth);
+            }
+            Kind::Colon => out.push_str(": "),
+            _ => out.push_str(text),
+        }
+        i += 1;
+    }
+    out.push('\n');
+    Ok(out)
+}
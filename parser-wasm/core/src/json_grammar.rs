@@ -0,0 +1,314 @@
+//! JSON grammar checked token by token.
+//!
+//! The strict lexer accepts some tokens JSON does not (`1.2.3`, `"\q"`) and
+//! does not look at their order. [`Grammar`] is fed the tokens as they are
+//! lexed and keeps the first error `serde_json` would report, in its
+//! wording, so content validated in steps needs no parse of the whole
+//! document once the last step has lexed it.
+
+use crate::json_lexer::{Kind, Token};
+use crate::line_index::{self, ColumnUnit};
+use crate::multi_validation::infer_json_span;
+use crate::ValidationResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Expect {
+    /// At the root, after `:` and after `,` in an array.
+    #[default]
+    Value,
+    /// After `[`.
+    ValueOrEnd,
+    /// After `{`.
+    KeyOrEnd,
+    /// After `,` in an object.
+    Key,
+    Colon,
+    /// After a value inside a container.
+    CommaOrEnd,
+    /// After the root value.
+    Nothing,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Grammar {
+    /// Open containers, `true` for an object.
+    stack: Vec<bool>,
+    expect: Expect,
+    /// The first error and the offset it points at.
+    error: Option<(&'static str, usize)>,
+}
+
+impl Grammar {
+    /// Checks the next token of `content`; tokens after the first error are
+    /// ignored.
+    pub(crate) fn feed(&mut self, content: &str, token: Token) {
+        if self.error.is_none() {
+            if let Err((message, at)) = self.step(content, token) {
+                self.error = Some((message, token.span.start + at));
+            }
+        }
+    }
+
+    /// Errors carry an offset into the token.
+    fn step(&mut self, content: &str, token: Token) -> Result<(), (&'static str, usize)> {
+        let text = &content[token.span.start..token.span.end];
+        let fail = |message| Err((message, 0));
+        let in_object = self.stack.last() == Some(&true);
+        match (self.expect, token.kind) {
+            (Expect::Nothing, _) => fail(self.after_value()),
+            (Expect::Colon, Kind::Colon) => {
+                self.expect = Expect::Value;
+                Ok(())
+            }
+            (Expect::Colon, _) => fail("expected `:`"),
+            (Expect::KeyOrEnd | Expect::CommaOrEnd, Kind::RBrace) if in_object => {
+                self.close();
+                Ok(())
+            }
+            (Expect::ValueOrEnd | Expect::CommaOrEnd, Kind::RBrack) if !in_object => {
+                self.close();
+                Ok(())
+            }
+            (Expect::CommaOrEnd, Kind::Comma) => {
+                self.expect = if in_object {
+                    Expect::Key
+                } else {
+                    Expect::Value
+                };
+                Ok(())
+            }
+            (Expect::CommaOrEnd, _) => fail(self.after_value()),
+            (Expect::KeyOrEnd | Expect::Key, Kind::StringLit) => {
+                check_string(text)?;
+                self.expect = Expect::Colon;
+                Ok(())
+            }
+            (Expect::KeyOrEnd | Expect::Key, _) => fail("key must be a string"),
+            (Expect::Value | Expect::ValueOrEnd, kind) => {
+                match kind {
+                    Kind::LBrace => {
+                        self.stack.push(true);
+                        self.expect = Expect::KeyOrEnd;
+                        return Ok(());
+                    }
+                    Kind::LBrack => {
+                        self.stack.push(false);
+                        self.expect = Expect::ValueOrEnd;
+                        return Ok(());
+                    }
+                    Kind::StringLit => check_string(text)?,
+                    Kind::NumberLit => {
+                        // `1.2.3` is the number `1.2` followed by `.3`
+                        let len = check_number(text)?;
+                        self.value_done();
+                        if len < text.len() {
+                            return Err((self.after_value(), len));
+                        }
+                        return Ok(());
+                    }
+                    Kind::True | Kind::False | Kind::Null => {}
+                    _ => return fail("expected value"),
+                }
+                self.value_done();
+                Ok(())
+            }
+        }
+    }
+
+    /// What was wanted instead of the token after a complete value.
+    fn after_value(&self) -> &'static str {
+        match (self.expect, self.stack.last()) {
+            (Expect::Nothing, _) => "trailing characters",
+            (_, Some(true)) => "expected `,` or `}`",
+            _ => "expected `,` or `]`",
+        }
+    }
+
+    fn close(&mut self) {
+        self.stack.pop();
+        self.value_done();
+    }
+
+    fn value_done(&mut self) {
+        self.expect = if self.stack.is_empty() {
+            Expect::Nothing
+        } else {
+            Expect::CommaOrEnd
+        };
+    }
+
+    /// The result once every token of `content` has been fed.
+    pub(crate) fn finish(&self, content: &str) -> ValidationResult {
+        if let Some((message, at)) = self.error {
+            return failure(content, message, at);
+        }
+        let message = match (self.expect, self.stack.last()) {
+            (Expect::Nothing, _) => return ValidationResult::success(),
+            (Expect::Value, _) => "EOF while parsing a value",
+            (_, Some(true)) => "EOF while parsing an object",
+            _ => "EOF while parsing a list",
+        };
+        failure(content, message, eof(content))
+    }
+
+    /// The result when the lexer rejects the token at `start`: an earlier
+    /// grammar error wins, and a token where no value or key can go is
+    /// reported as the token that was expected, as serde_json does.
+    pub(crate) fn lex_failure(
+        &self,
+        content: &str,
+        message: &str,
+        start: usize,
+    ) -> ValidationResult {
+        if self.error.is_some() {
+            return self.finish(content);
+        }
+        let wanted = match self.expect {
+            Expect::Value | Expect::ValueOrEnd => None,
+            Expect::KeyOrEnd | Expect::Key if content.as_bytes()[start] == b'"' => None,
+            Expect::KeyOrEnd | Expect::Key => Some("key must be a string"),
+            Expect::Colon => Some("expected `:`"),
+            Expect::CommaOrEnd | Expect::Nothing => Some(self.after_value()),
+        };
+        match wanted {
+            Some(message) => failure(content, message, start),
+            None => lex_failure(content, message, start),
+        }
+    }
+}
+
+/// Result for a token the lexer rejected, starting at `start`, in
+/// serde_json's wording rather than the lexer's.
+fn lex_failure(content: &str, message: &str, start: usize) -> ValidationResult {
+    let bytes = content.as_bytes();
+    let (message, at) = if message == "unterminated string" {
+        match memchr::memchr2(b'\n', b'\r', &bytes[start..]) {
+            Some(at) => (
+                "control character (\\u0000-\\u001F) found while parsing a string",
+                start + at - 1,
+            ),
+            None => ("EOF while parsing a string", eof(content)),
+        }
+    } else if !message.starts_with("unexpected byte") {
+        (message, start)
+    } else if let Some(word) = ["true", "false", "null"]
+        .into_iter()
+        .find(|word| word.as_bytes()[0] == bytes[start])
+    {
+        // a literal cut short or misspelled
+        let matched = word
+            .bytes()
+            .zip(&bytes[start..])
+            .take_while(|(a, b)| a == *b)
+            .count();
+        match start + matched {
+            at if at == bytes.len() => ("EOF while parsing a value", eof(content)),
+            at => ("expected ident", at),
+        }
+    } else {
+        ("expected value", start)
+    };
+    failure(content, message, at)
+}
+
+/// Where serde_json reports running out of input: the last character, or
+/// the end after a final newline.
+fn eof(content: &str) -> usize {
+    match content.chars().next_back() {
+        Some(c) if c != '\n' => content.len() - c.len_utf8(),
+        _ => content.len(),
+    }
+}
+
+/// The summary for an error at `at`, the last byte serde_json would have
+/// read: its message counts columns in bytes, the result in characters. A
+/// byte inside a multi-byte character moves on to the next character, as
+/// turning serde_json's byte column back into an offset does.
+fn failure(content: &str, message: &str, at: usize) -> ValidationResult {
+    let boundary = (at..=content.len())
+        .find(|&i| content.is_char_boundary(i))
+        .unwrap_or(content.len());
+    let span = infer_json_span(content, boundary);
+    let (line, column) = line_index::line_col_at(content, span.start, ColumnUnit::Char);
+    let line_start = memchr::memrchr(b'\n', &content.as_bytes()[..at]).map_or(0, |i| i + 1);
+    let bytes_read = at - line_start + usize::from(at < content.len());
+    ValidationResult::failure(
+        format!("{message} at line {line} column {bytes_read}"),
+        line,
+        column,
+        span,
+    )
+}
+
+/// A string token, quotes included: no control characters, and only the
+/// escapes JSON defines. Errors point where serde_json's do.
+fn check_string(token: &str) -> Result<(), (&'static str, usize)> {
+    let bytes = token.as_bytes();
+    let mut i = 1;
+    while i < bytes.len() - 1 {
+        match bytes[i] {
+            b'\\' => {
+                match bytes[i + 1] {
+                    b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => i += 2,
+                    // the closing quote stops a short escape; serde_json
+                    // points just past the character that is not a digit
+                    b'u' => match bytes[i + 2..]
+                        .iter()
+                        .take(4)
+                        .position(|b| !b.is_ascii_hexdigit())
+                    {
+                        None => i += 6,
+                        Some(at) => return Err(("invalid escape", i + 3 + at)),
+                    },
+                    _ => return Err(("invalid escape", i + 1)),
+                }
+                continue;
+            }
+            0..=0x1f => {
+                return Err((
+                    "control character (\\u0000-\\u001F) found while parsing a string",
+                    i - 1,
+                ))
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
+/// Length of the number `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`
+/// at the start of the token; errors point at the first character that does
+/// not fit, which may follow the token.
+fn check_number(token: &str) -> Result<usize, (&'static str, usize)> {
+    let bytes = token.as_bytes();
+    let digits = |from: usize| {
+        bytes[from..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+    };
+    let mut i = usize::from(bytes.first() == Some(&b'-'));
+    match digits(i) {
+        0 => return Err(("invalid number", i)),
+        n if n > 1 && bytes[i] == b'0' => return Err(("invalid number", i + 1)),
+        n => i += n,
+    }
+    if bytes.get(i) == Some(&b'.') {
+        match digits(i + 1) {
+            0 => return Err(("invalid number", i + 1)),
+            n => i += 1 + n,
+        }
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(bytes.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        match digits(i) {
+            0 => return Err(("invalid number", i)),
+            n => i += n,
+        }
+    }
+    Ok(i)
+}
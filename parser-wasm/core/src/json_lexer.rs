@@ -0,0 +1,359 @@
+//! Pieni, no‑alloc JSON‑tokenisoija, joka tuottaa Token { kind, span }.
+//! • Tukee RFC 8259: numerot, stringit, true/false/null, whitespace.
+//! • Ei kommentteja eikä trailing‑comma‑sallintaa (sama kuin virallinen JSON).
+//! • Span = byte‑indeksit alkuperäiseen buffiin (start..end).
+
+use crate::encoding::bom_len;
+use crate::limits::TokenBudget;
+use crate::Span;
+use serde::Serialize;
+use tsify::Tsify;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Tsify)]
+#[serde(rename = "TokenKind", rename_all = "lowercase")]
+pub enum Kind {
+    LBrace,
+    RBrace,
+    LBrack,
+    RBrack,
+    Colon,
+    Comma,
+    #[serde(rename = "string")]
+    StringLit,
+    #[serde(rename = "number")]
+    NumberLit,
+    True,
+    False,
+    Null,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Tsify)]
+pub struct Token {
+    #[tsify(type = "TokenKind")]
+    pub kind: Kind,
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct LexError {
+    pub code: &'static str,
+    pub message: String,
+    pub span: Span,
+}
+
+/// Scans a string body starting just after the opening quote, jumping between
+/// quote/backslash candidates with `memchr` instead of stepping byte by byte.
+///
+/// Returns `(end, true)` with `end` just past the closing quote, or
+/// `(end, false)` when an unescaped line break or the end of input comes
+/// first (`end` then points at the break / input end).
+#[inline]
+fn scan_string(bytes: &[u8], mut i: usize) -> (usize, bool) {
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        let Some(pos) = memchr::memchr2(b'"', b'\\', rest) else {
+            return match memchr::memchr2(b'\n', b'\r', rest) {
+                Some(nl) => (i + nl, false),
+                None => (bytes.len(), false),
+            };
+        };
+        if let Some(nl) = memchr::memchr2(b'\n', b'\r', &rest[..pos]) {
+            return (i + nl, false);
+        }
+        let at = i + pos;
+        if bytes[at] == b'"' {
+            return (at + 1, true);
+        }
+        // backslash: the next byte is escaped, whatever it is
+        i = at + 2;
+    }
+    (bytes.len(), false)
+}
+
+#[inline]
+fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+pub fn lex(buf: &str) -> Result<Vec<Token>, String> {
+    Lexer::new(buf).collect()
+}
+
+/// Resumable strict tokenizer. `lex` drains it in one go; `Document` keeps
+/// only the byte offset between `next_tokens` calls and resumes with `at`.
+/// The iterator stops after the first error.
+pub struct Lexer<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    budget: TokenBudget,
+    non_finite: bool,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(buf: &'a str) -> Self {
+        Self::at(buf, 0)
+    }
+
+    /// Starts lexing at `offset`, which must be a token boundary previously
+    /// returned by [`Lexer::offset`].
+    pub fn at(buf: &'a str, offset: usize) -> Self {
+        Self::resume(buf, offset, 0)
+    }
+
+    /// Like [`Lexer::at`], charging `maxTokens` as if the `spent` tokens
+    /// before `offset` came from this lexer.
+    pub(crate) fn resume(buf: &'a str, offset: usize, spent: usize) -> Self {
+        Self {
+            bytes: buf.as_bytes(),
+            pos: offset.clamp(bom_len(buf), buf.len()),
+            budget: TokenBudget::resumed(spent),
+            non_finite: false,
+        }
+    }
+
+    /// Also accepts `NaN`, `Infinity` and `-Infinity`, which some generators
+    /// write, as number tokens.
+    pub(crate) fn lenient(mut self) -> Self {
+        self.non_finite = true;
+        self
+    }
+
+    /// Byte offset where the next token scan begins.
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+
+    /// True when only whitespace remains.
+    pub fn is_finished(&self) -> bool {
+        skip_whitespace(self.bytes, self.pos) >= self.bytes.len()
+    }
+
+    fn emit(&mut self, kind: Kind, start: usize, end: usize) -> Option<Result<Token, String>> {
+        if let Err(e) = self.budget.spend() {
+            return self.fail(e);
+        }
+        self.pos = end;
+        Some(Ok(Token {
+            kind,
+            span: Span::new(start, end),
+        }))
+    }
+
+    fn fail(&mut self, message: String) -> Option<Result<Token, String>> {
+        self.pos = self.bytes.len();
+        Some(Err(message))
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Result<Token, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.bytes;
+        let i = skip_whitespace(bytes, self.pos);
+        self.pos = i;
+        if i >= bytes.len() {
+            return None;
+        }
+        match bytes[i] {
+            b'{' => self.emit(Kind::LBrace, i, i + 1),
+            b'}' => self.emit(Kind::RBrace, i, i + 1),
+            b'[' => self.emit(Kind::LBrack, i, i + 1),
+            b']' => self.emit(Kind::RBrack, i, i + 1),
+            b':' => self.emit(Kind::Colon, i, i + 1),
+            b',' => self.emit(Kind::Comma, i, i + 1),
+
+            b'"' => match scan_string(bytes, i + 1) {
+                (end, true) => self.emit(Kind::StringLit, i, end),
+                (_, false) => self.fail("unterminated string".into()),
+            },
+
+            b'N' if self.non_finite && bytes.get(i..i + 3) == Some(b"NaN") => {
+                self.emit(Kind::NumberLit, i, i + 3)
+            }
+            b'I' if self.non_finite && bytes.get(i..i + 8) == Some(b"Infinity") => {
+                self.emit(Kind::NumberLit, i, i + 8)
+            }
+            b'-' if self.non_finite && bytes.get(i + 1..i + 9) == Some(b"Infinity") => {
+                self.emit(Kind::NumberLit, i, i + 9)
+            }
+
+            b'-' | b'0'..=b'9' => {
+                let mut end = i + 1;
+                while end < bytes.len()
+                    && matches!(bytes[end], b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-')
+                {
+                    end += 1;
+                }
+                self.emit(Kind::NumberLit, i, end)
+            }
+
+            b't' if bytes.get(i..i + 4) == Some(b"true") => self.emit(Kind::True, i, i + 4),
+            b'f' if bytes.get(i..i + 5) == Some(b"false") => self.emit(Kind::False, i, i + 5),
+            b'n' if bytes.get(i..i + 4) == Some(b"null") => self.emit(Kind::Null, i, i + 4),
+
+            b => self.fail(format!("unexpected byte 0x{:02x} at {}", b, i)),
+        }
+    }
+}
+
+/// Erittäin kevyt syntaksivalidointi – tarkistaa sulkujen tasapainon ja
+/// object‑key → colon → value ‑järjestyksen pääpiirteissään.
+/// Riittää konfiguraatiokäyttöihin; syvällisempi tarkistus voidaan
+/// delegoida serde_jsonille, jos tarve.
+pub fn validate(tokens: &[Token]) -> Result<(), String> {
+    use Kind::*;
+    let mut stack = Vec::new();
+    let mut expect_key_or_end = false; // inside object
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match tokens[i].kind {
+            LBrace => {
+                stack.push(LBrace);
+                expect_key_or_end = true;
+                i += 1;
+            }
+            LBrack => {
+                stack.push(LBrack);
+                i += 1;
+            }
+            RBrace => {
+                if stack.pop() != Some(LBrace) {
+                    return Err("mismatched '}'".into());
+                }
+                i += 1;
+                expect_key_or_end = false;
+            }
+            RBrack => {
+                if stack.pop() != Some(LBrack) {
+                    return Err("mismatched ']'".into());
+                }
+                i += 1;
+            }
+            StringLit => {
+                if stack.last() == Some(&LBrace) && expect_key_or_end {
+                    // key position – next token must be Colon
+                    if tokens.get(i + 1).map(|t| t.kind) != Some(Colon) {
+                        return Err("object key not followed by ':'".into());
+                    }
+                }
+                i += 1;
+            }
+            Colon => {
+                expect_key_or_end = false;
+                i += 1;
+            }
+            Comma => {
+                expect_key_or_end = stack.last() == Some(&LBrace);
+                i += 1;
+            }
+            NumberLit | True | False | Null => {
+                i += 1;
+            }
+        }
+    }
+    if !stack.is_empty() {
+        return Err("unclosed brackets/braces".into());
+    }
+    Ok(())
+}
+
+/// Error-recovering tokenizer for multi-error validation: malformed input is
+/// reported as an `Err` item and scanning carries on after it. Callers
+/// consume it as a stream, so the token vector is never materialised. Stops
+/// when the token budget runs out.
+pub struct LenientLexer<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    budget: TokenBudget,
+}
+
+impl<'a> LenientLexer<'a> {
+    pub fn new(buf: &'a str) -> Self {
+        Self {
+            bytes: buf.as_bytes(),
+            pos: bom_len(buf),
+            budget: TokenBudget::new(),
+        }
+    }
+
+    fn emit(&mut self, kind: Kind, start: usize, end: usize) -> Option<Result<Token, LexError>> {
+        if self.budget.spend().is_err() {
+            // the limits guard reports the overrun
+            self.pos = self.bytes.len();
+            return None;
+        }
+        self.pos = end;
+        Some(Ok(Token {
+            kind,
+            span: Span::new(start, end),
+        }))
+    }
+}
+
+impl Iterator for LenientLexer<'_> {
+    type Item = Result<Token, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.bytes;
+        let i = skip_whitespace(bytes, self.pos);
+        self.pos = i;
+        if i >= bytes.len() {
+            return None;
+        }
+        match bytes[i] {
+            b'{' => self.emit(Kind::LBrace, i, i + 1),
+            b'}' => self.emit(Kind::RBrace, i, i + 1),
+            b'[' => self.emit(Kind::LBrack, i, i + 1),
+            b']' => self.emit(Kind::RBrack, i, i + 1),
+            b':' => self.emit(Kind::Colon, i, i + 1),
+            b',' => self.emit(Kind::Comma, i, i + 1),
+            b'"' => match scan_string(bytes, i + 1) {
+                (end, true) => self.emit(Kind::StringLit, i, end),
+                (mut end, false) => {
+                    while end < bytes.len() {
+                        if bytes[end] == b'"' {
+                            end += 1;
+                            break;
+                        }
+                        if bytes[end] == b'\n' || bytes[end] == b'\r' {
+                            break;
+                        }
+                        end += 1;
+                    }
+                    self.pos = end.min(bytes.len());
+                    Some(Err(LexError {
+                        code: "json.unterminated_string",
+                        message: "Unterminated string literal".into(),
+                        span: Span::new(i, self.pos),
+                    }))
+                }
+            },
+            b'-' | b'0'..=b'9' => {
+                let mut end = i + 1;
+                while end < bytes.len()
+                    && matches!(bytes[end], b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-')
+                {
+                    end += 1;
+                }
+                self.emit(Kind::NumberLit, i, end)
+            }
+            b't' if bytes.get(i..i + 4) == Some(b"true") => self.emit(Kind::True, i, i + 4),
+            b'f' if bytes.get(i..i + 5) == Some(b"false") => self.emit(Kind::False, i, i + 5),
+            b'n' if bytes.get(i..i + 4) == Some(b"null") => self.emit(Kind::Null, i, i + 4),
+            b => {
+                self.pos = (i + 1).min(bytes.len());
+                Some(Err(LexError {
+                    code: "json.unexpected_token",
+                    message: format!("Unexpected byte 0x{:02x}", b),
+                    span: Span::new(i, self.pos),
+                }))
+            }
+        }
+    }
+}
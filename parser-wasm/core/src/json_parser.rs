@@ -0,0 +1,370 @@
+//! JSON‑parseri, joka käyttää omaa minitokenisoijaa span‑hakuihin.
+
+use std::borrow::Cow;
+
+use crate::document::Occurrence;
+use crate::json_lexer::{lex, validate, Kind, Token};
+use crate::paths::{self, Segment};
+use crate::{BytePreservingParser, Span};
+
+pub struct JsonParser;
+impl Default for JsonParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonParser {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+// ────────── HELPER FUNCTIONS ──────────
+
+/// Index of the token closing the object opened at `start_idx`.
+fn find_matching_brace(tokens: &[Token], start_idx: usize) -> Result<usize, String> {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(start_idx) {
+        match token.kind {
+            Kind::LBrace => depth += 1,
+            Kind::RBrace => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    Err("Unmatched opening brace".to_string())
+}
+
+/// Index of the token closing the array opened at `start_idx`.
+fn find_matching_bracket(tokens: &[Token], start_idx: usize) -> Result<usize, String> {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(start_idx) {
+        match token.kind {
+            Kind::LBrack => depth += 1,
+            Kind::RBrack => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    Err("Unmatched opening bracket".to_string())
+}
+
+/// Matches of a path, counted until the requested occurrence is reached.
+struct Matches {
+    occurrence: Occurrence,
+    count: usize,
+    found: Option<Span>,
+}
+
+impl Matches {
+    /// Records a match; returns whether the scan can stop.
+    fn push(&mut self, span: Span) -> bool {
+        let index = self.count;
+        self.count += 1;
+        let wanted = match self.occurrence {
+            Occurrence::First => index == 0,
+            Occurrence::Nth(n) => index == n,
+            Occurrence::Last => true,
+        };
+        if wanted {
+            self.found = Some(span);
+        }
+        wanted && self.occurrence != Occurrence::Last
+    }
+}
+
+// ────────── PATH‑TRACKER ──────────
+/// Keys borrow their text from the content, so walking a document allocates
+/// nothing per key; only a key with escapes is decoded into a new string.
+#[derive(Debug, Clone)]
+enum Seg<'a> {
+    Key(Cow<'a, str>),
+    Idx(usize),
+}
+
+/// The decoded name of a member key token, so `"\u0070ort"` is `port`.
+pub(crate) fn member_name(raw: &str) -> Cow<'_, str> {
+    let body = &raw[1..raw.len() - 1];
+    if !body.contains('\\') {
+        return Cow::Borrowed(body);
+    }
+    decode_json_string(raw).map_or(Cow::Borrowed(body), Cow::Owned)
+}
+
+fn path_matches(stack: &[Seg], target: &[String]) -> bool {
+    if stack.len() != target.len() {
+        return false;
+    }
+    for (s, t) in stack.iter().zip(target) {
+        match *s {
+            Seg::Key(ref k) => match paths::parse(t) {
+                Segment::Key(key) | Segment::Plain(key) if key == k => (),
+                _ => return false,
+            },
+            Seg::Idx(i) => match paths::parse(t) {
+                Segment::Index(n) if n == i => (),
+                Segment::Plain(t) if index_matches(i, t) => (),
+                _ => return false,
+            },
+        }
+    }
+    true
+}
+
+/// `segment == idx.to_string()` without formatting: canonical decimal only.
+fn index_matches(idx: usize, segment: &str) -> bool {
+    paths::is_canonical_index(segment) && segment.parse() == Ok(idx)
+}
+
+impl BytePreservingParser for JsonParser {
+    fn validate_syntax(&self, content: &str) -> Result<(), String> {
+        let tokens = lex(content)?;
+        validate(&tokens)
+    }
+
+    fn find_value_span(&self, content: &str, path: &[String]) -> Result<Span, String> {
+        let tokens = lex(content)?;
+        find_value_span_with_tokens(&tokens, content, path)
+    }
+
+    fn validate_and_find_span(&self, content: &str, path: &[String]) -> Result<Span, String> {
+        let tokens = lex(content)?;
+        validate(&tokens)?;
+        find_value_span_with_tokens(&tokens, content, path)
+    }
+}
+
+pub(crate) fn find_value_span_with_tokens(
+    tokens: &[Token],
+    content: &str,
+    path: &[String],
+) -> Result<Span, String> {
+    find_occurrence(tokens, content, path, Occurrence::First)
+}
+
+/// Resolves `path`, picking `occurrence` among the matches that objects
+/// with a repeated key produce.
+pub(crate) fn find_occurrence(
+    tokens: &[Token],
+    content: &str,
+    path: &[String],
+    occurrence: Occurrence,
+) -> Result<Span, String> {
+    let mut matches = Matches {
+        occurrence,
+        count: 0,
+        found: None,
+    };
+    let mut path_stack = Vec::<Seg>::new();
+    let mut arr_idx_stack = Vec::<usize>::new();
+    // Open containers, so commas inside an object nested in an array don't
+    // advance the array index
+    let mut containers = Vec::<Kind>::new();
+    let mut expect_key: Option<Cow<str>> = None;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match tokens[i].kind {
+            Kind::LBrace => {
+                if let Some(key) = expect_key.take() {
+                    path_stack.push(Seg::Key(key));
+                    if path_matches(&path_stack, path) {
+                        let close = find_matching_brace(tokens, i)?;
+                        let span = crate::Span::new(tokens[i].span.start, tokens[close].span.end);
+                        if matches.push(span) {
+                            break;
+                        }
+                        // skip the matched value; a later duplicate may follow
+                        path_stack.pop();
+                        i = close + 1;
+                        continue;
+                    }
+                }
+                containers.push(Kind::LBrace);
+                i += 1;
+            }
+            Kind::RBrace => {
+                containers.pop();
+                if let Some(Seg::Key(_)) = path_stack.last() {
+                    path_stack.pop();
+                }
+                i += 1;
+            }
+            Kind::LBrack => {
+                if let Some(key) = expect_key.take() {
+                    path_stack.push(Seg::Key(key));
+                    if path_matches(&path_stack, path) {
+                        let close = find_matching_bracket(tokens, i)?;
+                        let span = crate::Span::new(tokens[i].span.start, tokens[close].span.end);
+                        if matches.push(span) {
+                            break;
+                        }
+                        // skip the matched value; a later duplicate may follow
+                        path_stack.pop();
+                        i = close + 1;
+                        continue;
+                    }
+                }
+                containers.push(Kind::LBrack);
+                arr_idx_stack.push(0);
+                path_stack.push(Seg::Idx(0));
+                i += 1;
+            }
+            Kind::RBrack => {
+                containers.pop();
+                arr_idx_stack.pop();
+                if let Some(Seg::Idx(_)) = path_stack.last() {
+                    path_stack.pop();
+                }
+                if let Some(Seg::Key(_)) = path_stack.last() {
+                    path_stack.pop();
+                }
+                i += 1;
+            }
+            Kind::StringLit => {
+                if tokens.get(i + 1).map(|t| t.kind) == Some(Kind::Colon) {
+                    let token = tokens[i].span;
+                    expect_key = Some(member_name(&content[token.start..token.end]));
+                    i += 2;
+                } else {
+                    if let Some(key) = expect_key.take() {
+                        path_stack.push(Seg::Key(key));
+                    }
+                    if path_matches(&path_stack, path) && matches.push(tokens[i].span) {
+                        break;
+                    }
+                    if let Some(Seg::Key(_)) = path_stack.last() {
+                        path_stack.pop();
+                    }
+                    i += 1;
+                }
+            }
+            Kind::NumberLit | Kind::True | Kind::False | Kind::Null => {
+                if let Some(key) = expect_key.take() {
+                    path_stack.push(Seg::Key(key));
+                }
+                if path_matches(&path_stack, path) && matches.push(tokens[i].span) {
+                    break;
+                }
+                if let Some(Seg::Key(_)) = path_stack.last() {
+                    path_stack.pop();
+                }
+                i += 1;
+            }
+            Kind::Comma => {
+                if containers.last() == Some(&Kind::LBrack) {
+                    if let Some(last) = arr_idx_stack.last_mut() {
+                        *last += 1;
+                        if let Some(Seg::Idx(ref mut n)) = path_stack.last_mut() {
+                            *n = *last;
+                        }
+                    }
+                }
+                i += 1;
+            }
+            Kind::Colon => {
+                i += 1;
+            }
+        }
+    }
+    match (matches.found, occurrence) {
+        (Some(span), _) => Ok(span),
+        (None, Occurrence::Nth(n)) if matches.count > 0 => Err(format!(
+            "Path not found: {} (occurrence {n} requested, {} found)",
+            path.join("/"),
+            matches.count
+        )),
+        (None, _) => Err(format!("Path not found: {}", path.join("/"))),
+    }
+}
+
+pub struct JsonSpanResolver<'a> {
+    content: &'a str,
+    tokens: Vec<Token>,
+}
+
+impl<'a> JsonSpanResolver<'a> {
+    pub fn new(content: &'a str) -> Result<Self, String> {
+        let tokens = lex(content)?;
+        Ok(Self { content, tokens })
+    }
+
+    pub fn find_path(&self, path: &[String]) -> Result<Span, String> {
+        find_value_span_with_tokens(&self.tokens, self.content, path)
+    }
+
+    pub fn span_for_pointer(&self, pointer: &str) -> Result<Span, String> {
+        let segments = paths::from_pointer(pointer)?;
+        if segments.is_empty() {
+            return Ok(Span::new(0, self.content.len()));
+        }
+        self.find_path(&segments)
+    }
+}
+
+/// Unescapes a JSON string token, quotes included. Escaped surrogate pairs
+/// combine into one character and a lone surrogate becomes U+FFFD, so any
+/// string the lexer accepts decodes. `None` when `raw` is not a string.
+pub(crate) fn decode_json_string(raw: &str) -> Option<String> {
+    let body = raw.strip_prefix('"')?.strip_suffix('"')?;
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    let mut pending_high: Option<u16> = None;
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            flush_surrogate(&mut out, &mut pending_high);
+            out.push(ch);
+            continue;
+        }
+        let decoded = match chars.next()? {
+            '"' => '"',
+            '\\' => '\\',
+            '/' => '/',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                let hex: String = chars.by_ref().take(4).collect();
+                let unit = u16::from_str_radix(&hex, 16).ok()?;
+                match unit {
+                    0xD800..=0xDBFF => {
+                        flush_surrogate(&mut out, &mut pending_high);
+                        pending_high = Some(unit);
+                        continue;
+                    }
+                    0xDC00..=0xDFFF => match pending_high.take() {
+                        Some(high) => {
+                            let pair = [high, unit];
+                            out.extend(char::decode_utf16(pair).map(|c| c.unwrap_or('\u{FFFD}')));
+                            continue;
+                        }
+                        None => '\u{FFFD}',
+                    },
+                    _ => char::from_u32(unit as u32)?,
+                }
+            }
+            _ => return None,
+        };
+        flush_surrogate(&mut out, &mut pending_high);
+        out.push(decoded);
+    }
+    flush_surrogate(&mut out, &mut pending_high);
+    Some(out)
+}
+
+fn flush_surrogate(out: &mut String, pending_high: &mut Option<u16>) {
+    if pending_high.take().is_some() {
+        out.push('\u{FFFD}');
+    }
+}
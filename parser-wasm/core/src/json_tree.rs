@@ -0,0 +1,172 @@
+//! Span-annotated JSON tree.
+//!
+//! Built from the lexer's tokens for the operations that compare or walk
+//! whole documents (`diff`). Every node keeps the byte span of its text in
+//! the source, so results can point back into the content; scalar values are
+//! not materialised, callers slice the source when they need the text.
+
+use crate::json_format::MAX_FORMAT_DEPTH;
+use crate::json_lexer::{Kind, Token};
+use crate::json_parser::member_name;
+use crate::paths::{parse, Segment};
+use crate::Span;
+
+/// Deepest nesting [`build`] accepts, the same bound `format` has. The walks
+/// over the tree recurse once per level.
+pub(crate) const MAX_TREE_DEPTH: usize = MAX_FORMAT_DEPTH;
+
+#[derive(Debug)]
+pub(crate) struct JsonNode {
+    /// The whole value, brackets or quotes included.
+    pub(crate) span: Span,
+    pub(crate) value: JsonValue,
+}
+
+#[derive(Debug)]
+pub(crate) enum JsonValue {
+    /// Members in document order; a repeated key appears once per
+    /// occurrence.
+    Object(Vec<Member>),
+    Array(Vec<JsonNode>),
+    /// A string, number or literal token.
+    Scalar(Kind),
+}
+
+#[derive(Debug)]
+pub(crate) struct Member {
+    /// The key with its escapes decoded.
+    pub(crate) name: String,
+    /// The key token, quotes included.
+    pub(crate) key_span: Span,
+    pub(crate) value: JsonNode,
+}
+
+enum Open {
+    Object {
+        start: usize,
+        members: Vec<Member>,
+        key: Option<(String, Span)>,
+    },
+    Array {
+        start: usize,
+        items: Vec<JsonNode>,
+    },
+}
+
+/// Builds the tree of `content` from its `tokens`. The caller checks syntax
+/// first; tokens that still do not form one JSON value are an error.
+pub(crate) fn build(tokens: &[Token], content: &str) -> Result<JsonNode, String> {
+    let mut stack: Vec<Open> = Vec::new();
+    let mut root: Option<JsonNode> = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        i += 1;
+        let node = match token.kind {
+            Kind::LBrace | Kind::LBrack => {
+                if stack.len() == MAX_TREE_DEPTH {
+                    return Err(format!("Content nests deeper than {MAX_TREE_DEPTH} levels"));
+                }
+                stack.push(if token.kind == Kind::LBrace {
+                    Open::Object {
+                        start: token.span.start,
+                        members: Vec::new(),
+                        key: None,
+                    }
+                } else {
+                    Open::Array {
+                        start: token.span.start,
+                        items: Vec::new(),
+                    }
+                });
+                continue;
+            }
+            Kind::RBrace | Kind::RBrack => {
+                let (start, value) = match (stack.pop(), token.kind) {
+                    (
+                        Some(Open::Object {
+                            start,
+                            members,
+                            key: None,
+                        }),
+                        Kind::RBrace,
+                    ) => (start, JsonValue::Object(members)),
+                    (Some(Open::Array { start, items }), Kind::RBrack) => {
+                        (start, JsonValue::Array(items))
+                    }
+                    _ => return Err(unexpected(token)),
+                };
+                JsonNode {
+                    span: Span::new(start, token.span.end),
+                    value,
+                }
+            }
+            Kind::Comma | Kind::Colon => continue,
+            Kind::StringLit => {
+                if let Some(Open::Object {
+                    key: key @ None, ..
+                }) = stack.last_mut()
+                {
+                    if tokens.get(i).map(|t| t.kind) != Some(Kind::Colon) {
+                        return Err(unexpected(token));
+                    }
+                    let raw = &content[token.span.start..token.span.end];
+                    *key = Some((member_name(raw).into_owned(), token.span));
+                    i += 1;
+                    continue;
+                }
+                scalar(token)
+            }
+            Kind::NumberLit | Kind::True | Kind::False | Kind::Null => scalar(token),
+        };
+        match stack.last_mut() {
+            None if root.is_none() => root = Some(node),
+            Some(Open::Object { members, key, .. }) => {
+                let (name, key_span) = key.take().ok_or_else(|| unexpected(token))?;
+                members.push(Member {
+                    name,
+                    key_span,
+                    value: node,
+                });
+            }
+            Some(Open::Array { items, .. }) => items.push(node),
+            None => return Err(unexpected(token)),
+        }
+    }
+    match root {
+        Some(root) if stack.is_empty() => Ok(root),
+        _ => Err("Unexpected end of JSON content".into()),
+    }
+}
+
+impl JsonNode {
+    /// The member or item `segment` selects, with its position among its
+    /// siblings; the first member of a repeated key.
+    pub(crate) fn child(&self, segment: &str) -> Option<(usize, &JsonNode)> {
+        match (&self.value, parse(segment)) {
+            (JsonValue::Object(members), Segment::Key(key) | Segment::Plain(key)) => members
+                .iter()
+                .position(|member| member.name == key)
+                .map(|i| (i, &members[i].value)),
+            (JsonValue::Array(items), Segment::Index(i)) => items.get(i).map(|item| (i, item)),
+            (JsonValue::Array(items), Segment::Plain(digits)) => {
+                let i = digits.parse::<usize>().ok()?;
+                (i.to_string() == digits)
+                    .then(|| items.get(i).map(|item| (i, item)))
+                    .flatten()
+            }
+            _ => None,
+        }
+    }
+}
+
+fn scalar(token: Token) -> JsonNode {
+    JsonNode {
+        span: token.span,
+        value: JsonValue::Scalar(token.kind),
+    }
+}
+
+fn unexpected(token: Token) -> String {
+    format!("Unexpected token at byte {}", token.span.start)
+}
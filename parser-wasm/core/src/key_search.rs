@@ -0,0 +1,215 @@
+//! Fuzzy search over the keys of a document.
+//!
+//! Every key is a candidate: JSON members, XML elements and attributes, ENV
+//! variables. Each is matched by its label, the names on its path joined
+//! with `.` (`services.0.port`, `config.db.@host`), compared without regard
+//! to case. Matches rank in tiers: the key itself equal to the query, the
+//! key containing it, the label containing it, then the query's characters
+//! appearing in order anywhere in the label, the tighter the better. Within
+//! a tier an earlier or tighter match wins, and ties keep document order.
+
+use serde::Serialize;
+use tsify::Tsify;
+
+#[cfg(feature = "env")]
+use crate::env_parser::EnvDocument;
+use crate::json_tree::{JsonNode, JsonValue};
+use crate::paths::escape_segment;
+#[cfg(feature = "xml")]
+use crate::xml_tree::XmlElement;
+use crate::Span;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyMatch {
+    /// Resolves with `find_value_span`; a repeated key or element resolves
+    /// to the first of its name.
+    pub path: Vec<String>,
+    /// What the query was matched against, for display.
+    pub label: String,
+    /// Higher is better; only meaningful within one search.
+    pub score: u32,
+    pub key_span: Span,
+    pub value_span: Span,
+}
+
+/// A key before it is matched.
+struct Candidate {
+    path: Vec<String>,
+    /// The last name on the path: member, element or attribute name.
+    key: String,
+    label: String,
+    key_span: Span,
+    value_span: Span,
+}
+
+/// Keys of `candidates` matching `query`, best first. An empty query
+/// matches every key with score 0, in document order.
+fn rank(candidates: Vec<Candidate>, query: &str) -> Vec<KeyMatch> {
+    let query: Vec<char> = query.trim().to_lowercase().chars().collect();
+    let mut matches: Vec<KeyMatch> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let score = score(&query, &candidate.key, &candidate.label)?;
+            Some(KeyMatch {
+                path: candidate.path,
+                label: candidate.label,
+                score,
+                key_span: candidate.key_span,
+                value_span: candidate.value_span,
+            })
+        })
+        .collect();
+    // stable, so equal scores keep document order
+    matches.sort_by_key(|found| std::cmp::Reverse(found.score));
+    matches
+}
+
+/// Tier base plus up to 999 for how early or tight the match is.
+fn score(query: &[char], key: &str, label: &str) -> Option<u32> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let key: Vec<char> = key.to_lowercase().chars().collect();
+    let label: Vec<char> = label.to_lowercase().chars().collect();
+    let closeness = |n: usize| 999 - n.min(999) as u32;
+    if key == query {
+        return Some(4000);
+    }
+    if let Some(at) = find(&key, query) {
+        return Some(3000 + closeness(at));
+    }
+    if let Some(at) = find(&label, query) {
+        return Some(2000 + closeness(at));
+    }
+    let gaps = subsequence_width(&label, query)? - query.len();
+    Some(1000 + closeness(gaps))
+}
+
+fn find(haystack: &[char], needle: &[char]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Width of the narrowest run of `label` ending where the leftmost match of
+/// `query` as a subsequence ends: the forward scan finds the end, a
+/// backward scan from it the latest start.
+fn subsequence_width(label: &[char], query: &[char]) -> Option<usize> {
+    let mut next = 0;
+    let mut end = None;
+    for (i, &c) in label.iter().enumerate() {
+        if c == query[next] {
+            next += 1;
+            if next == query.len() {
+                end = Some(i);
+                break;
+            }
+        }
+    }
+    let end = end?;
+    let mut remaining = query.len();
+    let mut start = end;
+    for i in (0..=end).rev() {
+        if label[i] == query[remaining - 1] {
+            remaining -= 1;
+            if remaining == 0 {
+                start = i;
+                break;
+            }
+        }
+    }
+    Some(end - start + 1)
+}
+
+pub(crate) fn json(root: &JsonNode, query: &str) -> Vec<KeyMatch> {
+    let mut out = Vec::new();
+    collect_json(root, &mut Vec::new(), &mut Vec::new(), &mut out);
+    rank(out, query)
+}
+
+fn collect_json(
+    node: &JsonNode,
+    path: &mut Vec<String>,
+    names: &mut Vec<String>,
+    out: &mut Vec<Candidate>,
+) {
+    match &node.value {
+        JsonValue::Object(members) => {
+            for member in members {
+                path.push(escape_segment(&member.name));
+                names.push(member.name.clone());
+                out.push(Candidate {
+                    path: path.clone(),
+                    key: member.name.clone(),
+                    label: names.join("."),
+                    key_span: member.key_span,
+                    value_span: member.value.span,
+                });
+                collect_json(&member.value, path, names, out);
+                names.pop();
+                path.pop();
+            }
+        }
+        JsonValue::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                path.push(i.to_string());
+                names.push(i.to_string());
+                collect_json(item, path, names, out);
+                names.pop();
+                path.pop();
+            }
+        }
+        JsonValue::Scalar(_) => {}
+    }
+}
+
+#[cfg(feature = "xml")]
+pub(crate) fn xml(root: &XmlElement, query: &str) -> Vec<KeyMatch> {
+    let mut out = Vec::new();
+    collect_xml(root, &mut Vec::new(), &mut out);
+    rank(out, query)
+}
+
+#[cfg(feature = "xml")]
+fn collect_xml(element: &XmlElement, path: &mut Vec<String>, out: &mut Vec<Candidate>) {
+    path.push(element.name.clone());
+    out.push(Candidate {
+        path: path.clone(),
+        key: element.name.clone(),
+        label: path.join("."),
+        key_span: element.name_span,
+        value_span: element.value_span(),
+    });
+    for (attribute, name) in element.attributes.iter().zip(&element.attribute_names) {
+        path.push(format!("@{}", attribute.name));
+        out.push(Candidate {
+            path: path.clone(),
+            key: attribute.name.clone(),
+            label: path.join("."),
+            key_span: *name,
+            value_span: attribute.span,
+        });
+        path.pop();
+    }
+    for child in &element.children {
+        collect_xml(child, path, out);
+    }
+    path.pop();
+}
+
+#[cfg(feature = "env")]
+pub(crate) fn env(doc: &EnvDocument, query: &str) -> Vec<KeyMatch> {
+    let candidates = doc
+        .entries()
+        .iter()
+        .map(|entry| Candidate {
+            path: vec![escape_segment(&entry.key)],
+            key: entry.key.clone(),
+            label: entry.key.clone(),
+            key_span: entry.key_span,
+            value_span: entry.value_span,
+        })
+        .collect();
+    rank(candidates, query)
+}
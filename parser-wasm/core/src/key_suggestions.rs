@@ -0,0 +1,87 @@
+//! Key completion from sibling objects.
+//!
+//! Ad-hoc configs often have no schema, but an array of objects (the
+//! entries under `services`, the `targets` of a build) is usually uniform:
+//! the keys the other entries have are the likely ones for the entry being
+//! edited. Keys are ranked by how many siblings have them, then by where
+//! they first appear, and the ones the entry already has are left out.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tsify::Tsify;
+
+use crate::json_tree::{JsonNode, JsonValue};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct KeySuggestion {
+    /// The key with its escapes decoded; `escape_segment` makes it a path
+    /// segment.
+    pub key: String,
+    /// How many sibling objects have it.
+    pub count: usize,
+}
+
+/// Keys for the object at `parent_path` taken from the other objects of the
+/// array it is an item of, or for a new item when `parent_path` is the
+/// array itself. Anything else has no siblings to learn from.
+pub(crate) fn suggest(
+    root: &JsonNode,
+    parent_path: &[String],
+) -> Result<Vec<KeySuggestion>, String> {
+    let mut array = None;
+    let mut target = root;
+    for (i, segment) in parent_path.iter().enumerate() {
+        let (index, next) = target
+            .child(segment)
+            .ok_or_else(|| format!("Path not found: {}", parent_path[..=i].join("/")))?;
+        array = match &target.value {
+            JsonValue::Array(items) => Some((items.as_slice(), index)),
+            _ => None,
+        };
+        target = next;
+    }
+    let (siblings, existing): (Vec<&JsonNode>, &[_]) = match (&target.value, array) {
+        (JsonValue::Array(items), _) => (items.iter().collect(), &[]),
+        (JsonValue::Object(members), Some((items, index))) => {
+            let others = items
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != index)
+                .map(|(_, item)| item)
+                .collect();
+            (others, members.as_slice())
+        }
+        _ => return Ok(Vec::new()),
+    };
+
+    let mut suggestions: Vec<KeySuggestion> = Vec::new();
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    for sibling in siblings {
+        let JsonValue::Object(members) = &sibling.value else {
+            continue;
+        };
+        for (i, member) in members.iter().enumerate() {
+            // a repeated key counts once per sibling
+            if members[..i].iter().any(|m| m.name == member.name)
+                || existing.iter().any(|m| m.name == member.name)
+            {
+                continue;
+            }
+            match positions.get(member.name.as_str()) {
+                Some(&at) => suggestions[at].count += 1,
+                None => {
+                    positions.insert(&member.name, suggestions.len());
+                    suggestions.push(KeySuggestion {
+                        key: member.name.clone(),
+                        count: 1,
+                    });
+                }
+            }
+        }
+    }
+    // stable, so equal counts keep first-seen order
+    suggestions.sort_by_key(|s| Reverse(s.count));
+    Ok(suggestions)
+}
@@ -0,0 +1,278 @@
+use serde::Serialize;
+use serde_json::Value;
+use tsify::Tsify;
+#[cfg(feature = "xml")]
+use xmlparser::Error as XmlError;
+
+#[cfg(feature = "wee_alloc")]
+#[global_allocator]
+static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
+
+mod array_items;
+mod convert;
+mod cursor;
+mod diff;
+mod document;
+mod duplicates;
+mod encoding;
+mod encryption;
+mod env_matrix;
+#[cfg(feature = "env")]
+mod env_merge;
+#[cfg(feature = "env")]
+mod env_organize;
+#[cfg(feature = "env")]
+mod env_parser;
+#[cfg(feature = "env")]
+mod env_resolve;
+#[cfg(feature = "env")]
+mod env_schema;
+mod formats;
+mod instantiate;
+mod json_format;
+mod json_grammar;
+mod json_lexer;
+mod json_parser;
+mod json_tree;
+mod key_search;
+mod key_suggestions;
+mod limits;
+mod line_index;
+mod lint;
+mod migrate;
+mod multi_validation;
+pub mod native;
+mod overlay;
+mod parse_cache;
+mod paths;
+mod profile;
+mod schema;
+mod schema_docs;
+mod schema_ids;
+mod schema_vocabulary;
+mod secrets;
+mod semantic_hash;
+mod template;
+mod unused_keys;
+#[cfg(feature = "xml")]
+mod xml_parser;
+#[cfg(feature = "xml")]
+mod xml_tree;
+
+// The suite covers every format
+#[cfg(all(test, feature = "xml", feature = "env"))]
+mod tests;
+
+pub use document::Document;
+use document::FileType;
+#[cfg(feature = "env")]
+pub use env_parser::EnvParser;
+pub use json_parser::JsonParser;
+#[cfg(feature = "xml")]
+use line_index::ColumnUnit;
+use line_index::LineIndex;
+use multi_validation::{MultiValidationOptions, MultiValidationResult, MAX_MULTI_ERRORS};
+#[cfg(feature = "xml")]
+pub use xml_parser::XmlParser;
+
+/// Span represents a byte range in the original content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Tsify)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// API expected by upper-level tooling.
+pub trait BytePreservingParser {
+    fn validate_syntax(&self, content: &str) -> Result<(), String>;
+    fn find_value_span(&self, content: &str, path: &[String]) -> Result<Span, String>;
+
+    /// Validates `content` and resolves `path` in a single parse. The default
+    /// runs both steps; parsers override it to share their tokens.
+    fn validate_and_find_span(&self, content: &str, path: &[String]) -> Result<Span, String> {
+        self.validate_syntax(content)?;
+        self.find_value_span(content, path)
+    }
+
+    /// Convenience: splice `new_val` into `content` at `span`, preserving every
+    /// other byte. **Caller must** ensure `span` came from `find_value_span`.
+    fn replace_value(&self, content: &str, span: Span, new_val: &str) -> String {
+        let mut out = String::with_capacity(content.len() - span.len() + new_val.len());
+        out.push_str(&content[..span.start]);
+        out.push_str(new_val);
+        out.push_str(&content[span.end..]);
+        out
+    }
+}
+
+/// Formats and optional features compiled into this build.
+#[derive(Debug, Serialize, Tsify)]
+pub struct Capabilities {
+    pub version: &'static str,
+    #[tsify(type = "Array<\"json\" | \"xml\" | \"env\">")]
+    pub formats: Vec<&'static str>,
+    #[tsify(type = "Array<\"threads\" | \"wee_alloc\">")]
+    pub features: Vec<&'static str>,
+}
+
+pub(crate) fn build_capabilities() -> Capabilities {
+    let enabled = |flags: &[(&'static str, bool)]| {
+        flags
+            .iter()
+            .filter_map(|&(name, on)| on.then_some(name))
+            .collect()
+    };
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        formats: formats::registrations()
+            .iter()
+            .map(|reg| reg.names[0])
+            .collect(),
+        features: enabled(&[
+            ("threads", cfg!(feature = "threads")),
+            ("wee_alloc", cfg!(feature = "wee_alloc")),
+        ]),
+    }
+}
+
+/// First-error result shared by the `validate` export and `Document::validate`.
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct ValidationResult {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+}
+
+impl ValidationResult {
+    pub(crate) fn success() -> Self {
+        Self {
+            valid: true,
+            message: None,
+            line: None,
+            column: None,
+            span: None,
+        }
+    }
+
+    pub(crate) fn failure(message: String, line: usize, column: usize, span: Span) -> Self {
+        Self {
+            valid: false,
+            message: Some(message),
+            line: Some(line),
+            column: Some(column),
+            span: Some(span),
+        }
+    }
+}
+
+#[cfg(feature = "xml")]
+pub(crate) fn xml_error_summary(content: &str, e: &XmlError) -> ValidationResult {
+    let start = xml_error_offset(content, e.pos());
+    let (line, column) = line_index::line_col_at(content, start, ColumnUnit::Char);
+    ValidationResult::failure(e.to_string(), line, column, Span::new(start, start))
+}
+
+/// Byte offset of an xmlparser position. xmlparser counts a leading BOM as a
+/// column of line 1; the shared mapper starts line 1 after it.
+#[cfg(feature = "xml")]
+pub(crate) fn xml_error_offset(content: &str, pos: xmlparser::TextPos) -> usize {
+    let mut column = pos.col as usize;
+    if pos.row == 1 && encoding::bom_len(content) > 0 {
+        column = column.saturating_sub(1);
+    }
+    line_index::offset_at(content, pos.row as usize, column, ColumnUnit::Char)
+}
+
+pub(crate) fn validation_summary(file_type: FileType, content: &str) -> ValidationResult {
+    file_type.format().summary(content)
+}
+
+pub(crate) fn multi_error_cap(max_errors: Option<u32>) -> usize {
+    let requested = max_errors.unwrap_or(3).clamp(1, MAX_MULTI_ERRORS as u32) as usize;
+    limits::cap_errors(requested)
+}
+
+pub(crate) fn multi_validation_result(
+    file_type: FileType,
+    content: &str,
+    cap: usize,
+    index: Option<&LineIndex>,
+    opts: &MultiValidationOptions,
+) -> MultiValidationResult {
+    file_type.format().diagnostics(content, cap, index, opts)
+}
+
+pub fn is_json_literal(s: &str) -> bool {
+    // Check for basic JSON literals
+    if matches!(s, "true" | "false" | "null") {
+        return true;
+    }
+
+    if let Ok(value) = serde_json::from_str::<Value>(s) {
+        return matches!(value, Value::Number(_) | Value::Array(_) | Value::Object(_));
+    }
+
+    false
+}
+
+pub(crate) fn escape_json_string(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '"' => "\\\"".to_string(),
+            '\\' => "\\\\".to_string(),
+            '\n' => "\\n".to_string(),
+            '\r' => "\\r".to_string(),
+            '\t' => "\\t".to_string(),
+            c if c.is_control() => format!("\\u{:04x}", c as u32),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(feature = "xml")]
+pub(crate) fn escape_xml_string(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            '\'' => "&apos;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(feature = "env")]
+pub(crate) fn escape_env_string(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '"' => "\\\"".to_string(),
+            '\\' => "\\\\".to_string(),
+            '\n' => "\\n".to_string(),
+            '\r' => "\\r".to_string(),
+            '\t' => "\\t".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
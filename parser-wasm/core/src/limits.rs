@@ -0,0 +1,144 @@
+//! Embedder-configurable input guards (`set_limits`).
+//!
+//! `maxBytes` is checked up front by every entry point, `maxTokens` is
+//! charged by the tokenizer loops through [`TokenBudget`], and `maxErrors`
+//! caps the error lists of `validate_multi` and the schema validators.
+//!
+//! Tokenizers report overruns through their usual `String` errors; the
+//! structured [`LimitExceeded`] is recorded on the side and picked up by
+//! [`guard`] at the API boundary. The state is thread-local, which on wasm is
+//! the whole module.
+
+use std::cell::Cell;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub max_bytes: Option<usize>,
+    pub max_tokens: Option<usize>,
+    pub max_errors: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    Bytes,
+    Tokens,
+}
+
+impl LimitKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LimitKind::Bytes => "maxBytes",
+            LimitKind::Tokens => "maxTokens",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitExceeded {
+    pub kind: LimitKind,
+    pub max: usize,
+    /// Observed size; tokenizers stop counting at `max + 1`.
+    pub actual: usize,
+}
+
+impl LimitExceeded {
+    pub fn message(&self) -> String {
+        match self.kind {
+            LimitKind::Bytes => format!(
+                "Limit exceeded: input is {} bytes, maxBytes is {}",
+                self.actual, self.max
+            ),
+            LimitKind::Tokens => format!(
+                "Limit exceeded: input has more than {} tokens (maxTokens)",
+                self.max
+            ),
+        }
+    }
+}
+
+thread_local! {
+    static LIMITS: Cell<Limits> = const { Cell::new(Limits { max_bytes: None, max_tokens: None, max_errors: None }) };
+    static TRIPPED: Cell<Option<LimitExceeded>> = const { Cell::new(None) };
+}
+
+pub(crate) fn current() -> Limits {
+    LIMITS.with(Cell::get)
+}
+
+pub(crate) fn set(limits: Limits) {
+    LIMITS.with(|cell| cell.set(limits));
+}
+
+/// Applies the global `maxErrors` on top of a per-call cap.
+pub(crate) fn cap_errors(requested: usize) -> usize {
+    match current().max_errors {
+        Some(max) => requested.min(max),
+        None => requested,
+    }
+}
+
+pub(crate) fn check_bytes(len: usize) -> Result<(), LimitExceeded> {
+    match current().max_bytes {
+        Some(max) if len > max => Err(LimitExceeded {
+            kind: LimitKind::Bytes,
+            max,
+            actual: len,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Runs an entry point over `content_len` bytes of input, turning a byte or
+/// token overrun into a structured error.
+pub(crate) fn guard<T>(content_len: usize, f: impl FnOnce() -> T) -> Result<T, LimitExceeded> {
+    check_bytes(content_len)?;
+    TRIPPED.with(|cell| cell.set(None));
+    let out = f();
+    match TRIPPED.with(Cell::take) {
+        Some(exceeded) => Err(exceeded),
+        None => Ok(out),
+    }
+}
+
+/// Whether a limit tripped inside the current guard.
+pub(crate) fn tripped() -> bool {
+    TRIPPED.with(|cell| cell.get().is_some())
+}
+
+/// Per-scan token counter charged once per produced token.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TokenBudget {
+    max: Option<usize>,
+    used: usize,
+}
+
+impl TokenBudget {
+    pub(crate) fn new() -> Self {
+        Self::resumed(0)
+    }
+
+    /// Budget for a scan that already produced `used` tokens.
+    pub(crate) fn resumed(used: usize) -> Self {
+        Self {
+            max: current().max_tokens,
+            used,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn spend(&mut self) -> Result<(), String> {
+        self.used += 1;
+        match self.max {
+            Some(max) if self.used > max => {
+                let exceeded = LimitExceeded {
+                    kind: LimitKind::Tokens,
+                    max,
+                    actual: self.used,
+                };
+                TRIPPED.with(|cell| cell.set(Some(exceeded)));
+                Err(exceeded.message())
+            }
+            _ => Ok(()),
+        }
+    }
+}
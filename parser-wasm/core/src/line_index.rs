@@ -0,0 +1,166 @@
+//! Line-start table for fast offset ↔ line/column conversion, plus the
+//! one-shot [`line_col_at`] / [`offset_at`] for callers without a table.
+//!
+//! Building the index walks the content once; lookups are a binary search.
+//! `Document` keeps one per content version so multi-validation, schema
+//! validation and the position exports don't rebuild it per call.
+//!
+//! Every module maps positions through here so line endings are handled one
+//! way: lines end at `\n`, and a `\r` directly before it belongs to the
+//! terminator. Columns never point into a terminator; offsets inside one map
+//! to the end of the line, and columns past the end clamp to before the
+//! `\r\n`. A lone `\r` is an ordinary character, as serde_json and xmlparser
+//! count lines. A leading BOM is not counted: line 1 starts after it, as
+//! editors show it.
+
+use crate::encoding::bom_len;
+
+/// Unit `line_col_in` counts columns in: Unicode scalar values, UTF-16 code
+/// units (what Monaco and CodeMirror expect), or bytes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnUnit {
+    #[default]
+    Char,
+    Utf16,
+    Byte,
+}
+
+impl ColumnUnit {
+    /// `"char"`, `"utf16"` or `"byte"`.
+    pub fn parse(label: &str) -> Option<Self> {
+        match label {
+            "char" => Some(ColumnUnit::Char),
+            "utf16" => Some(ColumnUnit::Utf16),
+            "byte" => Some(ColumnUnit::Byte),
+            _ => None,
+        }
+    }
+}
+
+/// Width of `text` in `unit`.
+fn width(text: &str, unit: ColumnUnit) -> usize {
+    match unit {
+        ColumnUnit::Char => text.chars().count(),
+        ColumnUnit::Utf16 => text.encode_utf16().count(),
+        ColumnUnit::Byte => text.len(),
+    }
+}
+
+/// End of the line starting at `line_start`, before its `\n` or `\r\n`.
+fn line_end(content: &str, line_start: usize) -> usize {
+    let bytes = content.as_bytes();
+    match memchr::memchr(b'\n', &bytes[line_start..]) {
+        Some(pos) => {
+            let nl = line_start + pos;
+            if nl > line_start && bytes[nl - 1] == b'\r' {
+                nl - 1
+            } else {
+                nl
+            }
+        }
+        None => bytes.len(),
+    }
+}
+
+/// 1-based column of `offset` on the line starting at `line_start`.
+fn column_in_line(content: &str, line_start: usize, offset: usize, unit: ColumnUnit) -> usize {
+    let mut clamped = offset.clamp(line_start, line_end(content, line_start));
+    while !content.is_char_boundary(clamped) {
+        clamped -= 1;
+    }
+    width(&content[line_start..clamped], unit) + 1
+}
+
+/// Byte offset of the 1-based `column` on the line starting at `line_start`,
+/// clamped to the end of the line.
+fn offset_in_line(content: &str, line_start: usize, column: usize, unit: ColumnUnit) -> usize {
+    let end = line_end(content, line_start);
+    let target = column.saturating_sub(1);
+    let mut seen = 0usize;
+    for (idx, ch) in content[line_start..end].char_indices() {
+        if seen >= target {
+            return line_start + idx;
+        }
+        seen += match unit {
+            ColumnUnit::Char => 1,
+            ColumnUnit::Utf16 => ch.len_utf16(),
+            ColumnUnit::Byte => ch.len_utf8(),
+        };
+    }
+    end
+}
+
+/// 1-based line and column for `offset` without building a [`LineIndex`].
+pub(crate) fn line_col_at(content: &str, offset: usize, unit: ColumnUnit) -> (usize, usize) {
+    let offset = offset.min(content.len());
+    let bytes = &content.as_bytes()[..offset];
+    let line = memchr::memchr_iter(b'\n', bytes).count() + 1;
+    let line_start = memchr::memrchr(b'\n', bytes).map_or(bom_len(content), |nl| nl + 1);
+    (line, column_in_line(content, line_start, offset, unit))
+}
+
+/// Byte offset for a 1-based line and column without building a
+/// [`LineIndex`]. Lines past the end clamp to the content length.
+pub(crate) fn offset_at(content: &str, line: usize, column: usize, unit: ColumnUnit) -> usize {
+    let mut line_start = bom_len(content);
+    for _ in 1..line.max(1) {
+        match memchr::memchr(b'\n', &content.as_bytes()[line_start..]) {
+            Some(pos) => line_start += pos + 1,
+            None => return content.len(),
+        }
+    }
+    offset_in_line(content, line_start, column, unit)
+}
+
+pub(crate) struct LineIndex {
+    offsets: Vec<usize>,
+    len: usize,
+}
+
+impl LineIndex {
+    pub(crate) fn new(content: &str) -> Self {
+        let mut offsets = Vec::with_capacity(content.len() / 32 + 1);
+        offsets.push(bom_len(content));
+        offsets.extend(memchr::memchr_iter(b'\n', content.as_bytes()).map(|idx| idx + 1));
+        Self {
+            offsets,
+            len: content.len(),
+        }
+    }
+
+    fn line_of(&self, offset: usize) -> usize {
+        match self.offsets.binary_search(&offset) {
+            Ok(i) => i,
+            Err(0) => 0,
+            Err(i) => i - 1,
+        }
+    }
+
+    /// 1-based line and character column for `offset`.
+    pub(crate) fn line_col_chars(&self, content: &str, offset: usize) -> (usize, usize) {
+        self.line_col_in(content, offset, ColumnUnit::Char)
+    }
+
+    /// 1-based line and column for `offset`, counted in `unit`.
+    pub(crate) fn line_col_in(
+        &self,
+        content: &str,
+        offset: usize,
+        unit: ColumnUnit,
+    ) -> (usize, usize) {
+        let clamped = offset.min(self.len);
+        let idx = self.line_of(clamped);
+        let column = column_in_line(content, self.offsets[idx], clamped, unit);
+        (idx + 1, column)
+    }
+
+    /// Byte offset for a 1-based line and character column. Columns past the
+    /// end of the line clamp to the line end; lines past the end clamp to the
+    /// content length.
+    pub(crate) fn offset(&self, content: &str, line: usize, column: usize) -> usize {
+        match self.offsets.get(line.saturating_sub(1)) {
+            Some(&line_start) => offset_in_line(content, line_start, column, ColumnUnit::Char),
+            None => self.len,
+        }
+    }
+}
@@ -0,0 +1,816 @@
+//! Style rules over valid documents.
+//!
+//! Each rule is off unless [`LintConfig`] gives it a severity. Line rules
+//! read the text and apply to every format; key and value rules walk the
+//! document's tree (`json_tree`, `xml_tree`) or ENV entries. A rule that
+//! cannot apply to a format, such as a header comment in JSON, is skipped.
+
+use serde::Serialize;
+use serde_json::Value;
+use tsify::Tsify;
+
+use crate::document::{Document, FileType};
+use crate::encoding::bom_len;
+use crate::json_lexer::{self, Kind};
+use crate::json_tree::{self, JsonNode, JsonValue, Member};
+use crate::schema::{in_place_subschemas, Severity};
+#[cfg(feature = "xml")]
+use crate::xml_tree::XmlElement;
+use crate::Span;
+
+/// Naming convention for keys, XML names and ENV variables.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyCase {
+    /// `camelCase`
+    #[default]
+    Camel,
+    /// `PascalCase`
+    Pascal,
+    /// `snake_case`
+    Snake,
+    /// `kebab-case`
+    Kebab,
+    /// `SCREAMING_SNAKE_CASE`; ENV nesting (`SERVER__PORT`) is allowed.
+    ScreamingSnake,
+}
+
+impl KeyCase {
+    pub fn parse(label: &str) -> Option<Self> {
+        match label {
+            "camelCase" => Some(Self::Camel),
+            "PascalCase" => Some(Self::Pascal),
+            "snake_case" => Some(Self::Snake),
+            "kebab-case" => Some(Self::Kebab),
+            "SCREAMING_SNAKE_CASE" => Some(Self::ScreamingSnake),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Camel => "camelCase",
+            Self::Pascal => "PascalCase",
+            Self::Snake => "snake_case",
+            Self::Kebab => "kebab-case",
+            Self::ScreamingSnake => "SCREAMING_SNAKE_CASE",
+        }
+    }
+
+    fn matches(self, name: &str) -> bool {
+        let alnum = |c: char, upper: bool| {
+            c.is_ascii_digit()
+                || if upper {
+                    c.is_ascii_uppercase()
+                } else {
+                    c.is_ascii_lowercase()
+                }
+        };
+        // words joined by `sep`, none empty unless `doubled` allows `__`
+        let words = |sep: char, upper: bool, doubled: bool| {
+            let starts_alpha = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic());
+            let doubled_ok = |name: &str| {
+                let single = sep.to_string();
+                let double = format!("{sep}{sep}");
+                if doubled {
+                    !name.contains(&format!("{double}{single}"))
+                } else {
+                    !name.contains(&double)
+                }
+            };
+            starts_alpha
+                && !name.ends_with(sep)
+                && doubled_ok(name)
+                && name.chars().all(|c| c == sep || alnum(c, upper))
+        };
+        match self {
+            Self::Camel | Self::Pascal => {
+                let mut chars = name.chars();
+                let first_ok = chars.next().is_some_and(|c| match self {
+                    Self::Camel => c.is_ascii_lowercase(),
+                    _ => c.is_ascii_uppercase(),
+                });
+                first_ok && chars.all(|c| c.is_ascii_alphanumeric())
+            }
+            Self::Snake => words('_', false, false),
+            Self::Kebab => words('-', false, false),
+            Self::ScreamingSnake => words('_', true, true),
+        }
+    }
+}
+
+/// Order `sort-keys` expects JSON object keys in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum KeyOrder {
+    /// By code point.
+    #[default]
+    Alphabetical,
+    /// The order of each level's `properties` in the schema registered
+    /// under this id, undeclared keys last. Objects the schema does not
+    /// describe are not checked.
+    Schema(String),
+}
+
+/// The property order a JSON schema declares, per object level, following
+/// `properties` and `items`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct SchemaKeyOrder {
+    properties: Vec<(String, SchemaKeyOrder)>,
+    items: Option<Box<SchemaKeyOrder>>,
+}
+
+impl SchemaKeyOrder {
+    /// Reads the order from schema text; a parsed `Value` would have lost
+    /// it.
+    pub(crate) fn parse(schema: &str) -> Result<Self, String> {
+        serde_json::from_str::<Value>(schema).map_err(|e| format!("Invalid schema JSON: {e}"))?;
+        let tokens = json_lexer::lex(schema)?;
+        Ok(Self::from_node(&json_tree::build(&tokens, schema)?))
+    }
+
+    fn from_node(node: &JsonNode) -> Self {
+        let mut order = Self::default();
+        let JsonValue::Object(members) = &node.value else {
+            return order;
+        };
+        for member in members {
+            match (member.name.as_str(), &member.value.value) {
+                ("properties", JsonValue::Object(properties)) => {
+                    order.properties = properties
+                        .iter()
+                        .map(|p| (p.name.clone(), Self::from_node(&p.value)))
+                        .collect();
+                }
+                ("items", JsonValue::Object(_)) => {
+                    order.items = Some(Box::new(Self::from_node(&member.value)));
+                }
+                _ => {}
+            }
+        }
+        order
+    }
+
+    fn rank(&self, name: &str) -> usize {
+        let declared = self.properties.iter().position(|(n, _)| n == name);
+        declared.unwrap_or(self.properties.len())
+    }
+
+    fn property(&self, name: &str) -> Option<&Self> {
+        let found = self.properties.iter().find(|(n, _)| n == name);
+        found.map(|(_, order)| order)
+    }
+}
+
+/// Which rules run, and at what severity. `None` turns a rule off.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintConfig {
+    /// `key-naming`: keys, XML element and attribute names and ENV
+    /// variables follow a convention.
+    pub key_naming: Option<(Severity, KeyCase)>,
+    /// `max-line-length`: lines of at most this many characters.
+    pub max_line_length: Option<(Severity, usize)>,
+    /// `header-comment`: the file opens with a comment (after any XML
+    /// declaration), containing the text when one is given. Not for JSON.
+    pub header_comment: Option<(Severity, Option<String>)>,
+    /// `no-tabs`: no tabs in indentation.
+    pub no_tabs: Option<Severity>,
+    /// `no-empty-values`: no empty strings, XML attributes or elements, or
+    /// ENV values.
+    pub no_empty_values: Option<Severity>,
+    /// `eol-last`: non-empty content ends with a newline.
+    pub eol_last: Option<Severity>,
+    /// `no-trailing-spaces`: no spaces or tabs at the end of a line, outside
+    /// quoted ENV values.
+    pub no_trailing_spaces: Option<Severity>,
+    /// `no-mixed-spaces-and-tabs`: indentation is not a mix of both; the fix
+    /// expands tabs to spaces at stops this many columns apart.
+    pub no_mixed_spaces_and_tabs: Option<(Severity, usize)>,
+    /// `sort-keys`: JSON object keys are in order; the fix reorders the
+    /// members, keeping the text between them.
+    pub sort_keys: Option<(Severity, KeyOrder)>,
+    /// `no-boolean-strings`: no boolean-like strings (`"true"`, `"yes"`,
+    /// `"on"`, ...) where the schema registered under this id expects a
+    /// boolean. ENV values are strings anyway, so only spellings other than
+    /// `true` / `false` are reported there.
+    pub no_boolean_strings: Option<(Severity, String)>,
+}
+
+/// `max-line-length` when its entry gives no length.
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 120;
+
+/// Tab width of `no-mixed-spaces-and-tabs` when its entry gives none.
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+impl LintConfig {
+    /// A config from ESLint-style `rules`: each rule id maps to its level,
+    /// `"off"`, `"warning"` or `"error"`, or to `[level, option]`, the
+    /// option being the case for `key-naming` (`"camelCase"` by default),
+    /// the length for `max-line-length`, the required text for
+    /// `header-comment`, the tab width for `no-mixed-spaces-and-tabs` and
+    /// `"asc"` (the default) or `{ "schemaId": "<registered id>" }` for
+    /// `sort-keys`, and `{ "schemaId": "<registered id>" }` (required) for
+    /// `no-boolean-strings`.
+    pub fn from_rules(rules: &Value) -> Result<Self, String> {
+        let Value::Object(rules) = rules else {
+            return Err("Invalid lint rules: must be an object".into());
+        };
+        let mut config = Self::default();
+        for (rule, entry) in rules {
+            let (level, option) = match entry {
+                Value::Array(items) => (items.first().unwrap_or(&Value::Null), items.get(1)),
+                level => (level, None),
+            };
+            let severity = match level.as_str() {
+                Some("off") => None,
+                Some("warning") => Some(Severity::Warning),
+                Some("error") => Some(Severity::Error),
+                _ => {
+                    return Err(format!(
+                        "Invalid level for lint rule '{rule}': expected \"off\", \"warning\" or \"error\""
+                    ))
+                }
+            };
+            let invalid = || format!("Invalid option for lint rule '{rule}'");
+            match rule.as_str() {
+                "key-naming" => {
+                    let case = match option {
+                        None => KeyCase::default(),
+                        Some(v) => v.as_str().and_then(KeyCase::parse).ok_or_else(invalid)?,
+                    };
+                    config.key_naming = severity.map(|s| (s, case));
+                }
+                "max-line-length" => {
+                    let max = match option {
+                        None => DEFAULT_MAX_LINE_LENGTH,
+                        Some(v) => v.as_u64().filter(|&n| n > 0).ok_or_else(invalid)? as usize,
+                    };
+                    config.max_line_length = severity.map(|s| (s, max));
+                }
+                "header-comment" => {
+                    let text = match option {
+                        None => None,
+                        Some(v) => Some(v.as_str().ok_or_else(invalid)?.to_string()),
+                    };
+                    config.header_comment = severity.map(|s| (s, text));
+                }
+                "no-mixed-spaces-and-tabs" => {
+                    let width = match option {
+                        None => DEFAULT_TAB_WIDTH,
+                        Some(v) => v.as_u64().filter(|&n| n > 0).ok_or_else(invalid)? as usize,
+                    };
+                    config.no_mixed_spaces_and_tabs = severity.map(|s| (s, width));
+                }
+                "sort-keys" => {
+                    let order = match option {
+                        None => KeyOrder::Alphabetical,
+                        Some(Value::String(order)) if order == "asc" => KeyOrder::Alphabetical,
+                        Some(Value::Object(opts)) => {
+                            let id = opts.get("schemaId").and_then(Value::as_str);
+                            KeyOrder::Schema(id.ok_or_else(invalid)?.to_string())
+                        }
+                        Some(_) => return Err(invalid()),
+                    };
+                    config.sort_keys = severity.map(|s| (s, order));
+                }
+                "no-boolean-strings" => {
+                    let id = option
+                        .and_then(|opts| opts.get("schemaId"))
+                        .and_then(Value::as_str);
+                    config.no_boolean_strings = match severity {
+                        None => None,
+                        Some(s) => Some((s, id.ok_or_else(invalid)?.to_string())),
+                    };
+                }
+                "no-tabs" => config.no_tabs = severity,
+                "no-empty-values" => config.no_empty_values = severity,
+                "eol-last" => config.eol_last = severity,
+                "no-trailing-spaces" => config.no_trailing_spaces = severity,
+                _ => return Err(format!("Unknown lint rule '{rule}'")),
+            }
+        }
+        Ok(config)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct LintDiagnostic {
+    pub message: String,
+    /// The rule that reported it, e.g. `key-naming`.
+    pub code: &'static str,
+    pub severity: Severity,
+    pub line: usize,
+    pub column: usize,
+    #[serde(flatten)]
+    pub span: Span,
+    /// An edit that resolves the diagnostic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub fix: Option<LintFix>,
+}
+
+/// Replaces `span` with `text`; positions are in the linted content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct LintFix {
+    #[serde(flatten)]
+    pub span: Span,
+    pub text: String,
+}
+
+/// `validate_multi`'s shape: `valid` unless an `error` diagnostic was
+/// reported, every diagnostic in document order, and the first as the
+/// summary.
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct LintResult {
+    pub valid: bool,
+    pub errors: Vec<LintDiagnostic>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<LintDiagnostic>,
+}
+
+struct Lint<'a> {
+    doc: &'a Document,
+    content: &'a str,
+    config: &'a LintConfig,
+    out: Vec<LintDiagnostic>,
+}
+
+/// Runs the enabled rules over a document that has passed validation.
+/// `schema` is the one `no-boolean-strings` names and `key_order` that of
+/// the one `sort-keys` names, both looked up by the caller.
+pub(crate) fn run(
+    doc: &Document,
+    config: &LintConfig,
+    schema: Option<&Value>,
+    key_order: Option<&SchemaKeyOrder>,
+) -> Result<LintResult, String> {
+    let mut lint = Lint {
+        doc,
+        content: doc.content_str(),
+        config,
+        out: Vec::new(),
+    };
+    let schema_for_booleans = schema.filter(|_| config.no_boolean_strings.is_some());
+    lint.lines();
+    #[cfg(any(feature = "xml", feature = "env"))]
+    lint.header();
+    match doc.file_type() {
+        FileType::Json => {
+            let tree = doc.json_tree()?;
+            lint.json(&tree, key_order);
+            if let Some(root) = schema_for_booleans {
+                lint.json_booleans(root, &tree, &in_place_subschemas(root, &[root]));
+            }
+        }
+        #[cfg(feature = "xml")]
+        FileType::Xml => lint.xml(&doc.xml_tree()?),
+        #[cfg(feature = "env")]
+        FileType::Env => {
+            lint.env()?;
+            if let Some(root) = schema_for_booleans {
+                lint.env_booleans(root)?;
+            }
+        }
+    }
+    let mut errors = lint.out;
+    errors.sort_by_key(|d| (d.span.start, d.span.end));
+    Ok(LintResult {
+        valid: !errors.iter().any(|d| d.severity == Severity::Error),
+        summary: errors.first().cloned(),
+        errors,
+    })
+}
+
+/// Whether `schemas` take a boolean and no string.
+fn expects_boolean(schemas: &[&Value]) -> bool {
+    let allows = |ty: &str| {
+        schemas.iter().any(|schema| match schema.get("type") {
+            Some(Value::String(t)) => t == ty,
+            Some(Value::Array(types)) => types.iter().any(|t| t == ty),
+            _ => false,
+        })
+    };
+    allows("boolean") && !allows("string")
+}
+
+/// The boolean a string such as `"yes"` or `"OFF"` stands for.
+fn boolean_like(text: &str) -> Option<bool> {
+    match text.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Columns `indentation` takes with tab stops `width` apart.
+fn expanded_width(indentation: &str, width: usize) -> usize {
+    indentation.chars().fold(0, |column, c| match c {
+        '\t' => (column / width + 1) * width,
+        _ => column + 1,
+    })
+}
+
+impl Lint<'_> {
+    fn push(&mut self, code: &'static str, severity: Severity, message: String, span: Span) {
+        let (line, column) = self.doc.position_of(span.start);
+        self.out.push(LintDiagnostic {
+            message,
+            code,
+            severity,
+            line,
+            column,
+            span,
+            fix: None,
+        });
+    }
+
+    /// [`push`](Self::push) with an edit that resolves the diagnostic.
+    fn push_fix(
+        &mut self,
+        code: &'static str,
+        severity: Severity,
+        message: String,
+        span: Span,
+        fix: LintFix,
+    ) {
+        self.push(code, severity, message, span);
+        if let Some(diagnostic) = self.out.last_mut() {
+            diagnostic.fix = Some(fix);
+        }
+    }
+
+    /// `max-line-length`, `no-tabs`, `no-trailing-spaces`,
+    /// `no-mixed-spaces-and-tabs` and `eol-last`.
+    fn lines(&mut self) {
+        let config = self.config;
+        let (length, tabs) = (config.max_line_length, config.no_tabs);
+        let (trailing, mixed) = (config.no_trailing_spaces, config.no_mixed_spaces_and_tabs);
+        self.eol_last();
+        if length.is_none() && tabs.is_none() && trailing.is_none() && mixed.is_none() {
+            return;
+        }
+        let verbatim = if trailing.is_some() {
+            self.verbatim_spans()
+        } else {
+            Vec::new()
+        };
+        let mut start = bom_len(self.content);
+        while start <= self.content.len() {
+            let rest = &self.content[start..];
+            let end = start + rest.find('\n').unwrap_or(rest.len());
+            let line = self.content[start..end].trim_end_matches('\r');
+            if let Some((severity, max)) = length {
+                if let Some((cut, _)) = line.char_indices().nth(max) {
+                    self.push(
+                        "max-line-length",
+                        severity,
+                        format!("Line is longer than {max} characters"),
+                        Span::new(start + cut, start + line.len()),
+                    );
+                }
+            }
+            let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+            if let Some(severity) = tabs {
+                if line[..indent].contains('\t') {
+                    self.push(
+                        "no-tabs",
+                        severity,
+                        "Indentation uses tabs".into(),
+                        Span::new(start, start + indent),
+                    );
+                }
+            }
+            if let Some((severity, width)) = mixed {
+                let indentation = &line[..indent];
+                if indentation.contains('\t') && indentation.contains(' ') {
+                    let span = Span::new(start, start + indent);
+                    let text = " ".repeat(expanded_width(indentation, width));
+                    self.push_fix(
+                        "no-mixed-spaces-and-tabs",
+                        severity,
+                        "Indentation mixes spaces and tabs".into(),
+                        span,
+                        LintFix { span, text },
+                    );
+                }
+            }
+            if let Some(severity) = trailing {
+                let kept = line.trim_end_matches([' ', '\t']).len();
+                let span = Span::new(start + kept, start + line.len());
+                let quoted = verbatim
+                    .iter()
+                    .any(|v| v.start < span.start && span.end < v.end);
+                if !span.is_empty() && !quoted {
+                    self.push_fix(
+                        "no-trailing-spaces",
+                        severity,
+                        "Trailing whitespace".into(),
+                        span,
+                        LintFix {
+                            span,
+                            text: String::new(),
+                        },
+                    );
+                }
+            }
+            start = end + 1;
+        }
+    }
+
+    /// `eol-last`.
+    fn eol_last(&mut self) {
+        let Some(severity) = self.config.eol_last else {
+            return;
+        };
+        let content = self.content;
+        if content.len() == bom_len(content) || content.ends_with('\n') {
+            return;
+        }
+        let newline = if content.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let end = Span::new(content.len(), content.len());
+        self.push_fix(
+            "eol-last",
+            severity,
+            "Missing newline at the end of the file".into(),
+            end,
+            LintFix {
+                span: end,
+                text: newline.into(),
+            },
+        );
+    }
+
+    /// Spans whose whitespace is part of a value: quoted ENV values.
+    fn verbatim_spans(&self) -> Vec<Span> {
+        match self.doc.file_type() {
+            #[cfg(feature = "env")]
+            FileType::Env => self.doc.env_doc().map_or_else(
+                |_| Vec::new(),
+                |env| {
+                    env.entries()
+                        .iter()
+                        .filter(|entry| entry.quote.is_some())
+                        .map(|entry| entry.value_span)
+                        .collect()
+                },
+            ),
+            _ => Vec::new(),
+        }
+    }
+
+    /// `header-comment`.
+    #[cfg(any(feature = "xml", feature = "env"))]
+    fn header(&mut self) {
+        let Some((severity, text)) = &self.config.header_comment else {
+            return;
+        };
+        let (open, close) = match self.doc.file_type() {
+            FileType::Json => return,
+            #[cfg(feature = "xml")]
+            FileType::Xml => ("<!--", "-->"),
+            #[cfg(feature = "env")]
+            FileType::Env => ("#", "\n"),
+        };
+        let mut at = bom_len(self.content);
+        let mut rest = &self.content[at..];
+        if rest.starts_with("<?xml") {
+            let end = rest.find("?>").map_or(rest.len(), |i| i + 2);
+            at += end;
+            rest = &rest[end..];
+        }
+        let blank = rest.len() - rest.trim_start().len();
+        at += blank;
+        rest = &rest[blank..];
+        let comment = rest
+            .strip_prefix(open)
+            .map(|body| &body[..body.find(close).unwrap_or(body.len())]);
+        let message = match (comment, text) {
+            (None, _) => "The file does not start with a header comment".to_string(),
+            (Some(body), Some(text)) if !body.contains(text.as_str()) => {
+                format!("The header comment does not contain \"{text}\"")
+            }
+            _ => return,
+        };
+        self.push("header-comment", *severity, message, Span::new(at, at));
+    }
+
+    fn key(&mut self, name: &str, span: Span) {
+        let Some((severity, case)) = self.config.key_naming else {
+            return;
+        };
+        if !case.matches(name) {
+            self.push(
+                "key-naming",
+                severity,
+                format!("'{name}' is not {}", case.as_str()),
+                span,
+            );
+        }
+    }
+
+    fn empty_value(&mut self, what: &str, span: Span) {
+        if let Some(severity) = self.config.no_empty_values {
+            self.push("no-empty-values", severity, format!("Empty {what}"), span);
+        }
+    }
+
+    /// `sort-keys` for the members of one object; `schema` describes the
+    /// object when the order comes from a schema.
+    fn key_order(&mut self, members: &[Member], schema: Option<&SchemaKeyOrder>) {
+        let Some((severity, order)) = &self.config.sort_keys else {
+            return;
+        };
+        let mut sorted: Vec<&Member> = members.iter().collect();
+        match (order, schema) {
+            (KeyOrder::Alphabetical, _) => sorted.sort_by(|a, b| a.name.cmp(&b.name)),
+            (KeyOrder::Schema(_), Some(schema)) => sorted.sort_by_key(|m| schema.rank(&m.name)),
+            (KeyOrder::Schema(_), None) => return,
+        }
+        let Some(first) = members
+            .iter()
+            .zip(&sorted)
+            .position(|(member, want)| !std::ptr::eq(member, *want))
+        else {
+            return;
+        };
+        // each member's text moves to the place of the one it replaces
+        let text = |m: &Member| &self.content[m.key_span.start..m.value.span.end];
+        let mut fixed = String::new();
+        for (i, member) in members.iter().enumerate() {
+            fixed.push_str(text(sorted[i]));
+            if let Some(next) = members.get(i + 1) {
+                fixed.push_str(&self.content[member.value.span.end..next.key_span.start]);
+            }
+        }
+        let region = Span::new(
+            members[0].key_span.start,
+            members[members.len() - 1].value.span.end,
+        );
+        let key = sorted[first].key_span;
+        self.push_fix(
+            "sort-keys",
+            *severity,
+            format!(
+                "'{}' should come before '{}'",
+                sorted[first].name, members[first].name
+            ),
+            Span::new(key.start + 1, key.end - 1),
+            LintFix {
+                span: region,
+                text: fixed,
+            },
+        );
+    }
+
+    fn json(&mut self, node: &JsonNode, schema: Option<&SchemaKeyOrder>) {
+        match &node.value {
+            JsonValue::Object(members) => {
+                self.key_order(members, schema);
+                for member in members {
+                    let key = member.key_span;
+                    self.key(&member.name, Span::new(key.start + 1, key.end - 1));
+                    self.json(&member.value, schema.and_then(|s| s.property(&member.name)));
+                }
+            }
+            JsonValue::Array(items) => {
+                for item in items {
+                    self.json(item, schema.and_then(|s| s.items.as_deref()));
+                }
+            }
+            JsonValue::Scalar(Kind::StringLit) if node.span.len() == 2 => {
+                self.empty_value("string", node.span)
+            }
+            JsonValue::Scalar(_) => {}
+        }
+    }
+
+    #[cfg(feature = "xml")]
+    fn xml(&mut self, element: &XmlElement) {
+        let start = element.span.start + 1;
+        let name_len = self.content[start..]
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .unwrap_or(0);
+        self.key(
+            &element.name,
+            Span::new(start + name_len - element.name.len(), start + name_len),
+        );
+        for attr in &element.attributes {
+            let before = &self.content[element.span.start..attr.span.start];
+            let name_end = before
+                .rfind('=')
+                .map(|eq| element.span.start + before[..eq].trim_end().len());
+            if let Some(end) = name_end {
+                self.key(&attr.name, Span::new(end - attr.name.len(), end));
+            }
+            if attr.raw.is_empty() {
+                self.empty_value("attribute", attr.span);
+            }
+        }
+        let empty =
+            element.attributes.is_empty() && element.text.is_empty() && element.children.is_empty();
+        if empty {
+            self.empty_value("element", element.span);
+        }
+        for child in &element.children {
+            self.xml(child);
+        }
+    }
+
+    fn boolean_string(&mut self, text: &str, span: Span) {
+        let Some((severity, _)) = self.config.no_boolean_strings else {
+            return;
+        };
+        let Some(value) = boolean_like(text) else {
+            return;
+        };
+        self.push_fix(
+            "no-boolean-strings",
+            severity,
+            format!("\"{text}\" is a string where the schema expects a boolean"),
+            span,
+            LintFix {
+                span,
+                text: value.to_string(),
+            },
+        );
+    }
+
+    /// `no-boolean-strings` over the JSON tree, `schemas` being the parts
+    /// of `root` that apply to `node`.
+    fn json_booleans(&mut self, root: &Value, node: &JsonNode, schemas: &[&Value]) {
+        match &node.value {
+            JsonValue::Object(members) => {
+                for member in members {
+                    let children: Vec<&Value> = schemas
+                        .iter()
+                        .filter_map(|s| s.get("properties")?.get(&member.name))
+                        .collect();
+                    if !children.is_empty() {
+                        self.json_booleans(
+                            root,
+                            &member.value,
+                            &in_place_subschemas(root, &children),
+                        );
+                    }
+                }
+            }
+            JsonValue::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    let children: Vec<&Value> = schemas
+                        .iter()
+                        .filter_map(|s| match s.get("prefixItems") {
+                            Some(prefix) => prefix.get(i).or_else(|| s.get("items")),
+                            None => s.get("items"),
+                        })
+                        .collect();
+                    if !children.is_empty() {
+                        self.json_booleans(root, item, &in_place_subschemas(root, &children));
+                    }
+                }
+            }
+            JsonValue::Scalar(Kind::StringLit) if expects_boolean(schemas) => {
+                let text = &self.content[node.span.start + 1..node.span.end - 1];
+                self.boolean_string(text, node.span);
+            }
+            JsonValue::Scalar(_) => {}
+        }
+    }
+
+    #[cfg(feature = "env")]
+    fn env(&mut self) -> Result<(), String> {
+        let doc = self.doc;
+        for entry in doc.env_doc()?.entries() {
+            self.key(&entry.key, entry.key_span);
+            let quotes = usize::from(entry.quote.is_some()) * 2;
+            if entry.value_span.len() == quotes {
+                self.empty_value("value", entry.value_span);
+            }
+        }
+        Ok(())
+    }
+
+    /// `no-boolean-strings` for the ENV variables `root` declares in its
+    /// `properties`.
+    #[cfg(feature = "env")]
+    fn env_booleans(&mut self, root: &Value) -> Result<(), String> {
+        let doc = self.doc;
+        for entry in doc.env_doc()?.entries() {
+            let Some(schema) = in_place_subschemas(root, &[root])
+                .iter()
+                .find_map(|s| s.get("properties")?.get(&entry.key))
+            else {
+                continue;
+            };
+            let quote = usize::from(entry.quote.is_some());
+            let span = entry.value_span;
+            let text = &self.content[span.start + quote..span.end - quote];
+            let canonical = text == "true" || text == "false";
+            if !canonical && expects_boolean(&in_place_subschemas(root, &[schema])) {
+                self.boolean_string(text, span);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,522 @@
+//! Declarative migrations: a script of edits applied in order.
+//!
+//! A script is a JSON array of steps such as
+//! `{ "op": "rename", "path": ["server", "hostname"], "to": "host" }`. Each
+//! step reads the content the previous one produced and splices its edit
+//! in, so whatever the script does not touch keeps its bytes. Values a step
+//! adds to JSON go through the overlay merge and take their siblings'
+//! layout. ENV files are flat: their paths are one key, and values are
+//! strings.
+
+use serde::Serialize;
+use tsify::Tsify;
+
+use crate::document::{Document, FileType};
+use crate::escape_json_string;
+use crate::json_lexer::Kind;
+use crate::json_parser::decode_json_string;
+use crate::json_tree::{JsonNode, JsonValue};
+use crate::overlay::{self, OverlayOptions};
+use crate::paths::{join_path, parse, split_path, Segment};
+use crate::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+pub enum StepStatus {
+    Applied,
+    /// Nothing to do: the path is missing, or already as the step wants it.
+    Skipped,
+    /// The step cannot apply to this content, which it leaves as it was.
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct MigrationStep {
+    /// `rename`, `move`, `setDefault`, `delete` or `changeType`.
+    pub op: &'static str,
+    /// The path the step reads: `from` for a move.
+    pub path: Vec<String>,
+    pub status: StepStatus,
+    /// Why the step was skipped or failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct Migration {
+    pub content: String,
+    /// One report per script step, in order.
+    pub steps: Vec<MigrationStep>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ValueType {
+    String,
+    Number,
+    Boolean,
+}
+
+impl ValueType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Number => "number",
+            Self::Boolean => "boolean",
+        }
+    }
+}
+
+pub(crate) enum Op {
+    /// Renames the key at `path`, which stays where it is.
+    Rename {
+        path: Vec<String>,
+        to: String,
+    },
+    /// Moves the value at `from` to `to`, creating missing parent objects.
+    Move {
+        from: Vec<String>,
+        to: Vec<String>,
+    },
+    /// Adds `value` (JSON text, as written in the script) at `path` when
+    /// nothing is there.
+    SetDefault {
+        path: Vec<String>,
+        value: String,
+    },
+    Delete {
+        path: Vec<String>,
+    },
+    /// Converts the scalar at `path`, e.g. `"8080"` to `8080`.
+    ChangeType {
+        path: Vec<String>,
+        to: ValueType,
+    },
+}
+
+impl Op {
+    fn name(&self) -> &'static str {
+        match self {
+            Op::Rename { .. } => "rename",
+            Op::Move { .. } => "move",
+            Op::SetDefault { .. } => "setDefault",
+            Op::Delete { .. } => "delete",
+            Op::ChangeType { .. } => "changeType",
+        }
+    }
+
+    fn path(&self) -> &[String] {
+        match self {
+            Op::Rename { path, .. }
+            | Op::SetDefault { path, .. }
+            | Op::Delete { path }
+            | Op::ChangeType { path, .. } => path,
+            Op::Move { from, .. } => from,
+        }
+    }
+}
+
+enum Outcome {
+    Applied(String),
+    Skipped(String),
+    Failed(String),
+}
+
+/// The steps of a script. Paths are arrays of segments or `join_path`
+/// strings.
+pub(crate) fn script(text: &str) -> Result<Vec<Op>, String> {
+    let invalid = |message: &str| format!("Invalid migration script: {message}");
+    let root = json_tree(text).map_err(|e| invalid(&e))?;
+    let JsonValue::Array(steps) = &root.value else {
+        return Err(invalid("must be an array of steps"));
+    };
+    steps
+        .iter()
+        .enumerate()
+        .map(|(i, step)| op(text, step).map_err(|e| invalid(&format!("step {}: {e}", i + 1))))
+        .collect()
+}
+
+fn op(text: &str, step: &JsonNode) -> Result<Op, String> {
+    let JsonValue::Object(members) = &step.value else {
+        return Err("must be an object".into());
+    };
+    let field = |name: &str| {
+        members
+            .iter()
+            .find(|member| member.name == name)
+            .map(|member| &member.value)
+            .ok_or_else(|| format!("missing \"{name}\""))
+    };
+    let text_field = |name: &str| {
+        field(name)
+            .and_then(|node| string(text, node).ok_or(format!("\"{name}\" must be a string")))
+    };
+    let path = |name: &str| {
+        let node = field(name)?;
+        let path = match &node.value {
+            JsonValue::Array(items) => items.iter().map(|item| string(text, item)).collect(),
+            _ => string(text, node).map(|joined| split_path(&joined)),
+        };
+        path.filter(|path| !path.is_empty())
+            .ok_or_else(|| format!("\"{name}\" must be a non-empty path"))
+    };
+    let op = match text_field("op")?.as_str() {
+        "rename" => Op::Rename {
+            path: path("path")?,
+            to: text_field("to")?,
+        },
+        "move" => Op::Move {
+            from: path("from")?,
+            to: path("to")?,
+        },
+        "setDefault" => {
+            let value = field("value")?.span;
+            Op::SetDefault {
+                path: path("path")?,
+                value: text[value.start..value.end].to_string(),
+            }
+        }
+        "delete" => Op::Delete {
+            path: path("path")?,
+        },
+        "changeType" => Op::ChangeType {
+            path: path("path")?,
+            to: match text_field("type")?.as_str() {
+                "string" => ValueType::String,
+                "number" => ValueType::Number,
+                "boolean" => ValueType::Boolean,
+                _ => return Err("\"type\" must be \"string\", \"number\" or \"boolean\"".into()),
+            },
+        },
+        other => return Err(format!("unknown op \"{other}\"")),
+    };
+    Ok(op)
+}
+
+/// A string node's decoded text.
+fn string(text: &str, node: &JsonNode) -> Option<String> {
+    match node.value {
+        JsonValue::Scalar(Kind::StringLit) => {
+            decode_json_string(&text[node.span.start..node.span.end])
+        }
+        _ => None,
+    }
+}
+
+/// Runs `ops` over checked `content`.
+pub(crate) fn run(file_type: FileType, content: &str, ops: &[Op]) -> Result<Migration, String> {
+    let mut content = content.to_string();
+    let mut steps = Vec::with_capacity(ops.len());
+    for op in ops {
+        let outcome = match file_type {
+            FileType::Json => json_step(&content, op)?,
+            #[cfg(feature = "xml")]
+            FileType::Xml => Outcome::Failed("XML documents cannot be migrated".into()),
+            #[cfg(feature = "env")]
+            FileType::Env => env_step(&content, op)?,
+        };
+        let (status, message) = match outcome {
+            Outcome::Applied(next) => {
+                content = next;
+                (StepStatus::Applied, None)
+            }
+            Outcome::Skipped(message) => (StepStatus::Skipped, Some(message)),
+            Outcome::Failed(message) => (StepStatus::Failed, Some(message)),
+        };
+        steps.push(MigrationStep {
+            op: op.name(),
+            path: op.path().to_vec(),
+            status,
+            message,
+        });
+    }
+    Ok(Migration { content, steps })
+}
+
+fn json_tree(content: &str) -> Result<JsonNode, String> {
+    let doc = Document::with_type(FileType::Json, content.to_string());
+    if let Some(message) = &doc.summary().message {
+        return Err(message.clone());
+    }
+    doc.check_syntax()?;
+    doc.json_tree()
+}
+
+fn splice(content: &str, span: Span, text: &str) -> String {
+    let mut out = String::with_capacity(content.len() + text.len());
+    out.push_str(&content[..span.start]);
+    out.push_str(text);
+    out.push_str(&content[span.end..]);
+    out
+}
+
+fn missing(path: &[String]) -> Outcome {
+    Outcome::Skipped(format!("'{}' not found", join_path(path)))
+}
+
+fn json_step(content: &str, op: &Op) -> Result<Outcome, String> {
+    let root = json_tree(content)?;
+    let outcome = match op {
+        Op::Rename { path, to } => match find(&root, path) {
+            None => missing(path),
+            Some(found) => rename(content, &found, to),
+        },
+        Op::Move { from, to } => {
+            let Some(found) = find(&root, from) else {
+                return Ok(missing(from));
+            };
+            if to.starts_with(from) {
+                return Ok(Outcome::Failed("cannot move a value into itself".into()));
+            }
+            if find(&root, to).is_some() {
+                return Ok(Outcome::Failed(format!(
+                    "'{}' already exists",
+                    join_path(to)
+                )));
+            }
+            let raw = content[found.node.span.start..found.node.span.end].to_string();
+            let removed = remove(content, &found);
+            insert(&removed, &json_tree(&removed)?, to, &raw)?
+        }
+        Op::SetDefault { path, value } => match find(&root, path) {
+            Some(_) => Outcome::Skipped(format!("'{}' is already set", join_path(path))),
+            None => insert(content, &root, path, value)?,
+        },
+        Op::Delete { path } => match find(&root, path) {
+            None => missing(path),
+            Some(found) => Outcome::Applied(remove(content, &found)),
+        },
+        Op::ChangeType { path, to } => match find(&root, path) {
+            None => missing(path),
+            Some(found) => change_type(content, found.node, *to),
+        },
+    };
+    Ok(outcome)
+}
+
+/// A value and the object or array entry holding it.
+struct Found<'t> {
+    node: &'t JsonNode,
+    container: &'t JsonNode,
+    index: usize,
+}
+
+fn find<'t>(root: &'t JsonNode, path: &[String]) -> Option<Found<'t>> {
+    let mut found = None;
+    let mut node = root;
+    for segment in path {
+        let (index, next) = node.child(segment)?;
+        found = Some(Found {
+            node: next,
+            container: node,
+            index,
+        });
+        node = next;
+    }
+    found
+}
+
+/// Spans of a container's entries: `"key": value` or an item.
+fn entries(container: &JsonNode) -> Vec<Span> {
+    match &container.value {
+        JsonValue::Object(members) => members
+            .iter()
+            .map(|member| Span::new(member.key_span.start, member.value.span.end))
+            .collect(),
+        JsonValue::Array(items) => items.iter().map(|item| item.span).collect(),
+        JsonValue::Scalar(_) => Vec::new(),
+    }
+}
+
+/// `content` without the entry of `found`, and the comma and whitespace
+/// that separated it from its neighbour.
+fn remove(content: &str, found: &Found) -> String {
+    let entries = entries(found.container);
+    let i = found.index;
+    let cut = if entries.len() == 1 {
+        let span = found.container.span;
+        Span::new(span.start + 1, span.end - 1)
+    } else if i + 1 < entries.len() {
+        Span::new(entries[i].start, entries[i + 1].start)
+    } else {
+        Span::new(entries[i - 1].end, entries[i].end)
+    };
+    splice(content, cut, "")
+}
+
+fn rename(content: &str, found: &Found, to: &str) -> Outcome {
+    let JsonValue::Object(members) = &found.container.value else {
+        return Outcome::Failed("only object keys can be renamed".into());
+    };
+    let member = &members[found.index];
+    if member.name == to {
+        return Outcome::Skipped(format!("already named '{to}'"));
+    }
+    if members.iter().any(|sibling| sibling.name == to) {
+        return Outcome::Failed(format!("'{to}' already exists"));
+    }
+    let key = format!("\"{}\"", escape_json_string(to));
+    Outcome::Applied(splice(content, member.key_span, &key))
+}
+
+/// Adds `raw` at `path`, which does not resolve, creating the missing
+/// objects on the way.
+fn insert(content: &str, root: &JsonNode, path: &[String], raw: &str) -> Result<Outcome, String> {
+    let mut node = Some(root);
+    let mut keys = Vec::with_capacity(path.len());
+    for (depth, segment) in path.iter().enumerate() {
+        let key = match parse(segment) {
+            Segment::Key(key) | Segment::Plain(key) => key,
+            Segment::Index(_) => return Ok(Outcome::Failed("cannot add array items".into())),
+        };
+        if let Some(parent) = node {
+            if !matches!(parent.value, JsonValue::Object(_)) {
+                let at = match depth {
+                    0 => "the document".to_string(),
+                    _ => format!("'{}'", join_path(&path[..depth])),
+                };
+                return Ok(Outcome::Failed(format!("{at} is not an object")));
+            }
+            node = parent.child(segment).map(|(_, next)| next);
+        }
+        keys.push(key);
+    }
+    let mut text = raw.to_string();
+    for key in keys.iter().rev() {
+        text = format!("{{\"{}\": {text}}}", escape_json_string(key));
+    }
+    let tree = json_tree(&text)?;
+    Ok(Outcome::Applied(overlay::apply(
+        content,
+        root,
+        &text,
+        &tree,
+        &OverlayOptions::default(),
+    )))
+}
+
+fn change_type(content: &str, node: &JsonNode, to: ValueType) -> Outcome {
+    let JsonValue::Scalar(kind) = node.value else {
+        return Outcome::Failed("only scalar values can change type".into());
+    };
+    let raw = &content[node.span.start..node.span.end];
+    let current = match kind {
+        Kind::StringLit => Some(ValueType::String),
+        Kind::NumberLit => Some(ValueType::Number),
+        Kind::True | Kind::False => Some(ValueType::Boolean),
+        _ => None,
+    };
+    if current == Some(to) {
+        return Outcome::Skipped(format!("already a {}", to.as_str()));
+    }
+    let text = decode_json_string(raw);
+    let converted = match (to, text.as_deref().map(str::trim)) {
+        (ValueType::String, _) => Some(format!("\"{raw}\"")),
+        (ValueType::Number, Some(number)) => serde_json::from_str::<serde_json::Number>(number)
+            .is_ok()
+            .then(|| number.to_string()),
+        (ValueType::Boolean, Some(flag @ ("true" | "false"))) => Some(flag.to_string()),
+        _ => None,
+    };
+    match converted {
+        Some(text) => Outcome::Applied(splice(content, node.span, &text)),
+        None => Outcome::Failed(format!("{raw} cannot be converted to a {}", to.as_str())),
+    }
+}
+
+#[cfg(feature = "env")]
+fn env_step(content: &str, op: &Op) -> Result<Outcome, String> {
+    use crate::env_organize;
+    use crate::env_resolve::{is_name, rename};
+    use crate::paths::literal;
+
+    let doc = Document::with_type(FileType::Env, content.to_string());
+    let env = doc.env_doc()?;
+    let key = |path: &[String]| match path {
+        [segment] => Some(literal(segment).to_string()),
+        _ => None,
+    };
+    let Some(name) = key(op.path()) else {
+        return Ok(Outcome::Failed("ENV paths are a single key".into()));
+    };
+    let defined = |key: &str| env.entries().iter().any(|entry| entry.key == key);
+    if !defined(&name) && !matches!(op, Op::SetDefault { .. }) {
+        return Ok(missing(op.path()));
+    }
+    let target = match op {
+        Op::Rename { to, .. } => Some(to.clone()),
+        Op::Move { to, .. } => key(to),
+        _ => None,
+    };
+    let outcome = match op {
+        Op::Rename { .. } | Op::Move { .. } => {
+            let Some(to) = target.filter(|to| is_name(to)) else {
+                return Ok(Outcome::Failed(
+                    "the new name is not a valid variable name".into(),
+                ));
+            };
+            if to == name {
+                return Ok(Outcome::Skipped(format!("already named '{to}'")));
+            }
+            if defined(&to) {
+                return Ok(Outcome::Failed(format!("'{to}' already exists")));
+            }
+            match op {
+                // references follow a rename
+                Op::Rename { .. } => {
+                    Outcome::Applied(rename(content, env, &name, &to, |_| (0, 0)).0)
+                }
+                _ => {
+                    let mut out = content.to_string();
+                    for entry in env.entries().iter().rev().filter(|e| e.key == name) {
+                        out.replace_range(entry.key_span.start..entry.key_span.end, &to);
+                    }
+                    Outcome::Applied(out)
+                }
+            }
+        }
+        Op::SetDefault { value, .. } => {
+            if defined(&name) {
+                return Ok(Outcome::Skipped(format!("'{name}' is already set")));
+            }
+            if !is_name(&name) {
+                return Ok(Outcome::Failed(format!(
+                    "'{name}' is not a valid variable name"
+                )));
+            }
+            let text = match value.as_bytes().first() {
+                Some(b'"') => decode_json_string(value).unwrap_or_else(|| value.clone()),
+                Some(b'{' | b'[') => {
+                    return Ok(Outcome::Failed(
+                        "ENV values cannot be objects or arrays".into(),
+                    ))
+                }
+                _ => value.clone(),
+            };
+            let newline = if content.contains("\r\n") {
+                "\r\n"
+            } else {
+                "\n"
+            };
+            let mut out = content.to_string();
+            if !out.is_empty() && !out.ends_with(['\r', '\n']) {
+                out.push_str(newline);
+            }
+            let value = FileType::Env.format().format_value(&text);
+            out.push_str(&format!("{name}={value}{newline}"));
+            Outcome::Applied(out)
+        }
+        Op::Delete { .. } => {
+            let mut out = content.to_string();
+            for entry in env.entries().iter().rev().filter(|e| e.key == name) {
+                let start = env_organize::line_start(content, entry.key_span.start);
+                let end = env_organize::line_end(content, entry.value_span.end);
+                out.replace_range(start..end, "");
+            }
+            Outcome::Applied(out)
+        }
+        Op::ChangeType { .. } => Outcome::Failed("ENV values are untyped strings".into()),
+    };
+    Ok(outcome)
+}
@@ -0,0 +1,987 @@
+use crate::encoding::bom_len;
+use crate::json_lexer::{Kind, LenientLexer, Token};
+#[cfg(feature = "xml")]
+use crate::limits::TokenBudget;
+use crate::line_index::{self, ColumnUnit, LineIndex};
+use crate::schema::{SchemaOutcome, Severity};
+use crate::Span;
+use serde::de::IgnoredAny;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tsify::Tsify;
+#[cfg(feature = "xml")]
+use xmlparser::{Error as XmlError, Tokenizer};
+
+pub(crate) const MAX_MULTI_ERRORS: usize = 10;
+
+/// How XML checking carries on after an error.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum XmlResync {
+    /// Skips the rest of the broken construct: a tag up to its `>` outside
+    /// quotes (or an unquoted `<`), a comment, CDATA section or processing
+    /// instruction up to its terminator. The other attributes of a broken
+    /// tag are still checked, and parsing resumes as element content.
+    #[default]
+    Markup,
+    /// Restarts at the next `<` after the error, as a new document.
+    NextTag,
+}
+
+impl XmlResync {
+    pub fn parse(label: &str) -> Option<Self> {
+        match label {
+            "markup" => Some(XmlResync::Markup),
+            "nextTag" => Some(XmlResync::NextTag),
+            _ => None,
+        }
+    }
+}
+
+/// Per-call settings of `validate_multi`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MultiValidationOptions {
+    pub xml_resync: XmlResync,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+pub struct DetailedError {
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<&'static str>,
+    pub line: usize,
+    pub column: usize,
+    #[serde(flatten)]
+    pub span: Span,
+    /// Later errors on the same line, after
+    /// [`MultiValidationResult::group_by_line`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[tsify(optional)]
+    pub sub_errors: Vec<DetailedError>,
+    /// Other places that explain the error, such as where an unclosed
+    /// structure was opened.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[tsify(optional)]
+    pub related: Vec<RelatedLocation>,
+}
+
+/// A location attached to a [`DetailedError`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct RelatedLocation {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+/// How many diagnostics the checks found, `errors` being capped.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorCounts {
+    pub total_found: usize,
+    /// `errors` lists fewer than `total_found`.
+    pub truncated: bool,
+    /// Diagnostics found per `code`; those without one only count toward
+    /// `total_found`.
+    pub by_code: BTreeMap<&'static str, usize>,
+}
+
+impl ErrorCounts {
+    fn add(&mut self, err: &DetailedError) {
+        self.total_found += 1;
+        if let Some(code) = err.code {
+            *self.by_code.entry(code).or_default() += 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct MultiValidationResult {
+    pub valid: bool,
+    pub errors: Vec<DetailedError>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<DetailedError>,
+    pub counts: ErrorCounts,
+}
+
+impl MultiValidationResult {
+    pub fn success() -> Self {
+        Self {
+            valid: true,
+            summary: None,
+            errors: Vec::new(),
+            counts: ErrorCounts::default(),
+        }
+    }
+
+    /// `summary` alone, for formats that stop at the first error.
+    pub fn single(summary: DetailedError) -> Self {
+        Self::invalid(summary, Found::new(0))
+    }
+
+    fn invalid(summary: DetailedError, found: Found) -> Self {
+        let Found {
+            mut errors,
+            mut counts,
+            ..
+        } = found;
+        if errors.is_empty()
+            || !errors
+                .iter()
+                .any(|e| e.span == summary.span && e.message == summary.message)
+        {
+            counts.add(&summary);
+            errors.insert(0, summary.clone());
+        }
+        counts.truncated = counts.total_found > errors.len();
+        Self {
+            valid: false,
+            summary: Some(summary),
+            errors,
+            counts,
+        }
+    }
+
+    /// Folds each error into the first one listed on its line, as
+    /// `sub_errors`, so a line that set off a cascade takes one entry.
+    /// `summary` and `counts` are unchanged.
+    pub fn group_by_line(mut self) -> Self {
+        let mut grouped: Vec<DetailedError> = Vec::with_capacity(self.errors.len());
+        for err in self.errors {
+            match grouped.iter_mut().find(|first| first.line == err.line) {
+                Some(first) => first.sub_errors.push(err),
+                None => grouped.push(err),
+            }
+        }
+        self.errors = grouped;
+        self
+    }
+
+    /// Adds the errors of a schema run to a result that passed the syntax
+    /// checks, coded `schema.violation`. Deprecation warnings are left out.
+    pub(crate) fn with_schema(self, outcome: &SchemaOutcome) -> Self {
+        let mut found = Found::new(usize::MAX);
+        for err in outcome
+            .errors
+            .iter()
+            .filter(|e| e.severity == Severity::Error)
+        {
+            found.push(DetailedError {
+                message: err.message.clone(),
+                code: Some("schema.violation"),
+                line: err.line.unwrap_or(1),
+                column: err.column.unwrap_or(1),
+                span: Span::new(err.start.unwrap_or(0), err.end.unwrap_or(0)),
+                sub_errors: Vec::new(),
+                related: Vec::new(),
+            });
+        }
+        match found.errors.first() {
+            Some(first) if self.valid => Self::invalid(first.clone(), found),
+            _ => self,
+        }
+    }
+
+    pub fn with_limit(mut self, max_errors: usize) -> Self {
+        if self.errors.len() > max_errors {
+            self.errors.truncate(max_errors);
+        }
+        self.counts.truncated = self.counts.total_found > self.errors.len();
+        self
+    }
+}
+
+/// The first `cap` diagnostics, with counts for every one reported.
+struct Found {
+    errors: Vec<DetailedError>,
+    cap: usize,
+    counts: ErrorCounts,
+}
+
+impl Found {
+    fn new(cap: usize) -> Self {
+        Self {
+            errors: Vec::new(),
+            cap,
+            counts: ErrorCounts::default(),
+        }
+    }
+
+    fn push(&mut self, err: DetailedError) {
+        self.counts.add(&err);
+        if self.errors.len() < self.cap {
+            self.errors.push(err);
+        }
+    }
+}
+
+/// Multi-error JSON validation; reuses a caller-owned line index when given.
+///
+/// Works the same at any size: serde checks syntax without building a
+/// `Value`, and the lexical and structural passes stream tokens through
+/// [`LenientLexer`] and [`StructuralChecker`], so memory stays proportional
+/// to nesting depth and the error cap rather than the input.
+pub(crate) fn validate_json_multi(
+    content: &str,
+    max_errors: usize,
+    index: Option<&LineIndex>,
+) -> MultiValidationResult {
+    let err = match serde_json::from_str::<IgnoredAny>(&content[bom_len(content)..]) {
+        Ok(_) => return MultiValidationResult::success(),
+        Err(err) => err,
+    };
+    // serde_json counts columns in bytes
+    let start = line_index::offset_at(
+        content,
+        err.line().max(1),
+        err.column().max(1),
+        ColumnUnit::Byte,
+    );
+    let mut summary = DetailedError {
+        message: err.to_string(),
+        code: None,
+        line: 0,
+        column: 0,
+        span: infer_json_span(content, start),
+        sub_errors: Vec::new(),
+        related: Vec::new(),
+    };
+
+    // Lexical errors first, then structural ones, as two streaming passes;
+    // both run to the end so the counts cover the whole input
+    let mut found = Found::new(max_errors.clamp(1, MAX_MULTI_ERRORS));
+    for lex_err in LenientLexer::new(content).filter_map(Result::err) {
+        found.push(simple_error(lex_err.span, lex_err.code, &lex_err.message));
+    }
+    let mut checker = StructuralChecker::new(found);
+    for token in LenientLexer::new(content).filter_map(Result::ok) {
+        checker.feed(token);
+    }
+    let mut found = checker.finish();
+
+    locate(content, index, std::slice::from_mut(&mut summary));
+    locate(content, index, &mut found.errors);
+    MultiValidationResult::invalid(summary, found)
+}
+
+#[cfg(feature = "xml")]
+/// Multi-error XML validation; reuses a caller-owned line index when given.
+/// The tokenizer is streaming, so large inputs are handled the same way.
+pub(crate) fn validate_xml_multi(
+    content: &str,
+    max_errors: usize,
+    index: Option<&LineIndex>,
+    resync: XmlResync,
+) -> MultiValidationResult {
+    let mut tokenizer = Tokenizer::from(content);
+    let mut budget = TokenBudget::new();
+    for tok in &mut tokenizer {
+        if budget.spend().is_err() {
+            break;
+        }
+        if let Err(err) = tok {
+            let mut found = Found::new(max_errors.clamp(1, MAX_MULTI_ERRORS));
+            match resync {
+                XmlResync::Markup => resync_at_markup(content, err, &mut found),
+                XmlResync::NextTag => resync_at_next_tag(content, err, &mut found),
+            }
+            locate(content, index, &mut found.errors);
+            let Some(summary) = found.errors.first().cloned() else {
+                return MultiValidationResult::success();
+            };
+            return MultiValidationResult::invalid(summary, found);
+        }
+    }
+    MultiValidationResult::success()
+}
+
+#[cfg(feature = "xml")]
+fn resync_at_next_tag(content: &str, first_error: XmlError, found: &mut Found) {
+    let mut cursor = 0usize;
+    let mut current_error = Some(first_error);
+
+    while cursor < content.len() {
+        let err = match current_error.take() {
+            Some(e) => e,
+            None => {
+                let mut tokenizer = Tokenizer::from(&content[cursor..]);
+                let mut caught: Option<XmlError> = None;
+                for tok in &mut tokenizer {
+                    if let Err(e) = tok {
+                        caught = Some(e);
+                        break;
+                    }
+                }
+                if let Some(e) = caught {
+                    e
+                } else {
+                    break;
+                }
+            }
+        };
+
+        let abs_offset = cursor + crate::xml_error_offset(&content[cursor..], err.pos());
+
+        let detailed = build_xml_error_at(content, &err, abs_offset);
+        cursor = find_next_tag_start(content, detailed.span.end).unwrap_or(content.len());
+        found.push(detailed);
+        current_error = None;
+    }
+}
+
+#[cfg(feature = "xml")]
+fn resync_at_markup(content: &str, first_error: XmlError, found: &mut Found) {
+    let mut rows = Rows::default();
+    // a construct boundary: the start, or the end of the last broken construct
+    let mut from = 0;
+    let mut pending = Some((first_error, 0));
+    loop {
+        let (err, base) = match pending.take() {
+            Some(first) => first,
+            None => {
+                if from >= content.len() {
+                    break;
+                }
+                // the tokenizer runs from the line start so that its
+                // positions only need their row shifted
+                let base = line_start(content, from);
+                let mut tokenizer =
+                    Tokenizer::from_fragment(&content[base..], from - base..content.len() - base);
+                match tokenizer.find_map(Result::err) {
+                    Some(err) => (err, base),
+                    None => break,
+                }
+            }
+        };
+        let at = base + crate::xml_error_offset(&content[base..], err.pos());
+        let message = shift_rows(&err.to_string(), rows.line_of(content, base) - 1);
+        found.push(simple_error(
+            infer_xml_span(content, at, &message),
+            classify_xml_code(&message),
+            &message,
+        ));
+
+        let mut start = from;
+        let end = loop {
+            let (end, tag) = construct_end(content, start);
+            if end > at || end >= content.len() {
+                if tag {
+                    check_attributes(content, at, end, &mut rows, found);
+                }
+                break end;
+            }
+            start = end;
+        };
+        from = end.max(at + 1);
+    }
+}
+
+/// End of the construct starting at `start`, and whether it is a tag.
+#[cfg(feature = "xml")]
+fn construct_end(content: &str, start: usize) -> (usize, bool) {
+    let bytes = content.as_bytes();
+    let rest = &content[start..];
+    let after = |terminator: &str, skip: usize| {
+        rest[skip..]
+            .find(terminator)
+            .map_or(content.len(), |i| start + skip + i + terminator.len())
+    };
+    if !rest.starts_with('<') {
+        let text = memchr::memchr(b'<', &bytes[start..]);
+        return (text.map_or(content.len(), |i| start + i), false);
+    }
+    if rest.starts_with("<!--") {
+        return (after("-->", 4), false);
+    }
+    if rest.starts_with("<![CDATA[") {
+        return (after("]]>", 9), false);
+    }
+    if rest.starts_with("<?") {
+        return (after("?>", 2), false);
+    }
+    if rest.starts_with("<!") {
+        // a DOCTYPE, whose internal subset holds its own `>`s
+        let mut depth = 0usize;
+        for (i, &b) in bytes.iter().enumerate().skip(start + 2) {
+            match b {
+                b'[' => depth += 1,
+                b']' => depth = depth.saturating_sub(1),
+                b'>' if depth == 0 => return (i + 1, false),
+                _ => {}
+            }
+        }
+        return (content.len(), false);
+    }
+    let mut quote = None;
+    let mut i = start + 1;
+    while i < bytes.len() {
+        let b = bytes[i];
+        match quote {
+            Some(q) if b == q => quote = None,
+            // a value left open at the end of its line, before the next
+            // tag, was never closed
+            Some(_) if b == b'\n' => {
+                let next = bytes[i..].iter().position(|b| !b.is_ascii_whitespace());
+                if let Some(next) = next.filter(|&n| bytes[i + n] == b'<') {
+                    return (i + next, true);
+                }
+            }
+            Some(_) => {}
+            None => match b {
+                b'"' | b'\'' => quote = Some(b),
+                b'>' => return (i + 1, true),
+                b'<' => return (i, true),
+                _ => {}
+            },
+        }
+        i += 1;
+    }
+    (content.len(), true)
+}
+
+/// Reports the attributes after the broken one at `at`, up to the tag's
+/// `end`, that are unquoted or have a `<` in their value, with messages in
+/// the tokenizer's wording.
+#[cfg(feature = "xml")]
+fn check_attributes(content: &str, at: usize, end: usize, rows: &mut Rows, found: &mut Found) {
+    let bytes = &content.as_bytes()[..end];
+    let is_name = |b: u8| !b.is_ascii_whitespace() && !matches!(b, b'=' | b'/' | b'>' | b'<');
+    let skip = |mut i: usize, keep: &dyn Fn(u8) -> bool| {
+        while i < bytes.len() && keep(bytes[i]) {
+            i += 1;
+        }
+        i
+    };
+    // past the broken attribute
+    let mut i = skip(at, &|b| b.is_ascii_whitespace());
+    i = skip(i, &is_name);
+    i = skip(i, &|b| b.is_ascii_whitespace() || b == b'=');
+    i = match bytes.get(i) {
+        Some(&q @ (b'"' | b'\'')) => memchr::memchr(q, &bytes[i + 1..]).map_or(end, |n| i + n + 2),
+        _ => skip(i, &is_name),
+    };
+    loop {
+        let name = skip(i, &|b| b.is_ascii_whitespace());
+        let eq = skip(name, &is_name);
+        if eq == name {
+            return;
+        }
+        let value = skip(eq, &|b| b.is_ascii_whitespace());
+        if bytes.get(value) != Some(&b'=') {
+            return;
+        }
+        let value = skip(value + 1, &|b| b.is_ascii_whitespace());
+        let cause = match bytes.get(value) {
+            Some(&q @ (b'"' | b'\'')) => {
+                let Some(close) = memchr::memchr(q, &bytes[value + 1..]) else {
+                    return;
+                };
+                i = value + close + 2;
+                let lt = memchr::memchr(b'<', &bytes[value + 1..i]);
+                lt.map(|n| (format!("expected '{}' not '<'", q as char), value + 1 + n))
+            }
+            Some(_) => {
+                i = skip(value, &is_name);
+                let found = content[value..].chars().next().unwrap_or(' ');
+                Some((format!("expected quote mark not '{found}'"), value))
+            }
+            None => return,
+        };
+        if let Some((cause, offset)) = cause {
+            let (row, col) = rows.position(content, name);
+            let (cause_row, cause_col) = rows.position(content, offset);
+            let message = format!(
+                "invalid attribute at {row}:{col} cause {cause} at {cause_row}:{cause_col}"
+            );
+            found.push(simple_error(
+                infer_xml_span(content, name, &message),
+                classify_xml_code(&message),
+                &message,
+            ));
+        }
+    }
+}
+
+/// `message` with `rows` added to each `row:column` position in it, for
+/// errors from a tokenizer that started past the first line.
+#[cfg(feature = "xml")]
+fn shift_rows(message: &str, rows: usize) -> String {
+    if rows == 0 {
+        return message.to_string();
+    }
+    let mut out = String::with_capacity(message.len() + 8);
+    let mut rest = message;
+    while let Some(i) = rest.find(" at ") {
+        let (head, tail) = rest.split_at(i + 4);
+        out.push_str(head);
+        let digits = tail.bytes().take_while(u8::is_ascii_digit).count();
+        match tail[..digits].parse::<usize>() {
+            Ok(row) if tail[digits..].starts_with(':') => {
+                out.push_str(&(row + rows).to_string());
+                rest = &tail[digits..];
+            }
+            _ => rest = tail,
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(feature = "xml")]
+fn line_start(content: &str, offset: usize) -> usize {
+    memchr::memrchr(b'\n', &content.as_bytes()[..offset]).map_or(0, |i| i + 1)
+}
+
+/// 1-based rows and character columns of offsets near the previous one,
+/// counted from there rather than from the start.
+#[cfg(feature = "xml")]
+struct Rows {
+    offset: usize,
+    line: usize,
+}
+
+#[cfg(feature = "xml")]
+impl Default for Rows {
+    fn default() -> Self {
+        Self { offset: 0, line: 1 }
+    }
+}
+
+#[cfg(feature = "xml")]
+impl Rows {
+    fn line_of(&mut self, content: &str, offset: usize) -> usize {
+        let bytes = content.as_bytes();
+        if offset >= self.offset {
+            self.line += memchr::memchr_iter(b'\n', &bytes[self.offset..offset]).count();
+        } else {
+            self.line -= memchr::memchr_iter(b'\n', &bytes[offset..self.offset]).count();
+        }
+        self.offset = offset;
+        self.line
+    }
+
+    fn position(&mut self, content: &str, offset: usize) -> (usize, usize) {
+        let line = self.line_of(content, offset);
+        let column = content[line_start(content, offset)..offset].chars().count() + 1;
+        (line, column)
+    }
+}
+
+#[cfg(feature = "xml")]
+fn build_xml_error_at(content: &str, err: &XmlError, start: usize) -> DetailedError {
+    let message = err.to_string();
+    let span = infer_xml_span(content, start, &message);
+    let code = classify_xml_code(&message);
+    simple_error(span, code, &message)
+}
+
+#[cfg(feature = "xml")]
+fn classify_xml_code(msg: &str) -> &'static str {
+    let lower = msg.to_lowercase();
+    if lower.contains("quote") {
+        "xml.unterminated_quote"
+    } else if lower.contains("mismatch") {
+        "xml.mismatched_tag"
+    } else if lower.contains("unexpected") {
+        "xml.unexpected_token"
+    } else {
+        "xml.parse_error"
+    }
+}
+
+#[cfg(feature = "xml")]
+fn find_next_tag_start(content: &str, from: usize) -> Option<usize> {
+    let bytes = content.as_bytes();
+    let mut i = from.min(bytes.len());
+    while i < bytes.len() {
+        if bytes[i] == b'<' {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(feature = "xml")]
+fn infer_xml_span(content: &str, start: usize, msg: &str) -> Span {
+    let bytes = content.as_bytes();
+    let clamped_start = start.min(bytes.len());
+    let lower = msg.to_lowercase();
+    let end = if lower.contains("quote") {
+        scan_until(bytes, clamped_start, b'"')
+    } else if lower.contains("unexpected") {
+        (clamped_start + 1).min(bytes.len())
+    } else {
+        scan_until(bytes, clamped_start, b'>')
+    };
+    Span::new(clamped_start, end)
+}
+
+#[cfg(feature = "xml")]
+fn scan_until(bytes: &[u8], start: usize, needle: u8) -> usize {
+    let mut i = start;
+    while i < bytes.len() {
+        if bytes[i] == needle || bytes[i] == b'\n' {
+            return i + 1;
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// Incremental form of the structural pass, fed one token at a time so large
+/// inputs never need the full token vector. Errors carry spans only; callers
+/// fill in line/column with [`locate`].
+struct StructuralChecker {
+    stack: Vec<Context>,
+    errors: Found,
+}
+
+impl StructuralChecker {
+    /// Reports into `errors`, after what is already there.
+    fn new(errors: Found) -> Self {
+        Self {
+            stack: Vec::new(),
+            errors,
+        }
+    }
+
+    fn feed(&mut self, token: Token) {
+        let stack = &mut self.stack;
+        let errors = &mut self.errors;
+        // Recovery arms `continue` to look at the same token again once the
+        // context state has been repaired.
+        loop {
+            if let Some(Context::Array(arr)) = stack.last_mut() {
+                if !arr.expect_value && !matches!(token.kind, Kind::Comma | Kind::RBrack) {
+                    errors.push(missing_comma_error(token.span));
+                    arr.expect_value = true;
+                    arr.comma_guard = false;
+                    continue;
+                }
+            }
+
+            if let Some(Context::Object(obj)) = stack.last_mut() {
+                if matches!(obj.state, ObjectState::ExpectCommaOrEnd)
+                    && !matches!(token.kind, Kind::Comma | Kind::RBrace)
+                {
+                    errors.push(missing_comma_error(token.span));
+                    obj.state = ObjectState::ExpectKeyOrEnd;
+                    obj.comma_guard = false;
+                    continue;
+                }
+            }
+
+            match token.kind {
+                Kind::LBrace => {
+                    note_value_consumed(stack);
+                    stack.push(Context::Object(ObjectContext::new(token.span)));
+                }
+                Kind::RBrace => {
+                    if let Some(Context::Object(obj)) = stack.last() {
+                        if matches!(obj.state, ObjectState::ExpectKeyOrEnd) && obj.comma_guard {
+                            errors.push(trailing_comma_error(token.span));
+                        }
+                    }
+                    match stack.pop() {
+                        Some(Context::Object(_)) => {
+                            note_value_consumed(stack);
+                        }
+                        _ => errors.push(mismatched_error(token.span, "json.mismatched_brace")),
+                    }
+                }
+                Kind::LBrack => {
+                    note_value_consumed(stack);
+                    stack.push(Context::Array(ArrayContext {
+                        opened: token.span,
+                        expect_value: true,
+                        comma_guard: false,
+                        has_value: false,
+                    }));
+                }
+                Kind::RBrack => {
+                    if let Some(Context::Array(arr)) = stack.last() {
+                        if arr.expect_value && arr.has_value {
+                            errors.push(trailing_comma_error(token.span));
+                        }
+                    }
+                    match stack.pop() {
+                        Some(Context::Array(_)) => {
+                            note_value_consumed(stack);
+                        }
+                        _ => errors.push(mismatched_error(token.span, "json.mismatched_bracket")),
+                    }
+                }
+                Kind::StringLit => {
+                    if let Some(Context::Object(obj)) = stack.last_mut() {
+                        match obj.state {
+                            ObjectState::ExpectKeyOrEnd => {
+                                obj.state = ObjectState::ExpectColon {
+                                    key_span: token.span,
+                                };
+                                obj.comma_guard = false;
+                            }
+                            ObjectState::ExpectColon { key_span } => {
+                                errors.push(missing_colon_error(key_span));
+                                obj.state = ObjectState::ExpectValue;
+                                continue;
+                            }
+                            _ => note_value_consumed(stack),
+                        }
+                    } else {
+                        note_value_consumed(stack);
+                    }
+                }
+                Kind::NumberLit | Kind::True | Kind::False | Kind::Null => {
+                    note_value_consumed(stack);
+                }
+                Kind::Colon => match stack.last_mut() {
+                    Some(Context::Object(obj))
+                        if matches!(obj.state, ObjectState::ExpectColon { .. }) =>
+                    {
+                        obj.state = ObjectState::ExpectValue;
+                    }
+                    _ => errors.push(simple_error(
+                        token.span,
+                        "json.unexpected_colon",
+                        "Unexpected ':'",
+                    )),
+                },
+                Kind::Comma => match stack.last_mut() {
+                    Some(Context::Object(obj))
+                        if matches!(obj.state, ObjectState::ExpectCommaOrEnd) =>
+                    {
+                        obj.state = ObjectState::ExpectKeyOrEnd;
+                        obj.comma_guard = true;
+                    }
+                    Some(Context::Array(arr)) if !arr.expect_value => {
+                        arr.expect_value = true;
+                        arr.comma_guard = true;
+                    }
+                    _ => errors.push(simple_error(
+                        token.span,
+                        "json.unexpected_comma",
+                        "Unexpected ','",
+                    )),
+                },
+            }
+            return;
+        }
+    }
+
+    /// Reports contexts still open at the end of input, at their opening
+    /// delimiter.
+    fn finish(mut self) -> Found {
+        for ctx in std::mem::take(&mut self.stack).into_iter().rev() {
+            let (opened, code, message, note) = match ctx {
+                Context::Object(obj) => (
+                    obj.opened,
+                    "json.unclosed_object",
+                    "Unclosed '{'",
+                    "'{' opened here",
+                ),
+                Context::Array(arr) => (
+                    arr.opened,
+                    "json.unclosed_array",
+                    "Unclosed '['",
+                    "'[' opened here",
+                ),
+            };
+            let mut err = simple_error(opened, code, message);
+            err.related.push(RelatedLocation {
+                message: note.to_string(),
+                line: 0,
+                column: 0,
+                span: opened,
+            });
+            self.errors.push(err);
+        }
+        self.errors
+    }
+}
+
+fn note_value_consumed(stack: &mut [Context]) {
+    if let Some(ctx) = stack.last_mut() {
+        match ctx {
+            Context::Object(obj) => {
+                obj.state = ObjectState::ExpectCommaOrEnd;
+                obj.comma_guard = false;
+            }
+            Context::Array(arr) => {
+                arr.expect_value = false;
+                arr.comma_guard = false;
+                arr.has_value = true;
+            }
+        }
+    }
+}
+
+fn missing_colon_error(span: Span) -> DetailedError {
+    simple_error(span, "json.missing_colon", "Missing ':' after object key")
+}
+
+fn missing_comma_error(span: Span) -> DetailedError {
+    simple_error(span, "json.missing_comma", "Missing ',' between items")
+}
+
+fn trailing_comma_error(span: Span) -> DetailedError {
+    simple_error(
+        span,
+        "json.trailing_comma",
+        "Trailing ',' before closing delimiter",
+    )
+}
+
+fn mismatched_error(span: Span, code: &'static str) -> DetailedError {
+    simple_error(span, code, "Mismatched closing delimiter")
+}
+
+/// Error without a position yet; see [`locate`].
+fn simple_error(span: Span, code: &'static str, message: &str) -> DetailedError {
+    DetailedError {
+        message: message.to_string(),
+        code: Some(code),
+        line: 0,
+        column: 0,
+        span,
+        sub_errors: Vec::new(),
+        related: Vec::new(),
+    }
+}
+
+/// Fills in line/character column for `errors` and their related
+/// locations, through `index` when the caller has one and otherwise with a
+/// single newline count over `content` in offset order, so large inputs
+/// don't need a line table.
+fn locate(content: &str, index: Option<&LineIndex>, errors: &mut [DetailedError]) {
+    if let Some(index) = index {
+        for err in errors.iter_mut() {
+            (err.line, err.column) = index.line_col_chars(content, err.span.start);
+            for rel in &mut err.related {
+                (rel.line, rel.column) = index.line_col_chars(content, rel.span.start);
+            }
+        }
+        return;
+    }
+    let bytes = content.as_bytes();
+    // (offset, error, related location)
+    let mut order: Vec<(usize, usize, Option<usize>)> = Vec::with_capacity(errors.len());
+    for (i, err) in errors.iter().enumerate() {
+        order.push((err.span.start, i, None));
+        order.extend((0..err.related.len()).map(|j| (err.related[j].span.start, i, Some(j))));
+    }
+    order.sort_by_key(|&(offset, ..)| offset);
+    let (mut scanned, mut line, mut line_start) = (0usize, 1usize, 0usize);
+    for (offset, i, related) in order {
+        let offset = offset.min(bytes.len());
+        for nl in memchr::memchr_iter(b'\n', &bytes[scanned..offset]) {
+            line += 1;
+            line_start = scanned + nl + 1;
+        }
+        scanned = offset;
+        let column = line_index::line_col_at(
+            &content[line_start..],
+            offset - line_start,
+            ColumnUnit::Char,
+        )
+        .1;
+        match related {
+            None => (errors[i].line, errors[i].column) = (line, column),
+            Some(j) => (errors[i].related[j].line, errors[i].related[j].column) = (line, column),
+        }
+    }
+}
+
+pub(crate) fn infer_json_span(content: &str, start: usize) -> Span {
+    if start >= content.len() {
+        return Span::new(content.len(), content.len());
+    }
+    let slice = &content[start..];
+    let mut chars = slice.char_indices();
+    if let Some((_, ch)) = chars.next() {
+        match ch {
+            '"' => {
+                let mut i = start + ch.len_utf8();
+                let bytes = content.as_bytes();
+                let mut esc = false;
+                while i < content.len() {
+                    let b = bytes[i];
+                    if b == b'\\' && !esc {
+                        esc = true;
+                        i += 1;
+                        continue;
+                    }
+                    if b == b'"' && !esc {
+                        i += 1;
+                        break;
+                    }
+                    esc = false;
+                    i += 1;
+                }
+                return Span::new(start, i);
+            }
+            '-' | '0'..='9' => {
+                let mut i = start + ch.len_utf8();
+                while i < content.len() {
+                    let c = content.as_bytes()[i] as char;
+                    if matches!(c, '0'..='9' | '+' | '-' | 'e' | 'E' | '.') {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                return Span::new(start, i);
+            }
+            _ => {
+                let mut i = start + ch.len_utf8();
+                while i < content.len() {
+                    let c = content.as_bytes()[i] as char;
+                    if c.is_whitespace() {
+                        break;
+                    }
+                    i += 1;
+                }
+                return Span::new(start, i);
+            }
+        }
+    }
+    Span::new(start, start)
+}
+
+enum Context {
+    Object(ObjectContext),
+    Array(ArrayContext),
+}
+
+struct ObjectContext {
+    opened: Span,
+    state: ObjectState,
+    comma_guard: bool,
+}
+
+impl ObjectContext {
+    fn new(opened: Span) -> Self {
+        Self {
+            opened,
+            state: ObjectState::ExpectKeyOrEnd,
+            comma_guard: false,
+        }
+    }
+}
+
+struct ArrayContext {
+    opened: Span,
+    expect_value: bool,
+    comma_guard: bool,
+    has_value: bool,
+}
+
+#[derive(Clone, Copy)]
+#[allow(clippy::enum_variant_names)]
+enum ObjectState {
+    ExpectKeyOrEnd,
+    ExpectColon { key_span: Span },
+    ExpectValue,
+    ExpectCommaOrEnd,
+}
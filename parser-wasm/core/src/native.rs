@@ -0,0 +1,1687 @@
+//! Plain Rust API.
+//!
+//! The parsers, span resolution and byte-preserving edits behind every
+//! binding: the wasm adapter, the Node addon and the CLI all call into this
+//! module, so their results are identical. Failures come back as [`Error`].
+//! Limits, the parse cache and the schema registry are thread-local state
+//! shared by all entry points.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use crate::document::{self, FileType};
+use crate::encoding;
+use crate::limits;
+use crate::line_index::LineIndex;
+use crate::{convert, json_format, multi_error_cap, parse_cache, profile, schema};
+
+pub use crate::array_items::FoundItem;
+pub use crate::convert::ConvertOptions;
+pub use crate::cursor::{Ancestor, AncestorKind, PathAtOffset};
+pub use crate::diff::{ChangeKind, DiffEntry, DiffOptions, DiffSide, Equality, EqualsOptions};
+pub use crate::document::{
+    CommentSpacing, LookupOptions, MixedContent, Occurrence, TokenBatch, UpdateOptions,
+};
+pub use crate::duplicates::{ConfigFile, DuplicateCluster, DuplicateOccurrence};
+pub use crate::encoding::{DecodedText, TextEncoding};
+pub use crate::encryption::{seal, unseal, SensitiveValue, ENCRYPTED_PREFIX};
+pub use crate::env_matrix::{EnvMatrix, EnvironmentFile, MatrixRow};
+#[cfg(feature = "env")]
+pub use crate::env_merge::{EnvDefinition, EnvFile, MergedEntry, MergedEnv};
+#[cfg(feature = "env")]
+pub use crate::env_organize::OrganizeEnvOptions;
+#[cfg(feature = "env")]
+pub use crate::env_resolve::{
+    RenameEdit, RenameKind, RenamedEnv, RenamedEnvFile, ResolvedEntry, ResolvedEnv,
+};
+pub use crate::instantiate::InstantiateOptions;
+pub use crate::json_lexer::{Kind as TokenKind, Token};
+pub use crate::key_search::KeyMatch;
+pub use crate::key_suggestions::KeySuggestion;
+pub use crate::limits::{LimitExceeded, LimitKind, Limits};
+pub use crate::line_index::ColumnUnit;
+pub use crate::lint::{
+    KeyCase, KeyOrder, LintConfig, LintDiagnostic, LintFix, LintResult, DEFAULT_MAX_LINE_LENGTH,
+    DEFAULT_TAB_WIDTH,
+};
+pub use crate::migrate::{Migration, MigrationStep, StepStatus};
+pub use crate::multi_validation::{
+    DetailedError, ErrorCounts, MultiValidationOptions, MultiValidationResult, RelatedLocation,
+    XmlResync,
+};
+pub use crate::overlay::{ArrayStrategy, OverlayOptions};
+pub use crate::paths::{
+    escape_segment, index_segment, join_path, key_segment, split_path, to_pointer,
+};
+pub use crate::profile::Timings;
+pub use crate::schema::{SchemaErrorDescriptor, SchemaOutcome, SchemaValidationOptions, Severity};
+pub use crate::schema_docs::PropertyDoc;
+pub use crate::schema_vocabulary::Vocabulary;
+pub use crate::secrets::{SecretFinding, SecretKind};
+pub use crate::template::{Placeholder, PlaceholderSyntax, TemplateOptions};
+pub use crate::unused_keys::UnusedKey;
+#[cfg(feature = "xml")]
+pub use crate::xml_parser::{
+    CommentPlacement, PseudoAttribute, XmlAttribute, XmlComment, XmlInstruction,
+};
+pub use crate::{Capabilities, Span, ValidationResult};
+pub use jsonschema::Draft;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// File type that is unknown or not compiled into this build.
+    UnsupportedFileType(String),
+    /// Malformed arguments, such as an empty path.
+    InvalidArgument(String),
+    /// The content does not parse; `span` locates the first error when the
+    /// validator reports one.
+    Syntax { message: String, span: Option<Span> },
+    /// The content parses but the path does not resolve.
+    PathNotFound(String),
+    /// The operation is not available for this file type.
+    UnsupportedOperation(String),
+    /// Schema source, compilation or registry failure.
+    Schema(String),
+    /// A [`set_limits`] guard tripped.
+    LimitExceeded(LimitExceeded),
+}
+
+impl Error {
+    /// Stable identifier that every binding attaches as `code`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::UnsupportedFileType(_) => "unsupported_file_type",
+            Error::InvalidArgument(_) => "invalid_argument",
+            Error::Syntax { .. } => "syntax_error",
+            Error::PathNotFound(_) => "path_not_found",
+            Error::UnsupportedOperation(_) => "unsupported_operation",
+            Error::Schema(_) => "schema_error",
+            Error::LimitExceeded(_) => "limit_exceeded",
+        }
+    }
+
+    /// Byte range the error points at, when known.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Error::Syntax { span, .. } => *span,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnsupportedFileType(raw) => f.write_str(&document::unsupported_file_type(raw)),
+            Error::InvalidArgument(msg)
+            | Error::Syntax { message: msg, .. }
+            | Error::PathNotFound(msg)
+            | Error::UnsupportedOperation(msg)
+            | Error::Schema(msg) => f.write_str(msg),
+            Error::LimitExceeded(exceeded) => f.write_str(&exceeded.message()),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<LimitExceeded> for Error {
+    fn from(exceeded: LimitExceeded) -> Self {
+        Error::LimitExceeded(exceeded)
+    }
+}
+
+/// Results whose `line`/`column` can be recomputed from their byte offsets,
+/// for [`apply_column_unit`].
+pub trait Positioned {
+    /// Replaces each reported line/column with `locate(byte_offset)`.
+    fn relocate(&mut self, locate: &dyn Fn(usize) -> (usize, usize));
+}
+
+impl Positioned for ValidationResult {
+    fn relocate(&mut self, locate: &dyn Fn(usize) -> (usize, usize)) {
+        if let Some(span) = self.span {
+            let (line, column) = locate(span.start);
+            self.line = Some(line);
+            self.column = Some(column);
+        }
+    }
+}
+
+impl Positioned for MultiValidationResult {
+    fn relocate(&mut self, locate: &dyn Fn(usize) -> (usize, usize)) {
+        for err in self.summary.iter_mut().chain(self.errors.iter_mut()) {
+            err.relocate(locate);
+        }
+    }
+}
+
+impl Positioned for DetailedError {
+    fn relocate(&mut self, locate: &dyn Fn(usize) -> (usize, usize)) {
+        (self.line, self.column) = locate(self.span.start);
+        for rel in &mut self.related {
+            (rel.line, rel.column) = locate(rel.span.start);
+        }
+        for sub in &mut self.sub_errors {
+            sub.relocate(locate);
+        }
+    }
+}
+
+impl Positioned for LintResult {
+    fn relocate(&mut self, locate: &dyn Fn(usize) -> (usize, usize)) {
+        for diagnostic in self.summary.iter_mut().chain(self.errors.iter_mut()) {
+            (diagnostic.line, diagnostic.column) = locate(diagnostic.span.start);
+        }
+    }
+}
+
+impl Positioned for SchemaOutcome {
+    fn relocate(&mut self, locate: &dyn Fn(usize) -> (usize, usize)) {
+        for err in &mut self.errors {
+            if let Some(start) = err.start {
+                let (line, column) = locate(start);
+                err.line = Some(line);
+                err.column = Some(column);
+            }
+        }
+    }
+}
+
+/// Recomputes the lines and columns of a result for `content`, counting
+/// columns in `unit`, so every result type agrees with the editor's model.
+pub fn apply_column_unit(result: &mut impl Positioned, content: &str, unit: ColumnUnit) {
+    let index = LineIndex::new(content);
+    result.relocate(&|offset| index.line_col_in(content, offset, unit));
+}
+
+/// Applies `opts.column_unit` to a schema outcome.
+fn schema_columns(
+    mut outcome: SchemaOutcome,
+    content: &str,
+    opts: &SchemaValidationOptions,
+) -> SchemaOutcome {
+    if let Some(unit) = opts.column_unit {
+        apply_column_unit(&mut outcome, content, unit);
+    }
+    outcome
+}
+
+fn file_type(raw: &str) -> Result<FileType, Error> {
+    FileType::parse(raw).ok_or_else(|| Error::UnsupportedFileType(raw.to_string()))
+}
+
+/// Sorts a lookup or edit failure: a syntax error when the content does not
+/// pass the structural check, otherwise a path that does not resolve.
+fn failure(doc: &document::Document, message: String) -> Error {
+    if doc.check_syntax().is_err() {
+        Error::Syntax {
+            message,
+            span: doc.summary().span,
+        }
+    } else {
+        Error::PathNotFound(message)
+    }
+}
+
+fn non_empty(path: &[String]) -> Result<(), Error> {
+    if path.is_empty() {
+        return Err(Error::InvalidArgument("Path cannot be empty".into()));
+    }
+    Ok(())
+}
+
+/// First-error syntax check, as the `validate` export.
+pub fn validate(file_type_raw: &str, content: &str) -> Result<ValidationResult, Error> {
+    let ty = file_type(file_type_raw)?;
+    Ok(limits::guard(content.len(), || {
+        parse_cache::with_document(ty, content, |doc| doc.summary().clone())
+    })?)
+}
+
+/// Up to `max_errors` (default 3, at most 10) diagnostics, as `validate_multi`.
+pub fn validate_multi(
+    file_type_raw: &str,
+    content: &str,
+    max_errors: Option<u32>,
+) -> Result<MultiValidationResult, Error> {
+    validate_multi_with(file_type_raw, content, max_errors, &Default::default())
+}
+
+/// [`validate_multi`] with per-call settings.
+pub fn validate_multi_with(
+    file_type_raw: &str,
+    content: &str,
+    max_errors: Option<u32>,
+    opts: &MultiValidationOptions,
+) -> Result<MultiValidationResult, Error> {
+    let ty = file_type(file_type_raw)?;
+    let cap = multi_error_cap(max_errors);
+    Ok(limits::guard(content.len(), || {
+        parse_cache::with_document(ty, content, |doc| doc.multi_result(cap, opts))
+    })?)
+}
+
+pub fn find_value_span(file_type_raw: &str, content: &str, path: &[String]) -> Result<Span, Error> {
+    find_value_span_with(file_type_raw, content, path, &LookupOptions::default())
+}
+
+/// [`find_value_span`] with lookup options.
+pub fn find_value_span_with(
+    file_type_raw: &str,
+    content: &str,
+    path: &[String],
+    options: &LookupOptions,
+) -> Result<Span, Error> {
+    let ty = file_type(file_type_raw)?;
+    limits::guard(content.len(), || {
+        parse_cache::with_document(ty, content, |doc| {
+            doc.span_for(path, options).map_err(|e| failure(doc, e))
+        })
+    })?
+}
+
+/// One lookup result per path; a syntax error fails the whole call.
+pub fn find_value_spans(
+    file_type_raw: &str,
+    content: &str,
+    paths: &[Vec<String>],
+) -> Result<Vec<Result<Span, String>>, Error> {
+    let ty = file_type(file_type_raw)?;
+    limits::guard(content.len(), || {
+        parse_cache::with_document(ty, content, |doc| {
+            doc.spans_for(paths).map_err(|e| failure(doc, e))
+        })
+    })?
+}
+
+/// The JSON value at `path` with string escapes (`\n`, `\uXXXX`, surrogate
+/// pairs) decoded; numbers, literals and containers come back as written.
+pub fn get_string(content: &str, path: &[String]) -> Result<String, Error> {
+    get_value("json", content, path, true)
+}
+
+/// The text of the value at `path`, as written or with `decode` as the user
+/// edits it: JSON string escapes and XML entity / character references
+/// decoded, ENV quotes removed and escapes decoded. `update_value` re-encodes
+/// the text, so a decoded value round-trips without double escaping.
+pub fn get_value(
+    file_type_raw: &str,
+    content: &str,
+    path: &[String],
+    decode: bool,
+) -> Result<String, Error> {
+    get_value_with(
+        file_type_raw,
+        content,
+        path,
+        decode,
+        &LookupOptions::default(),
+    )
+}
+
+/// [`get_value`] with lookup options.
+pub fn get_value_with(
+    file_type_raw: &str,
+    content: &str,
+    path: &[String],
+    decode: bool,
+    options: &LookupOptions,
+) -> Result<String, Error> {
+    let ty = file_type(file_type_raw)?;
+    limits::guard(content.len(), || {
+        parse_cache::with_document(ty, content, |doc| {
+            doc.value_for(path, decode, options)
+                .map_err(|e| failure(doc, e))
+        })
+    })?
+}
+
+/// Resolves `${VAR}` / `$VAR` references in ENV `content` against its own
+/// keys: each entry's raw text and value (expanded when `expand` is set), the
+/// keys each entry references, and diagnostics for undefined or circular
+/// references. Those diagnostics do not fail the call; a syntax error does.
+#[cfg(feature = "env")]
+pub fn resolve_env(content: &str, expand: bool) -> Result<ResolvedEnv, Error> {
+    limits::guard(content.len(), || {
+        parse_cache::with_document(FileType::Env, content, |doc| {
+            doc.resolve_env(expand).map_err(|e| failure(doc, e))
+        })
+    })?
+}
+
+/// Renames the ENV variable `old` to `new` in `content` and rewrites the
+/// `${old}` / `$old` references to it, doing the same in each of `others`
+/// (e.g. `.env.local` next to `.env`). Every edit comes back with its span
+/// in the original text, so a UI can preview the refactor. Fails when
+/// `content` does not define `old`, or any file already defines `new`.
+#[cfg(feature = "env")]
+pub fn rename_env_key(
+    content: &str,
+    old: &str,
+    new: &str,
+    others: &[EnvFile],
+) -> Result<RenamedEnv, Error> {
+    if !crate::env_resolve::is_name(new) {
+        return Err(Error::InvalidArgument(format!(
+            "'{new}' is not a valid variable name"
+        )));
+    }
+    let rename = |label: &str, content: &str| {
+        parsed(FileType::Env, label, content, |doc| {
+            let env = doc.env_doc().map_err(|e| failure(doc, e))?;
+            let defined = |key: &str| env.entries().iter().any(|entry| entry.key == key);
+            if old != new && defined(new) {
+                let file = match label {
+                    "" => String::new(),
+                    _ => format!(" in {label}"),
+                };
+                return Err(Error::InvalidArgument(format!(
+                    "'{new}' is already defined{file}"
+                )));
+            }
+            let (renamed, edits) =
+                crate::env_resolve::rename(doc.content_str(), env, old, new, |offset| {
+                    doc.position_of(offset)
+                });
+            Ok((renamed, edits, defined(old)))
+        })
+    };
+    let (content, edits, defined) = rename("", content)?;
+    if !defined {
+        return Err(Error::PathNotFound(format!("'{old}' is not defined")));
+    }
+    let files = others
+        .iter()
+        .map(|file| {
+            let (content, edits, _) = rename(&file.name, &file.content)?;
+            Ok(RenamedEnvFile {
+                name: file.name.clone(),
+                content,
+                edits,
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+    Ok(RenamedEnv {
+        content,
+        edits,
+        files,
+    })
+}
+
+/// Reorders the entries of ENV `content` by key, in prefix groups (`DB_`,
+/// `REDIS_`) unless `opts` says otherwise. Each entry moves with its lines
+/// and the comments above it; see `env_organize` for what stays in place.
+#[cfg(feature = "env")]
+pub fn organize_env(content: &str, opts: &OrganizeEnvOptions) -> Result<String, Error> {
+    parsed(FileType::Env, "", content, |doc| {
+        let env = doc.env_doc().map_err(|e| failure(doc, e))?;
+        Ok(crate::env_organize::organize(doc.content_str(), env, opts))
+    })
+}
+
+/// Merges ENV `files` with later files overriding earlier ones, reporting for
+/// each key the file and line that won and the definitions it overrode. A
+/// syntax error in any file fails the call, with the file name in the
+/// message.
+#[cfg(feature = "env")]
+pub fn merge_env(files: &[EnvFile]) -> Result<MergedEnv, Error> {
+    let mut merge = crate::env_merge::Merge::default();
+    for file in files {
+        limits::guard(file.content.len(), || {
+            parse_cache::with_document(FileType::Env, &file.content, |doc| {
+                let env = doc.env_doc().map_err(|e| match failure(doc, e) {
+                    Error::Syntax { message, span } => Error::Syntax {
+                        message: format!("{}: {message}", file.name),
+                        span,
+                    },
+                    other => other,
+                })?;
+                merge.layer(&file.name, doc.content_str(), env, |offset| {
+                    doc.position_of(offset)
+                });
+                Ok::<(), Error>(())
+            })
+        })??;
+    }
+    Ok(merge.finish())
+}
+
+/// The XML declaration (target `xml`) and processing instructions, in
+/// document order, with their `name="value"` pseudo-attributes. Update one
+/// through the `["?target", "@name"]` path (`["?xml", "@encoding"]`), or its
+/// whole content through `["?target"]`; the other bytes stay as written.
+#[cfg(feature = "xml")]
+pub fn xml_instructions(content: &str) -> Result<Vec<XmlInstruction>, Error> {
+    limits::guard(content.len(), || {
+        parse_cache::with_document(FileType::Xml, content, |doc| {
+            doc.check_syntax().map_err(|e| failure(doc, e))?;
+            crate::xml_parser::instructions(content).map_err(|e| failure(doc, e))
+        })
+    })?
+}
+
+/// Attributes of the XML element at `path`, in document order, each with
+/// its raw text and span (what `update_value` replaces) and its decoded
+/// value (what a user reads). A path ending in `@name` returns just that
+/// attribute, and fails when the element does not have it; one ending in
+/// `@*` returns every attribute, none included. The single-value lookups
+/// (`find_value_span`, `get_value`, `update_value`) reject `@*`.
+#[cfg(feature = "xml")]
+pub fn xml_attributes(content: &str, path: &[String]) -> Result<Vec<XmlAttribute>, Error> {
+    non_empty(path)?;
+    if path[0].starts_with('?') {
+        return Err(Error::InvalidArgument(
+            "Instruction pseudo-attributes are listed by xml_instructions".into(),
+        ));
+    }
+    limits::guard(content.len(), || {
+        parse_cache::with_document(FileType::Xml, content, |doc| {
+            doc.check_syntax().map_err(|e| failure(doc, e))?;
+            crate::xml_parser::attributes_for(content, path).map_err(|e| failure(doc, e))
+        })
+    })?
+}
+
+/// Comments next to the XML element at `path`: the ones directly above it,
+/// its direct children, and one trailing its end tag on the same line. An
+/// empty path returns the comments outside the root element.
+///
+/// Edits never move these: `update_value` splices only the value span, so
+/// every comment byte outside it is kept in place.
+#[cfg(feature = "xml")]
+pub fn xml_comments(content: &str, path: &[String]) -> Result<Vec<XmlComment>, Error> {
+    if path.last().is_some_and(|last| last.starts_with('@')) {
+        return Err(Error::InvalidArgument(
+            "Comments belong to elements, not attributes".into(),
+        ));
+    }
+    limits::guard(content.len(), || {
+        parse_cache::with_document(FileType::Xml, content, |doc| {
+            doc.check_syntax().map_err(|e| failure(doc, e))?;
+            crate::xml_parser::comments_for(content, path).map_err(|e| failure(doc, e))
+        })
+    })?
+}
+
+/// The innermost value around byte `offset` of `content`: its path, the
+/// span of its key or name and the span of the value, or `None` when the
+/// offset is outside every value (whitespace around a JSON or XML root, an
+/// ENV comment or blank line). An offset past the end is an invalid
+/// argument; see `cursor.rs` for what each format counts as inside a value.
+pub fn path_at_offset(
+    file_type_raw: &str,
+    content: &str,
+    offset: usize,
+) -> Result<Option<PathAtOffset>, Error> {
+    let ty = file_type(file_type_raw)?;
+    if offset > content.len() {
+        return Err(Error::InvalidArgument(format!(
+            "Offset {offset} is past the end of the content ({} bytes)",
+            content.len()
+        )));
+    }
+    parsed(ty, "", content, |doc| match ty {
+        FileType::Json => Ok(crate::cursor::json_path_at(&json_tree(doc)?, offset)),
+        #[cfg(feature = "xml")]
+        FileType::Xml => {
+            let root = doc.xml_tree().map_err(Error::UnsupportedOperation)?;
+            Ok(crate::cursor::xml_path_at(&root, offset))
+        }
+        #[cfg(feature = "env")]
+        FileType::Env => {
+            let env = doc.env_doc().map_err(Error::UnsupportedOperation)?;
+            Ok(crate::cursor::env_path_at(env, offset))
+        }
+    })
+}
+
+/// Keys to offer for the JSON object at `parent_path` when there is no
+/// schema: the keys of the other objects in the same array that it lacks,
+/// most common first. A path to the array itself suggests keys for a new
+/// item; an object outside any array gets none.
+pub fn suggest_keys(content: &str, parent_path: &[String]) -> Result<Vec<KeySuggestion>, Error> {
+    parsed(FileType::Json, "", content, |doc| {
+        crate::key_suggestions::suggest(&json_tree(doc)?, parent_path).map_err(Error::PathNotFound)
+    })
+}
+
+/// Keys of `content` whose path matches `query` as a substring or, failing
+/// that, as characters in order, best first, with the spans of each key and
+/// its value: a "go to setting" palette. Case is ignored; see
+/// `key_search.rs` for the ranking.
+pub fn fuzzy_find(file_type_raw: &str, content: &str, query: &str) -> Result<Vec<KeyMatch>, Error> {
+    let ty = file_type(file_type_raw)?;
+    parsed(ty, "", content, |doc| match ty {
+        FileType::Json => Ok(crate::key_search::json(&json_tree(doc)?, query)),
+        #[cfg(feature = "xml")]
+        FileType::Xml => {
+            let root = doc.xml_tree().map_err(Error::UnsupportedOperation)?;
+            Ok(crate::key_search::xml(&root, query))
+        }
+        #[cfg(feature = "env")]
+        FileType::Env => {
+            let env = doc.env_doc().map_err(Error::UnsupportedOperation)?;
+            Ok(crate::key_search::env(env, query))
+        }
+    })
+}
+
+/// The first object in the JSON array at `array_path` whose `field` holds
+/// `value`, as its index, path and span, or `None` when no item matches.
+/// Values compare as parsed, so `8080` matches `8080.0` and `"w\u0065b"`
+/// matches `"web"`. A path that does not reach an array fails with
+/// [`Error::PathNotFound`].
+pub fn find_item(
+    content: &str,
+    array_path: &[String],
+    field: &str,
+    value: &serde_json::Value,
+) -> Result<Option<FoundItem>, Error> {
+    parsed(FileType::Json, "", content, |doc| {
+        let tree = json_tree(doc)?;
+        crate::array_items::find(content, &tree, array_path, field, value)
+            .map_err(Error::PathNotFound)
+    })
+}
+
+/// Path for an RFC 6901 JSON Pointer; see [`escape_segment`].
+pub fn from_pointer(pointer: &str) -> Result<Vec<String>, Error> {
+    crate::paths::from_pointer(pointer).map_err(Error::InvalidArgument)
+}
+
+/// Returns `content` with the value at `path` replaced, every other byte
+/// unchanged.
+pub fn update_value(
+    file_type_raw: &str,
+    content: &str,
+    path: &[String],
+    new_val: &str,
+) -> Result<String, Error> {
+    update_value_with(
+        file_type_raw,
+        content,
+        path,
+        new_val,
+        &UpdateOptions::default(),
+    )
+}
+
+/// [`update_value`] with lookup and layout options. Whatever the options,
+/// an ENV value's trailing `# comment` survives the edit unchanged.
+pub fn update_value_with(
+    file_type_raw: &str,
+    content: &str,
+    path: &[String],
+    new_val: &str,
+    options: &UpdateOptions,
+) -> Result<String, Error> {
+    non_empty(path)?;
+    let ty = file_type(file_type_raw)?;
+    limits::guard(content.len(), || {
+        parse_cache::with_document(ty, content, |doc| {
+            doc.updated_content(path, new_val, options)
+                .map_err(|e| failure(doc, e))
+        })
+    })?
+}
+
+/// Pretty-prints JSON with two-space indentation, keeping key order and the
+/// literal text of every value. XML and ENV have no formatter.
+pub fn format(file_type_raw: &str, content: &str) -> Result<String, Error> {
+    if file_type(file_type_raw)? != FileType::Json {
+        return Err(Error::UnsupportedOperation(format!(
+            "Formatting is not supported for {} files",
+            file_type_raw.to_lowercase()
+        )));
+    }
+    let summary = validate(file_type_raw, content)?;
+    if let Some(message) = summary.message {
+        return Err(Error::Syntax {
+            message,
+            span: summary.span,
+        });
+    }
+    limits::guard(content.len(), || json_format::format_json(content, "  "))?.map_err(|e| match e {
+        json_format::FormatError::Syntax(message) => Error::Syntax {
+            message,
+            span: None,
+        },
+        json_format::FormatError::TooDeep => Error::UnsupportedOperation(format!(
+            "Formatting is not supported for content nested deeper than {} levels",
+            json_format::MAX_FORMAT_DEPTH
+        )),
+    })
+}
+
+/// What changed between two versions of a document: paths that were
+/// added, removed or given a different value, with the value text and span
+/// in each version that has it. Formatting and key order are not changes.
+pub fn diff(file_type_raw: &str, old: &str, new: &str) -> Result<Vec<DiffEntry>, Error> {
+    diff_with(file_type_raw, old, new, &DiffOptions::default())
+}
+
+/// [`diff`] with XML key attributes: repeated sibling elements are paired
+/// by the first of `key_attributes` they carry, and a reordered element is
+/// reported as [`ChangeKind::Moved`].
+pub fn diff_with(
+    file_type_raw: &str,
+    old: &str,
+    new: &str,
+    opts: &DiffOptions,
+) -> Result<Vec<DiffEntry>, Error> {
+    diff_typed(file_type(file_type_raw)?, old, new, opts)
+}
+
+#[cfg_attr(not(feature = "xml"), allow(unused_variables))]
+fn diff_typed(
+    ty: FileType,
+    old: &str,
+    new: &str,
+    opts: &DiffOptions,
+) -> Result<Vec<DiffEntry>, Error> {
+    match ty {
+        FileType::Json => {
+            let old_tree = parsed(FileType::Json, "old", old, json_tree)?;
+            let new_tree = parsed(FileType::Json, "new", new, json_tree)?;
+            Ok(crate::diff::json(old, &old_tree, new, &new_tree))
+        }
+        #[cfg(feature = "xml")]
+        FileType::Xml => {
+            let tree =
+                |doc: &document::Document| doc.xml_tree().map_err(Error::UnsupportedOperation);
+            let old_root = parsed(FileType::Xml, "old", old, tree)?;
+            let new_root = parsed(FileType::Xml, "new", new, tree)?;
+            Ok(crate::diff::xml(old, &old_root, new, &new_root, opts))
+        }
+        #[cfg(feature = "env")]
+        FileType::Env => diff_env(old, new),
+    }
+}
+
+/// Whether `a` and `b` hold the same configuration, whatever their
+/// whitespace, escapes, number spelling or ENV quoting, with the first
+/// path where they differ. Key order counts unless
+/// `opts.ignore_key_order`; a changed value is reported ahead of a
+/// reordering. An added or dropped ENV `export` is a difference. Syntax
+/// errors name the documents `old` and `new`, as in [`diff`].
+pub fn equals(
+    file_type_raw: &str,
+    a: &str,
+    b: &str,
+    opts: &EqualsOptions,
+) -> Result<Equality, Error> {
+    let ty = file_type(file_type_raw)?;
+    let first = diff_typed(ty, a, b, &DiffOptions::default())?
+        .into_iter()
+        .find(|entry| match entry.kind {
+            ChangeKind::Quoting => false,
+            ChangeKind::Moved => !opts.ignore_key_order,
+            _ => true,
+        });
+    let path = match first {
+        Some(entry) => Some(entry.path),
+        None if opts.ignore_key_order => None,
+        None => match ty {
+            FileType::Json => {
+                let a_tree = parsed(ty, "old", a, json_tree)?;
+                let b_tree = parsed(ty, "new", b, json_tree)?;
+                crate::diff::json_key_order(&a_tree, &b_tree)
+            }
+            // a reordered element is `Moved`
+            #[cfg(feature = "xml")]
+            FileType::Xml => None,
+            #[cfg(feature = "env")]
+            FileType::Env => parsed(ty, "old", a, |a_doc| {
+                let a_env = a_doc.env_doc().map_err(|e| failure(a_doc, e))?;
+                parsed(ty, "new", b, |b_doc| {
+                    let b_env = b_doc.env_doc().map_err(|e| failure(b_doc, e))?;
+                    Ok(crate::diff::env_key_order(a_env, b_env))
+                })
+            })?,
+        },
+    };
+    Ok(Equality {
+        equal: path.is_none(),
+        path,
+    })
+}
+
+/// [`diff`] of two ENV files, for drift reports. Values compare as the
+/// application reads them, so reordering, spacing around `=` and
+/// equivalent escapes are not changes. A value written with other quotes
+/// is reported as [`ChangeKind::Quoting`] and an added or dropped `export`
+/// as [`ChangeKind::Export`], each next to any `Changed` entry for the key.
+#[cfg(feature = "env")]
+pub fn diff_env(old: &str, new: &str) -> Result<Vec<DiffEntry>, Error> {
+    parsed(FileType::Env, "old", old, |old_doc| {
+        let old_env = old_doc.env_doc().map_err(|e| failure(old_doc, e))?;
+        parsed(FileType::Env, "new", new, |new_doc| {
+            let new_env = new_doc.env_doc().map_err(|e| failure(new_doc, e))?;
+            Ok(crate::diff::env(old, old_env, new, new_env))
+        })
+    })
+}
+
+/// Deep-merges JSON `overlays` into `base`, each overlay taking precedence
+/// over the base and the overlays before it. Objects merge key by key,
+/// arrays as `opts.arrays` says, other values are replaced. The result is
+/// the base with edits applied: values no overlay changes keep their bytes,
+/// and added ones follow the layout of their siblings. A syntax error names
+/// `base` or the 1-based overlay.
+pub fn overlay(base: &str, overlays: &[String], opts: &OverlayOptions) -> Result<String, Error> {
+    parsed(FileType::Json, "base", base, json_tree)?;
+    let mut content = base.to_string();
+    for (i, overlay) in overlays.iter().enumerate() {
+        let overlay_tree = parsed(
+            FileType::Json,
+            &format!("overlay {}", i + 1),
+            overlay,
+            json_tree,
+        )?;
+        content = parsed(FileType::Json, "base", &content, |doc| {
+            let base_tree = json_tree(doc)?;
+            Ok(crate::overlay::apply(
+                doc.content_str(),
+                &base_tree,
+                overlay,
+                &overlay_tree,
+                opts,
+            ))
+        })?;
+    }
+    Ok(content)
+}
+
+/// `content` of type `from_type` rewritten as `to_type`. The mapping is
+/// fixed (see [`ConvertOptions`]), so the same input always gives the same
+/// output; the result is laid out fresh. Content the target format cannot
+/// hold, such as a key that is not a valid XML name, is
+/// [`Error::UnsupportedOperation`].
+#[cfg_attr(not(any(feature = "xml", feature = "env")), allow(unused_variables))]
+pub fn convert(
+    from_type: &str,
+    to_type: &str,
+    content: &str,
+    opts: &ConvertOptions,
+) -> Result<String, Error> {
+    let from = file_type(from_type)?;
+    let to = file_type(to_type)?;
+    let value = neutral(from, "", content, opts)?;
+    let out = match to {
+        FileType::Json => Ok(convert::to_json(&value)),
+        #[cfg(feature = "xml")]
+        FileType::Xml => convert::to_xml(&value, &opts.root),
+        #[cfg(feature = "env")]
+        FileType::Env => convert::to_env(&value, opts),
+    };
+    out.map_err(Error::UnsupportedOperation)
+}
+
+/// A stable hash of what `content` means: the same for any two documents
+/// `convert` reads to the same value, whatever their whitespace, key order,
+/// escapes, number spelling, ENV quoting or `export`. Hosts compare it
+/// across reloads to tell a real change from a reformat. XML sibling
+/// elements of different names are compared as groups, so interleaving
+/// them does not change the hash; repeated elements keep their order.
+pub fn semantic_hash(file_type_raw: &str, content: &str) -> Result<String, Error> {
+    let ty = file_type(file_type_raw)?;
+    let opts = ConvertOptions {
+        separator: String::new(),
+        ..Default::default()
+    };
+    let value = neutral(ty, "", content, &opts)?;
+    Ok(crate::semantic_hash::hash(&value))
+}
+
+/// Every leaf path of the per-environment `files` (all of `file_type`) with
+/// its value in each environment, for a side-by-side table: which
+/// environments lack a path, and whether it is set alike everywhere. ENV
+/// keys are not split into nested paths. Syntax errors are prefixed with
+/// the environment's name; names must be unique.
+pub fn env_matrix(file_type_raw: &str, files: &[EnvironmentFile]) -> Result<EnvMatrix, Error> {
+    let ty = file_type(file_type_raw)?;
+    let opts = ConvertOptions {
+        separator: String::new(),
+        ..Default::default()
+    };
+    let mut envs: Vec<String> = Vec::with_capacity(files.len());
+    let mut values = Vec::with_capacity(files.len());
+    for file in files {
+        if envs.contains(&file.env) {
+            return Err(Error::InvalidArgument(format!(
+                "Duplicate environment '{}'",
+                file.env
+            )));
+        }
+        values.push(neutral(ty, &file.env, &file.content, &opts)?);
+        envs.push(file.env.clone());
+    }
+    #[cfg(feature = "xml")]
+    let xml = ty == FileType::Xml;
+    #[cfg(not(feature = "xml"))]
+    let xml = false;
+    Ok(crate::env_matrix::build(envs, &values, xml))
+}
+
+/// `content` read into `convert`'s format-neutral value; `label` as for
+/// [`parsed`].
+#[cfg_attr(not(any(feature = "xml", feature = "env")), allow(unused_variables))]
+fn neutral(
+    ty: FileType,
+    label: &str,
+    content: &str,
+    opts: &ConvertOptions,
+) -> Result<convert::Value, Error> {
+    parsed(ty, label, content, |doc| match ty {
+        FileType::Json => Ok(convert::from_json(content, &json_tree(doc)?)),
+        #[cfg(feature = "xml")]
+        FileType::Xml => {
+            let root = doc.xml_tree().map_err(Error::UnsupportedOperation)?;
+            Ok(convert::from_xml(content, &root, opts.infer_types))
+        }
+        #[cfg(feature = "env")]
+        FileType::Env => {
+            let env = doc.env_doc().map_err(|e| failure(doc, e))?;
+            convert::from_env(content, env, &opts.separator, opts.infer_types)
+                .map_err(Error::UnsupportedOperation)
+        }
+    })
+}
+
+/// A JSON config as `.env` lines: nested keys joined with
+/// `opts.separator` (`SERVER__PORT=8080`), upper-cased with
+/// `opts.uppercase`. Shorthand for [`convert`] from `json` to `env`.
+#[cfg(feature = "env")]
+pub fn to_env(content: &str, opts: &ConvertOptions) -> Result<String, Error> {
+    convert("json", "env", content, opts)
+}
+
+/// The nested JSON document an ENV file's `opts.separator`-delimited keys
+/// describe, pretty-printed; with `opts.infer_types`, values that spell a
+/// number or boolean become one. Shorthand for [`convert`] from `env` to
+/// `json`.
+#[cfg(feature = "env")]
+pub fn env_to_json(content: &str, opts: &ConvertOptions) -> Result<String, Error> {
+    convert("env", "json", content, opts)
+}
+
+/// Placeholders (`${NAME}`, or `{{ NAME }}` with [`PlaceholderSyntax::Braces`])
+/// in the string values of `content`, with the path of their value and
+/// their own span, in document order.
+pub fn list_placeholders(
+    file_type_raw: &str,
+    content: &str,
+    opts: &TemplateOptions,
+) -> Result<Vec<Placeholder>, Error> {
+    let ty = file_type(file_type_raw)?;
+    parsed(ty, "", content, |doc| {
+        Ok(crate::template::placeholders(
+            content,
+            &value_sites(doc)?,
+            opts,
+        ))
+    })
+}
+
+/// `content` with each placeholder that names one of `variables` replaced
+/// by its value, escaped for where it lands (an ENV value is re-quoted when
+/// it needs to be). Other placeholders and every other byte stay as
+/// written.
+pub fn render(
+    file_type_raw: &str,
+    content: &str,
+    variables: &HashMap<String, String>,
+    opts: &TemplateOptions,
+) -> Result<String, Error> {
+    let ty = file_type(file_type_raw)?;
+    parsed(ty, "", content, |doc| {
+        Ok(crate::template::render(
+            content,
+            &value_sites(doc)?,
+            variables,
+            opts,
+        ))
+    })
+}
+
+/// Likely secrets in the values of `content`: AWS access key ids, private
+/// key blocks, tokens with a known prefix and high-entropy strings. Each
+/// finding has the path of its value and the secret's span and position,
+/// not its text.
+pub fn scan_secrets(file_type_raw: &str, content: &str) -> Result<Vec<SecretFinding>, Error> {
+    let ty = file_type(file_type_raw)?;
+    parsed(ty, "", content, |doc| {
+        Ok(crate::secrets::scan(doc, &value_sites(doc)?))
+    })
+}
+
+/// What [`redact`] writes by default.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// `content` with the value at each of `paths` replaced by `replacement`
+/// (see [`REDACTED`]), written as [`update_value`] writes values. Every
+/// other byte stays as written.
+pub fn redact(
+    file_type_raw: &str,
+    content: &str,
+    paths: &[Vec<String>],
+    replacement: &str,
+) -> Result<String, Error> {
+    let mut out = content.to_string();
+    for path in paths {
+        out = update_value(file_type_raw, &out, path, replacement)?;
+    }
+    Ok(out)
+}
+
+/// The plain text of the values at `paths`, to encrypt; values already
+/// stored as `ENC[…]` are left out. In JSON only strings can be encrypted,
+/// so they decrypt back to strings.
+pub fn sensitive_values(
+    file_type_raw: &str,
+    content: &str,
+    paths: &[Vec<String>],
+) -> Result<Vec<SensitiveValue>, Error> {
+    let ty = file_type(file_type_raw)?;
+    let mut out: Vec<SensitiveValue> = Vec::new();
+    for path in paths {
+        non_empty(path)?;
+        if ty == FileType::Json && !get_value(file_type_raw, content, path, false)?.starts_with('"')
+        {
+            return Err(Error::InvalidArgument(format!(
+                "Only string values can be encrypted: {}",
+                join_path(path)
+            )));
+        }
+        let value = get_value(file_type_raw, content, path, true)?;
+        if unseal(&value).is_none() && !out.iter().any(|item| item.path == *path) {
+            out.push(SensitiveValue {
+                path: path.clone(),
+                value,
+            });
+        }
+    }
+    Ok(out)
+}
+
+/// The ciphertext of every value stored as `ENC[…]`, in document order.
+pub fn encrypted_values(file_type_raw: &str, content: &str) -> Result<Vec<SensitiveValue>, Error> {
+    let ty = file_type(file_type_raw)?;
+    let paths = parsed(ty, "", content, |doc| {
+        let sites = value_sites(doc)?;
+        Ok(sites
+            .into_iter()
+            .filter(|site| unseal(&content[site.span.start..site.span.end]).is_some())
+            .map(|site| site.path)
+            .collect::<Vec<_>>())
+    })?;
+    let mut out: Vec<SensitiveValue> = Vec::new();
+    for path in paths {
+        let value = get_value(file_type_raw, content, &path, true)?;
+        if let Some(ciphertext) = unseal(&value) {
+            if !out.iter().any(|item| item.path == path) {
+                out.push(SensitiveValue {
+                    path,
+                    value: ciphertext.to_string(),
+                });
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// `content` with each value written at its path: as a JSON string in JSON,
+/// otherwise as [`update_value`] writes it.
+pub fn store_values(
+    file_type_raw: &str,
+    content: &str,
+    values: &[SensitiveValue],
+) -> Result<String, Error> {
+    let ty = file_type(file_type_raw)?;
+    let mut out = content.to_string();
+    for item in values {
+        out = match ty {
+            FileType::Json => {
+                let span = find_value_span(file_type_raw, &out, &item.path)?;
+                let text = format!("\"{}\"", crate::escape_json_string(&item.value));
+                format!("{}{text}{}", &out[..span.start], &out[span.end..])
+            }
+            #[cfg(any(feature = "xml", feature = "env"))]
+            _ => update_value(file_type_raw, &out, &item.path, &item.value)?,
+        };
+    }
+    Ok(out)
+}
+
+/// Encrypts the values at `paths` in place: `encrypt` gets each value's
+/// path and plain text and returns its ciphertext, stored as `ENC[…]`.
+/// Values already encrypted are skipped, and the rest of the file keeps its
+/// bytes.
+pub fn encrypt_values(
+    file_type_raw: &str,
+    content: &str,
+    paths: &[Vec<String>],
+    mut encrypt: impl FnMut(&SensitiveValue) -> Result<String, Error>,
+) -> Result<String, Error> {
+    let sealed = sensitive_values(file_type_raw, content, paths)?
+        .into_iter()
+        .map(|item| {
+            let value = seal(&encrypt(&item)?);
+            Ok(SensitiveValue { value, ..item })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    store_values(file_type_raw, content, &sealed)
+}
+
+/// Decrypts every `ENC[…]` value in place: `decrypt` gets each value's path
+/// and ciphertext and returns its plain text.
+pub fn decrypt_values(
+    file_type_raw: &str,
+    content: &str,
+    mut decrypt: impl FnMut(&SensitiveValue) -> Result<String, Error>,
+) -> Result<String, Error> {
+    let plain = encrypted_values(file_type_raw, content)?
+        .into_iter()
+        .map(|item| {
+            let value = decrypt(&item)?;
+            Ok(SensitiveValue { value, ..item })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    store_values(file_type_raw, content, &plain)
+}
+
+/// Runs a migration `script` over `content`: a JSON array of steps, each
+/// `rename` (`path`, `to`), `move` (`from`, `to`), `setDefault` (`path`,
+/// `value`), `delete` (`path`) or `changeType` (`path`, `type`). Steps apply
+/// in order as in-place edits, and each reports whether it was applied,
+/// skipped (nothing to do) or failed (left the content as it was). JSON and
+/// ENV documents only.
+pub fn migrate(file_type_raw: &str, content: &str, script: &str) -> Result<Migration, Error> {
+    let ty = file_type(file_type_raw)?;
+    #[cfg(feature = "xml")]
+    if ty == FileType::Xml {
+        return Err(Error::UnsupportedOperation(
+            "migrate is only supported for JSON and ENV documents".into(),
+        ));
+    }
+    let ops = crate::migrate::script(script).map_err(Error::InvalidArgument)?;
+    parsed(ty, "", content, |doc| {
+        crate::migrate::run(ty, doc.content_str(), &ops).map_err(|e| failure(doc, e))
+    })
+}
+
+/// Key/value pairs set to the same string in two or more of `files`, e.g.
+/// a connection string copied into several configs. Each cluster lists
+/// every occurrence with its file, path and value span. A syntax error in
+/// any file fails the call, with the file name in the message.
+pub fn find_duplicates(files: &[ConfigFile]) -> Result<Vec<DuplicateCluster>, Error> {
+    let mut finder = crate::duplicates::Finder::default();
+    for (i, file) in files.iter().enumerate() {
+        let ty = file_type(&file.file_type)?;
+        parsed(ty, &file.name, &file.content, |doc| {
+            finder.add(i, &file.name, doc, &value_sites(doc)?);
+            Ok(())
+        })?;
+    }
+    Ok(finder.finish())
+}
+
+/// Runs the rules `config` enables over `content`. Diagnostics come back
+/// in document order, shaped like [`validate_multi`]'s errors with the rule
+/// as `code`; the result is valid unless one has `error` severity. The
+/// schemas `no-boolean-strings` and `sort-keys` name must be registered, or
+/// the call fails with [`Error::Schema`]; `sort-keys` also needs one
+/// registered under its own id rather than named by a `$id` or `$anchor`,
+/// since only the registered text keeps the declaration order.
+pub fn lint(file_type_raw: &str, content: &str, config: &LintConfig) -> Result<LintResult, Error> {
+    let ty = file_type(file_type_raw)?;
+    let registered = |schema_id: &str| {
+        schema::get_cached_schema(schema_id)
+            .ok_or_else(|| Error::Schema(format!("Schema '{schema_id}' is not registered")))
+    };
+    let schema = match &config.no_boolean_strings {
+        Some((_, schema_id)) => Some(registered(schema_id)?),
+        None => None,
+    };
+    let key_order = match &config.sort_keys {
+        Some((_, KeyOrder::Schema(schema_id))) => {
+            let registered = registered(schema_id)?;
+            let source = registered.source.as_deref().ok_or_else(|| {
+                Error::Schema(format!(
+                    "Schema '{schema_id}' is a subschema; sort-keys needs a registered id"
+                ))
+            })?;
+            Some(crate::lint::SchemaKeyOrder::parse(source).map_err(Error::Schema)?)
+        }
+        _ => None,
+    };
+    parsed(ty, "", content, |doc| {
+        crate::lint::run(
+            doc,
+            config,
+            schema.as_ref().map(|s| &s.value),
+            key_order.as_ref(),
+        )
+        .map_err(|e| failure(doc, e))
+    })
+}
+
+fn value_sites(doc: &document::Document) -> Result<Vec<crate::template::Site>, Error> {
+    use crate::template;
+
+    match doc.file_type() {
+        FileType::Json => Ok(template::json_sites(&json_tree(doc)?)),
+        #[cfg(feature = "xml")]
+        FileType::Xml => doc
+            .xml_tree()
+            .map(|root| template::xml_sites(&root))
+            .map_err(Error::UnsupportedOperation),
+        #[cfg(feature = "env")]
+        FileType::Env => doc
+            .env_doc()
+            .map(template::env_sites)
+            .map_err(|e| failure(doc, e)),
+    }
+}
+
+fn json_tree(doc: &document::Document) -> Result<crate::json_tree::JsonNode, Error> {
+    doc.json_tree().map_err(Error::UnsupportedOperation)
+}
+
+/// `build` over `content` once it validates; syntax errors are prefixed
+/// with a non-empty `label` so the caller knows which input failed.
+fn parsed<T>(
+    ty: FileType,
+    label: &str,
+    content: &str,
+    build: impl FnOnce(&document::Document) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let labelled = |message: &str| match label {
+        "" => message.to_string(),
+        _ => format!("{label}: {message}"),
+    };
+    limits::guard(content.len(), || {
+        parse_cache::with_document(ty, content, |doc| {
+            let summary = doc.summary();
+            if let Some(message) = &summary.message {
+                return Err(Error::Syntax {
+                    message: labelled(message),
+                    span: summary.span,
+                });
+            }
+            // XML's summary leaves unclosed elements to the structural check
+            if let Err(message) = doc.check_syntax() {
+                return Err(Error::Syntax {
+                    message: labelled(&message),
+                    span: None,
+                });
+            }
+            build(doc)
+        })
+    })?
+}
+
+pub fn set_limits(limits: Limits) {
+    limits::set(limits);
+    parse_cache::clear();
+}
+
+/// Drops the cached parses and the compiled inline schemas.
+pub fn clear_parse_cache() {
+    parse_cache::clear();
+    schema::clear_inline_cache();
+}
+
+pub fn capabilities() -> Capabilities {
+    crate::build_capabilities()
+}
+
+/// Replaces the millisecond clock behind `profile` timings. Hosts without
+/// `std::time` (wasm32-unknown-unknown) install theirs here.
+pub fn set_profile_clock(clock: fn() -> f64) {
+    profile::set_clock(clock);
+}
+
+/// Decodes file bytes (UTF-8, or UTF-16 with or without a BOM), reporting
+/// the encoding and BOM for [`encode_text`].
+pub fn decode_bytes(bytes: &[u8]) -> Result<DecodedText, Error> {
+    limits::check_bytes(bytes.len())?;
+    encoding::decode(bytes).map_err(Error::InvalidArgument)
+}
+
+/// Re-encodes edited content the way [`decode_bytes`] found it.
+pub fn encode_text(content: &str, encoding: TextEncoding, bom: bool) -> Vec<u8> {
+    encoding::encode(content, encoding, bom)
+}
+
+/// Draft for a label such as `"draft7"` or `"2020-12"`, as accepted by the
+/// wasm `draft` option.
+pub fn parse_draft(label: &str) -> Option<Draft> {
+    schema::parse_draft_label(label)
+}
+
+pub fn register_schema(schema_id: &str, schema: &str) -> Result<(), Error> {
+    limits::guard(schema.len(), || {
+        schema::register_schema_source(schema_id, schema)
+    })?
+    .map_err(Error::Schema)
+}
+
+pub fn export_schema(schema_id: &str) -> Result<Vec<u8>, Error> {
+    schema::export_schema_bytes(schema_id).map_err(Error::Schema)
+}
+
+pub fn import_schema(schema_id: &str, bytes: &[u8]) -> Result<(), Error> {
+    limits::guard(bytes.len(), || {
+        schema::import_schema_bytes(schema_id, bytes)
+    })?
+    .map_err(Error::Schema)
+}
+
+pub fn validate_schema(
+    content: &str,
+    schema: &str,
+    options: &SchemaValidationOptions,
+) -> Result<SchemaOutcome, Error> {
+    let opts = options.clone().capped();
+    let outcome = limits::guard(content.len().max(schema.len()), || {
+        schema::profiled(&opts, || {
+            schema::validate_inline_source(content, schema, &opts)
+        })
+    })?;
+    Ok(schema_columns(outcome, content, &opts))
+}
+
+pub fn validate_schema_with_id(
+    content: &str,
+    schema_id: &str,
+    options: &SchemaValidationOptions,
+) -> Result<SchemaOutcome, Error> {
+    let opts = options.clone().capped();
+    let outcome = limits::guard(content.len(), || {
+        schema::profiled(&opts, || {
+            schema::validate_registered(content, schema_id, &opts)
+        })
+    })?;
+    Ok(schema_columns(outcome, content, &opts))
+}
+
+/// [`validate_schema`] for an ENV file, its values coerced to the types
+/// the schema declares for them; see `env_schema.rs`. Errors point at the
+/// offending variable's value.
+#[cfg(feature = "env")]
+pub fn validate_env_schema(
+    content: &str,
+    schema: &str,
+    options: &SchemaValidationOptions,
+) -> Result<SchemaOutcome, Error> {
+    let opts = options.clone().capped();
+    let outcome = limits::guard(content.len().max(schema.len()), || {
+        schema::profiled(&opts, || {
+            crate::env_schema::validate_source(content, schema, &opts)
+        })
+    })?;
+    Ok(schema_columns(outcome, content, &opts))
+}
+
+/// [`validate_env_schema`] against the schema registered as `schema_id`.
+#[cfg(feature = "env")]
+pub fn validate_env_schema_with_id(
+    content: &str,
+    schema_id: &str,
+    options: &SchemaValidationOptions,
+) -> Result<SchemaOutcome, Error> {
+    let opts = options.clone().capped();
+    let outcome = limits::guard(content.len(), || {
+        schema::profiled(&opts, || {
+            crate::env_schema::validate_registered(content, schema_id, &opts)
+        })
+    })?;
+    Ok(schema_columns(outcome, content, &opts))
+}
+
+pub fn validate_schema_all(
+    content: &str,
+    schema_ids: &[String],
+    options: &SchemaValidationOptions,
+) -> Result<SchemaOutcome, Error> {
+    let opts = options.clone().capped();
+    let outcome = limits::guard(content.len(), || {
+        schema::profiled(&opts, || {
+            schema::validate_against_registered(content, schema_ids, &opts)
+        })
+    })?;
+    Ok(schema_columns(outcome, content, &opts))
+}
+
+/// Members of the JSON `content` that the schema registered as `schema_id`
+/// neither describes nor constrains, in document order, e.g. settings left
+/// behind by an upgrade. Members of free-form objects are not reported;
+/// see `unused_keys.rs` for what counts as describing a key.
+pub fn unused_keys(content: &str, schema_id: &str) -> Result<Vec<UnusedKey>, Error> {
+    let schema = schema::get_cached_schema(schema_id)
+        .ok_or_else(|| Error::Schema(format!("Schema '{schema_id}' is not registered")))?;
+    parsed(FileType::Json, "", content, |doc| {
+        let tree = json_tree(doc)?;
+        Ok(crate::unused_keys::find(doc, &tree, &schema.value))
+    })
+}
+
+/// A property reference for the schema registered as `schema_id`: path,
+/// types, whether it is required, default, description and constraints for
+/// every property, depth first. See `schema_docs.rs` for how `$ref`s,
+/// combinators, items and pattern properties are shown.
+pub fn schema_docs(schema_id: &str) -> Result<Vec<PropertyDoc>, Error> {
+    let schema = schema::get_cached_schema(schema_id)
+        .ok_or_else(|| Error::Schema(format!("Schema '{schema_id}' is not registered")))?;
+    Ok(crate::schema_docs::document(&schema.value))
+}
+
+/// [`schema_docs`] as a Markdown table, one row per property.
+pub fn schema_docs_markdown(schema_id: &str) -> Result<String, Error> {
+    schema_docs(schema_id).map(|docs| crate::schema_docs::markdown(&docs))
+}
+
+/// A new document for the schema registered as `schema_id`, written fresh
+/// as `opts.file_type`: defaults, examples or first enum options where the
+/// schema has them, otherwise empty values, for every required property
+/// (every property with `opts.include_optional`). A skeleton the target
+/// format cannot hold, such as a top-level array as ENV, is
+/// [`Error::UnsupportedOperation`].
+pub fn instantiate_schema(schema_id: &str, opts: &InstantiateOptions) -> Result<String, Error> {
+    let to = file_type(&opts.file_type)?;
+    let schema = schema::get_cached_schema(schema_id)
+        .ok_or_else(|| Error::Schema(format!("Schema '{schema_id}' is not registered")))?;
+    let value = crate::instantiate::instance(&schema.value, opts.include_optional);
+    let out = match to {
+        FileType::Json => Ok(convert::to_json(&value)),
+        #[cfg(feature = "xml")]
+        FileType::Xml => convert::to_xml(&value, &opts.convert.root),
+        #[cfg(feature = "env")]
+        FileType::Env => convert::to_env(&value, &opts.convert),
+    };
+    out.map_err(Error::UnsupportedOperation)
+}
+
+/// Outcome of [`Document::validate_chunked`].
+#[derive(Debug, Clone)]
+pub enum ChunkedOutcome {
+    Done(ValidationResult),
+    /// The progress callback returned `false` after `processed` bytes.
+    Cancelled {
+        processed: usize,
+    },
+}
+
+/// Outcome of [`Document::validate_step`].
+#[derive(Debug, Clone)]
+pub enum ValidationStep {
+    Done(ValidationResult),
+    /// More input remains; `processed` bytes have been scanned so far.
+    Pending {
+        processed: usize,
+    },
+}
+
+/// Snapshots a [`Document`] keeps before dropping the oldest.
+pub const MAX_SNAPSHOTS: usize = 64;
+
+/// Parse-once handle with the same caching as the wasm `Document`.
+pub struct Document(document::Document, Snapshots);
+
+/// Saved contents of a handle, oldest first, under increasing ids.
+#[derive(Default)]
+struct Snapshots {
+    next: u32,
+    saved: VecDeque<(u32, String)>,
+}
+
+impl Snapshots {
+    fn get(&self, id: u32) -> Result<&str, Error> {
+        self.saved
+            .iter()
+            .find(|(saved, _)| *saved == id)
+            .map(|(_, content)| content.as_str())
+            .ok_or_else(|| Error::InvalidArgument(format!("Unknown snapshot {id}")))
+    }
+}
+
+impl Document {
+    pub fn new(file_type_raw: &str, content: &str) -> Result<Self, Error> {
+        limits::check_bytes(content.len())?;
+        let ty = file_type(file_type_raw)?;
+        Ok(Self(
+            document::Document::with_type(ty, content.to_string()),
+            Snapshots::default(),
+        ))
+    }
+
+    pub fn content(&self) -> &str {
+        self.0.content_str()
+    }
+
+    pub fn set_content(&mut self, content: &str) -> Result<(), Error> {
+        limits::check_bytes(content.len())?;
+        self.0.replace_content(content.to_string());
+        Ok(())
+    }
+
+    /// Saves the current content and returns its id, for [`diff_since`]
+    /// and [`restore`]. Past [`MAX_SNAPSHOTS`] the oldest is dropped.
+    ///
+    /// [`diff_since`]: Document::diff_since
+    /// [`restore`]: Document::restore
+    pub fn snapshot(&mut self) -> u32 {
+        let snapshots = &mut self.1;
+        let id = snapshots.next;
+        snapshots.next += 1;
+        if snapshots.saved.len() == MAX_SNAPSHOTS {
+            snapshots.saved.pop_front();
+        }
+        snapshots
+            .saved
+            .push_back((id, self.0.content_str().to_string()));
+        id
+    }
+
+    /// [`diff`] from snapshot `id` to the current content, for reverting a
+    /// section with the old values it reports.
+    pub fn diff_since(&self, id: u32) -> Result<Vec<DiffEntry>, Error> {
+        let old = self.1.get(id)?;
+        diff_typed(
+            self.0.file_type(),
+            old,
+            self.0.content_str(),
+            &DiffOptions::default(),
+        )
+    }
+
+    /// Puts snapshot `id` back as the content. The snapshot, and any taken
+    /// after it, stay available.
+    pub fn restore(&mut self, id: u32) -> Result<(), Error> {
+        let content = self.1.get(id)?.to_string();
+        self.0.replace_content(content);
+        Ok(())
+    }
+
+    pub fn validate(&mut self) -> Result<ValidationResult, Error> {
+        Ok(self.0.limited(|doc| doc.summary().clone())?)
+    }
+
+    pub fn validate_multi(
+        &mut self,
+        max_errors: Option<u32>,
+    ) -> Result<MultiValidationResult, Error> {
+        self.validate_multi_with(max_errors, &Default::default())
+    }
+
+    pub fn validate_multi_with(
+        &mut self,
+        max_errors: Option<u32>,
+        opts: &MultiValidationOptions,
+    ) -> Result<MultiValidationResult, Error> {
+        let cap = multi_error_cap(max_errors);
+        Ok(self.0.limited(|doc| doc.multi_result(cap, opts))?)
+    }
+
+    pub fn find_value_span(&mut self, path: &[String]) -> Result<Span, Error> {
+        self.find_value_span_with(path, &LookupOptions::default())
+    }
+
+    pub fn find_value_span_with(
+        &mut self,
+        path: &[String],
+        options: &LookupOptions,
+    ) -> Result<Span, Error> {
+        self.0
+            .limited(|doc| doc.span_for(path, options).map_err(|e| failure(doc, e)))?
+    }
+
+    pub fn find_value_spans(
+        &mut self,
+        paths: &[Vec<String>],
+    ) -> Result<Vec<Result<Span, String>>, Error> {
+        self.0
+            .limited(|doc| doc.spans_for(paths).map_err(|e| failure(doc, e)))?
+    }
+
+    /// See [`get_string`]; JSON documents only.
+    pub fn get_string(&mut self, path: &[String]) -> Result<String, Error> {
+        if self.0.file_type() != FileType::Json {
+            return Err(Error::UnsupportedOperation(
+                "get_string is only supported for JSON documents".into(),
+            ));
+        }
+        self.get_value(path, true)
+    }
+
+    /// See [`get_value`].
+    pub fn get_value(&mut self, path: &[String], decode: bool) -> Result<String, Error> {
+        self.get_value_with(path, decode, &LookupOptions::default())
+    }
+
+    pub fn get_value_with(
+        &mut self,
+        path: &[String],
+        decode: bool,
+        options: &LookupOptions,
+    ) -> Result<String, Error> {
+        self.0.limited(|doc| {
+            doc.value_for(path, decode, options)
+                .map_err(|e| failure(doc, e))
+        })?
+    }
+
+    /// Replaces the value at `path` and keeps the new content on the handle.
+    pub fn update_value(&mut self, path: &[String], new_val: &str) -> Result<String, Error> {
+        self.update_value_with(path, new_val, &UpdateOptions::default())
+    }
+
+    pub fn update_value_with(
+        &mut self,
+        path: &[String],
+        new_val: &str,
+        options: &UpdateOptions,
+    ) -> Result<String, Error> {
+        non_empty(path)?;
+        self.0.limited(|doc| {
+            doc.apply_update(path, new_val, options)
+                .map_err(|e| failure(doc, e))
+        })?
+    }
+
+    pub fn validate_schema_with_id(
+        &mut self,
+        schema_id: &str,
+        options: &SchemaValidationOptions,
+    ) -> Result<SchemaOutcome, Error> {
+        let opts = options.clone().capped();
+        let mut outcome = self.0.limited(|doc| doc.schema_outcome(schema_id, &opts))?;
+        if let Some(unit) = opts.column_unit {
+            self.apply_column_unit(&mut outcome, unit);
+        }
+        Ok(outcome)
+    }
+
+    /// Next `max` JSON tokens; see [`Document::reset_tokens`].
+    pub fn next_tokens(&mut self, max: usize) -> Result<TokenBatch, Error> {
+        self.0.limited(|doc| {
+            doc.token_batch(max).map_err(|message| {
+                if doc.file_type() == FileType::Json {
+                    Error::Syntax {
+                        message,
+                        span: doc.summary().span,
+                    }
+                } else {
+                    Error::UnsupportedOperation(message)
+                }
+            })
+        })?
+    }
+
+    pub fn reset_tokens(&self) {
+        self.0.rewind_tokens();
+    }
+
+    /// Validates in `chunk_size`-byte slices (64 KiB when 0), calling
+    /// `progress(processed, total)` between slices; returning `false` cancels.
+    pub fn validate_chunked(
+        &mut self,
+        chunk_size: usize,
+        progress: impl FnMut(usize, usize) -> bool,
+    ) -> Result<ChunkedOutcome, Error> {
+        Ok(self
+            .0
+            .limited(|doc| match doc.validate_in_chunks(chunk_size, progress) {
+                document::ChunkedValidation::Done(summary) => ChunkedOutcome::Done(summary.clone()),
+                document::ChunkedValidation::Cancelled { processed } => {
+                    ChunkedOutcome::Cancelled { processed }
+                }
+            })?)
+    }
+
+    /// Validates about `chunk_size` more bytes (64 KiB when 0) per call, for
+    /// callers that yield to an event loop between slices. Call until it
+    /// returns [`ValidationStep::Done`]; an edit restarts the scan.
+    pub fn validate_step(&mut self, chunk_size: usize) -> Result<ValidationStep, Error> {
+        Ok(self.0.limited(|doc| match doc.validate_step(chunk_size) {
+            document::ValidationStep::Done(summary) => ValidationStep::Done(summary.clone()),
+            document::ValidationStep::Pending { processed } => {
+                ValidationStep::Pending { processed }
+            }
+        })?)
+    }
+
+    /// Appends a chunk of streamed input (e.g. from a `ReadableStream`) to
+    /// the content, lexing JSON incrementally; call [`Document::finish`] after
+    /// the last chunk. Start from an empty document.
+    pub fn feed(&mut self, chunk: &str) -> Result<(), Error> {
+        limits::check_bytes(self.0.content_str().len() + chunk.len())?;
+        Ok(self.0.limited(|doc| doc.feed(chunk))?)
+    }
+
+    /// Lexes whatever the fed chunks left and validates the full content.
+    pub fn finish(&mut self) -> Result<ValidationResult, Error> {
+        Ok(self.0.limited(|doc| loop {
+            if let document::ValidationStep::Done(summary) = doc.validate_step(usize::MAX) {
+                break summary.clone();
+            }
+        })?)
+    }
+
+    /// [`apply_column_unit`] against this document's cached line index.
+    pub fn apply_column_unit(&self, result: &mut impl Positioned, unit: ColumnUnit) {
+        let (content, index) = (self.0.content_str(), self.0.line_index());
+        result.relocate(&|offset| index.line_col_in(content, offset, unit));
+    }
+
+    /// 1-based `(line, column)` with character columns.
+    pub fn offset_to_position(&self, offset: usize) -> (usize, usize) {
+        self.0.position_of(offset)
+    }
+
+    pub fn position_to_offset(&self, line: usize, column: usize) -> usize {
+        self.0.offset_of(line, column)
+    }
+}
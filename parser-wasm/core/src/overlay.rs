@@ -0,0 +1,311 @@
+//! Deep merge of JSON overlays into a base document.
+//!
+//! The merge walks the span-annotated trees of the base and the overlay and
+//! turns what differs into text edits on the base, so every value the
+//! overlay leaves alone keeps its bytes: spacing, key order, number
+//! spelling and escapes. Values the overlay brings in are written the way
+//! their new siblings are laid out.
+
+use std::collections::HashMap;
+
+use crate::diff::{self, same_scalar};
+use crate::json_format::format_json;
+use crate::json_lexer::{Kind, Lexer};
+use crate::json_tree::{JsonNode, JsonValue, Member};
+use crate::Span;
+
+/// How an overlay array combines with the base array at the same path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ArrayStrategy {
+    /// The overlay's array replaces the base's.
+    #[default]
+    Replace,
+    /// The overlay's items are appended to the base's.
+    Concat,
+    /// Object items with equal values under this key are merged; the other
+    /// overlay items are appended.
+    MergeByKey(String),
+}
+
+impl ArrayStrategy {
+    /// `"replace"`, `"concat"` or `"mergeByKey"`, the last pairing items by
+    /// `key`.
+    pub fn parse(label: &str, key: &str) -> Option<Self> {
+        match label {
+            "replace" => Some(Self::Replace),
+            "concat" => Some(Self::Concat),
+            "mergeByKey" => Some(Self::MergeByKey(key.to_string())),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OverlayOptions {
+    pub arrays: ArrayStrategy,
+}
+
+/// `base` with `overlay` merged in: objects merge key by key, arrays follow
+/// `opts.arrays`, anything else is replaced when its value differs. Both
+/// documents have been checked.
+pub(crate) fn apply(
+    base: &str,
+    base_tree: &JsonNode,
+    overlay: &str,
+    overlay_tree: &JsonNode,
+    opts: &OverlayOptions,
+) -> String {
+    let mut merge = Merge {
+        base,
+        overlay,
+        arrays: &opts.arrays,
+        layout: Layout::of(base, base_tree),
+        edits: Vec::new(),
+    };
+    merge.node(base_tree, overlay_tree);
+    let mut edits = merge.edits;
+    edits.sort_by_key(|(span, _)| (span.start, span.end));
+    let mut out = String::with_capacity(base.len());
+    let mut at = 0;
+    for (span, text) in edits {
+        out.push_str(&base[at..span.start]);
+        out.push_str(&text);
+        at = span.end;
+    }
+    out.push_str(&base[at..]);
+    out
+}
+
+/// How the base is laid out, for writing new values into it.
+struct Layout {
+    /// One level of indentation; `None` when the base is on one line.
+    unit: Option<String>,
+    newline: &'static str,
+}
+
+impl Layout {
+    fn of(content: &str, root: &JsonNode) -> Self {
+        let text = &content[root.span.start..root.span.end];
+        let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        if !text.contains('\n') {
+            return Self {
+                unit: None,
+                newline,
+            };
+        }
+        let unit = text
+            .lines()
+            .skip(1)
+            .map(|line| &line[..line.len() - line.trim_start_matches([' ', '\t']).len()])
+            .find(|indent| !indent.is_empty())
+            .unwrap_or("  ");
+        Self {
+            unit: Some(unit.to_string()),
+            newline,
+        }
+    }
+}
+
+struct Merge<'a> {
+    base: &'a str,
+    overlay: &'a str,
+    arrays: &'a ArrayStrategy,
+    layout: Layout,
+    /// Replacements and insertions (empty spans) on the base; they never
+    /// overlap.
+    edits: Vec<(Span, String)>,
+}
+
+impl Merge<'_> {
+    fn node(&mut self, base: &JsonNode, over: &JsonNode) {
+        match (&base.value, &over.value) {
+            (JsonValue::Object(members), JsonValue::Object(updates)) if !members.is_empty() => {
+                self.object(members, updates)
+            }
+            (JsonValue::Array(items), JsonValue::Array(updates)) if !items.is_empty() => match self
+                .arrays
+            {
+                ArrayStrategy::Replace => self.replace(base, over),
+                ArrayStrategy::Concat => self.append(items, &updates.iter().collect::<Vec<_>>()),
+                ArrayStrategy::MergeByKey(key) => self.merge_items(items, updates, key),
+            },
+            (JsonValue::Scalar(kb), JsonValue::Scalar(ko))
+                if same_scalar(*kb, text(self.base, base), *ko, text(self.overlay, over)) => {}
+            _ => self.replace(base, over),
+        }
+    }
+
+    /// Merges into members the base already has, the first occurrence of a
+    /// repeated key, and adds the others after the last member. A key the
+    /// overlay repeats takes its last value, as `JSON.parse` does.
+    fn object(&mut self, members: &[Member], updates: &[Member]) {
+        let mut existing: HashMap<&str, &Member> = HashMap::new();
+        for member in members {
+            existing.entry(member.name.as_str()).or_insert(member);
+        }
+        let last_update: HashMap<&str, usize> = updates
+            .iter()
+            .enumerate()
+            .map(|(i, update)| (update.name.as_str(), i))
+            .collect();
+        let mut added = Vec::new();
+        for (i, update) in updates.iter().enumerate() {
+            if last_update[update.name.as_str()] != i {
+                continue;
+            }
+            match existing.get(update.name.as_str()) {
+                Some(member) => self.node(&member.value, &update.value),
+                None => added.push(update),
+            }
+        }
+        let Some(last) = members.last() else {
+            return;
+        };
+        if added.is_empty() {
+            return;
+        }
+        let previous = members
+            .len()
+            .checked_sub(2)
+            .map(|i| members[i].value.span.end);
+        let (separator, indent) = self.separator(previous, last.key_span.start);
+        let colon = &self.base[last.key_span.end..last.value.span.start];
+        let mut text = String::new();
+        for update in added {
+            text.push(',');
+            text.push_str(&separator);
+            text.push_str(&self.overlay[update.key_span.start..update.key_span.end]);
+            text.push_str(colon);
+            text.push_str(&self.render(&update.value, &indent));
+        }
+        let end = last.value.span.end;
+        self.edits.push((Span::new(end, end), text));
+    }
+
+    /// Pairs object items by the value under `key`; each base item takes at
+    /// most one overlay item, the rest are appended.
+    fn merge_items(&mut self, items: &[JsonNode], updates: &[JsonNode], key: &str) {
+        let mut taken = vec![false; items.len()];
+        let mut added = Vec::new();
+        for update in updates {
+            let matched = key_value(self.overlay, update, key).and_then(|(kind, value)| {
+                (0..items.len()).find(|&i| {
+                    !taken[i]
+                        && key_value(self.base, &items[i], key)
+                            .is_some_and(|(k, v)| same_scalar(k, v, kind, value))
+                })
+            });
+            match matched {
+                Some(i) => {
+                    taken[i] = true;
+                    self.node(&items[i], update);
+                }
+                None => added.push(update),
+            }
+        }
+        self.append(items, &added);
+    }
+
+    fn append(&mut self, items: &[JsonNode], added: &[&JsonNode]) {
+        let Some(last) = items.last() else {
+            return;
+        };
+        if added.is_empty() {
+            return;
+        }
+        let previous = items.len().checked_sub(2).map(|i| items[i].span.end);
+        let (separator, indent) = self.separator(previous, last.span.start);
+        let mut text = String::new();
+        for item in added {
+            text.push(',');
+            text.push_str(&separator);
+            text.push_str(&self.render(item, &indent));
+        }
+        let end = last.span.end;
+        self.edits.push((Span::new(end, end), text));
+    }
+
+    /// Writes the overlay's value over the base's, unless they are equal.
+    fn replace(&mut self, base: &JsonNode, over: &JsonNode) {
+        if diff::json(self.base, base, self.overlay, over).is_empty() {
+            return;
+        }
+        let indent = line_indent(self.base, base.span.start);
+        let text = self.render(over, indent);
+        self.edits.push((base.span, text));
+    }
+
+    /// What follows the comma before a new sibling of the entry at `start`
+    /// (whose predecessor ends at `previous`), and the indentation of that
+    /// entry's line.
+    fn separator(&self, previous: Option<usize>, start: usize) -> (String, String) {
+        let indent = line_indent(self.base, start).to_string();
+        if let Some(previous) = previous {
+            let gap = &self.base[previous..start];
+            if let Some(comma) = gap.find(',') {
+                return (gap[comma + 1..].to_string(), indent);
+            }
+        }
+        let line_start = self.base[..start].rfind('\n').map_or(0, |i| i + 1);
+        if line_start > 0 && line_start + indent.len() == start {
+            (format!("{}{indent}", self.layout.newline), indent)
+        } else {
+            (" ".into(), indent)
+        }
+    }
+
+    /// An overlay value as base text: as written when it is on one line,
+    /// otherwise re-indented for the base, continuation lines starting with
+    /// `indent`, or put on one line when the base is.
+    fn render(&self, node: &JsonNode, indent: &str) -> String {
+        let raw = text(self.overlay, node);
+        if !raw.contains(['\n', '\r']) {
+            return raw.to_string();
+        }
+        let Some(unit) = &self.layout.unit else {
+            return one_line(raw);
+        };
+        match format_json(raw, unit) {
+            Ok(pretty) => pretty
+                .trim_end_matches('\n')
+                .replace('\n', &format!("{}{indent}", self.layout.newline)),
+            Err(_) => raw.to_string(),
+        }
+    }
+}
+
+fn text<'c>(content: &'c str, node: &JsonNode) -> &'c str {
+    &content[node.span.start..node.span.end]
+}
+
+/// Spaces and tabs at the start of the line holding `at`.
+fn line_indent(content: &str, at: usize) -> &str {
+    let start = content[..at].rfind('\n').map_or(0, |i| i + 1);
+    let line = &content[start..at];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// The scalar under `key` of an object node.
+fn key_value<'c>(content: &'c str, node: &JsonNode, key: &str) -> Option<(Kind, &'c str)> {
+    let JsonValue::Object(members) = &node.value else {
+        return None;
+    };
+    let member = members.iter().find(|member| member.name == key)?;
+    match member.value.value {
+        JsonValue::Scalar(kind) => Some((kind, text(content, &member.value))),
+        _ => None,
+    }
+}
+
+/// `raw` with its line breaks and indentation dropped: one space after each
+/// comma and colon.
+fn one_line(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for token in Lexer::new(raw).flatten() {
+        out.push_str(&raw[token.span.start..token.span.end]);
+        if matches!(token.kind, Kind::Comma | Kind::Colon) {
+            out.push(' ');
+        }
+    }
+    out
+}
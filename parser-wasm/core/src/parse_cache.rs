@@ -0,0 +1,91 @@
+//! Small LRU of recent parses for the stateless exports.
+//!
+//! Hosts often call `validate`, `validate_multi` and `find_value_spans` on
+//! the same unchanged content in turn. Each call used to build a throwaway
+//! `Document`; this keeps the last few keyed by file type and content hash,
+//! so their cached tokens, spans and line index carry over between calls.
+//! Hits are confirmed by comparing the content itself.
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use crate::document::{Document, FileType};
+use crate::limits;
+
+const PARSE_CACHE_CAPACITY: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Key {
+    file_type: FileType,
+    hash: u64,
+    len: usize,
+}
+
+impl Key {
+    fn new(file_type: FileType, content: &str) -> Self {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        Self {
+            file_type,
+            hash: hasher.finish(),
+            len: content.len(),
+        }
+    }
+}
+
+thread_local! {
+    /// Most recently used first.
+    static CACHE: RefCell<VecDeque<(Key, Rc<Document>)>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// Runs `f` against a cached parse of `content`, creating one on a miss. An
+/// entry that trips a limit while in use is dropped, since its caches may
+/// hold truncated results.
+pub(crate) fn with_document<T>(
+    file_type: FileType,
+    content: &str,
+    f: impl FnOnce(&Document) -> T,
+) -> T {
+    let key = Key::new(file_type, content);
+    let doc = CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let hit = cache
+            .iter()
+            .position(|(k, doc)| *k == key && doc.content_str() == content);
+        match hit {
+            Some(pos) => {
+                let entry = cache.remove(pos).expect("position is in range");
+                let doc = Rc::clone(&entry.1);
+                cache.push_front(entry);
+                doc
+            }
+            None => {
+                let doc = Rc::new(Document::with_type(file_type, content.to_string()));
+                cache.push_front((key, Rc::clone(&doc)));
+                cache.truncate(PARSE_CACHE_CAPACITY);
+                doc
+            }
+        }
+    });
+    let out = f(&doc);
+    if limits::tripped() {
+        CACHE.with(|cache| {
+            cache
+                .borrow_mut()
+                .retain(|(_, cached)| !Rc::ptr_eq(cached, &doc))
+        });
+    }
+    out
+}
+
+pub(crate) fn clear() {
+    CACHE.with(|cache| cache.borrow_mut().clear());
+}
+
+#[cfg(all(test, feature = "xml", feature = "env"))]
+pub(crate) fn len() -> usize {
+    CACHE.with(|cache| cache.borrow().len())
+}
@@ -0,0 +1,149 @@
+//! Path segment escaping.
+//!
+//! Paths are arrays of segments, and a plain segment names a key verbatim or,
+//! in a JSON array, the index it spells in canonical decimal. Two forms pin
+//! down which one is meant ([`Segment`]):
+//!
+//! - A leading `\` makes the rest of the segment a literal key: `\@id` is
+//!   the JSON key `"@id"` or an XML element named `@id`, never an attribute,
+//!   and `\0` never matches an array index. The escape is also needed for a
+//!   key that starts with a backslash or looks like an index segment.
+//! - `[n]` is array index `n` only, so it never matches an object key `"n"`.
+//!
+//! [`escape_segment`] adds the backslash only when it is needed; the JS
+//! bindings also accept `{ key }` / `{ index }` segments and encode them
+//! with [`key_segment`] / [`index_segment`].
+//!
+//! The string forms are for hosts that keep a path in one string (form field
+//! names, CLI arguments): [`join_path`] / [`split_path`] use `.` between
+//! segments with `\.` and `\\` inside them, and [`to_pointer`] /
+//! [`from_pointer`] use RFC 6901 JSON Pointers (`~0` for `~`, `~1` for `/`).
+
+/// What a path segment selects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Segment<'a> {
+    /// `\key`: an object key / element / variable name, never an index.
+    Key(&'a str),
+    /// `[n]`: an array index, never a key.
+    Index(usize),
+    /// Anything else: a key, or a canonical decimal array index.
+    Plain(&'a str),
+}
+
+pub(crate) fn parse(segment: &str) -> Segment<'_> {
+    if let Some(key) = segment.strip_prefix('\\') {
+        return Segment::Key(key);
+    }
+    let index = segment
+        .strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .filter(|digits| is_canonical_index(digits))
+        .and_then(|digits| digits.parse().ok());
+    match index {
+        Some(index) => Segment::Index(index),
+        None => Segment::Plain(segment),
+    }
+}
+
+/// Non-empty decimal digits without a leading zero (other than `0` itself).
+pub(crate) fn is_canonical_index(digits: &str) -> bool {
+    !digits.is_empty()
+        && digits.bytes().all(|b| b.is_ascii_digit())
+        && (digits.len() == 1 || !digits.starts_with('0'))
+}
+
+/// The segment that addresses the literal key `key`.
+pub fn escape_segment(key: &str) -> String {
+    let ambiguous = key.starts_with(['@', '\\']) || matches!(parse(key), Segment::Index(_));
+    if ambiguous {
+        format!("\\{key}")
+    } else {
+        key.to_string()
+    }
+}
+
+/// The segment that matches only the key `key`, never an array index.
+pub fn key_segment(key: &str) -> String {
+    format!("\\{key}")
+}
+
+/// The segment that matches only array index `index`.
+pub fn index_segment(index: usize) -> String {
+    format!("[{index}]")
+}
+
+/// The name `segment` looks up in formats without arrays (XML elements, ENV
+/// keys): the key with any escape removed. Index segments stay as written.
+#[cfg(any(feature = "xml", feature = "env"))]
+pub(crate) fn literal(segment: &str) -> &str {
+    match parse(segment) {
+        Segment::Key(key) | Segment::Plain(key) => key,
+        Segment::Index(_) => segment,
+    }
+}
+
+/// `path` as one dotted string, escaping `.` and `\` inside segments.
+pub fn join_path(path: &[String]) -> String {
+    let mut out = String::new();
+    for (i, segment) in path.iter().enumerate() {
+        if i > 0 {
+            out.push('.');
+        }
+        for ch in segment.chars() {
+            if matches!(ch, '.' | '\\') {
+                out.push('\\');
+            }
+            out.push(ch);
+        }
+    }
+    out
+}
+
+/// Inverse of [`join_path`]. A `\` before any other character is kept.
+pub fn split_path(text: &str) -> Vec<String> {
+    let mut path = vec![String::new()];
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        let segment = path.last_mut().unwrap();
+        match ch {
+            '.' => path.push(String::new()),
+            '\\' => match chars.next() {
+                Some(next @ ('.' | '\\')) => segment.push(next),
+                Some(next) => {
+                    segment.push('\\');
+                    segment.push(next);
+                }
+                None => segment.push('\\'),
+            },
+            _ => segment.push(ch),
+        }
+    }
+    path
+}
+
+/// RFC 6901 pointer for `path`. Escapes are removed and index segments are
+/// written as plain numbers, so the pointer names the same values.
+pub fn to_pointer(path: &[String]) -> String {
+    path.iter()
+        .map(|segment| match parse(segment) {
+            Segment::Index(index) => format!("/{index}"),
+            Segment::Key(key) | Segment::Plain(key) => {
+                format!("/{}", key.replace('~', "~0").replace('/', "~1"))
+            }
+        })
+        .collect()
+}
+
+/// Path for an RFC 6901 pointer, with each key escaped as needed.
+pub fn from_pointer(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(format!("Invalid JSON Pointer: {pointer}"));
+    };
+    Ok(rest
+        .split('/')
+        .map(|token| escape_segment(&token.replace("~1", "/").replace("~0", "~")))
+        .collect())
+}
@@ -0,0 +1,110 @@
+//! Opt-in timing breakdowns (`{ profile: true }`).
+//!
+//! An entry point calls [`begin`], the pipeline wraps its phases in
+//! [`time`], and [`finish`] returns the accumulated [`Timings`]. Recording
+//! is thread-local like the limits state, and [`time`] is a plain call when
+//! no profile is active.
+
+use std::cell::Cell;
+
+use serde::Serialize;
+use tsify::Tsify;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Phase {
+    Lex,
+    StructuralCheck,
+    SchemaCompile,
+    SchemaValidate,
+}
+
+/// Milliseconds per phase; phases that did not run are omitted. Repeated
+/// phases (e.g. several schemas in `validate_schema_all`) are summed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Tsify)]
+#[serde(rename = "ProfileTimings", rename_all = "camelCase")]
+pub struct Timings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lex: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structural_check: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_compile: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_validate: Option<f64>,
+    pub total: f64,
+}
+
+impl Timings {
+    fn add(&mut self, phase: Phase, ms: f64) {
+        let slot = match phase {
+            Phase::Lex => &mut self.lex,
+            Phase::StructuralCheck => &mut self.structural_check,
+            Phase::SchemaCompile => &mut self.schema_compile,
+            Phase::SchemaValidate => &mut self.schema_validate,
+        };
+        *slot = Some(slot.unwrap_or(0.0) + ms);
+    }
+}
+
+thread_local! {
+    static ACTIVE: Cell<Option<(f64, Timings)>> = const { Cell::new(None) };
+}
+
+pub(crate) fn begin() {
+    ACTIVE.with(|cell| cell.set(Some((now_ms(), Timings::default()))));
+}
+
+pub(crate) fn finish() -> Option<Timings> {
+    ACTIVE.with(Cell::take).map(|(started, mut timings)| {
+        timings.total = now_ms() - started;
+        timings
+    })
+}
+
+pub(crate) fn time<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    if ACTIVE.with(|cell| cell.get().is_none()) {
+        return f();
+    }
+    let started = now_ms();
+    let out = f();
+    let elapsed = now_ms() - started;
+    ACTIVE.with(|cell| {
+        if let Some((start, mut timings)) = cell.get() {
+            timings.add(phase, elapsed);
+            cell.set(Some((start, timings)));
+        }
+    });
+    out
+}
+
+thread_local! {
+    static CLOCK: Cell<Option<fn() -> f64>> = const { Cell::new(None) };
+}
+
+/// Installs the millisecond clock behind `timings`; the wasm adapter passes
+/// `performance.now()`, which `std::time` cannot reach on wasm32.
+pub(crate) fn set_clock(clock: fn() -> f64) {
+    CLOCK.with(|cell| cell.set(Some(clock)));
+}
+
+fn now_ms() -> f64 {
+    match CLOCK.with(Cell::get) {
+        Some(clock) => clock(),
+        None => fallback_ms(),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn fallback_ms() -> f64 {
+    use once_cell::sync::Lazy;
+    use std::time::Instant;
+    static EPOCH: Lazy<Instant> = Lazy::new(Instant::now);
+    EPOCH.elapsed().as_secs_f64() * 1000.0
+}
+
+/// `Instant` panics on wasm32-unknown-unknown; timings read 0 until a host
+/// clock is installed.
+#[cfg(target_arch = "wasm32")]
+fn fallback_ms() -> f64 {
+    0.0
+}
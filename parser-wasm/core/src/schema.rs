@@ -0,0 +1,1426 @@
+use crate::encoding::bom_len;
+use crate::json_parser::JsonSpanResolver;
+use crate::line_index::{self, ColumnUnit, LineIndex};
+use crate::multi_validation::infer_json_span;
+use crate::paths;
+use crate::profile::{self, Phase, Timings};
+use crate::schema_ids::{self, rewrite_anchor_refs, Declared};
+use crate::schema_vocabulary::{self, Vocabulary};
+use crate::Span;
+use jsonschema::error::{TypeKind, ValidationError, ValidationErrorKind};
+use jsonschema::primitive_type::PrimitiveType;
+use jsonschema::{Draft, JSONSchema};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use tsify::Tsify;
+
+const DEFAULT_MAX_SCHEMA_ERRORS: usize = 50;
+const MAX_SCHEMA_ERROR_CAP: usize = 200;
+
+const MAX_ANNOTATION_DEPTH: usize = 64;
+
+static SCHEMA_CACHE: Lazy<Mutex<SchemaRegistry>> = Lazy::new(Default::default);
+
+const INLINE_CACHE_CAPACITY: usize = 8;
+
+/// Recent compiles of inline schema text, most recently used first, so a UI
+/// passing the same schema on every keystroke compiles it once. Cleared when
+/// a schema is registered, since `$ref`s may resolve to registered schemas.
+static INLINE_CACHE: Lazy<Mutex<VecDeque<InlineEntry>>> = Lazy::new(Default::default);
+
+/// Key, schema text and compile.
+type InlineEntry = (InlineKey, String, Arc<RegisteredSchema>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InlineKey {
+    hash: u64,
+    len: usize,
+    draft: Option<Draft>,
+    toggles: Toggles,
+}
+
+impl InlineKey {
+    fn new(schema: &str, opts: &SchemaValidationOptions) -> Self {
+        let mut hasher = DefaultHasher::new();
+        schema.hash(&mut hasher);
+        Self {
+            hash: hasher.finish(),
+            len: schema.len(),
+            draft: opts.draft,
+            toggles: Toggles::of(opts),
+        }
+    }
+}
+
+/// The compile settings of [`SchemaValidationOptions`] that also apply to
+/// registered schemas.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+struct Toggles {
+    format_assertion: Option<bool>,
+    /// Sorted, without repeats.
+    disabled: Vec<Vocabulary>,
+}
+
+impl Toggles {
+    fn of(opts: &SchemaValidationOptions) -> Self {
+        let mut disabled = opts.disabled_vocabularies.clone();
+        disabled.sort();
+        disabled.dedup();
+        Self {
+            format_assertion: opts.format_assertion,
+            disabled,
+        }
+    }
+}
+
+/// Registered schemas by the caller's id, with the identifiers they declare
+/// (see `schema_ids.rs`).
+#[derive(Default)]
+struct SchemaRegistry {
+    schemas: HashMap<String, Arc<RegisteredSchema>>,
+    /// Absolute `$id` / `$anchor` identifier to the registration declaring
+    /// it; a later registration takes over an identifier.
+    declared: HashMap<String, DeclaredIn>,
+    /// Compiles made on lookup, of subschemas named by a declared
+    /// identifier or with non-default [`Toggles`]. Dropped whenever a schema
+    /// is registered.
+    variants: HashMap<(String, Toggles), Arc<RegisteredSchema>>,
+}
+
+#[derive(Clone)]
+struct DeclaredIn {
+    schema_id: String,
+    declared: Declared,
+}
+
+impl SchemaRegistry {
+    /// Anchor identifiers to the references jsonschema resolves, for
+    /// [`rewrite_anchor_refs`].
+    fn anchors(&self) -> HashMap<String, String> {
+        self.declared
+            .iter()
+            .filter(|(_, entry)| entry.declared.is_anchor())
+            .map(|(uri, entry)| (uri.clone(), entry.declared.target.clone()))
+            .collect()
+    }
+}
+
+/// Compiled schema together with the raw document it was built from, so that
+/// annotation keywords (e.g. `deprecated`) can be inspected after validation.
+pub(crate) struct RegisteredSchema {
+    pub(crate) value: Value,
+    pub(crate) compiled: JSONSchema,
+    /// Draft forced at registration; `None` detects it from `$schema`.
+    pub(crate) draft: Option<Draft>,
+    /// For a subschema looked up by identifier, the `$ref` that was compiled
+    /// in its place; keyword locations start with it.
+    pub(crate) reference: Option<String>,
+    /// The schema text, which keeps the declaration order `value` loses;
+    /// `None` for a subschema looked up by identifier.
+    pub(crate) source: Option<Arc<str>>,
+}
+
+impl RegisteredSchema {
+    /// `absoluteKeywordLocation` for an error's `schema_path`.
+    fn absolute_location(&self, schema_path: &str) -> Option<String> {
+        match &self.reference {
+            Some(target) => {
+                let wrapper = serde_json::json!({ "$ref": target });
+                schema_ids::absolute_location(&wrapper, schema_path)
+            }
+            None => schema_ids::absolute_location(&self.value, schema_path),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SchemaValidationOptions {
+    pub max_errors: usize,
+    pub collect_positions: bool,
+    pub draft: Option<Draft>,
+    pub report_deprecated: bool,
+    pub profile: bool,
+    /// Recomputes error columns in this unit; `None` keeps the default.
+    pub column_unit: Option<ColumnUnit>,
+    /// Whether `format` fails validation; `None` keeps the draft's default
+    /// (asserting up to draft 7, annotation only from 2019-09 on).
+    pub format_assertion: Option<bool>,
+    /// Vocabularies whose keywords are ignored.
+    pub disabled_vocabularies: Vec<Vocabulary>,
+    /// Lists at most this many values in an `enum` message and counts the
+    /// rest; the error's `allowed_values` then holds them all.
+    pub max_message_values: Option<usize>,
+    /// Cuts longer messages to this many characters followed by `…`.
+    pub max_message_length: Option<usize>,
+}
+
+impl Default for SchemaValidationOptions {
+    fn default() -> Self {
+        Self {
+            max_errors: DEFAULT_MAX_SCHEMA_ERRORS,
+            collect_positions: true,
+            draft: None,
+            report_deprecated: true,
+            profile: false,
+            column_unit: None,
+            format_assertion: None,
+            disabled_vocabularies: Vec::new(),
+            max_message_values: None,
+            max_message_length: None,
+        }
+    }
+}
+
+impl SchemaValidationOptions {
+    /// Clamps `max_errors` to the supported range and the global `maxErrors`.
+    pub(crate) fn capped(mut self) -> Self {
+        self.max_errors = crate::limits::cap_errors(self.max_errors.clamp(1, MAX_SCHEMA_ERROR_CAP));
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Tsify)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[serde(rename = "SchemaValidationError", rename_all = "camelCase")]
+pub struct SchemaErrorDescriptor {
+    pub message: String,
+    pub severity: Severity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyword: Option<String>,
+    pub instance_path: String,
+    /// `instance_path` as a path for the lookup and edit APIs.
+    pub instance_path_segments: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_path: Option<String>,
+    /// The failing keyword as `<schema URI>#<pointer>`, with `$ref`s
+    /// resolved, when it sits in a schema with an absolute `$id`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub absolute_keyword_location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(type = "unknown")]
+    pub suggested_value: Option<Value>,
+    /// Every value an `enum` allows, when the message lists only some (see
+    /// [`SchemaValidationOptions::max_message_values`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(optional, type = "unknown[]")]
+    pub allowed_values: Option<Vec<Value>>,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[serde(rename = "SchemaValidationResult")]
+pub struct SchemaOutcome {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[tsify(optional, type = "SchemaValidationError[]")]
+    pub errors: Vec<SchemaErrorDescriptor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(type = "ProfileTimings")]
+    pub profile: Option<Timings>,
+}
+
+impl SchemaOutcome {
+    fn success() -> Self {
+        Self {
+            valid: true,
+            errors: Vec::new(),
+            profile: None,
+        }
+    }
+
+    fn from_errors(errors: Vec<SchemaErrorDescriptor>) -> Self {
+        // Warnings (e.g. deprecated properties) are informational only
+        let valid = !errors.iter().any(|e| e.severity == Severity::Error);
+        Self {
+            valid,
+            errors,
+            profile: None,
+        }
+    }
+}
+
+/// Runs `f` with timing collection when `opts.profile` is set and attaches
+/// the breakdown to the outcome.
+pub(crate) fn profiled(
+    opts: &SchemaValidationOptions,
+    f: impl FnOnce() -> SchemaOutcome,
+) -> SchemaOutcome {
+    if !opts.profile {
+        return f();
+    }
+    profile::begin();
+    let mut outcome = f();
+    outcome.profile = profile::finish();
+    outcome
+}
+
+#[derive(Debug, Clone)]
+struct SyntaxErrorDetail {
+    message: String,
+    line: usize,
+    column: usize,
+    span: Span,
+}
+
+pub(crate) fn validate_inline_source(
+    content: &str,
+    schema: &str,
+    opts: &SchemaValidationOptions,
+) -> SchemaOutcome {
+    let instance_value = match parse_instance(content) {
+        Ok(val) => val,
+        Err(detail) => return schema_outcome_from_syntax(detail, opts),
+    };
+
+    let schema = match compile_source(schema, opts) {
+        Ok(compiled) => compiled,
+        Err(outcome) => return outcome,
+    };
+
+    schema_validate_instance(&schema, &instance_value, content, opts)
+}
+
+/// Parses and compiles inline schema text, reusing a recent compile of the
+/// same text and draft; a failure is the outcome to report.
+pub(crate) fn compile_source(
+    schema: &str,
+    opts: &SchemaValidationOptions,
+) -> Result<Arc<RegisteredSchema>, SchemaOutcome> {
+    let key = InlineKey::new(schema, opts);
+    if let Some(cached) = inline_cached(&key, schema) {
+        return Ok(cached);
+    }
+    let schema_value = serde_json::from_str::<Value>(schema)
+        .map_err(|err| schema_issue_outcome(format!("Schema parse error: {err}")))?;
+    let compiled = profile::time(Phase::SchemaCompile, || {
+        compile_schema(&schema_value, opts.draft, &key.toggles)
+    })
+    .map_err(|err| schema_issue_outcome(format!("Schema compilation failed: {err}")))?;
+    let compiled = Arc::new(RegisteredSchema {
+        value: schema_value,
+        compiled,
+        draft: opts.draft,
+        reference: None,
+        source: Some(schema.into()),
+    });
+    if let Ok(mut cache) = INLINE_CACHE.lock() {
+        cache.push_front((key, schema.to_string(), Arc::clone(&compiled)));
+        cache.truncate(INLINE_CACHE_CAPACITY);
+    }
+    Ok(compiled)
+}
+
+/// A cached compile of `schema`, moved to the front. Hits are confirmed by
+/// comparing the text.
+fn inline_cached(key: &InlineKey, schema: &str) -> Option<Arc<RegisteredSchema>> {
+    let mut cache = INLINE_CACHE.lock().ok()?;
+    let pos = cache
+        .iter()
+        .position(|(k, source, _)| k == key && source == schema)?;
+    let entry = cache.remove(pos)?;
+    let compiled = Arc::clone(&entry.2);
+    cache.push_front(entry);
+    Some(compiled)
+}
+
+/// Drops the compiled inline schemas.
+pub(crate) fn clear_inline_cache() {
+    if let Ok(mut cache) = INLINE_CACHE.lock() {
+        cache.clear();
+    }
+}
+
+pub(crate) fn validate_registered(
+    content: &str,
+    schema_id: &str,
+    opts: &SchemaValidationOptions,
+) -> SchemaOutcome {
+    let instance_value = match parse_instance(content) {
+        Ok(val) => val,
+        Err(detail) => return schema_outcome_from_syntax(detail, opts),
+    };
+
+    let schema = match registered_for(schema_id, opts) {
+        Some(schema) => schema,
+        None => return schema_issue_outcome(format!("Schema '{schema_id}' is not registered")),
+    };
+
+    schema_validate_instance(&schema, &instance_value, content, opts)
+}
+
+/// Validates one instance against several registered schemas, merging their
+/// findings in schema order and dropping duplicates reported by more than one.
+/// With the `threads` feature the schemas run in parallel; positions are
+/// attached afterwards on the calling thread, where the limits and profile
+/// state live.
+pub(crate) fn validate_against_registered(
+    content: &str,
+    schema_ids: &[String],
+    opts: &SchemaValidationOptions,
+) -> SchemaOutcome {
+    let instance_value = match parse_instance(content) {
+        Ok(val) => val,
+        Err(detail) => return schema_outcome_from_syntax(detail, opts),
+    };
+
+    let schemas: Vec<Option<Arc<RegisteredSchema>>> = schema_ids
+        .iter()
+        .map(|id| registered_for(id, opts))
+        .collect();
+    let findings: Vec<Option<Findings>> = profile::time(Phase::SchemaValidate, || {
+        #[cfg(feature = "threads")]
+        {
+            use rayon::prelude::*;
+            schemas
+                .par_iter()
+                .map(|schema| run_registered(schema, &instance_value, opts))
+                .collect()
+        }
+        #[cfg(not(feature = "threads"))]
+        {
+            schemas
+                .iter()
+                .map(|schema| run_registered(schema, &instance_value, opts))
+                .collect()
+        }
+    });
+
+    let mut merged: Vec<SchemaErrorDescriptor> = Vec::new();
+    for (schema_id, findings) in schema_ids.iter().zip(findings) {
+        let outcome = match findings {
+            Some(findings) => describe_findings(findings, content, opts, None),
+            None => schema_issue_outcome(format!("Schema '{schema_id}' is not registered")),
+        };
+        for err in outcome.errors {
+            if !merged.iter().any(|existing| same_finding(existing, &err)) {
+                merged.push(err);
+            }
+        }
+    }
+    merged.truncate(opts.max_errors);
+    SchemaOutcome::from_errors(merged)
+}
+
+fn same_finding(a: &SchemaErrorDescriptor, b: &SchemaErrorDescriptor) -> bool {
+    a.message == b.message
+        && a.severity == b.severity
+        && a.keyword == b.keyword
+        && a.instance_path == b.instance_path
+        && a.start == b.start
+        && a.end == b.end
+}
+
+/// Registered-schema validation for callers that already hold a line index.
+pub(crate) fn validate_registered_indexed(
+    content: &str,
+    schema_id: &str,
+    opts: &SchemaValidationOptions,
+    index: &LineIndex,
+) -> SchemaOutcome {
+    let instance_value = match parse_instance(content) {
+        Ok(val) => val,
+        Err(detail) => return schema_outcome_from_syntax(detail, opts),
+    };
+    match registered_for(schema_id, opts) {
+        Some(schema) => {
+            schema_validate_instance_indexed(&schema, &instance_value, content, opts, Some(index))
+        }
+        None => schema_issue_outcome(format!("Schema '{schema_id}' is not registered")),
+    }
+}
+
+pub(crate) fn register_schema_source(schema_id: &str, schema: &str) -> Result<(), String> {
+    let schema_value: Value = serde_json::from_str(schema)
+        .map_err(|err| format!("Invalid schema JSON for '{schema_id}': {err}"))?;
+    insert_schema(schema_id, schema_value, schema, None)
+}
+
+fn insert_schema(
+    schema_id: &str,
+    schema_value: Value,
+    source: &str,
+    draft: Option<Draft>,
+) -> Result<(), String> {
+    let compiled = compile_schema(&schema_value, draft, &Toggles::default())?;
+    let declared = schema_ids::declared(&schema_value);
+
+    let mut cache = SCHEMA_CACHE.lock().expect("schema cache lock poisoned");
+    cache
+        .declared
+        .retain(|_, entry| entry.schema_id != schema_id);
+    for declared in declared.into_iter().filter(Declared::is_absolute) {
+        let entry = DeclaredIn {
+            schema_id: schema_id.to_string(),
+            declared,
+        };
+        cache.declared.insert(entry.declared.uri.clone(), entry);
+    }
+    cache.variants.clear();
+    clear_inline_cache();
+    cache.schemas.insert(
+        schema_id.to_string(),
+        Arc::new(RegisteredSchema {
+            value: schema_value,
+            compiled,
+            draft,
+            reference: None,
+            source: Some(source.into()),
+        }),
+    );
+    Ok(())
+}
+
+// ───── Schema import / export ─────
+//
+// jsonschema's compiled form cannot be serialized, so an export carries the
+// schema text and its draft; importing recompiles. Layout: `KSCH`, format
+// version, draft code (0 = from `$schema`), then the schema text as
+// registered, which keeps its declaration order.
+
+const EXPORT_MAGIC: &[u8; 4] = b"KSCH";
+const EXPORT_VERSION: u8 = 1;
+const EXPORT_HEADER_LEN: usize = EXPORT_MAGIC.len() + 2;
+
+pub(crate) fn export_schema_bytes(schema_id: &str) -> Result<Vec<u8>, String> {
+    let schema = get_cached_schema(schema_id)
+        .ok_or_else(|| format!("Schema '{schema_id}' is not registered"))?;
+    let mut out = Vec::with_capacity(EXPORT_HEADER_LEN + 256);
+    out.extend_from_slice(EXPORT_MAGIC);
+    out.push(EXPORT_VERSION);
+    out.push(draft_code(schema.draft));
+    match &schema.source {
+        Some(source) => out.extend_from_slice(source.as_bytes()),
+        None => serde_json::to_writer(&mut out, &schema.value).map_err(|err| err.to_string())?,
+    }
+    Ok(out)
+}
+
+pub(crate) fn import_schema_bytes(schema_id: &str, bytes: &[u8]) -> Result<(), String> {
+    if bytes.len() < EXPORT_HEADER_LEN || &bytes[..EXPORT_MAGIC.len()] != EXPORT_MAGIC {
+        return Err(format!("Invalid schema export for '{schema_id}'"));
+    }
+    let version = bytes[EXPORT_MAGIC.len()];
+    if version != EXPORT_VERSION {
+        return Err(format!(
+            "Unsupported schema export version {version} for '{schema_id}'"
+        ));
+    }
+    let draft = draft_from_code(bytes[EXPORT_MAGIC.len() + 1])
+        .ok_or_else(|| format!("Invalid schema export for '{schema_id}': unknown draft"))?;
+    let source = std::str::from_utf8(&bytes[EXPORT_HEADER_LEN..])
+        .map_err(|err| format!("Invalid schema export for '{schema_id}': {err}"))?;
+    let schema_value: Value = serde_json::from_str(source)
+        .map_err(|err| format!("Invalid schema export for '{schema_id}': {err}"))?;
+    insert_schema(schema_id, schema_value, source, draft)
+}
+
+fn draft_code(draft: Option<Draft>) -> u8 {
+    match draft {
+        None => 0,
+        Some(Draft::Draft4) => 4,
+        Some(Draft::Draft6) => 6,
+        Some(Draft::Draft7) => 7,
+        Some(Draft::Draft201909) => 19,
+        Some(Draft::Draft202012) => 20,
+        // `Draft` is non-exhaustive; anything newer falls back to detection
+        Some(_) => 0,
+    }
+}
+
+/// Outer `None` rejects an unknown code; `Some(None)` means "detect".
+fn draft_from_code(code: u8) -> Option<Option<Draft>> {
+    match code {
+        0 => Some(None),
+        4 => Some(Some(Draft::Draft4)),
+        6 => Some(Some(Draft::Draft6)),
+        7 => Some(Some(Draft::Draft7)),
+        19 => Some(Some(Draft::Draft201909)),
+        20 => Some(Some(Draft::Draft202012)),
+        _ => None,
+    }
+}
+
+#[cfg(all(test, feature = "xml", feature = "env"))]
+pub(crate) fn validate_schema_for_tests(
+    schema_json: &str,
+    content: &str,
+    options: Option<SchemaValidationOptions>,
+) -> SchemaOutcome {
+    let schema_value: Value = serde_json::from_str(schema_json).unwrap();
+    let schema = RegisteredSchema {
+        compiled: JSONSchema::compile(&schema_value).unwrap(),
+        value: schema_value,
+        draft: None,
+        reference: None,
+        source: Some(schema_json.into()),
+    };
+    let instance_value = serde_json::from_str::<Value>(&content[bom_len(content)..]).unwrap();
+    let opts = options.unwrap_or_default();
+    schema_validate_instance(&schema, &instance_value, content, &opts)
+}
+
+fn parse_instance(content: &str) -> Result<Value, SyntaxErrorDetail> {
+    match profile::time(Phase::StructuralCheck, || {
+        serde_json::from_str::<Value>(&content[bom_len(content)..])
+    }) {
+        Ok(val) => Ok(val),
+        Err(err) => {
+            // serde_json counts columns in bytes; report characters
+            let start = line_index::offset_at(content, err.line(), err.column(), ColumnUnit::Byte);
+            let span = infer_json_span(content, start);
+            let (line, column) = line_index::line_col_at(content, span.start, ColumnUnit::Char);
+            Err(SyntaxErrorDetail {
+                message: err.to_string(),
+                line,
+                column,
+                span,
+            })
+        }
+    }
+}
+
+/// Compiles `schema_value` with `$ref`s to anchors rewritten and other
+/// documents resolved from the registered schemas, under `toggles`.
+fn compile_schema(
+    schema_value: &Value,
+    draft: Option<Draft>,
+    toggles: &Toggles,
+) -> Result<JSONSchema, String> {
+    let stripped = schema_vocabulary::without(schema_value, &toggles.disabled);
+    let schema_value = stripped.as_ref().unwrap_or(schema_value);
+    let mut anchors = SCHEMA_CACHE
+        .lock()
+        .map(|cache| cache.anchors())
+        .unwrap_or_default();
+    for declared in schema_ids::declared(schema_value) {
+        if declared.is_anchor() {
+            anchors.insert(declared.uri, declared.target);
+        }
+    }
+    let rewritten = rewrite_anchor_refs(schema_value, &anchors);
+    let mut options = JSONSchema::options();
+    options.with_resolver(schema_ids::Registry {
+        disabled: toggles.disabled.clone(),
+    });
+    if let Some(draft) = draft {
+        options.with_draft(draft);
+    }
+    if let Some(flag) = toggles.format_assertion {
+        options.should_validate_formats(flag);
+    }
+    options
+        .compile(rewritten.as_ref().unwrap_or(schema_value))
+        .map_err(|err| err.to_string())
+}
+
+fn schema_validate_instance(
+    schema: &RegisteredSchema,
+    instance: &Value,
+    content: &str,
+    opts: &SchemaValidationOptions,
+) -> SchemaOutcome {
+    schema_validate_instance_indexed(schema, instance, content, opts, None)
+}
+
+/// Validation core; positions are computed through `index` when the caller
+/// already has one for `content` (e.g. a `Document`).
+pub(crate) fn schema_validate_instance_indexed(
+    schema: &RegisteredSchema,
+    instance: &Value,
+    content: &str,
+    opts: &SchemaValidationOptions,
+    index: Option<&LineIndex>,
+) -> SchemaOutcome {
+    // jsonschema yields errors lazily, so drain them inside the timed phase
+    let findings = profile::time(Phase::SchemaValidate, || run_schema(schema, instance, opts));
+    describe_findings(findings, content, opts, index)
+}
+
+/// Validates an instance built from non-JSON `content`, such as an ENV
+/// file; `span_of` maps an instance path to the span in `content` that
+/// errors at it are reported at.
+#[cfg(feature = "env")]
+pub(crate) fn validate_mapped(
+    schema: &RegisteredSchema,
+    instance: &Value,
+    content: &str,
+    opts: &SchemaValidationOptions,
+    span_of: &dyn Fn(&str) -> Option<Span>,
+) -> SchemaOutcome {
+    let findings = profile::time(Phase::SchemaValidate, || run_schema(schema, instance, opts));
+    let index = opts.collect_positions.then(|| LineIndex::new(content));
+    let positions = index.as_ref().map(|index| (span_of, index));
+    describe_positioned(findings, content, opts, positions)
+}
+
+/// Raw results of one schema run, before positions are attached.
+struct Findings<'i> {
+    /// Errors with their absolute keyword locations.
+    errors: Vec<(ValidationError<'i>, Option<String>)>,
+    deprecated: Vec<DeprecatedHit>,
+}
+
+/// The thread-safe half of validation: touches no thread-local state, so
+/// several schemas can run on worker threads.
+fn run_schema<'i>(
+    schema: &'i RegisteredSchema,
+    instance: &'i Value,
+    opts: &SchemaValidationOptions,
+) -> Findings<'i> {
+    let deprecated = if opts.report_deprecated {
+        collect_deprecated(&schema.value, instance)
+    } else {
+        Vec::new()
+    };
+    let errors = match schema.compiled.validate(instance) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .take(opts.max_errors)
+            .map(|error| {
+                let location = schema.absolute_location(&error.schema_path.to_string());
+                (error, location)
+            })
+            .collect(),
+    };
+    Findings { errors, deprecated }
+}
+
+fn run_registered<'i>(
+    schema: &'i Option<Arc<RegisteredSchema>>,
+    instance: &'i Value,
+    opts: &SchemaValidationOptions,
+) -> Option<Findings<'i>> {
+    let schema = schema.as_deref()?;
+    Some(run_schema(schema, instance, opts))
+}
+
+fn describe_findings(
+    findings: Findings,
+    content: &str,
+    opts: &SchemaValidationOptions,
+    index: Option<&LineIndex>,
+) -> SchemaOutcome {
+    if findings.errors.is_empty() && findings.deprecated.is_empty() {
+        return SchemaOutcome::success();
+    }
+
+    let resolver = if opts.collect_positions {
+        profile::time(Phase::Lex, || JsonSpanResolver::new(content).ok())
+    } else {
+        None
+    };
+    let owned;
+    let index = match (index, &resolver) {
+        (Some(index), _) => Some(index),
+        (None, Some(_)) => {
+            owned = LineIndex::new(content);
+            Some(&owned)
+        }
+        (None, None) => None,
+    };
+    let span_of = |pointer: &str| resolve_pointer_span(resolver.as_ref()?, pointer);
+    let positions = index
+        .filter(|_| resolver.is_some())
+        .map(|index| (&span_of as &dyn Fn(&str) -> Option<Span>, index));
+    describe_positioned(findings, content, opts, positions)
+}
+
+fn describe_positioned(
+    findings: Findings,
+    content: &str,
+    opts: &SchemaValidationOptions,
+    positions: PositionSource,
+) -> SchemaOutcome {
+    let Findings { errors, deprecated } = findings;
+    if errors.is_empty() && deprecated.is_empty() {
+        return SchemaOutcome::success();
+    }
+    let mut collected = Vec::new();
+    for (error, location) in errors {
+        let mut descriptor = descriptor_from_error(error, content, opts, positions);
+        descriptor.absolute_keyword_location = location;
+        collected.push(descriptor);
+    }
+    for hit in deprecated {
+        if collected.len() >= opts.max_errors {
+            break;
+        }
+        collected.push(descriptor_from_deprecated(hit, content, positions));
+    }
+    for descriptor in &mut collected {
+        shorten(&mut descriptor.message, opts.max_message_length);
+    }
+    SchemaOutcome::from_errors(collected)
+}
+
+/// Where errors go: the span of an instance path's value, and the line
+/// index of the content.
+type PositionSource<'a> = Option<(&'a dyn Fn(&str) -> Option<Span>, &'a LineIndex)>;
+
+fn descriptor_from_error(
+    error: ValidationError,
+    content: &str,
+    opts: &SchemaValidationOptions,
+    positions: PositionSource,
+) -> SchemaErrorDescriptor {
+    let instance_path = error.instance_path.to_string();
+    let schema_path = Some(error.schema_path.to_string());
+    let keyword = keyword_from_kind(&error.kind).map(|kw| kw.to_string());
+    let suggested_value = suggest_value(&error.kind, error.instance.as_ref());
+
+    let (line, column, start, end) = positions
+        .and_then(|(span_of, index)| span_of(&instance_path).map(|span| (span, index)))
+        .map(|(span, index)| {
+            let (line, column) = index.line_col_chars(content, span.start);
+            (Some(line), Some(column), Some(span.start), Some(span.end))
+        })
+        .unwrap_or((None, None, None, None));
+
+    let (message, allowed_values) = error_message(&error, opts);
+    SchemaErrorDescriptor {
+        message,
+        severity: Severity::Error,
+        keyword,
+        instance_path_segments: path_segments(&instance_path),
+        instance_path,
+        schema_path,
+        line,
+        column,
+        start,
+        end,
+        suggested_value,
+        allowed_values,
+        absolute_keyword_location: None,
+    }
+}
+
+/// The message for `error`, listing at most `max_message_values` of an
+/// `enum`, with the full list when it leaves values out.
+fn error_message(
+    error: &ValidationError,
+    opts: &SchemaValidationOptions,
+) -> (String, Option<Vec<Value>>) {
+    let options = match (&error.kind, opts.max_message_values) {
+        (ValidationErrorKind::Enum { options }, Some(max)) => options
+            .as_array()
+            .filter(|options| options.len() > max)
+            .map(|options| (options, max)),
+        _ => None,
+    };
+    let Some((options, max)) = options else {
+        return (error.to_string(), None);
+    };
+    let mut listed: Vec<String> = options[..max].iter().map(Value::to_string).collect();
+    listed.push("...".to_string());
+    let message = format!(
+        "{} is not one of [{}] ({} more)",
+        error.instance,
+        listed.join(","),
+        options.len() - max
+    );
+    (message, Some(options.clone()))
+}
+
+/// Path segments for an instance pointer; jsonschema only produces valid
+/// ones.
+fn path_segments(pointer: &str) -> Vec<String> {
+    paths::from_pointer(pointer).unwrap_or_default()
+}
+
+/// `message` cut to `max_length` characters, marked with `…`.
+fn shorten(message: &mut String, max_length: Option<usize>) {
+    if let Some((cut, _)) = max_length.and_then(|max| message.char_indices().nth(max)) {
+        message.truncate(cut);
+        message.push('…');
+    }
+}
+
+/// The outcome for content that does not parse, at `span` when known.
+#[cfg(feature = "env")]
+pub(crate) fn syntax_outcome(
+    message: String,
+    span: Option<Span>,
+    content: &str,
+    opts: &SchemaValidationOptions,
+) -> SchemaOutcome {
+    let span = span.unwrap_or(Span::new(0, 0));
+    let (line, column) = line_index::line_col_at(content, span.start, ColumnUnit::Char);
+    let detail = SyntaxErrorDetail {
+        message,
+        line,
+        column,
+        span,
+    };
+    schema_outcome_from_syntax(detail, opts)
+}
+
+fn schema_outcome_from_syntax(
+    detail: SyntaxErrorDetail,
+    opts: &SchemaValidationOptions,
+) -> SchemaOutcome {
+    let mut descriptor = SchemaErrorDescriptor {
+        message: detail.message,
+        severity: Severity::Error,
+        keyword: Some("syntax".into()),
+        instance_path: String::new(),
+        instance_path_segments: Vec::new(),
+        schema_path: None,
+        line: None,
+        column: None,
+        start: None,
+        end: None,
+        suggested_value: None,
+        allowed_values: None,
+        absolute_keyword_location: None,
+    };
+    if opts.collect_positions {
+        descriptor.line = Some(detail.line);
+        descriptor.column = Some(detail.column);
+        descriptor.start = Some(detail.span.start);
+        descriptor.end = Some(detail.span.end);
+    }
+    SchemaOutcome::from_errors(vec![descriptor])
+}
+
+pub(crate) fn schema_issue_outcome(message: String) -> SchemaOutcome {
+    SchemaOutcome::from_errors(vec![SchemaErrorDescriptor {
+        message,
+        severity: Severity::Error,
+        keyword: Some("schema".into()),
+        instance_path: String::new(),
+        instance_path_segments: Vec::new(),
+        schema_path: None,
+        line: None,
+        column: None,
+        start: None,
+        end: None,
+        suggested_value: None,
+        allowed_values: None,
+        absolute_keyword_location: None,
+    }])
+}
+
+#[allow(unreachable_patterns)]
+fn keyword_from_kind(kind: &ValidationErrorKind) -> Option<&'static str> {
+    use ValidationErrorKind::*;
+    match kind {
+        AdditionalItems { .. } => Some("additionalItems"),
+        AdditionalProperties { .. } => Some("additionalProperties"),
+        AnyOf => Some("anyOf"),
+        BacktrackLimitExceeded { .. } => Some("format"),
+        Constant { .. } => Some("const"),
+        Contains => Some("contains"),
+        ContentEncoding { .. } => Some("contentEncoding"),
+        ContentMediaType { .. } => Some("contentMediaType"),
+        Enum { .. } => Some("enum"),
+        ExclusiveMaximum { .. } => Some("exclusiveMaximum"),
+        ExclusiveMinimum { .. } => Some("exclusiveMinimum"),
+        FalseSchema => Some("false"),
+        FileNotFound { .. } => Some("$ref"),
+        Format { .. } => Some("format"),
+        FromUtf8 { .. } => Some("contentEncoding"),
+        Utf8 { .. } => Some("contentEncoding"),
+        JSONParse { .. } => Some("$ref"),
+        InvalidReference { .. } => Some("$ref"),
+        InvalidURL { .. } => Some("$ref"),
+        MaxItems { .. } => Some("maxItems"),
+        Maximum { .. } => Some("maximum"),
+        MaxLength { .. } => Some("maxLength"),
+        MaxProperties { .. } => Some("maxProperties"),
+        MinItems { .. } => Some("minItems"),
+        Minimum { .. } => Some("minimum"),
+        MinLength { .. } => Some("minLength"),
+        MinProperties { .. } => Some("minProperties"),
+        MultipleOf { .. } => Some("multipleOf"),
+        Not { .. } => Some("not"),
+        OneOfMultipleValid | OneOfNotValid => Some("oneOf"),
+        Pattern { .. } => Some("pattern"),
+        PropertyNames { .. } => Some("propertyNames"),
+        Required { .. } => Some("required"),
+        Schema => Some("$schema"),
+        Type { .. } => Some("type"),
+        UnevaluatedProperties { .. } => Some("unevaluatedProperties"),
+        UniqueItems => Some("uniqueItems"),
+        UnknownReferenceScheme { .. } => Some("$ref"),
+        Resolver { .. } => Some("$ref"),
+        _ => None,
+    }
+}
+
+fn resolve_pointer_span(resolver: &JsonSpanResolver, pointer: &str) -> Option<Span> {
+    for candidate in pointer_candidates(pointer) {
+        if let Ok(span) = resolver.span_for_pointer(&candidate) {
+            return Some(span);
+        }
+    }
+    None
+}
+
+fn pointer_candidates(pointer: &str) -> Vec<String> {
+    if pointer.is_empty() {
+        return vec![String::new()];
+    }
+    let mut current = pointer.to_string();
+    let mut out = Vec::new();
+    loop {
+        out.push(current.clone());
+        if current.is_empty() {
+            break;
+        }
+        if let Some(idx) = current.rfind('/') {
+            if idx == 0 {
+                current.clear();
+            } else {
+                current.truncate(idx);
+            }
+        } else {
+            current.clear();
+        }
+        if current.is_empty() {
+            out.push(String::new());
+            break;
+        }
+    }
+    out
+}
+
+pub(crate) fn parse_draft_label(raw: &str) -> Option<Draft> {
+    let normalized = raw.trim().to_ascii_lowercase();
+    match normalized.as_str() {
+        "draft4" | "draft-4" | "4" => Some(Draft::Draft4),
+        "draft6" | "draft-6" | "6" => Some(Draft::Draft6),
+        "draft7" | "draft-7" | "7" => Some(Draft::Draft7),
+        "2019-09" | "draft2019-09" | "2019" | "201909" => Some(Draft::Draft201909),
+        "2020-12" | "draft2020-12" | "2020" | "202012" => Some(Draft::Draft202012),
+        _ => None,
+    }
+}
+
+/// The schema registered as `id`, or the (sub)schema a registered schema
+/// declares as `id` with `$id` or `$anchor`.
+pub(crate) fn get_cached_schema(id: &str) -> Option<Arc<RegisteredSchema>> {
+    registered_with(id, &Toggles::default())
+}
+
+/// [`get_cached_schema`] compiled with the format and vocabulary settings of
+/// `opts`.
+pub(crate) fn registered_for(
+    id: &str,
+    opts: &SchemaValidationOptions,
+) -> Option<Arc<RegisteredSchema>> {
+    registered_with(id, &Toggles::of(opts))
+}
+
+fn registered_with(id: &str, toggles: &Toggles) -> Option<Arc<RegisteredSchema>> {
+    let key = (id.to_string(), toggles.clone());
+    let (pointer, target, root) = {
+        let cache = SCHEMA_CACHE.lock().ok()?;
+        if let Some(schema) = cache.variants.get(&key) {
+            return Some(Arc::clone(schema));
+        }
+        match cache.schemas.get(id) {
+            Some(root) => (String::new(), None, Arc::clone(root)),
+            None => {
+                let entry = cache.declared.get(id)?;
+                let root = Arc::clone(cache.schemas.get(&entry.schema_id)?);
+                let declared = &entry.declared;
+                (
+                    declared.pointer.clone(),
+                    Some(declared.target.clone()),
+                    root,
+                )
+            }
+        }
+    };
+    if pointer.is_empty() && *toggles == Toggles::default() {
+        return Some(root);
+    }
+    let (compiled, reference) = match target.filter(|_| !pointer.is_empty()) {
+        None => (compile_schema(&root.value, root.draft, toggles).ok()?, None),
+        Some(target) => {
+            // A subschema compiles as a reference into its registered
+            // document, so its own `$ref`s still resolve against that
+            // document.
+            let mut wrapper = serde_json::Map::new();
+            if let Some(dialect) = root.value.get("$schema") {
+                wrapper.insert("$schema".into(), dialect.clone());
+            }
+            wrapper.insert("$ref".into(), Value::String(target.clone()));
+            let compiled = compile_schema(&Value::Object(wrapper), root.draft, toggles).ok()?;
+            (compiled, Some(target))
+        }
+    };
+    let schema = Arc::new(RegisteredSchema {
+        value: root.value.pointer(&pointer)?.clone(),
+        compiled,
+        draft: root.draft,
+        reference,
+        source: root.source.clone().filter(|_| pointer.is_empty()),
+    });
+    let mut cache = SCHEMA_CACHE.lock().ok()?;
+    cache.variants.insert(key, Arc::clone(&schema));
+    Some(schema)
+}
+
+/// The reference that resolves to what a registered schema declares as
+/// `uri`, for anchors.
+pub(crate) fn declared_target(uri: &str) -> Option<String> {
+    let cache = SCHEMA_CACHE.lock().ok()?;
+    Some(cache.declared.get(uri)?.declared.target.clone())
+}
+
+/// The subschema a registered schema declares as the resource `uri`, for
+/// [`schema_ids::Registry`].
+pub(crate) fn declared_subschema(uri: &str) -> Option<Value> {
+    let cache = SCHEMA_CACHE.lock().ok()?;
+    let entry = cache.declared.get(uri)?;
+    let root = cache.schemas.get(&entry.schema_id)?;
+    root.value.pointer(&entry.declared.pointer).cloned()
+}
+
+// ───── Deprecation annotations ─────
+
+#[derive(Debug, Clone)]
+struct DeprecatedHit {
+    instance_path: String,
+    schema_path: String,
+    description: Option<String>,
+}
+
+/// Walks the schema alongside the instance and reports every present property
+/// whose (sub)schema carries `deprecated: true`.
+fn collect_deprecated(schema: &Value, instance: &Value) -> Vec<DeprecatedHit> {
+    let mut hits = Vec::new();
+    walk_deprecated(
+        schema,
+        schema,
+        instance,
+        String::new(),
+        String::new(),
+        &mut hits,
+        0,
+    );
+    hits.sort_by(|a, b| a.instance_path.cmp(&b.instance_path));
+    hits.dedup_by(|a, b| a.instance_path == b.instance_path);
+    hits
+}
+
+fn walk_deprecated(
+    root: &Value,
+    schema: &Value,
+    instance: &Value,
+    instance_path: String,
+    schema_path: String,
+    hits: &mut Vec<DeprecatedHit>,
+    depth: usize,
+) {
+    if depth > MAX_ANNOTATION_DEPTH {
+        return;
+    }
+    let Some(obj) = schema.as_object() else {
+        return;
+    };
+
+    if let Some(target) = obj.get("$ref").and_then(Value::as_str) {
+        if let Some(resolved) = resolve_local_ref(root, target) {
+            walk_deprecated(
+                root,
+                resolved,
+                instance,
+                instance_path.clone(),
+                format!("{schema_path}/$ref"),
+                hits,
+                depth + 1,
+            );
+        }
+    }
+
+    if let Some(branches) = obj.get("allOf").and_then(Value::as_array) {
+        for (idx, branch) in branches.iter().enumerate() {
+            walk_deprecated(
+                root,
+                branch,
+                instance,
+                instance_path.clone(),
+                format!("{schema_path}/allOf/{idx}"),
+                hits,
+                depth + 1,
+            );
+        }
+    }
+
+    match instance {
+        Value::Object(members) => {
+            let Some(props) = obj.get("properties").and_then(Value::as_object) else {
+                return;
+            };
+            for (key, sub) in props {
+                let Some(child) = members.get(key) else {
+                    continue;
+                };
+                let child_instance = format!("{instance_path}/{}", escape_pointer_segment(key));
+                let child_schema =
+                    format!("{schema_path}/properties/{}", escape_pointer_segment(key));
+                let effective = deref_schema(root, sub);
+                if effective.get("deprecated").and_then(Value::as_bool) == Some(true) {
+                    hits.push(DeprecatedHit {
+                        instance_path: child_instance.clone(),
+                        schema_path: format!("{child_schema}/deprecated"),
+                        description: effective
+                            .get("description")
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                    });
+                }
+                walk_deprecated(
+                    root,
+                    sub,
+                    child,
+                    child_instance,
+                    child_schema,
+                    hits,
+                    depth + 1,
+                );
+            }
+        }
+        Value::Array(items) => {
+            let prefix = obj
+                .get("prefixItems")
+                .or_else(|| obj.get("items").filter(|v| v.is_array()))
+                .and_then(Value::as_array);
+            let keyword = if obj.contains_key("prefixItems") {
+                "prefixItems"
+            } else {
+                "items"
+            };
+            let rest = obj.get("items").filter(|v| v.is_object());
+            for (idx, item) in items.iter().enumerate() {
+                let (sub, sub_path) = match prefix.and_then(|p| p.get(idx)) {
+                    Some(sub) => (sub, format!("{schema_path}/{keyword}/{idx}")),
+                    None => match rest {
+                        Some(sub) => (sub, format!("{schema_path}/items")),
+                        None => continue,
+                    },
+                };
+                walk_deprecated(
+                    root,
+                    sub,
+                    item,
+                    format!("{instance_path}/{idx}"),
+                    sub_path,
+                    hits,
+                    depth + 1,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Follows a chain of local `$ref`s so annotations declared on the target are
+/// visible at the referencing location.
+fn deref_schema<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    let mut current = schema;
+    for _ in 0..MAX_ANNOTATION_DEPTH {
+        let has_annotation = current.get("deprecated").is_some();
+        match current.get("$ref").and_then(Value::as_str) {
+            Some(target) if !has_annotation => match resolve_local_ref(root, target) {
+                Some(next) => current = next,
+                None => break,
+            },
+            _ => break,
+        }
+    }
+    current
+}
+
+/// Subschema nesting [`in_place_subschemas`] follows.
+const MAX_IN_PLACE_DEPTH: usize = 32;
+
+/// `schemas` with the subschemas that apply in place: local `$ref`s and
+/// `allOf` / `anyOf` / `oneOf` branches.
+pub(crate) fn in_place_subschemas<'a>(root: &'a Value, schemas: &[&'a Value]) -> Vec<&'a Value> {
+    let mut out = Vec::new();
+    let mut pending: Vec<(&Value, usize)> = schemas.iter().map(|s| (*s, 0)).collect();
+    while let Some((schema, depth)) = pending.pop() {
+        if depth > MAX_IN_PLACE_DEPTH {
+            continue;
+        }
+        let target = schema.get("$ref").and_then(Value::as_str);
+        let resolved = target
+            .and_then(|t| t.strip_prefix('#'))
+            .and_then(|pointer| root.pointer(pointer));
+        pending.extend(resolved.map(|s| (s, depth + 1)));
+        for keyword in ["allOf", "anyOf", "oneOf"] {
+            let branches = schema.get(keyword).and_then(Value::as_array);
+            pending.extend(branches.into_iter().flatten().map(|b| (b, depth + 1)));
+        }
+        out.push(schema);
+    }
+    out
+}
+
+fn resolve_local_ref<'a>(root: &'a Value, target: &str) -> Option<&'a Value> {
+    let pointer = target.strip_prefix('#')?;
+    if pointer.is_empty() {
+        return Some(root);
+    }
+    root.pointer(pointer)
+}
+
+fn escape_pointer_segment(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+fn descriptor_from_deprecated(
+    hit: DeprecatedHit,
+    content: &str,
+    positions: PositionSource,
+) -> SchemaErrorDescriptor {
+    let (line, column, start, end) = positions
+        .and_then(|(span_of, index)| span_of(&hit.instance_path).map(|span| (span, index)))
+        .map(|(span, index)| {
+            let (line, column) = index.line_col_chars(content, span.start);
+            (Some(line), Some(column), Some(span.start), Some(span.end))
+        })
+        .unwrap_or((None, None, None, None));
+    let message = hit
+        .description
+        .unwrap_or_else(|| format!("Property '{}' is deprecated", hit.instance_path));
+
+    SchemaErrorDescriptor {
+        message,
+        severity: Severity::Warning,
+        keyword: Some("deprecated".into()),
+        instance_path_segments: path_segments(&hit.instance_path),
+        instance_path: hit.instance_path,
+        schema_path: Some(hit.schema_path),
+        line,
+        column,
+        start,
+        end,
+        suggested_value: None,
+        allowed_values: None,
+        absolute_keyword_location: None,
+    }
+}
+
+// ───── Fix suggestions ─────
+
+/// Proposes a replacement for `enum`, `const` and `type` violations so the UI
+/// can offer a one-click "change to X" action.
+fn suggest_value(kind: &ValidationErrorKind, instance: &Value) -> Option<Value> {
+    match kind {
+        ValidationErrorKind::Constant { expected_value } => Some(expected_value.clone()),
+        ValidationErrorKind::Enum { options } => {
+            let options = options.as_array()?;
+            if let Some(hit) = options
+                .iter()
+                .find(|opt| coerce_losslessly(instance, opt).as_ref() == Some(*opt))
+            {
+                return Some(hit.clone());
+            }
+            closest_enum_option(instance, options).cloned()
+        }
+        ValidationErrorKind::Type { kind } => {
+            let allowed: Vec<PrimitiveType> = match kind {
+                TypeKind::Single(ty) => vec![*ty],
+                TypeKind::Multiple(types) => types.into_iter().collect(),
+            };
+            allowed
+                .into_iter()
+                .find_map(|ty| coerce_to_type(instance, ty))
+        }
+        _ => None,
+    }
+}
+
+fn closest_enum_option<'a>(instance: &Value, options: &'a [Value]) -> Option<&'a Value> {
+    let needle = match instance {
+        Value::String(s) => s.to_lowercase(),
+        other => other.to_string().to_lowercase(),
+    };
+    options
+        .iter()
+        .filter_map(|opt| match opt {
+            Value::String(s) => Some((opt, edit_distance(&needle, &s.to_lowercase()))),
+            _ => None,
+        })
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(opt, _)| opt)
+        .or_else(|| options.first())
+}
+
+/// Converts `instance` to the type of `target` when no information is lost,
+/// e.g. `"8080"` → `8080` or `"true"` → `true`.
+fn coerce_losslessly(instance: &Value, target: &Value) -> Option<Value> {
+    let ty = match target {
+        Value::Null => PrimitiveType::Null,
+        Value::Bool(_) => PrimitiveType::Boolean,
+        Value::Number(n) if n.is_i64() || n.is_u64() => PrimitiveType::Integer,
+        Value::Number(_) => PrimitiveType::Number,
+        Value::String(_) => PrimitiveType::String,
+        Value::Array(_) => PrimitiveType::Array,
+        Value::Object(_) => PrimitiveType::Object,
+    };
+    coerce_to_type(instance, ty)
+}
+
+fn coerce_to_type(instance: &Value, ty: PrimitiveType) -> Option<Value> {
+    match (instance, ty) {
+        (Value::String(s), PrimitiveType::Integer) => {
+            let parsed: i64 = s.trim().parse().ok()?;
+            (parsed.to_string() == s.trim()).then(|| Value::from(parsed))
+        }
+        (Value::String(s), PrimitiveType::Number) => {
+            let parsed: serde_json::Number = serde_json::from_str(s.trim()).ok()?;
+            (parsed.to_string() == s.trim()).then_some(Value::Number(parsed))
+        }
+        (Value::String(s), PrimitiveType::Boolean) => match s.trim() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        (Value::String(s), PrimitiveType::Null) => (s.trim() == "null").then_some(Value::Null),
+        (Value::Number(n), PrimitiveType::String) => Some(Value::String(n.to_string())),
+        (Value::Bool(b), PrimitiveType::String) => Some(Value::String(b.to_string())),
+        (Value::Number(n), PrimitiveType::Integer) => {
+            let f = n.as_f64()?;
+            (f.fract() == 0.0 && f.abs() < i64::MAX as f64).then(|| Value::from(f as i64))
+        }
+        _ => None,
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b_chars.len()).collect();
+    let mut curr = vec![0; b_chars.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b_chars.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            curr[j + 1] = (prev[j + 1] + 1).min(curr[j] + 1).min(prev[j] + cost);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b_chars.len()]
+}
@@ -0,0 +1,302 @@
+//! Property reference generated from a registered schema.
+//!
+//! One entry per property, depth first: the root's properties, then each
+//! property's own properties and array items before its next sibling. A
+//! property's facts come from its schema together with the schemas it
+//! pulls in: local `$ref`s and the `allOf`, `anyOf` and `oneOf` branches.
+//! Array items are documented under a `[]` segment, `additionalProperties`
+//! under `*` and each `patternProperties` pattern as `/pattern/`. A `$ref`
+//! back to a schema being documented gets its entry but is not expanded
+//! again.
+//!
+//! Registered schemas are kept as parsed values, which do not remember key
+//! order, so siblings are listed alphabetically.
+
+use serde::Serialize;
+use serde_json::Value;
+use tsify::Tsify;
+
+use crate::paths::join_path;
+
+/// Subschemas followed through `$ref` and combinators for one property.
+const MAX_BRANCH_DEPTH: usize = 32;
+
+/// Keywords listed as constraints, in this order.
+const CONSTRAINTS: &[&str] = &[
+    "const",
+    "enum",
+    "format",
+    "pattern",
+    "minLength",
+    "maxLength",
+    "minimum",
+    "exclusiveMinimum",
+    "maximum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "minProperties",
+    "maxProperties",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Tsify)]
+pub struct PropertyDoc {
+    pub path: Vec<String>,
+    /// From `type`, or implied by `properties`, `items`, `enum` or `const`.
+    pub types: Vec<String>,
+    /// Listed in its parent's `required`.
+    pub required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(optional, type = "unknown")]
+    pub default: Option<Value>,
+    /// `description`, or `title` when there is none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub description: Option<String>,
+    /// `keyword: value` for each validation keyword, value as JSON.
+    pub constraints: Vec<String>,
+    pub deprecated: bool,
+}
+
+pub(crate) fn document(root: &Value) -> Vec<PropertyDoc> {
+    let mut docs = Docs {
+        root,
+        open: Vec::new(),
+        out: Vec::new(),
+    };
+    let branches = docs.branches(root);
+    docs.open
+        .extend(branches.iter().map(|b| *b as *const Value));
+    docs.children(&branches, &mut Vec::new());
+    docs.out
+}
+
+struct Docs<'a> {
+    root: &'a Value,
+    /// Schemas being documented on the current path, by address.
+    open: Vec<*const Value>,
+    out: Vec<PropertyDoc>,
+}
+
+impl<'a> Docs<'a> {
+    /// `schema` after its `$ref`s, with every `allOf` / `anyOf` / `oneOf`
+    /// branch it pulls in.
+    fn branches(&self, schema: &'a Value) -> Vec<&'a Value> {
+        let mut out = Vec::new();
+        let mut pending = vec![(schema, 0)];
+        while let Some((schema, depth)) = pending.pop() {
+            if depth > MAX_BRANCH_DEPTH || !schema.is_object() {
+                continue;
+            }
+            if let Some(target) = schema.get("$ref").and_then(Value::as_str) {
+                if let Some(next) = self.resolve(target) {
+                    pending.push((next, depth + 1));
+                }
+            }
+            for keyword in ["oneOf", "anyOf", "allOf"] {
+                let branches = schema.get(keyword).and_then(Value::as_array);
+                for branch in branches.into_iter().flatten().rev() {
+                    pending.push((branch, depth + 1));
+                }
+            }
+            out.push(schema);
+        }
+        out
+    }
+
+    fn resolve(&self, target: &str) -> Option<&'a Value> {
+        let pointer = target.strip_prefix('#')?;
+        if pointer.is_empty() {
+            Some(self.root)
+        } else {
+            self.root.pointer(pointer)
+        }
+    }
+
+    /// Entries for what sits under the schema made of `branches`.
+    fn children(&mut self, branches: &[&'a Value], path: &mut Vec<String>) {
+        let required: Vec<&str> = branches
+            .iter()
+            .filter_map(|b| b.get("required")?.as_array())
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        let mut properties: Vec<(&'a String, Vec<&'a Value>)> = Vec::new();
+        for branch in branches {
+            for (name, schema) in branch
+                .get("properties")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+            {
+                match properties.iter_mut().find(|(n, _)| *n == name) {
+                    Some((_, schemas)) => schemas.push(schema),
+                    None => properties.push((name, vec![schema])),
+                }
+            }
+        }
+        properties.sort_by_key(|(name, _)| *name);
+        for (name, schemas) in properties {
+            path.push(name.clone());
+            self.entry(&schemas, required.contains(&name.as_str()), path);
+            path.pop();
+        }
+        for branch in branches {
+            let patterns = branch.get("patternProperties").and_then(Value::as_object);
+            for (pattern, schema) in patterns.into_iter().flatten() {
+                path.push(format!("/{pattern}/"));
+                self.entry(&[schema], false, path);
+                path.pop();
+            }
+        }
+        let nested = [("additionalProperties", "*"), ("items", "[]")];
+        for (keyword, segment) in nested {
+            let schemas: Vec<&Value> = branches
+                .iter()
+                .filter_map(|b| b.get(keyword))
+                .filter(|schema| schema.as_object().is_some_and(|s| !s.is_empty()))
+                .collect();
+            if !schemas.is_empty() {
+                path.push(segment.into());
+                self.entry(&schemas, false, path);
+                path.pop();
+            }
+        }
+    }
+
+    fn entry(&mut self, schemas: &[&'a Value], required: bool, path: &mut Vec<String>) {
+        let mut branches: Vec<&Value> = Vec::new();
+        for schema in schemas {
+            for branch in self.branches(schema) {
+                if !branches.iter().any(|b| std::ptr::eq(*b, branch)) {
+                    branches.push(branch);
+                }
+            }
+        }
+        let first = |keyword: &str| branches.iter().find_map(|b| b.get(keyword));
+        let description = first("description")
+            .or_else(|| first("title"))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let mut constraints = Vec::new();
+        for keyword in CONSTRAINTS {
+            for branch in &branches {
+                if let Some(value) = branch.get(*keyword) {
+                    let line = format!("{keyword}: {value}");
+                    if !constraints.contains(&line) {
+                        constraints.push(line);
+                    }
+                }
+            }
+        }
+        self.out.push(PropertyDoc {
+            path: path.clone(),
+            types: types(&branches),
+            required,
+            default: first("default").cloned(),
+            description,
+            constraints,
+            deprecated: branches
+                .iter()
+                .any(|b| b.get("deprecated") == Some(&Value::Bool(true))),
+        });
+        // recursion stops at a schema already open on this path
+        let targets: Vec<*const Value> = branches.iter().map(|b| *b as *const Value).collect();
+        if targets.iter().any(|t| self.open.contains(t)) {
+            return;
+        }
+        let depth = self.open.len();
+        self.open.extend(targets);
+        self.children(&branches, path);
+        self.open.truncate(depth);
+    }
+}
+
+fn types(branches: &[&Value]) -> Vec<String> {
+    fn add(out: &mut Vec<String>, ty: &str) {
+        if !out.iter().any(|t| t == ty) {
+            out.push(ty.to_string());
+        }
+    }
+    let mut out = Vec::new();
+    for branch in branches {
+        match branch.get("type") {
+            Some(Value::String(ty)) => add(&mut out, ty),
+            Some(Value::Array(types)) => {
+                for ty in types.iter().filter_map(Value::as_str) {
+                    add(&mut out, ty);
+                }
+            }
+            _ => {}
+        }
+    }
+    if !out.is_empty() {
+        return out;
+    }
+    for branch in branches {
+        if branch.get("properties").is_some() {
+            add(&mut out, "object");
+        }
+        if branch.get("items").is_some() {
+            add(&mut out, "array");
+        }
+        let values = branch
+            .get("enum")
+            .and_then(Value::as_array)
+            .map(|values| values.iter().collect::<Vec<_>>())
+            .or_else(|| branch.get("const").map(|value| vec![value]))
+            .unwrap_or_default();
+        for value in values {
+            let ty = match value {
+                Value::Null => "null",
+                Value::Bool(_) => "boolean",
+                Value::Number(n) if n.is_f64() => "number",
+                Value::Number(_) => "integer",
+                Value::String(_) => "string",
+                Value::Array(_) => "array",
+                Value::Object(_) => "object",
+            };
+            add(&mut out, ty);
+        }
+    }
+    out
+}
+
+/// The entries as a Markdown table.
+pub(crate) fn markdown(docs: &[PropertyDoc]) -> String {
+    let cell = |text: &str| text.replace('|', "\\|").replace(['\r', '\n'], " ");
+    let mut out = String::from(
+        "| Path | Type | Required | Default | Description | Constraints |\n\
+         | --- | --- | --- | --- | --- | --- |\n",
+    );
+    for doc in docs {
+        let default = doc
+            .default
+            .as_ref()
+            .map(|value| format!("`{}`", cell(&value.to_string())))
+            .unwrap_or_default();
+        let mut description = cell(doc.description.as_deref().unwrap_or(""));
+        if doc.deprecated {
+            description = format!("**Deprecated.** {description}")
+                .trim_end()
+                .to_string();
+        }
+        let constraints: Vec<String> = doc
+            .constraints
+            .iter()
+            .map(|c| format!("`{}`", cell(c)))
+            .collect();
+        out.push_str(&format!(
+            "| `{}` | {} | {} | {} | {} | {} |\n",
+            cell(&join_path(&doc.path)),
+            cell(&doc.types.join(" | ")),
+            if doc.required { "yes" } else { "no" },
+            default,
+            description,
+            constraints.join(", "),
+        ));
+    }
+    out
+}
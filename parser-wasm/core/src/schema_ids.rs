@@ -0,0 +1,382 @@
+//! Identifiers schemas declare for themselves.
+//!
+//! A schema names itself and its subschemas with `$id`, and marks
+//! subschemas with `$anchor` (or, before 2019-09, an `$id` that is only a
+//! fragment). jsonschema 0.17 resolves `$id`s inside the document it
+//! compiles, but has no `$anchor` support and would fetch any other document
+//! over the network. So registered schemas are indexed by what they declare:
+//! the identifiers work as schema ids, [`Registry`] serves `$ref`s to other
+//! registered schemas, and `$ref`s to an anchor are rewritten to a JSON
+//! pointer fragment before compiling.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use jsonschema::{SchemaResolver, SchemaResolverError};
+use serde_json::Value;
+use url::Url;
+
+use crate::schema_vocabulary::{self, Vocabulary};
+
+/// Base of schemas without a root `$id`, the one jsonschema uses. Identifiers
+/// relative to it have no absolute form and are not indexed.
+const DEFAULT_BASE: &str = "json-schema:///";
+
+/// Deepest subschema the walks visit.
+const MAX_ID_DEPTH: usize = 64;
+
+/// An identifier declared inside a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Declared {
+    /// The identifier resolved against its base: a resource URI for `$id`,
+    /// `<resource>#<name>` for an anchor.
+    pub(crate) uri: String,
+    /// JSON pointer to the subschema from the schema root.
+    pub(crate) pointer: String,
+    /// A reference jsonschema resolves to the same subschema: the enclosing
+    /// resource, with a JSON pointer fragment for an anchor.
+    pub(crate) target: String,
+}
+
+impl Declared {
+    pub(crate) fn is_anchor(&self) -> bool {
+        self.uri.contains('#')
+    }
+
+    /// Absolute identifiers, the ones usable outside the schema.
+    pub(crate) fn is_absolute(&self) -> bool {
+        !self.uri.starts_with(DEFAULT_BASE)
+    }
+}
+
+/// Every `$id` and `$anchor` in `schema`, in document order.
+pub(crate) fn declared(schema: &Value) -> Vec<Declared> {
+    let mut found = Vec::new();
+    let base = Url::parse(DEFAULT_BASE).expect("default base is a valid URL");
+    let walk = Walk {
+        resource: base,
+        pointer: String::new(),
+        relative: String::new(),
+    };
+    collect(schema, &walk, &mut found, 0);
+    found
+}
+
+/// Position of the walk: the enclosing resource, and the current subschema
+/// from the schema root and from that resource.
+#[derive(Clone)]
+struct Walk {
+    resource: Url,
+    pointer: String,
+    relative: String,
+}
+
+impl Walk {
+    /// The walk as seen from `node`, with the identifiers `node` declares.
+    fn enter(&self, node: &Value, found: &mut Vec<Declared>) -> Walk {
+        let mut walk = self.clone();
+        let Value::Object(object) = node else {
+            return walk;
+        };
+        if let Some(id) = object.get("$id").and_then(Value::as_str) {
+            if let Ok(mut url) = walk.resource.join(id) {
+                match url.fragment().map(str::to_string) {
+                    Some(name) if !name.is_empty() => found.push(walk.anchor(&name)),
+                    _ => {
+                        url.set_fragment(None);
+                        walk.resource = url;
+                        walk.relative.clear();
+                        found.push(Declared {
+                            uri: walk.resource.to_string(),
+                            pointer: walk.pointer.clone(),
+                            target: walk.resource.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        if let Some(name) = object.get("$anchor").and_then(Value::as_str) {
+            found.push(walk.anchor(name));
+        }
+        walk
+    }
+
+    fn anchor(&self, name: &str) -> Declared {
+        let mut uri = self.resource.clone();
+        uri.set_fragment(Some(name));
+        let mut target = self.resource.clone();
+        target.set_fragment(Some(&self.relative));
+        Declared {
+            uri: uri.to_string(),
+            pointer: self.pointer.clone(),
+            target: target.to_string(),
+        }
+    }
+
+    fn child(&self, segment: &str) -> Walk {
+        let segment = segment.replace('~', "~0").replace('/', "~1");
+        Walk {
+            resource: self.resource.clone(),
+            pointer: format!("{}/{segment}", self.pointer),
+            relative: format!("{}/{segment}", self.relative),
+        }
+    }
+}
+
+/// Subschemas of `node` with their keys. `enum` and `const` hold data, not
+/// schemas, and are skipped as jsonschema skips them.
+fn children(node: &Value) -> Vec<(String, &Value)> {
+    match node {
+        Value::Object(object) => object
+            .iter()
+            .filter(|(key, _)| *key != "enum" && *key != "const")
+            .map(|(key, value)| (key.clone(), value))
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (i.to_string(), item))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn collect(node: &Value, walk: &Walk, found: &mut Vec<Declared>, depth: usize) {
+    if depth > MAX_ID_DEPTH {
+        return;
+    }
+    let here = walk.enter(node, found);
+    for (key, child) in children(node) {
+        collect(child, &here.child(&key), found, depth + 1);
+    }
+}
+
+/// `schema` with every `$ref` that names one of `anchors` (identifier to
+/// target) replaced by the target, or `None` when no `$ref` does.
+pub(crate) fn rewrite_anchor_refs(
+    schema: &Value,
+    anchors: &HashMap<String, String>,
+) -> Option<Value> {
+    if anchors.is_empty() {
+        return None;
+    }
+    let mut rewritten = schema.clone();
+    let base = Url::parse(DEFAULT_BASE).expect("default base is a valid URL");
+    let mut changed = false;
+    rewrite(&mut rewritten, &base, anchors, &mut changed, 0);
+    changed.then_some(rewritten)
+}
+
+fn rewrite(
+    node: &mut Value,
+    base: &Url,
+    anchors: &HashMap<String, String>,
+    changed: &mut bool,
+    depth: usize,
+) {
+    if depth > MAX_ID_DEPTH {
+        return;
+    }
+    let mut base = base.clone();
+    match node {
+        Value::Object(object) => {
+            if let Some(id) = object.get("$id").and_then(Value::as_str) {
+                if let Ok(mut url) = base.join(id) {
+                    url.set_fragment(None);
+                    base = url;
+                }
+            }
+            if let Some(Value::String(reference)) = object.get_mut("$ref") {
+                let target = base
+                    .join(reference)
+                    .ok()
+                    .and_then(|url| anchors.get(url.as_str()));
+                if let Some(target) = target {
+                    *reference = target.clone();
+                    *changed = true;
+                }
+            }
+            for (key, child) in object.iter_mut() {
+                if key != "enum" && key != "const" {
+                    rewrite(child, &base, anchors, changed, depth + 1);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                rewrite(item, &base, anchors, changed, depth + 1);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Resolves `$ref`s to other documents against the registered schemas,
+/// by the identifiers they declare, instead of fetching them.
+pub(crate) struct Registry {
+    /// Vocabularies stripped from the resolved documents too.
+    pub(crate) disabled: Vec<Vocabulary>,
+}
+
+impl SchemaResolver for Registry {
+    fn resolve(
+        &self,
+        _root_schema: &Value,
+        url: &Url,
+        _original_reference: &str,
+    ) -> Result<Arc<Value>, SchemaResolverError> {
+        let mut resource = url.clone();
+        resource.set_fragment(None);
+        let schema = crate::schema::declared_subschema(resource.as_str())
+            .ok_or_else(|| Unregistered(resource.to_string()))?;
+        let schema = schema_vocabulary::without(&schema, &self.disabled).unwrap_or(schema);
+        Ok(Arc::new(schema))
+    }
+}
+
+#[derive(Debug)]
+struct Unregistered(String);
+
+impl fmt::Display for Unregistered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no registered schema declares '{}'", self.0)
+    }
+}
+
+impl std::error::Error for Unregistered {}
+
+/// `<resource>#<pointer>` of the keyword at `schema_path`, a keyword
+/// location from `root` as jsonschema reports it, or `None` when the keyword
+/// does not sit in a resource with an absolute URI. jsonschema leaves `$ref`
+/// out of the path, so a segment the current subschema lacks is looked up
+/// through its `$ref`.
+pub(crate) fn absolute_location(root: &Value, schema_path: &str) -> Option<String> {
+    let local = declared(root);
+    let mut at = Location {
+        doc: Cow::Borrowed(root),
+        pointer: String::new(),
+        resource: Url::parse(DEFAULT_BASE).ok()?,
+        relative: String::new(),
+    };
+    at.enter_resource();
+    let segments: Vec<String> = match schema_path.strip_prefix('/') {
+        Some(rest) => rest
+            .split('/')
+            .map(|s| s.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+        None => Vec::new(),
+    };
+    for (i, segment) in segments.iter().enumerate() {
+        for _ in 0..MAX_ID_DEPTH {
+            let node = at.node()?;
+            if node.get(segment.as_str()).is_some() || node.is_array() {
+                break;
+            }
+            let reference = node.get("$ref")?.as_str()?;
+            let url = at.resource.join(reference).ok()?;
+            at.jump(url, root, &local)?;
+        }
+        at.descend(segment);
+        if i + 1 < segments.len() {
+            at.enter_resource();
+        }
+    }
+    (at.resource.as_str() != DEFAULT_BASE).then(|| format!("{}#{}", at.resource, at.relative))
+}
+
+/// A subschema being walked to: `pointer` into `doc`, and its place
+/// `relative` to the innermost `resource`.
+struct Location<'a> {
+    doc: Cow<'a, Value>,
+    pointer: String,
+    resource: Url,
+    relative: String,
+}
+
+impl<'a> Location<'a> {
+    fn node(&self) -> Option<&Value> {
+        self.doc.pointer(&self.pointer)
+    }
+
+    fn descend(&mut self, segment: &str) {
+        let segment = segment.replace('~', "~0").replace('/', "~1");
+        self.pointer = format!("{}/{segment}", self.pointer);
+        self.relative = format!("{}/{segment}", self.relative);
+    }
+
+    /// Starts a new resource when the current node has an `$id`.
+    fn enter_resource(&mut self) {
+        let id = self.node().and_then(|node| node.get("$id")?.as_str());
+        let Some(mut url) = id.and_then(|id| self.resource.join(id).ok()) else {
+            return;
+        };
+        if url.fragment().is_some_and(|name| !name.is_empty()) {
+            return;
+        }
+        url.set_fragment(None);
+        self.resource = url;
+        self.relative.clear();
+    }
+
+    /// Moves to what `url` references: in this document when it declares the
+    /// resource, otherwise in the registered schema that does.
+    fn jump(&mut self, url: Url, root: &'a Value, local: &[Declared]) -> Option<()> {
+        let fragment = percent_decode(url.fragment().unwrap_or(""));
+        if !fragment.is_empty() && !fragment.starts_with('/') {
+            let target = match local.iter().find(|d| d.uri == url.as_str()) {
+                Some(declared) => declared.target.clone(),
+                None => crate::schema::declared_target(url.as_str())?,
+            };
+            return self.jump(Url::parse(&target).ok()?, root, local);
+        }
+        let mut resource = url;
+        resource.set_fragment(None);
+        let in_document = local
+            .iter()
+            .find(|d| !d.is_anchor() && d.uri == resource.as_str())
+            .map(|d| d.pointer.clone())
+            .or_else(|| (resource.as_str() == DEFAULT_BASE).then(String::new));
+        match in_document {
+            Some(pointer) => {
+                self.doc = Cow::Borrowed(root);
+                self.pointer = pointer;
+            }
+            None => {
+                self.doc = Cow::Owned(crate::schema::declared_subschema(resource.as_str())?);
+                self.pointer = String::new();
+            }
+        }
+        self.pointer.push_str(&fragment);
+        self.resource = resource;
+        self.relative = fragment;
+        self.enter_resource();
+        Some(())
+    }
+}
+
+/// `%XX` escapes of a URL fragment decoded; invalid ones are kept.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|pair| std::str::from_utf8(pair).ok())
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|pair| u8::from_str_radix(pair, 16).ok());
+        match hex {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
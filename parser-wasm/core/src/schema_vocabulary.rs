@@ -0,0 +1,167 @@
+//! Switching off JSON Schema vocabularies.
+//!
+//! Draft 2020-12 groups keywords into vocabularies a meta-schema can turn
+//! off through `$vocabulary`. jsonschema 0.17 always applies every keyword
+//! it knows, so a disabled vocabulary is emulated by compiling a copy of the
+//! schema without its keywords. The copy is only compiled; the schema value
+//! kept for positions, annotations and `$ref` walks is the original.
+
+use serde_json::Value;
+
+/// A vocabulary that can be disabled, named as in draft 2020-12.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Vocabulary {
+    /// Subschema keywords: `properties`, `items`, `allOf`, `if`, ...
+    Applicator,
+    /// `unevaluatedProperties` and `unevaluatedItems`.
+    Unevaluated,
+    /// Assertions on the instance itself: `type`, `enum`, `minimum`,
+    /// `required`, ...
+    Validation,
+    /// `format`, whether it asserts or only annotates.
+    Format,
+    /// `contentEncoding`, `contentMediaType` and `contentSchema`.
+    Content,
+}
+
+impl Vocabulary {
+    /// Parses a vocabulary name (`"applicator"`, `"unevaluated"`,
+    /// `"validation"`, `"format"`, `"content"`).
+    pub fn parse(label: &str) -> Option<Self> {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "applicator" => Some(Self::Applicator),
+            "unevaluated" => Some(Self::Unevaluated),
+            "validation" => Some(Self::Validation),
+            "format" | "format-annotation" | "format-assertion" => Some(Self::Format),
+            "content" => Some(Self::Content),
+            _ => None,
+        }
+    }
+
+    /// Keywords of the vocabulary, including their draft 4-7 spellings.
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            Self::Applicator => &[
+                "prefixItems",
+                "items",
+                "additionalItems",
+                "contains",
+                "additionalProperties",
+                "properties",
+                "patternProperties",
+                "dependentSchemas",
+                "dependencies",
+                "propertyNames",
+                "if",
+                "then",
+                "else",
+                "allOf",
+                "anyOf",
+                "oneOf",
+                "not",
+            ],
+            Self::Unevaluated => &["unevaluatedItems", "unevaluatedProperties"],
+            Self::Validation => &[
+                "type",
+                "const",
+                "enum",
+                "multipleOf",
+                "maximum",
+                "exclusiveMaximum",
+                "minimum",
+                "exclusiveMinimum",
+                "maxLength",
+                "minLength",
+                "pattern",
+                "maxItems",
+                "minItems",
+                "uniqueItems",
+                "maxContains",
+                "minContains",
+                "maxProperties",
+                "minProperties",
+                "required",
+                "dependentRequired",
+            ],
+            Self::Format => &["format"],
+            Self::Content => &["contentEncoding", "contentMediaType", "contentSchema"],
+        }
+    }
+}
+
+/// Deepest subschema [`without`] visits, as deep as the other schema walks.
+const MAX_VOCABULARY_DEPTH: usize = 64;
+
+/// Keywords whose value is one subschema.
+const SCHEMA_KEYWORDS: &[&str] = &[
+    "additionalItems",
+    "additionalProperties",
+    "contains",
+    "contentSchema",
+    "else",
+    "if",
+    "items",
+    "not",
+    "propertyNames",
+    "then",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+
+/// Keywords whose value is an array of subschemas.
+const SCHEMA_LIST_KEYWORDS: &[&str] = &["allOf", "anyOf", "oneOf", "prefixItems", "items"];
+
+/// Keywords whose value maps names to subschemas.
+const SCHEMA_MAP_KEYWORDS: &[&str] = &[
+    "$defs",
+    "definitions",
+    "dependentSchemas",
+    "dependencies",
+    "patternProperties",
+    "properties",
+];
+
+/// `schema` without the keywords of `disabled` in any subschema, or `None`
+/// when nothing is disabled.
+pub(crate) fn without(schema: &Value, disabled: &[Vocabulary]) -> Option<Value> {
+    if disabled.is_empty() {
+        return None;
+    }
+    let keywords: Vec<&str> = disabled
+        .iter()
+        .flat_map(|vocabulary| vocabulary.keywords())
+        .copied()
+        .collect();
+    let mut stripped = schema.clone();
+    strip(&mut stripped, &keywords, 0);
+    Some(stripped)
+}
+
+fn strip(schema: &mut Value, keywords: &[&str], depth: usize) {
+    if depth > MAX_VOCABULARY_DEPTH {
+        return;
+    }
+    let Value::Object(object) = schema else {
+        return;
+    };
+    object.retain(|key, _| !keywords.contains(&key.as_str()));
+    for (key, value) in object.iter_mut() {
+        let key = key.as_str();
+        match value {
+            Value::Array(items) if SCHEMA_LIST_KEYWORDS.contains(&key) => {
+                for item in items {
+                    strip(item, keywords, depth + 1);
+                }
+            }
+            Value::Object(map) if SCHEMA_MAP_KEYWORDS.contains(&key) => {
+                // `dependencies` mixes subschemas with arrays of names;
+                // `strip` leaves the arrays alone.
+                for subschema in map.values_mut() {
+                    strip(subschema, keywords, depth + 1);
+                }
+            }
+            _ if SCHEMA_KEYWORDS.contains(&key) => strip(value, keywords, depth + 1),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Secret detection in values.
+//!
+//! Built-in patterns only, checked against the string values `template`
+//! collects (as written, so escapes do not hide a key prefix): AWS access
+//! key ids, PEM private key blocks, tokens with a well-known prefix and
+//! long high-entropy strings. Findings locate the secret without repeating
+//! it, so a report can be logged safely.
+
+use serde::Serialize;
+use tsify::Tsify;
+
+use crate::document::Document;
+use crate::template::Site;
+use crate::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+pub enum SecretKind {
+    /// `AKIA…` and the other AWS access key id prefixes.
+    AwsAccessKey,
+    /// A `-----BEGIN … PRIVATE KEY-----` block.
+    PrivateKey,
+    /// A token with a known prefix: GitHub, GitLab, Slack, Stripe, npm,
+    /// Google API keys.
+    Token,
+    /// A long string of random-looking characters.
+    HighEntropy,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct SecretFinding {
+    pub kind: SecretKind,
+    /// The value holding the secret; pass it to `redact`.
+    pub path: Vec<String>,
+    pub line: usize,
+    pub column: usize,
+    /// The secret itself, which may be part of the value.
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+const AWS_PREFIXES: &[&str] = &["AKIA", "ASIA", "ABIA", "ACCA"];
+
+const TOKEN_PREFIXES: &[&str] = &[
+    "ghp_",
+    "gho_",
+    "ghu_",
+    "ghs_",
+    "ghr_",
+    "github_pat_",
+    "glpat-",
+    "xoxb-",
+    "xoxp-",
+    "xoxa-",
+    "xoxr-",
+    "xoxs-",
+    "sk_live_",
+    "rk_live_",
+    "npm_",
+    "AIza",
+];
+
+/// Characters after a token prefix.
+const MIN_TOKEN_BODY: usize = 16;
+
+const MIN_ENTROPY_LEN: usize = 20;
+
+/// Shannon entropy, in bits per character, from which a string of hex
+/// digits or of other characters counts as random.
+const HEX_ENTROPY: f64 = 3.0;
+const TEXT_ENTROPY: f64 = 4.0;
+
+pub(crate) fn scan(doc: &Document, sites: &[Site]) -> Vec<SecretFinding> {
+    let content = doc.content_str();
+    let mut out = Vec::new();
+    for site in sites {
+        let text = &content[site.span.start..site.span.end];
+        for (kind, span) in find(text) {
+            let start = site.span.start + span.start;
+            let (line, column) = doc.position_of(start);
+            out.push(SecretFinding {
+                kind,
+                path: site.path.clone(),
+                line,
+                column,
+                span: Span::new(start, site.span.start + span.end),
+            });
+        }
+    }
+    out
+}
+
+/// Secrets in one value, as byte ranges within `text`, in order.
+fn find(text: &str) -> Vec<(SecretKind, Span)> {
+    let mut found = private_keys(text);
+    let in_key_block = |at: usize, found: &[(SecretKind, Span)]| {
+        found
+            .iter()
+            .any(|(_, span)| span.start <= at && at < span.end)
+    };
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        let word_char = c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+' | '/' | '=');
+        match (start, word_char) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                words.push(Span::new(s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    for span in words {
+        if in_key_block(span.start, &found) {
+            continue;
+        }
+        let word = &text[span.start..span.end];
+        if let Some(kind) = classify(word) {
+            found.push((kind, span));
+        }
+    }
+    found.sort_by_key(|(_, span)| span.start);
+    found
+}
+
+fn classify(word: &str) -> Option<SecretKind> {
+    let aws = word.len() == 20
+        && AWS_PREFIXES.iter().any(|prefix| word.starts_with(prefix))
+        && word
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit());
+    if aws {
+        return Some(SecretKind::AwsAccessKey);
+    }
+    let token = TOKEN_PREFIXES.iter().any(|prefix| {
+        word.strip_prefix(prefix).is_some_and(|body| {
+            body.len() >= MIN_TOKEN_BODY
+                && body
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-'))
+        })
+    });
+    if token {
+        return Some(SecretKind::Token);
+    }
+    let mixed =
+        word.bytes().any(|b| b.is_ascii_digit()) && word.bytes().any(|b| b.is_ascii_alphabetic());
+    if word.len() < MIN_ENTROPY_LEN || !mixed {
+        return None;
+    }
+    let threshold = if word.bytes().all(|b| b.is_ascii_hexdigit()) {
+        HEX_ENTROPY
+    } else {
+        TEXT_ENTROPY
+    };
+    (entropy(word) >= threshold).then_some(SecretKind::HighEntropy)
+}
+
+fn entropy(word: &str) -> f64 {
+    let mut counts = [0usize; 256];
+    for b in word.bytes() {
+        counts[b as usize] += 1;
+    }
+    let len = word.len() as f64;
+    counts
+        .iter()
+        .filter(|&&n| n > 0)
+        .map(|&n| {
+            let p = n as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// `-----BEGIN … PRIVATE KEY-----` to the matching `-----END …-----`, or to
+/// the end of the value when it is cut off.
+fn private_keys(text: &str) -> Vec<(SecretKind, Span)> {
+    const BEGIN: &str = "-----BEGIN ";
+    let mut found = Vec::new();
+    let mut at = 0;
+    while let Some(start) = text[at..].find(BEGIN).map(|i| at + i) {
+        let label_start = start + BEGIN.len();
+        let Some(len) = text[label_start..].find("-----") else {
+            break;
+        };
+        let label = &text[label_start..label_start + len];
+        let after = label_start + len + "-----".len();
+        if !label.ends_with("PRIVATE KEY") {
+            at = after;
+            continue;
+        }
+        let end = text[after..]
+            .find("-----END ")
+            .and_then(|i| {
+                let marker = after + i + "-----END ".len();
+                text[marker..]
+                    .find("-----")
+                    .map(|j| marker + j + "-----".len())
+            })
+            .unwrap_or(text.len());
+        found.push((SecretKind::PrivateKey, Span::new(start, end)));
+        at = end;
+    }
+    found
+}
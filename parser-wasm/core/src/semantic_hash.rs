@@ -0,0 +1,82 @@
+//! Hashes of what a document means rather than how it is written.
+//!
+//! The hash is taken over `convert`'s neutral value: strings decoded,
+//! numbers by value (`1.0`, `1` and `1e0` hash alike), object members and
+//! XML attributes in sorted order. Array items keep their order. The
+//! function is 64-bit FNV-1a over a tagged encoding of the value, fixed so
+//! that a hash stays valid across releases and platforms.
+
+use crate::convert::Value;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// The hash as 16 lowercase hex digits, which JS holds without rounding.
+pub(crate) fn hash(value: &Value) -> String {
+    let mut hasher = Fnv(FNV_OFFSET);
+    hasher.value(value);
+    format!("{:016x}", hasher.0)
+}
+
+struct Fnv(u64);
+
+impl Fnv {
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= u64::from(b);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// Length-prefixed, so adjacent strings cannot run together.
+    fn text(&mut self, text: &str) {
+        self.bytes(&(text.len() as u64).to_le_bytes());
+        self.bytes(text.as_bytes());
+    }
+
+    fn value(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.bytes(b"n"),
+            Value::Bool(true) => self.bytes(b"t"),
+            Value::Bool(false) => self.bytes(b"f"),
+            Value::Number(text) => {
+                self.bytes(b"d");
+                self.text(&canonical_number(text));
+            }
+            Value::String(text) => {
+                self.bytes(b"s");
+                self.text(text);
+            }
+            Value::Array(items) => {
+                self.bytes(b"a");
+                self.bytes(&(items.len() as u64).to_le_bytes());
+                for item in items {
+                    self.value(item);
+                }
+            }
+            Value::Object(members) => {
+                let mut sorted: Vec<_> = members.iter().collect();
+                sorted.sort_by(|(a, _), (b, _)| a.cmp(b));
+                self.bytes(b"o");
+                self.bytes(&(sorted.len() as u64).to_le_bytes());
+                for (key, value) in sorted {
+                    self.text(key);
+                    self.value(value);
+                }
+            }
+        }
+    }
+}
+
+/// One spelling per number, compared the way `diff` compares them:
+/// integers exactly, anything else as a double.
+pub(crate) fn canonical_number(text: &str) -> String {
+    if let Ok(n) = text.parse::<i128>() {
+        return n.to_string();
+    }
+    match text.parse::<f64>() {
+        Ok(n) if n.fract() == 0.0 && n.abs() < 1e38 => (n as i128).to_string(),
+        Ok(n) => format!("{n:e}"),
+        Err(_) => text.to_string(),
+    }
+}
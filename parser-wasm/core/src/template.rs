@@ -0,0 +1,317 @@
+//! Placeholder substitution in values.
+//!
+//! Placeholders (`${NAME}` or `{{ NAME }}`) are looked for in string
+//! values only: JSON strings, XML attribute values and text, and ENV
+//! values. Rendering edits those values in place, escaping each
+//! substitution for where it lands, so every other byte of the document
+//! stays as written.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tsify::Tsify;
+
+#[cfg(any(feature = "xml", feature = "env"))]
+use crate::document::FileType;
+#[cfg(feature = "env")]
+use crate::env_parser::{EnvDocument, Quote};
+use crate::escape_json_string;
+use crate::json_lexer::Kind;
+use crate::json_tree::{JsonNode, JsonValue};
+use crate::paths::escape_segment;
+#[cfg(feature = "xml")]
+use crate::xml_tree::XmlElement;
+use crate::Span;
+
+/// How placeholders are delimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlaceholderSyntax {
+    /// `${NAME}`
+    #[default]
+    Dollar,
+    /// `{{ NAME }}`; spaces inside the braces are optional.
+    Braces,
+}
+
+impl PlaceholderSyntax {
+    /// `"${}"` or `"{{}}"`.
+    pub fn parse(label: &str) -> Option<Self> {
+        match label {
+            "${}" => Some(Self::Dollar),
+            "{{}}" => Some(Self::Braces),
+            _ => None,
+        }
+    }
+
+    fn delimiters(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Dollar => ("${", "}"),
+            Self::Braces => ("{{", "}}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplateOptions {
+    pub syntax: PlaceholderSyntax,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct Placeholder {
+    /// The variable name, spaces around it trimmed.
+    pub name: String,
+    /// The value holding the placeholder; resolves with `find_value_span`.
+    pub path: Vec<String>,
+    /// The placeholder, delimiters included.
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+/// A string value: where placeholders (and secrets) are looked for.
+pub(crate) struct Site {
+    pub(crate) path: Vec<String>,
+    /// The text between any quotes, as written.
+    pub(crate) span: Span,
+    escape: Escape,
+}
+
+impl Site {
+    /// The value as the application reads it: escapes decoded.
+    pub(crate) fn decoded(&self, content: &str) -> String {
+        let raw = &content[self.span.start..self.span.end];
+        match self.escape {
+            Escape::Json => {
+                let quoted = &content[self.span.start - 1..self.span.end + 1];
+                crate::json_parser::decode_json_string(quoted).unwrap_or_else(|| raw.to_string())
+            }
+            #[cfg(feature = "xml")]
+            Escape::Xml => FileType::Xml.format().decode_value_at(content, self.span),
+            #[cfg(feature = "env")]
+            Escape::Env(Some(Quote::Double)) => crate::env_parser::unescape(raw),
+            #[cfg(feature = "env")]
+            Escape::Env(_) => raw.to_string(),
+        }
+    }
+}
+
+/// How a substitution is written into a site.
+#[derive(Clone, Copy)]
+enum Escape {
+    Json,
+    #[cfg(feature = "xml")]
+    Xml,
+    /// An ENV value; unquoted and single-quoted ones are re-quoted when a
+    /// substitution needs it.
+    #[cfg(feature = "env")]
+    Env(Option<Quote>),
+}
+
+pub(crate) fn json_sites(root: &JsonNode) -> Vec<Site> {
+    let mut sites = Vec::new();
+    let mut path = Vec::new();
+    collect_json(root, &mut path, &mut sites);
+    sites
+}
+
+fn collect_json(node: &JsonNode, path: &mut Vec<String>, out: &mut Vec<Site>) {
+    match &node.value {
+        JsonValue::Object(members) => {
+            for member in members {
+                path.push(escape_segment(&member.name));
+                collect_json(&member.value, path, out);
+                path.pop();
+            }
+        }
+        JsonValue::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                path.push(i.to_string());
+                collect_json(item, path, out);
+                path.pop();
+            }
+        }
+        JsonValue::Scalar(Kind::StringLit) => out.push(Site {
+            path: path.clone(),
+            span: Span::new(node.span.start + 1, node.span.end - 1),
+            escape: Escape::Json,
+        }),
+        JsonValue::Scalar(_) => {}
+    }
+}
+
+#[cfg(feature = "xml")]
+pub(crate) fn xml_sites(root: &XmlElement) -> Vec<Site> {
+    let mut sites = Vec::new();
+    let mut path = vec![root.name.clone()];
+    collect_xml(root, &mut path, &mut sites);
+    sites
+}
+
+#[cfg(feature = "xml")]
+fn collect_xml(element: &XmlElement, path: &mut Vec<String>, out: &mut Vec<Site>) {
+    for attr in &element.attributes {
+        let mut at = path.clone();
+        at.push(format!("@{}", attr.name));
+        out.push(Site {
+            path: at,
+            span: attr.span,
+            escape: Escape::Xml,
+        });
+    }
+    for part in &element.text {
+        out.push(Site {
+            path: path.clone(),
+            span: part.span,
+            escape: Escape::Xml,
+        });
+    }
+    for child in &element.children {
+        path.push(child.name.clone());
+        collect_xml(child, path, out);
+        path.pop();
+    }
+}
+
+#[cfg(feature = "env")]
+pub(crate) fn env_sites(doc: &EnvDocument) -> Vec<Site> {
+    doc.entries()
+        .iter()
+        .map(|entry| {
+            let span = entry.value_span;
+            let span = match entry.quote {
+                Some(_) => Span::new(span.start + 1, span.end - 1),
+                None => span,
+            };
+            Site {
+                path: vec![escape_segment(&entry.key)],
+                span,
+                escape: Escape::Env(entry.quote),
+            }
+        })
+        .collect()
+}
+
+/// Each placeholder in `text` as (name, byte range within `text`).
+fn scan(text: &str, syntax: PlaceholderSyntax) -> Vec<(&str, Span)> {
+    let (open, close) = syntax.delimiters();
+    let mut found = Vec::new();
+    let mut at = 0;
+    while let Some(start) = text[at..].find(open).map(|i| at + i) {
+        let body_start = start + open.len();
+        let Some(len) = text[body_start..].find(close) else {
+            break;
+        };
+        let body = &text[body_start..body_start + len];
+        let name = body.trim();
+        if name.is_empty() || body.contains(['{', '}', '\n', '\r']) {
+            at = body_start;
+            continue;
+        }
+        let end = body_start + len + close.len();
+        found.push((name, Span::new(start, end)));
+        at = end;
+    }
+    found
+}
+
+pub(crate) fn placeholders(
+    content: &str,
+    sites: &[Site],
+    opts: &TemplateOptions,
+) -> Vec<Placeholder> {
+    let mut out = Vec::new();
+    for site in sites {
+        let text = &content[site.span.start..site.span.end];
+        for (name, span) in scan(text, opts.syntax) {
+            out.push(Placeholder {
+                name: name.to_string(),
+                path: site.path.clone(),
+                span: Span::new(site.span.start + span.start, site.span.start + span.end),
+            });
+        }
+    }
+    out
+}
+
+/// `content` with every placeholder that has a variable replaced by its
+/// value. Placeholders without one are left as written.
+pub(crate) fn render(
+    content: &str,
+    sites: &[Site],
+    variables: &HashMap<String, String>,
+    opts: &TemplateOptions,
+) -> String {
+    let mut edits: Vec<(Span, String)> = Vec::new();
+    for site in sites {
+        let text = &content[site.span.start..site.span.end];
+        let found: Vec<_> = scan(text, opts.syntax)
+            .into_iter()
+            .filter_map(|(name, span)| Some((span, variables.get(name)?)))
+            .collect();
+        if found.is_empty() {
+            continue;
+        }
+        match site.escape {
+            #[cfg(feature = "env")]
+            Escape::Env(quote) if quote != Some(Quote::Double) => {
+                edits.push(requote(content, site, quote, &found));
+                continue;
+            }
+            _ => {}
+        }
+        for (span, value) in found {
+            let span = Span::new(site.span.start + span.start, site.span.start + span.end);
+            let text = match site.escape {
+                Escape::Json => escape_json_string(value),
+                #[cfg(feature = "xml")]
+                Escape::Xml => FileType::Xml
+                    .format()
+                    .format_value_at(content, site.span, value),
+                #[cfg(feature = "env")]
+                Escape::Env(_) => crate::escape_env_string(value),
+            };
+            edits.push((span, text));
+        }
+    }
+    edits.sort_by_key(|(span, _)| span.start);
+    let mut out = String::with_capacity(content.len());
+    let mut at = 0;
+    for (span, text) in edits {
+        out.push_str(&content[at..span.start]);
+        out.push_str(&text);
+        at = span.end;
+    }
+    out.push_str(&content[at..]);
+    out
+}
+
+/// An unquoted or single-quoted ENV value, which has no escapes, rendered
+/// whole: kept in its quotes when the result still fits them, otherwise
+/// quoted the way `update_value` writes values.
+#[cfg(feature = "env")]
+fn requote(
+    content: &str,
+    site: &Site,
+    quote: Option<Quote>,
+    found: &[(Span, &String)],
+) -> (Span, String) {
+    let text = &content[site.span.start..site.span.end];
+    let mut rendered = String::with_capacity(text.len());
+    let mut at = 0;
+    for (span, value) in found {
+        rendered.push_str(&text[at..span.start]);
+        rendered.push_str(value);
+        at = span.end;
+    }
+    rendered.push_str(&text[at..]);
+    let span = match quote {
+        Some(_) => Span::new(site.span.start - 1, site.span.end + 1),
+        None => site.span,
+    };
+    let fits_quotes = quote.is_some() && !rendered.contains(['\'', '\n', '\r']);
+    let text = if fits_quotes {
+        format!("'{rendered}'")
+    } else {
+        FileType::Env.format().format_value(&rendered)
+    };
+    (span, text)
+}
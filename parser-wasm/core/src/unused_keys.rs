@@ -0,0 +1,250 @@
+//! Keys a schema says nothing about.
+//!
+//! The JSON tree is walked together with the schemas that apply at each
+//! value: the schema itself plus its `$ref`, `allOf`, `anyOf`, `oneOf`,
+//! `if`/`then`/`else` and `dependentSchemas` subschemas, all at once. A
+//! member is used when one of them names it in `properties` or `required`,
+//! matches it with a `patternProperties` pattern or gives it an
+//! `additionalProperties` schema. Objects whose schemas do not describe
+//! their keys at all (no such keywords, `additionalProperties: true`, or a
+//! `$ref` that does not resolve locally) are free-form and never reported.
+//! An unused member is reported once; its contents are not walked.
+
+use std::collections::HashMap;
+
+use jsonschema::JSONSchema;
+use serde::Serialize;
+use serde_json::Value as Json;
+use tsify::Tsify;
+
+use crate::document::Document;
+use crate::json_tree::{JsonNode, JsonValue};
+use crate::paths::escape_segment;
+use crate::Span;
+
+/// Subschema nesting followed before a value counts as free-form.
+const MAX_SCHEMA_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tsify)]
+pub struct UnusedKey {
+    pub path: Vec<String>,
+    pub line: usize,
+    pub column: usize,
+    /// The member, key through value, for pruning it.
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+pub(crate) fn find(doc: &Document, root: &JsonNode, schema: &Json) -> Vec<UnusedKey> {
+    let mut walk = Walk {
+        doc,
+        root: schema,
+        patterns: HashMap::new(),
+        path: Vec::new(),
+        out: Vec::new(),
+    };
+    if let Some(schemas) = walk.applicable(&[schema]) {
+        walk.node(root, &schemas);
+    }
+    walk.out
+}
+
+struct Walk<'a> {
+    doc: &'a Document,
+    root: &'a Json,
+    /// Compiled `patternProperties` patterns; `None` for one that does not
+    /// compile, which is taken to match.
+    patterns: HashMap<&'a str, Option<JSONSchema>>,
+    path: Vec<String>,
+    out: Vec<UnusedKey>,
+}
+
+impl<'a> Walk<'a> {
+    /// `schemas` with every in-place subschema added, or `None` when one of
+    /// them leaves the value unconstrained.
+    fn applicable(&self, schemas: &[&'a Json]) -> Option<Vec<&'a Json>> {
+        let mut out = Vec::new();
+        let mut pending: Vec<(&Json, usize)> = schemas.iter().map(|s| (*s, 0)).collect();
+        while let Some((schema, depth)) = pending.pop() {
+            let Json::Object(keywords) = schema else {
+                // `true` allows anything; `false` allows nothing
+                match schema {
+                    Json::Bool(false) => continue,
+                    _ => return None,
+                }
+            };
+            if depth > MAX_SCHEMA_DEPTH {
+                return None;
+            }
+            if let Some(target) = keywords.get("$ref").and_then(Json::as_str) {
+                pending.push((self.resolve(target)?, depth + 1));
+            }
+            for keyword in ["allOf", "anyOf", "oneOf"] {
+                for branch in keywords
+                    .get(keyword)
+                    .and_then(Json::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    pending.push((branch, depth + 1));
+                }
+            }
+            for keyword in ["if", "then", "else"] {
+                if let Some(branch) = keywords.get(keyword) {
+                    pending.push((branch, depth + 1));
+                }
+            }
+            if let Some(Json::Object(dependent)) = keywords.get("dependentSchemas") {
+                pending.extend(dependent.values().map(|branch| (branch, depth + 1)));
+            }
+            out.push(schema);
+        }
+        Some(out)
+    }
+
+    /// A local reference, `#` or `#/json/pointer`.
+    fn resolve(&self, target: &str) -> Option<&'a Json> {
+        let pointer = target.strip_prefix('#')?;
+        if pointer.is_empty() {
+            Some(self.root)
+        } else {
+            self.root.pointer(pointer)
+        }
+    }
+
+    fn node(&mut self, node: &JsonNode, schemas: &[&'a Json]) {
+        match &node.value {
+            JsonValue::Object(members) => {
+                if !describes_keys(schemas) {
+                    return;
+                }
+                for member in members {
+                    self.path.push(escape_segment(&member.name));
+                    match self.member_schemas(schemas, &member.name) {
+                        Some(children) => {
+                            if let Some(children) = self.applicable(&children) {
+                                self.node(&member.value, &children);
+                            }
+                        }
+                        None => {
+                            let span = Span::new(member.key_span.start, member.value.span.end);
+                            let (line, column) = self.doc.position_of(span.start);
+                            self.out.push(UnusedKey {
+                                path: self.path.clone(),
+                                line,
+                                column,
+                                span,
+                            });
+                        }
+                    }
+                    self.path.pop();
+                }
+            }
+            JsonValue::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    let children = item_schemas(schemas, i);
+                    if children.is_empty() {
+                        continue;
+                    }
+                    if let Some(children) = self.applicable(&children) {
+                        self.path.push(i.to_string());
+                        self.node(item, &children);
+                        self.path.pop();
+                    }
+                }
+            }
+            JsonValue::Scalar(_) => {}
+        }
+    }
+
+    /// The schemas a member named `name` is held to, or `None` when no
+    /// schema mentions it.
+    fn member_schemas(&mut self, schemas: &[&'a Json], name: &str) -> Option<Vec<&'a Json>> {
+        let mut used = false;
+        let mut out = Vec::new();
+        for schema in schemas {
+            let mut named = false;
+            if let Some(child) = schema.get("properties").and_then(|p| p.get(name)) {
+                out.push(child);
+                named = true;
+            }
+            let required = schema.get("required").and_then(Json::as_array);
+            named |= required.is_some_and(|names| names.iter().any(|n| n == name));
+            if let Some(Json::Object(patterns)) = schema.get("patternProperties") {
+                for (pattern, child) in patterns {
+                    if self.matches(pattern, name) {
+                        out.push(child);
+                        named = true;
+                    }
+                }
+            }
+            if !named {
+                for keyword in ["additionalProperties", "unevaluatedProperties"] {
+                    if let Some(child @ Json::Object(_)) = schema.get(keyword) {
+                        out.push(child);
+                        named = true;
+                    }
+                }
+            }
+            used |= named;
+        }
+        used.then_some(out)
+    }
+
+    fn matches(&mut self, pattern: &'a str, name: &str) -> bool {
+        let compiled = self.patterns.entry(pattern).or_insert_with(|| {
+            JSONSchema::compile(&serde_json::json!({ "pattern": pattern })).ok()
+        });
+        compiled
+            .as_ref()
+            .is_none_or(|schema| schema.is_valid(&Json::String(name.to_string())))
+    }
+}
+
+/// Whether the schemas say which keys an object has; without that every key
+/// is as good as any other.
+fn describes_keys(schemas: &[&Json]) -> bool {
+    let open = |value: Option<&Json>| match value {
+        Some(Json::Bool(true)) => true,
+        Some(Json::Object(schema)) => schema.is_empty(),
+        _ => false,
+    };
+    let describes = schemas.iter().any(|schema| {
+        [
+            "properties",
+            "patternProperties",
+            "required",
+            "additionalProperties",
+        ]
+        .iter()
+        .any(|keyword| schema.get(keyword).is_some())
+    });
+    describes
+        && !schemas.iter().any(|schema| {
+            open(schema.get("additionalProperties")) || open(schema.get("unevaluatedProperties"))
+        })
+}
+
+/// Schemas for the item at `index`: `prefixItems` or tuple `items` at that
+/// position, then `items` / `additionalItems` past them.
+fn item_schemas<'a>(schemas: &[&'a Json], index: usize) -> Vec<&'a Json> {
+    let mut out = Vec::new();
+    for schema in schemas {
+        let tuple = schema
+            .get("prefixItems")
+            .or_else(|| schema.get("items").filter(|items| items.is_array()))
+            .and_then(Json::as_array);
+        match tuple {
+            Some(tuple) if index < tuple.len() => out.push(&tuple[index]),
+            Some(_) => {
+                let rest = match schema.get("prefixItems") {
+                    Some(_) => schema.get("items"),
+                    None => schema.get("additionalItems"),
+                };
+                out.extend(rest);
+            }
+            None => out.extend(schema.get("items")),
+        }
+    }
+    out
+}
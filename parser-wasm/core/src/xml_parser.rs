@@ -0,0 +1,945 @@
+// xml_parser.rs
+// Uses: xmlparser = "0.13"
+
+use serde::Serialize;
+use tsify::Tsify;
+use xmlparser::{ElementEnd, Token, Tokenizer};
+
+use crate::document::{LookupOptions, MixedContent};
+use crate::limits::TokenBudget;
+use crate::paths;
+use crate::BytePreservingParser;
+
+pub struct XmlParser;
+impl Default for XmlParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl XmlParser {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+// ─────────────────── PATH FORMAT ───────────────────
+
+/// One element step: a local name plus `[@attr="value"]` predicates that
+/// must all hold for the element to match.
+#[derive(Debug, Clone, PartialEq)]
+struct Step {
+    name: String,
+    predicates: Vec<(String, String)>,
+}
+
+impl Step {
+    /// Parses `name[@a="x"][@b='y']`. An escaped segment is a literal name.
+    fn parse(segment: &str) -> Result<Self, String> {
+        let literal = |name: &str| Self {
+            name: name.to_string(),
+            predicates: Vec::new(),
+        };
+        if segment.starts_with('\\') {
+            return Ok(literal(paths::literal(segment)));
+        }
+        let Some(open) = segment.find('[') else {
+            return Ok(literal(segment));
+        };
+        let invalid = || format!("Invalid predicate in path segment: {segment}");
+        let mut predicates = Vec::new();
+        let mut rest = &segment[open..];
+        while !rest.is_empty() {
+            let body = rest.strip_prefix("[@").ok_or_else(invalid)?;
+            let eq = body.find('=').ok_or_else(invalid)?;
+            let attr = body[..eq].trim();
+            let value = body[eq + 1..].trim_start();
+            let quote = value
+                .chars()
+                .next()
+                .filter(|q| matches!(q, '"' | '\''))
+                .ok_or_else(invalid)?;
+            let close = value[1..].find(quote).ok_or_else(invalid)? + 1;
+            rest = value[close + 1..]
+                .trim_start()
+                .strip_prefix(']')
+                .ok_or_else(invalid)?;
+            if attr.is_empty() {
+                return Err(invalid());
+            }
+            predicates.push((attr.to_string(), value[1..close].to_string()));
+        }
+        Ok(Self {
+            name: segment[..open].to_string(),
+            predicates,
+        })
+    }
+
+    fn matches(&self, frame: &Frame) -> bool {
+        self.name == frame.name
+            && self.predicates.iter().all(|(attr, want)| {
+                frame
+                    .attributes
+                    .iter()
+                    .any(|(name, value)| name == attr && decode_entities(value.as_str()) == *want)
+            })
+    }
+}
+
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)?;
+        for (attr, value) in &self.predicates {
+            write!(f, "[@{attr}=\"{value}\"]")?;
+        }
+        Ok(())
+    }
+}
+
+/// The attribute name that matches every attribute (`["config", "@*"]`).
+const ANY_ATTRIBUTE: &str = "*";
+
+#[derive(Debug, Clone)]
+struct XmlPath {
+    /// `?target` paths address the XML declaration (`?xml`) or a processing
+    /// instruction instead of an element.
+    instruction: Option<String>,
+    elements: Vec<Step>,
+    attribute: Option<String>,
+    mixed: MixedContent,
+    /// Keep the indentation around an element's text in its span.
+    raw_text: bool,
+}
+impl XmlPath {
+    fn from(path: &[String]) -> Result<Self, String> {
+        if let Some(target) = path.first().and_then(|first| first.strip_prefix('?')) {
+            let attribute = match path {
+                [_] => None,
+                [_, attr] if attr.starts_with('@') => Some(attr[1..].to_string()),
+                _ => {
+                    return Err(format!(
+                        "Processing instruction paths are ['?{target}'] or ['?{target}', '@name']"
+                    ))
+                }
+            };
+            return Ok(Self {
+                instruction: Some(target.to_string()),
+                elements: Vec::new(),
+                attribute,
+                mixed: MixedContent::First,
+                raw_text: false,
+            });
+        }
+        let elements = |segments: &[String]| {
+            segments
+                .iter()
+                .map(|s| Step::parse(s))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(match path.split_last() {
+            Some((last, parents)) if last.starts_with('@') => Self {
+                instruction: None,
+                elements: elements(parents)?,
+                attribute: Some(last.trim_start_matches('@').to_string()),
+                mixed: MixedContent::First,
+                raw_text: false,
+            },
+            _ => Self {
+                instruction: None,
+                elements: elements(path)?,
+                attribute: None,
+                mixed: MixedContent::First,
+                raw_text: false,
+            },
+        })
+    }
+
+    /// This path, unless it ends in `@*`, which names every attribute of the
+    /// element and so has no single span.
+    fn single(self) -> Result<Self, String> {
+        match self.attribute.as_deref() {
+            Some(ANY_ATTRIBUTE) => Err(
+                "'@*' matches every attribute of the element; list them with xml_attributes".into(),
+            ),
+            _ => Ok(self),
+        }
+    }
+
+    /// Whether the open elements are exactly this path's elements.
+    fn matches(&self, stack: &[Frame]) -> bool {
+        self.instruction.is_none()
+            && stack.len() == self.elements.len()
+            && self
+                .elements
+                .iter()
+                .zip(stack)
+                .all(|(step, frame)| step.matches(frame))
+    }
+}
+
+// ──────────────── MAIN PARSER IMPL ────────────────
+
+impl BytePreservingParser for XmlParser {
+    fn validate_syntax(&self, content: &str) -> Result<(), String> {
+        scan(content, &[], true).map(|_| ())
+    }
+
+    fn find_value_span(&self, content: &str, path: &[String]) -> Result<crate::Span, String> {
+        lookup(content, path, &LookupOptions::default(), false)
+    }
+
+    fn validate_and_find_span(
+        &self,
+        content: &str,
+        path: &[String],
+    ) -> Result<crate::Span, String> {
+        lookup(content, path, &LookupOptions::default(), true)
+    }
+
+    fn replace_value(&self, content: &str, span: crate::Span, new_val: &str) -> String {
+        let mut out = String::with_capacity(content.len() - span.len() + new_val.len());
+        out.push_str(&content[..span.start]);
+        out.push_str(new_val);
+        out.push_str(&content[span.end..]);
+        out
+    }
+}
+
+/// Resolves one path; `opts` decides which text an element with child
+/// elements resolves to and whether indentation is part of it.
+pub(crate) fn lookup(
+    content: &str,
+    path: &[String],
+    opts: &LookupOptions,
+    check_structure: bool,
+) -> Result<crate::Span, String> {
+    let path = match XmlPath::from(path).and_then(XmlPath::single) {
+        Ok(path) => XmlPath {
+            mixed: opts.mixed_content,
+            raw_text: opts.raw_text,
+            ..path
+        },
+        // still report syntax errors first
+        Err(e) if check_structure => return scan(content, &[], true).and(Err(e)),
+        Err(e) => return Err(e),
+    };
+    let mut found = scan(content, std::slice::from_ref(&path), check_structure)?;
+    found
+        .pop()
+        .flatten()
+        .unwrap_or_else(|| Err(path_not_found(&path)))
+}
+
+/// Resolves several paths in one tokenizer pass. The outer error is a
+/// syntax error; each entry is that path's lookup result.
+pub(crate) fn find_value_spans(
+    content: &str,
+    paths: &[Vec<String>],
+) -> Result<Vec<Result<crate::Span, String>>, String> {
+    let parsed: Vec<Result<XmlPath, String>> = paths
+        .iter()
+        .map(|p| XmlPath::from(p).and_then(XmlPath::single))
+        .collect();
+    let targets: Vec<XmlPath> = parsed.iter().filter_map(|p| p.clone().ok()).collect();
+    let mut found = scan(content, &targets, false)?
+        .into_iter()
+        .zip(&targets)
+        .map(|(lookup, path)| lookup.unwrap_or_else(|| Err(path_not_found(path))));
+    Ok(parsed
+        .into_iter()
+        .map(|path| path.and_then(|_| found.next().expect("one result per target")))
+        .collect())
+}
+
+// ──────────────── SINGLE-PASS SCANNER ────────────────
+
+/// An open element: its local name and raw attribute values, which
+/// predicates and attribute lookups read once the start tag is complete.
+struct Frame<'a> {
+    name: String,
+    attributes: Vec<(&'a str, xmlparser::StrSpan<'a>)>,
+}
+
+/// Walks the token stream once, resolving every entry of `targets` and, when
+/// `check_structure` is set, verifying that every element is closed.
+///
+/// The outer `Result` carries syntax errors; each inner entry is one target's
+/// lookup outcome (`None` when the path was never reached). Without
+/// structural checking the scan stops as soon as every lookup is decided.
+fn scan(
+    content: &str,
+    targets: &[XmlPath],
+    check_structure: bool,
+) -> Result<Vec<Option<Result<crate::Span, String>>>, String> {
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut results: Vec<Option<Result<crate::Span, String>>> = vec![None; targets.len()];
+    let mut runs = vec![TextRun::default(); targets.len()];
+    let mut pending = targets.len();
+
+    let mut budget = TokenBudget::new();
+    for token in Tokenizer::from(content) {
+        if pending == 0 && !check_structure {
+            break;
+        }
+        let token = token.map_err(|e| format!("XML parsing error: {e}"))?;
+        budget.spend()?;
+        match token {
+            Token::ElementStart { local, .. } => {
+                for (target, run) in targets.iter().zip(&mut runs) {
+                    if target.attribute.is_none() && target.matches(&stack) {
+                        run.child = true;
+                    }
+                }
+                stack.push(Frame {
+                    name: local.to_string(),
+                    attributes: Vec::new(),
+                });
+            }
+
+            Token::Attribute { local, value, .. } => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.attributes.push((local.as_str(), value));
+                }
+            }
+
+            Token::ElementEnd { end, .. } => {
+                if matches!(end, ElementEnd::Open | ElementEnd::Empty) {
+                    // the start tag is complete: attribute lookups can resolve
+                    for (target, result) in targets.iter().zip(&mut results) {
+                        let Some(attr) = &target.attribute else {
+                            continue;
+                        };
+                        if result.is_some() || !target.matches(&stack) {
+                            continue;
+                        }
+                        let frame = stack.last().expect("a start tag was pushed");
+                        let value = frame.attributes.iter().find(|(name, _)| name == attr);
+                        *result = Some(match value {
+                            Some((_, value)) => Ok(crate::Span::new(value.start(), value.end())),
+                            None => Err(format!("Attribute '{}' not found", attr)),
+                        });
+                        pending -= 1;
+                    }
+                }
+                if matches!(end, ElementEnd::Close(..) | ElementEnd::Empty) {
+                    let name = stack.last().map_or("", |frame| frame.name.as_str());
+                    for (i, target) in targets.iter().enumerate() {
+                        if target.matches(&stack)
+                            && runs[i].close(target.mixed, name, &mut results[i])
+                        {
+                            pending -= 1;
+                        }
+                    }
+                    stack.pop();
+                }
+            }
+
+            Token::Text { text } => {
+                let span = crate::Span::new(text.start(), text.end());
+                let significant = !text.as_str().trim().is_empty();
+                let raw = TextNode {
+                    inner: span,
+                    outer: span,
+                    significant,
+                    cdata: false,
+                };
+                let trimmed = if significant {
+                    let span = without_indentation(content, span);
+                    TextNode {
+                        inner: span,
+                        outer: span,
+                        ..raw
+                    }
+                } else {
+                    raw
+                };
+                for (i, target) in targets.iter().enumerate() {
+                    let open = results[i].is_none() || runs[i].open;
+                    let node = if target.raw_text { &raw } else { &trimmed };
+                    if open
+                        && target.attribute.is_none()
+                        && target.matches(&stack)
+                        && runs[i].add(target.mixed, node, &mut results[i])
+                    {
+                        pending -= 1;
+                    }
+                }
+            }
+
+            Token::Declaration { span, .. } => {
+                let body = instruction_body(content, "xml", span);
+                resolve_instruction(content, "xml", body, targets, &mut results, &mut pending);
+            }
+
+            Token::ProcessingInstruction { target, span, .. } => {
+                let body = instruction_body(content, target.as_str(), span);
+                resolve_instruction(
+                    content,
+                    target.as_str(),
+                    body,
+                    targets,
+                    &mut results,
+                    &mut pending,
+                );
+            }
+
+            // the inner text, so reads and writes stay inside the section
+            Token::Cdata { text, span } => {
+                let node = TextNode {
+                    inner: crate::Span::new(text.start(), text.end()),
+                    outer: crate::Span::new(span.start(), span.end()),
+                    significant: true,
+                    cdata: true,
+                };
+                for (i, target) in targets.iter().enumerate() {
+                    let open = results[i].is_none() || runs[i].open;
+                    if open
+                        && target.attribute.is_none()
+                        && target.matches(&stack)
+                        && runs[i].add(target.mixed, &node, &mut results[i])
+                    {
+                        pending -= 1;
+                    }
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    if check_structure && !stack.is_empty() {
+        let names: Vec<&str> = stack.iter().map(|frame| frame.name.as_str()).collect();
+        return Err(format!("Unclosed tags: {:?}", names));
+    }
+    Ok(results)
+}
+
+/// `span` without leading or trailing whitespace that holds a line break,
+/// the indentation of pretty-printed markup. Spaces on the value's own line
+/// stay.
+pub(crate) fn without_indentation(content: &str, span: crate::Span) -> crate::Span {
+    let is_space = |c: char| matches!(c, ' ' | '\t' | '\n' | '\r');
+    let is_break = |c: char| matches!(c, '\n' | '\r');
+    let text = &content[span.start..span.end];
+    let lead = text.len() - text.trim_start_matches(is_space).len();
+    let trail = text.len() - text.trim_end_matches(is_space).len();
+    let mut trimmed = span;
+    if text[..lead].contains(is_break) {
+        trimmed.start += lead;
+    }
+    if text[text.len() - trail..].contains(is_break) {
+        trimmed.end -= trail;
+    }
+    trimmed
+}
+
+/// A direct text node or CDATA section of an element a lookup matched.
+#[derive(Clone, Copy)]
+struct TextNode {
+    /// The text itself; a CDATA section's body.
+    inner: crate::Span,
+    /// A CDATA section's markup included.
+    outer: crate::Span,
+    significant: bool,
+    cdata: bool,
+}
+
+/// Text seen so far in the element a text lookup matched.
+#[derive(Debug, Clone, Copy, Default)]
+struct TextRun {
+    /// The result can still change before the element closes: only
+    /// whitespace so far, or a policy that needs the whole element.
+    open: bool,
+    /// A non-blank text node or a CDATA section was seen.
+    significant: bool,
+    /// More than one significant node was seen.
+    spread: bool,
+    /// Start of the first and end of the last significant node.
+    start: usize,
+    end: usize,
+    /// The element has a child element.
+    child: bool,
+}
+
+impl TextRun {
+    /// Applies `node` to `result`; returns whether the result is final.
+    fn add(
+        &mut self,
+        policy: MixedContent,
+        node: &TextNode,
+        result: &mut Option<Result<crate::Span, String>>,
+    ) -> bool {
+        if policy == MixedContent::First {
+            // whitespace-only text is kept unless a CDATA section follows
+            if result.is_none() || node.cdata {
+                *result = Some(Ok(node.inner));
+                self.open = !node.significant;
+            }
+            return !self.open;
+        }
+        if node.significant {
+            if self.significant {
+                self.spread = true;
+            } else {
+                *result = Some(Ok(node.inner));
+                self.significant = true;
+                self.start = node.outer.start;
+            }
+            self.end = node.outer.end;
+        } else if result.is_none() {
+            *result = Some(Ok(node.inner));
+        }
+        self.open = true;
+        false
+    }
+
+    /// Settles `result` as the matched element `name` closes; returns
+    /// whether it was still open.
+    fn close(
+        &mut self,
+        policy: MixedContent,
+        name: &str,
+        result: &mut Option<Result<crate::Span, String>>,
+    ) -> bool {
+        let child = std::mem::take(&mut self.child);
+        if !std::mem::take(&mut self.open) {
+            return false;
+        }
+        match policy {
+            MixedContent::Concat if self.spread => {
+                *result = Some(Ok(crate::Span::new(self.start, self.end)));
+            }
+            MixedContent::Error if self.significant && child => {
+                *result = Some(Err(format!(
+                    "Element '{name}' has mixed content: text and child elements"
+                )));
+            }
+            _ => {}
+        }
+        true
+    }
+}
+
+/// Resolves `?target` lookups against one declaration or processing
+/// instruction whose content is `content[body]`.
+fn resolve_instruction(
+    content: &str,
+    name: &str,
+    body: std::ops::Range<usize>,
+    targets: &[XmlPath],
+    results: &mut [Option<Result<crate::Span, String>>],
+    pending: &mut usize,
+) {
+    for (target, result) in targets.iter().zip(results.iter_mut()) {
+        if result.is_some() || target.instruction.as_deref() != Some(name) {
+            continue;
+        }
+        *result = Some(match &target.attribute {
+            None => Ok(crate::Span::new(body.start, body.end)),
+            Some(attr) => pseudo_attributes(content, body.clone())
+                .into_iter()
+                .find(|(key, _)| key == attr)
+                .map(|(_, span)| span)
+                .ok_or_else(|| format!("Attribute '{attr}' not found in <?{name}?>")),
+        });
+        *pending -= 1;
+    }
+}
+
+/// The content of `<?target content?>` spanning `span`, without surrounding
+/// whitespace; an empty range before `?>` when there is none.
+fn instruction_body(
+    content: &str,
+    target: &str,
+    span: xmlparser::StrSpan,
+) -> std::ops::Range<usize> {
+    let start = span.start() + "<?".len() + target.len();
+    let end = span.end() - "?>".len();
+    let inner = &content[start..end];
+    let lead = inner.len() - inner.trim_start().len();
+    if lead == inner.len() {
+        return end..end;
+    }
+    start + lead..start + inner.trim_end().len()
+}
+
+/// `name="value"` pairs in an instruction body, each with its value span
+/// (inside the quotes). Parsing stops at the first text that is not one.
+fn pseudo_attributes(content: &str, body: std::ops::Range<usize>) -> Vec<(String, crate::Span)> {
+    let mut out = Vec::new();
+    let mut pos = body.start;
+    loop {
+        let rest = &content[pos..body.end];
+        let trimmed = rest.trim_start();
+        pos += rest.len() - trimmed.len();
+        let Some(eq) = trimmed.find('=') else {
+            break;
+        };
+        let name = trimmed[..eq].trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            break;
+        }
+        let after = &trimmed[eq + 1..];
+        let value = after.trim_start();
+        let Some(quote) = value.chars().next().filter(|q| matches!(q, '"' | '\'')) else {
+            break;
+        };
+        let Some(close) = value[1..].find(quote) else {
+            break;
+        };
+        let value_start = pos + eq + 1 + (after.len() - value.len()) + 1;
+        out.push((
+            name.to_string(),
+            crate::Span::new(value_start, value_start + close),
+        ));
+        pos = value_start + close + 1;
+    }
+    out
+}
+
+/// A pseudo-attribute of an instruction; the span is the value inside its
+/// quotes.
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct PseudoAttribute {
+    pub name: String,
+    pub value: String,
+    #[serde(flatten)]
+    pub span: crate::Span,
+}
+
+/// The XML declaration (target `xml`) or a processing instruction.
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct XmlInstruction {
+    pub target: String,
+    /// Everything after the target, trimmed; empty when there is none.
+    pub content: String,
+    /// `name="value"` pairs parsed from the content.
+    pub attributes: Vec<PseudoAttribute>,
+    /// The whole `<? ... ?>`.
+    #[serde(flatten)]
+    pub span: crate::Span,
+}
+
+/// The declaration and every processing instruction, in document order.
+pub(crate) fn instructions(content: &str) -> Result<Vec<XmlInstruction>, String> {
+    let mut out = Vec::new();
+    let mut budget = TokenBudget::new();
+    for token in Tokenizer::from(content) {
+        let token = token.map_err(|e| format!("XML parsing error: {e}"))?;
+        budget.spend()?;
+        let (target, span) = match token {
+            Token::Declaration { span, .. } => ("xml", span),
+            Token::ProcessingInstruction { target, span, .. } => (target.as_str(), span),
+            _ => continue,
+        };
+        let body = instruction_body(content, target, span);
+        let attributes = pseudo_attributes(content, body.clone())
+            .into_iter()
+            .map(|(name, span)| PseudoAttribute {
+                name,
+                value: decode_entities(&content[span.start..span.end]),
+                span,
+            })
+            .collect();
+        out.push(XmlInstruction {
+            target: target.to_string(),
+            content: content[body].to_string(),
+            attributes,
+            span: crate::Span::new(span.start(), span.end()),
+        });
+    }
+    Ok(out)
+}
+
+// ──────────────── ATTRIBUTES ────────────────
+
+/// An attribute of an element. `raw` and the span are the value inside its
+/// quotes, which an update replaces; `value` has entity and character
+/// references decoded for display.
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct XmlAttribute {
+    pub name: String,
+    pub raw: String,
+    pub value: String,
+    #[serde(flatten)]
+    pub span: crate::Span,
+}
+
+/// Attributes of the first element matching `path`, in document order. A
+/// path ending in `@name` selects that attribute alone; `@*` selects them
+/// all, as a path to the element does.
+pub(crate) fn attributes_for(content: &str, path: &[String]) -> Result<Vec<XmlAttribute>, String> {
+    let target = XmlPath::from(path)?;
+    if target.instruction.is_some() {
+        return Err("Instruction pseudo-attributes are listed by xml_instructions".into());
+    }
+    if target.elements.is_empty() {
+        return Err(path_not_found(&target));
+    }
+    let wanted = target
+        .attribute
+        .as_deref()
+        .filter(|name| *name != ANY_ATTRIBUTE);
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut budget = TokenBudget::new();
+    for token in Tokenizer::from(content) {
+        let token = token.map_err(|e| format!("XML parsing error: {e}"))?;
+        budget.spend()?;
+        match token {
+            Token::ElementStart { local, .. } => stack.push(Frame {
+                name: local.to_string(),
+                attributes: Vec::new(),
+            }),
+            Token::Attribute { local, value, .. } => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.attributes.push((local.as_str(), value));
+                }
+            }
+            Token::ElementEnd { end, .. } => {
+                if matches!(end, ElementEnd::Open | ElementEnd::Empty) && target.matches(&stack) {
+                    let frame = stack.last().expect("a start tag was pushed");
+                    let attributes = frame
+                        .attributes
+                        .iter()
+                        .filter(|(name, _)| wanted.is_none_or(|a| a == *name))
+                        .map(|(name, value)| XmlAttribute {
+                            name: name.to_string(),
+                            raw: value.as_str().to_string(),
+                            value: decode_entities(value.as_str()),
+                            span: crate::Span::new(value.start(), value.end()),
+                        })
+                        .collect::<Vec<_>>();
+                    return match wanted {
+                        Some(attr) if attributes.is_empty() => {
+                            Err(format!("Attribute '{}' not found", attr))
+                        }
+                        _ => Ok(attributes),
+                    };
+                }
+                if matches!(end, ElementEnd::Close(..) | ElementEnd::Empty) {
+                    stack.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(path_not_found(&target))
+}
+
+// ──────────────── COMMENTS ────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Tsify)]
+#[serde(rename_all = "lowercase")]
+pub enum CommentPlacement {
+    /// Directly above the element: only whitespace or other comments between.
+    Before,
+    /// A direct child of the element.
+    Inside,
+    /// On the same line as the element's end tag.
+    After,
+}
+
+/// A comment next to an element. The span covers `<!--` to `-->`; `text`
+/// is the part in between.
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct XmlComment {
+    pub placement: CommentPlacement,
+    pub text: String,
+    #[serde(flatten)]
+    pub span: crate::Span,
+}
+
+/// Comments adjacent to the first element matching `path`, in document
+/// order. An empty path is the document itself: the comments outside the
+/// root element come back as `Inside`.
+pub(crate) fn comments_for(content: &str, path: &[String]) -> Result<Vec<XmlComment>, String> {
+    let target = XmlPath::from(path)?;
+    if target.attribute.is_some() || target.instruction.is_some() {
+        return Err("Comments belong to elements, not attributes".into());
+    }
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut out = Vec::new();
+    // comments since the last token that was not a comment or whitespace
+    let mut recent: Vec<XmlComment> = Vec::new();
+    let mut before_start: Vec<XmlComment> = Vec::new();
+    // depth of the matched element while inside it
+    let mut inside: Option<usize> = target.elements.is_empty().then_some(0);
+    // end of the matched element's end tag, while looking for a trailing comment
+    let mut closed_at: Option<usize> = None;
+    // end of the last token other than a comment or whitespace
+    let mut last_end: Option<usize> = None;
+
+    let mut budget = TokenBudget::new();
+    for token in Tokenizer::from(content) {
+        let token = token.map_err(|e| format!("XML parsing error: {e}"))?;
+        budget.spend()?;
+        if let Some(end) = closed_at {
+            if let Token::Comment { text, span } = token {
+                if !content[end..span.start()].contains('\n') {
+                    out.push(comment(CommentPlacement::After, text, span));
+                }
+            }
+            if !matches!(&token, Token::Text { text } if !text.as_str().contains('\n')) {
+                return Ok(out);
+            }
+            continue;
+        }
+        match token {
+            Token::Comment { text, span } => {
+                let trailing =
+                    last_end.is_some_and(|end| !content[end..span.start()].contains('\n'));
+                if inside == Some(stack.len()) {
+                    out.push(comment(CommentPlacement::Inside, text, span));
+                } else if trailing {
+                    // ends the line of whatever precedes it, not above the next element
+                    recent.clear();
+                } else {
+                    recent.push(comment(CommentPlacement::Before, text, span));
+                }
+                continue;
+            }
+            Token::Text { text } if text.as_str().trim().is_empty() => continue,
+            Token::ElementStart { local, .. } => {
+                before_start = std::mem::take(&mut recent);
+                stack.push(Frame {
+                    name: local.to_string(),
+                    attributes: Vec::new(),
+                });
+            }
+            Token::Attribute { local, value, .. } => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.attributes.push((local.as_str(), value));
+                }
+            }
+            Token::ElementEnd { end, span } => {
+                let found = inside.is_none()
+                    && matches!(end, ElementEnd::Open | ElementEnd::Empty)
+                    && target.matches(&stack);
+                if found {
+                    out.append(&mut before_start);
+                    inside = Some(stack.len());
+                }
+                if matches!(end, ElementEnd::Close(..) | ElementEnd::Empty) {
+                    if !target.elements.is_empty() && inside == Some(stack.len()) {
+                        closed_at = Some(span.end());
+                    }
+                    stack.pop();
+                }
+            }
+            _ => {}
+        }
+        last_end = Some(token_end(&token));
+        recent.clear();
+    }
+    if inside.is_none() {
+        return Err(path_not_found(&target));
+    }
+    Ok(out)
+}
+
+fn token_end(token: &Token) -> usize {
+    match token {
+        Token::Declaration { span, .. }
+        | Token::ProcessingInstruction { span, .. }
+        | Token::Comment { span, .. }
+        | Token::DtdStart { span, .. }
+        | Token::EmptyDtd { span, .. }
+        | Token::EntityDeclaration { span, .. }
+        | Token::DtdEnd { span }
+        | Token::ElementStart { span, .. }
+        | Token::Attribute { span, .. }
+        | Token::ElementEnd { span, .. }
+        | Token::Cdata { span, .. } => span.end(),
+        Token::Text { text } => text.end(),
+    }
+}
+
+fn comment(
+    placement: CommentPlacement,
+    text: xmlparser::StrSpan,
+    span: xmlparser::StrSpan,
+) -> XmlComment {
+    XmlComment {
+        placement,
+        text: text.as_str().to_string(),
+        span: crate::Span::new(span.start(), span.end()),
+    }
+}
+
+fn path_not_found(path: &XmlPath) -> String {
+    format!(
+        "Path not found: {}",
+        path.instruction
+            .iter()
+            .map(|target| format!("?{target}"))
+            .chain(path.elements.iter().map(Step::to_string))
+            .collect::<Vec<_>>()
+            .join("/")
+            + &path
+                .attribute
+                .as_ref()
+                .map_or(String::new(), |a| format!("/@{a}"))
+    )
+}
+
+/// Whether `span` is the body of a `<![CDATA[ ... ]]>` section.
+pub(crate) fn in_cdata(content: &str, span: crate::Span) -> bool {
+    content[..span.start].ends_with("<![CDATA[") && content[span.end..].starts_with("]]>")
+}
+
+/// Whether `span` is the empty content of a `<?target?>` instruction.
+pub(crate) fn bare_instruction(content: &str, span: crate::Span) -> bool {
+    span.is_empty()
+        && content[span.end..].starts_with("?>")
+        && !content[..span.start].ends_with(char::is_whitespace)
+}
+
+/// `value` as a CDATA body: taken literally, with any `]]>` split across two
+/// sections so it cannot close the one being written.
+pub(crate) fn escape_cdata(value: &str) -> String {
+    value.replace("]]>", "]]]]><![CDATA[>")
+}
+
+/// Replaces the predefined entities (`&amp;`, `&lt;`, `&gt;`, `&quot;`,
+/// `&apos;`) and character references (`&#10;`, `&#xA;`) in a text or
+/// attribute value. Other references are DTD-defined and kept as written.
+pub(crate) fn decode_entities(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest
+            .find(';')
+            .and_then(|semi| Some((decode_reference(&rest[1..semi])?, semi)));
+        match decoded {
+            Some((ch, semi)) => {
+                out.push(ch);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_reference(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        _ => {
+            let code = match name.strip_prefix("#x") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => name.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Element tree of an XML document.
+//!
+//! The XML counterpart of `json_tree`, built for the operations that compare
+//! whole documents (`diff`). Elements keep the span of their markup, their
+//! attributes as `xml_attributes` reports them and their direct text, so
+//! results can point back into the content. Comments, processing
+//! instructions and whitespace-only text are left out.
+
+use xmlparser::{ElementEnd, Token, Tokenizer};
+
+use crate::json_tree::MAX_TREE_DEPTH;
+use crate::limits::TokenBudget;
+use crate::xml_parser::{decode_entities, without_indentation, XmlAttribute};
+use crate::Span;
+
+#[derive(Debug)]
+pub(crate) struct XmlElement {
+    pub(crate) name: String,
+    /// Start tag to end tag, or the whole empty-element tag.
+    pub(crate) span: Span,
+    /// The name in the start tag, prefix included.
+    pub(crate) name_span: Span,
+    pub(crate) attributes: Vec<XmlAttribute>,
+    /// The name of each of `attributes`, prefix included.
+    pub(crate) attribute_names: Vec<Span>,
+    /// Non-blank text nodes and CDATA sections directly inside, in order.
+    pub(crate) text: Vec<TextPart>,
+    pub(crate) children: Vec<XmlElement>,
+}
+
+#[derive(Debug)]
+pub(crate) struct TextPart {
+    /// What a lookup resolves to: indentation left out, a CDATA section's
+    /// body.
+    pub(crate) span: Span,
+    /// References decoded; a CDATA body as written.
+    pub(crate) value: String,
+}
+
+/// Builds the tree of the root element. The caller checks syntax first.
+pub(crate) fn build(content: &str) -> Result<XmlElement, String> {
+    let mut stack: Vec<XmlElement> = Vec::new();
+    let mut root: Option<XmlElement> = None;
+    let mut budget = TokenBudget::new();
+    for token in Tokenizer::from(content) {
+        let token = token.map_err(|e| format!("XML parsing error: {e}"))?;
+        budget.spend()?;
+        match token {
+            Token::ElementStart {
+                prefix,
+                local,
+                span,
+            } => {
+                if stack.len() == MAX_TREE_DEPTH {
+                    return Err(format!("Content nests deeper than {MAX_TREE_DEPTH} levels"));
+                }
+                let name_start = if prefix.is_empty() {
+                    local.start()
+                } else {
+                    prefix.start()
+                };
+                stack.push(XmlElement {
+                    name: local.to_string(),
+                    span: Span::new(span.start(), span.end()),
+                    name_span: Span::new(name_start, local.end()),
+                    attributes: Vec::new(),
+                    attribute_names: Vec::new(),
+                    text: Vec::new(),
+                    children: Vec::new(),
+                });
+            }
+            Token::Attribute {
+                prefix,
+                local,
+                value,
+                ..
+            } => {
+                if let Some(element) = stack.last_mut() {
+                    element.attributes.push(XmlAttribute {
+                        name: local.to_string(),
+                        raw: value.as_str().to_string(),
+                        value: decode_entities(value.as_str()),
+                        span: Span::new(value.start(), value.end()),
+                    });
+                    let start = if prefix.is_empty() {
+                        local.start()
+                    } else {
+                        prefix.start()
+                    };
+                    element.attribute_names.push(Span::new(start, local.end()));
+                }
+            }
+            Token::ElementEnd { end, span } => {
+                if matches!(end, ElementEnd::Open) {
+                    continue;
+                }
+                let mut element = stack
+                    .pop()
+                    .ok_or_else(|| "Unexpected end tag".to_string())?;
+                element.span.end = span.end();
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => root = Some(element),
+                }
+            }
+            Token::Text { text } if !text.as_str().trim().is_empty() => {
+                if let Some(element) = stack.last_mut() {
+                    let span = without_indentation(content, Span::new(text.start(), text.end()));
+                    element.text.push(TextPart {
+                        span,
+                        value: decode_entities(&content[span.start..span.end]),
+                    });
+                }
+            }
+            Token::Cdata { text, .. } => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push(TextPart {
+                        span: Span::new(text.start(), text.end()),
+                        value: text.as_str().to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    match root {
+        Some(root) if stack.is_empty() => Ok(root),
+        _ => Err("Unexpected end of XML content".into()),
+    }
+}
+
+impl XmlElement {
+    /// The direct text as one string, what the diff compares.
+    pub(crate) fn text_value(&self) -> String {
+        self.text.iter().map(|part| part.value.as_str()).collect()
+    }
+
+    /// What a path to the element is shown as: its first text, or the whole
+    /// element when it has none.
+    pub(crate) fn value_span(&self) -> Span {
+        self.text.first().map_or(self.span, |part| part.span)
+    }
+
+    /// From the first text part to the last, inline children included; for
+    /// an element with one text node, the span a lookup resolves.
+    pub(crate) fn text_span(&self) -> Option<Span> {
+        let first = self.text.first()?;
+        let last = self.text.last()?;
+        Some(Span::new(first.span.start, last.span.end))
+    }
+}
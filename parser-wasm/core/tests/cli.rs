@@ -0,0 +1,179 @@
+//! `konficurator-cli` run as a process: argument parsing, output and exit
+//! status of each command.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A fresh directory for one test's files.
+fn scratch(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("konficurator-cli-{}-{test}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Exit code, stdout and stderr of the CLI run in `dir`.
+fn run(dir: &Path, args: &[&str]) -> (i32, String, String) {
+    let out = Command::new(env!("CARGO_BIN_EXE_konficurator-cli"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    (
+        out.status.code().unwrap(),
+        String::from_utf8(out.stdout).unwrap(),
+        String::from_utf8(out.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn usage_errors_exit_2() {
+    let dir = scratch("usage");
+    let (code, out, _) = run(&dir, &[]);
+    assert_eq!(code, 0);
+    assert!(out.starts_with("Usage:"), "{out}");
+    assert_eq!(run(&dir, &["--help"]).0, 0);
+
+    for args in [
+        &["check", "a.json"][..],
+        &["validate"],
+        &["validate", "--max-errors"],
+        &["validate-multi", "--max-errors", "many", "a.json"],
+        &["validate", "--type"],
+        &["validate", "--verbose", "a.json"],
+        &["update", "a.json", "a"],
+        &["update", "a.json", "a", "1", "2"],
+        &["format"],
+    ] {
+        let (code, _, err) = run(&dir, args);
+        assert_eq!(code, 2, "{args:?}");
+        assert!(err.contains("Usage:"), "{args:?}: {err}");
+    }
+}
+
+#[test]
+fn validate_reports_the_first_error_per_file() {
+    let dir = scratch("validate");
+    std::fs::write(dir.join("good.json"), r#"{"a": 1}"#).unwrap();
+    std::fs::write(dir.join("bad.json"), "{\"a\": 1,\n}").unwrap();
+    std::fs::write(dir.join("notes.txt"), "{}").unwrap();
+
+    assert_eq!(
+        run(&dir, &["validate", "good.json"]),
+        (0, String::new(), String::new())
+    );
+    let (code, out, _) = run(&dir, &["validate", "good.json", "bad.json"]);
+    assert_eq!(code, 1);
+    assert!(out.starts_with("bad.json:2:1: "), "{out}");
+
+    let (code, out, _) = run(&dir, &["validate", "--json", "bad.json"]);
+    assert_eq!(code, 1);
+    let report: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(
+        (report["file"].as_str(), report["valid"].as_bool()),
+        (Some("bad.json"), Some(false))
+    );
+
+    // a file type that cannot be inferred or read is a failure, not misuse
+    let (code, _, err) = run(&dir, &["validate", "notes.txt"]);
+    assert_eq!(code, 1);
+    assert!(err.contains("pass --type"), "{err}");
+    assert_eq!(run(&dir, &["validate", "--type", "json", "notes.txt"]).0, 0);
+    let (code, out, _) = run(&dir, &["validate", "--json", "missing.json"]);
+    assert_eq!(code, 1);
+    assert!(out.contains("\"error\":\"cannot read"), "{out}");
+}
+
+#[test]
+fn validate_multi_caps_the_errors() {
+    let dir = scratch("validate-multi");
+    std::fs::write(
+        dir.join("bad.json"),
+        "{\"a\": tru, \"b\": fals, \"c\": nul}",
+    )
+    .unwrap();
+
+    let (code, out, _) = run(&dir, &["validate-multi", "--max-errors", "2", "bad.json"]);
+    assert_eq!(code, 1);
+    assert_eq!(out.lines().count(), 2, "{out}");
+    let (code, out, _) = run(&dir, &["validate-multi", "--json", "bad.json"]);
+    assert_eq!(code, 1);
+    let report: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(report["errors"].as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn update_prints_or_writes_the_edit() {
+    let dir = scratch("update");
+    std::fs::write(dir.join("app.json"), r#"{"server": {"port": 80}}"#).unwrap();
+
+    let (code, out, _) = run(&dir, &["update", "app.json", "server.port", "8080"]);
+    assert_eq!((code, out.as_str()), (0, r#"{"server": {"port": 8080}}"#));
+    let (code, out, _) = run(
+        &dir,
+        &[
+            "update",
+            "--json",
+            "app.json",
+            r#"["server", "port"]"#,
+            "8080",
+        ],
+    );
+    assert_eq!(code, 0);
+    let report: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(report["changed"], true);
+    assert_eq!(report["content"], r#"{"server": {"port": 8080}}"#);
+
+    let (code, out, _) = run(
+        &dir,
+        &[
+            "update",
+            "--write",
+            "--json",
+            "app.json",
+            "server.port",
+            "8080",
+        ],
+    );
+    assert_eq!(code, 0);
+    assert_eq!(
+        out.trim(),
+        r#"{"file":"app.json","changed":true,"written":true}"#
+    );
+    let written = std::fs::read_to_string(dir.join("app.json")).unwrap();
+    assert_eq!(written, r#"{"server": {"port": 8080}}"#);
+
+    let (code, _, err) = run(&dir, &["update", "app.json", "server.host", "x"]);
+    assert_eq!(code, 1);
+    assert!(err.starts_with("app.json: "), "{err}");
+}
+
+#[test]
+fn format_prints_or_writes_pretty_json() {
+    let dir = scratch("format");
+    std::fs::write(dir.join("a.json"), r#"{"a": [1]}"#).unwrap();
+    std::fs::write(dir.join("b.json"), "{\n  \"b\": 2\n}\n").unwrap();
+
+    let (code, out, _) = run(&dir, &["format", "a.json"]);
+    assert_eq!((code, out.as_str()), (0, "{\n  \"a\": [\n    1\n  ]\n}\n"));
+    let (code, out, _) = run(&dir, &["format", "--write", "--json", "a.json", "b.json"]);
+    assert_eq!(code, 0);
+    let written: Vec<_> = out.lines().collect();
+    assert_eq!(
+        written,
+        [
+            r#"{"file":"a.json","changed":true,"written":true}"#,
+            r#"{"file":"b.json","changed":false,"written":false}"#,
+        ]
+    );
+    let (code, out, _) = run(&dir, &["format", "--json", "a.json"]);
+    assert_eq!(code, 0);
+    let report: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(
+        (report["changed"].as_bool(), report["content"].as_str()),
+        (Some(false), Some("{\n  \"a\": [\n    1\n  ]\n}\n"))
+    );
+
+    std::fs::write(dir.join("c.json"), "{").unwrap();
+    assert_eq!(run(&dir, &["format", "c.json"]).0, 1);
+}
@@ -0,0 +1,1133 @@
+//! Node.js N-API bindings over `parser_core::native`.
+//!
+//! Mirrors the wasm exports (same snake_case names, same result shapes) so
+//! backend services can apply byte-preserving edits without the browser
+//! glue. Results go through `serde_json::Value`, which serializes the result
+//! structs exactly as the wasm build's `to_js` does. Thrown errors carry
+//! the same `code` as the wasm build (`native::Error::code`).
+//!
+//! `validate_chunked` is not bound: it exists to keep a browser UI thread
+//! responsive, and Node callers can use a worker instead.
+
+use std::collections::HashMap;
+
+use napi::bindgen_prelude::{Buffer, Either};
+use napi::{Env, JsFunction, JsString};
+use napi_derive::napi;
+use parser_core::native::{
+    self, Error, Limits, LookupOptions, SchemaValidationOptions, UpdateOptions,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+type Result<T> = napi::Result<T, String>;
+
+fn to_napi(err: Error) -> napi::Error<String> {
+    napi::Error::new(err.code().to_string(), err.to_string())
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+fn span_lookups(spans: Vec<std::result::Result<native::Span, String>>) -> Value {
+    spans
+        .into_iter()
+        .map(|span| match span {
+            Ok(span) => to_value(&span),
+            Err(error) => json!({ "error": error }),
+        })
+        .collect()
+}
+
+/// `{ maxErrors, collectPositions, draft, reportDeprecated, profile,
+/// columnUnit, formatAssertion, disabledVocabularies, maxMessageValues,
+/// maxMessageLength }`, as accepted by the wasm schema exports.
+#[napi(object)]
+pub struct SchemaOptions {
+    pub max_errors: Option<u32>,
+    pub collect_positions: Option<bool>,
+    pub draft: Option<String>,
+    pub report_deprecated: Option<bool>,
+    pub profile: Option<bool>,
+    pub column_unit: Option<String>,
+    pub format_assertion: Option<bool>,
+    pub disabled_vocabularies: Option<Vec<String>>,
+    pub max_message_values: Option<u32>,
+    pub max_message_length: Option<u32>,
+}
+
+/// `{ columnUnit }` of `validate` / `validate_multi`.
+#[napi(object)]
+pub struct PositionOptions {
+    pub column_unit: Option<String>,
+}
+
+/// `{ columnUnit, groupByLine, xmlResync }` of `validate_multi`.
+#[napi(object)]
+pub struct MultiOptions {
+    pub column_unit: Option<String>,
+    pub group_by_line: Option<bool>,
+    pub xml_resync: Option<String>,
+}
+
+fn column_unit(label: Option<&str>) -> Option<native::ColumnUnit> {
+    label.and_then(native::ColumnUnit::parse)
+}
+
+fn position_unit(options: Option<PositionOptions>) -> Option<native::ColumnUnit> {
+    column_unit(options.and_then(|opts| opts.column_unit).as_deref())
+}
+
+fn multi_options(
+    options: Option<MultiOptions>,
+) -> (
+    Option<native::ColumnUnit>,
+    bool,
+    native::MultiValidationOptions,
+) {
+    options.map_or((None, false, Default::default()), |opts| {
+        let xml_resync = opts
+            .xml_resync
+            .as_deref()
+            .and_then(native::XmlResync::parse)
+            .unwrap_or_default();
+        (
+            column_unit(opts.column_unit.as_deref()),
+            opts.group_by_line == Some(true),
+            native::MultiValidationOptions { xml_resync },
+        )
+    })
+}
+
+fn schema_options(options: Option<SchemaOptions>) -> SchemaValidationOptions {
+    let mut opts = SchemaValidationOptions::default();
+    if let Some(options) = options {
+        if let Some(max) = options.max_errors.filter(|&max| max > 0) {
+            opts.max_errors = max as usize;
+        }
+        if let Some(flag) = options.collect_positions {
+            opts.collect_positions = flag;
+        }
+        if let Some(label) = options.draft {
+            opts.draft = native::parse_draft(&label);
+        }
+        if let Some(flag) = options.report_deprecated {
+            opts.report_deprecated = flag;
+        }
+        opts.profile = options.profile.unwrap_or(false);
+        opts.column_unit = column_unit(options.column_unit.as_deref());
+        opts.format_assertion = options.format_assertion;
+        opts.disabled_vocabularies = options
+            .disabled_vocabularies
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|label| native::Vocabulary::parse(label))
+            .collect();
+        opts.max_message_values = options.max_message_values.map(|max| max as usize);
+        opts.max_message_length = options.max_message_length.map(|max| max as usize);
+    }
+    opts
+}
+
+/// `{ maxBytes, maxTokens, maxErrors }`; missing or non-positive fields
+/// disable the limit.
+#[napi(object)]
+pub struct LimitOptions {
+    pub max_bytes: Option<f64>,
+    pub max_tokens: Option<f64>,
+    pub max_errors: Option<f64>,
+}
+
+fn positive(value: Option<f64>) -> Option<usize> {
+    value
+        .filter(|num| num.is_finite() && *num >= 1.0)
+        .map(|num| num as usize)
+}
+
+#[napi(js_name = "validate")]
+pub fn validate(
+    file_type: String,
+    content: String,
+    options: Option<PositionOptions>,
+) -> Result<Value> {
+    let mut result = native::validate(&file_type, &content).map_err(to_napi)?;
+    if let Some(unit) = position_unit(options) {
+        native::apply_column_unit(&mut result, &content, unit);
+    }
+    Ok(to_value(&result))
+}
+
+#[napi(js_name = "validate_multi")]
+pub fn validate_multi(
+    file_type: String,
+    content: String,
+    max_errors: Option<u32>,
+    options: Option<MultiOptions>,
+) -> Result<Value> {
+    let (unit, group, opts) = multi_options(options);
+    let mut result =
+        native::validate_multi_with(&file_type, &content, max_errors, &opts).map_err(to_napi)?;
+    if let Some(unit) = unit {
+        native::apply_column_unit(&mut result, &content, unit);
+    }
+    if group {
+        result = result.group_by_line();
+    }
+    Ok(to_value(&result))
+}
+
+/// `{ mixedContent, occurrence, lenient, rawText }` of a lookup:
+/// `"first"`, `"concat"` or `"error"`, `"first"`, `"last"` or a 0-based
+/// number, `true` to read JSON `NaN` / `Infinity`, and `true` to keep the
+/// indentation around XML text.
+#[napi(object)]
+pub struct LookupInput {
+    pub mixed_content: Option<String>,
+    pub occurrence: Option<Either<String, u32>>,
+    pub lenient: Option<bool>,
+    pub raw_text: Option<bool>,
+}
+
+/// Unknown values keep the default.
+fn lookup_options(input: LookupInput) -> LookupOptions {
+    LookupOptions {
+        mixed_content: input
+            .mixed_content
+            .as_deref()
+            .and_then(native::MixedContent::parse)
+            .unwrap_or_default(),
+        occurrence: match input.occurrence {
+            Some(Either::A(label)) => native::Occurrence::parse(&label).unwrap_or_default(),
+            Some(Either::B(n)) => native::Occurrence::Nth(n as usize),
+            None => native::Occurrence::default(),
+        },
+        lenient: input.lenient.unwrap_or(false),
+        raw_text: input.raw_text.unwrap_or(false),
+    }
+}
+
+fn lookup(options: Option<LookupInput>) -> LookupOptions {
+    options.map(lookup_options).unwrap_or_default()
+}
+
+/// `{ ...LookupInput, commentSpacing, skipValidation }` of `update_value`:
+/// `"preserve"`, `"align"` or `"single"`, and `true` to skip the structural
+/// check for content that was just validated.
+#[napi(object)]
+pub struct UpdateInput {
+    pub mixed_content: Option<String>,
+    pub occurrence: Option<Either<String, u32>>,
+    pub lenient: Option<bool>,
+    pub raw_text: Option<bool>,
+    pub comment_spacing: Option<String>,
+    pub skip_validation: Option<bool>,
+}
+
+fn update_options(options: Option<UpdateInput>) -> UpdateOptions {
+    let Some(opts) = options else {
+        return UpdateOptions::default();
+    };
+    UpdateOptions {
+        lookup: lookup_options(LookupInput {
+            mixed_content: opts.mixed_content,
+            occurrence: opts.occurrence,
+            lenient: opts.lenient,
+            raw_text: opts.raw_text,
+        }),
+        comment_spacing: opts
+            .comment_spacing
+            .as_deref()
+            .and_then(native::CommentSpacing::parse)
+            .unwrap_or_default(),
+        skip_validation: opts.skip_validation.unwrap_or(false),
+    }
+}
+
+#[napi(js_name = "update_value")]
+pub fn update_value(
+    file_type: String,
+    content: String,
+    path: Vec<String>,
+    new_val: String,
+    options: Option<UpdateInput>,
+) -> Result<String> {
+    native::update_value_with(
+        &file_type,
+        &content,
+        &path,
+        &new_val,
+        &update_options(options),
+    )
+    .map_err(to_napi)
+}
+
+#[napi(js_name = "get_string")]
+pub fn get_string(content: String, path: Vec<String>) -> Result<String> {
+    native::get_string(&content, &path).map_err(to_napi)
+}
+
+/// `{ decode, ...LookupInput }` of `get_value`.
+#[napi(object)]
+pub struct ReadOptions {
+    pub decode: Option<bool>,
+    pub mixed_content: Option<String>,
+    pub occurrence: Option<Either<String, u32>>,
+    pub lenient: Option<bool>,
+    pub raw_text: Option<bool>,
+}
+
+fn read_options(options: Option<ReadOptions>) -> (bool, LookupOptions) {
+    match options {
+        Some(opts) => (
+            opts.decode.unwrap_or(false),
+            lookup_options(LookupInput {
+                mixed_content: opts.mixed_content,
+                occurrence: opts.occurrence,
+                lenient: opts.lenient,
+                raw_text: opts.raw_text,
+            }),
+        ),
+        None => (false, LookupOptions::default()),
+    }
+}
+
+#[napi(js_name = "get_value")]
+pub fn get_value(
+    file_type: String,
+    content: String,
+    path: Vec<String>,
+    options: Option<ReadOptions>,
+) -> Result<String> {
+    let (decode, lookup) = read_options(options);
+    native::get_value_with(&file_type, &content, &path, decode, &lookup).map_err(to_napi)
+}
+
+#[napi(js_name = "xml_instructions")]
+pub fn xml_instructions(content: String) -> Result<Value> {
+    native::xml_instructions(&content)
+        .map(|instructions| to_value(&instructions))
+        .map_err(to_napi)
+}
+
+#[napi(object)]
+pub struct DiffInput {
+    pub key_attributes: Option<Vec<String>>,
+}
+
+#[napi(js_name = "diff")]
+pub fn diff(
+    file_type: String,
+    old: String,
+    new: String,
+    options: Option<DiffInput>,
+) -> Result<Value> {
+    let opts = native::DiffOptions {
+        key_attributes: options
+            .and_then(|options| options.key_attributes)
+            .unwrap_or_default(),
+    };
+    native::diff_with(&file_type, &old, &new, &opts)
+        .map(|entries| to_value(&entries))
+        .map_err(to_napi)
+}
+
+#[napi(object)]
+pub struct EqualsInput {
+    pub ignore_key_order: Option<bool>,
+}
+
+#[napi(js_name = "equals")]
+pub fn equals(
+    file_type: String,
+    a: String,
+    b: String,
+    options: Option<EqualsInput>,
+) -> Result<Value> {
+    let opts = native::EqualsOptions {
+        ignore_key_order: options
+            .and_then(|options| options.ignore_key_order)
+            .unwrap_or(false),
+    };
+    native::equals(&file_type, &a, &b, &opts)
+        .map(|result| to_value(&result))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "semantic_hash")]
+pub fn semantic_hash(file_type: String, content: String) -> Result<String> {
+    native::semantic_hash(&file_type, &content).map_err(to_napi)
+}
+
+#[napi(js_name = "xml_attributes")]
+pub fn xml_attributes(content: String, path: Vec<String>) -> Result<Value> {
+    native::xml_attributes(&content, &path)
+        .map(|attributes| to_value(&attributes))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "xml_comments")]
+pub fn xml_comments(content: String, path: Vec<String>) -> Result<Value> {
+    native::xml_comments(&content, &path)
+        .map(|comments| to_value(&comments))
+        .map_err(to_napi)
+}
+
+/// `{ expand }` of `resolve_env`; defaults to true.
+#[napi(object)]
+pub struct ResolveOptions {
+    pub expand: Option<bool>,
+}
+
+#[napi(js_name = "resolve_env")]
+pub fn resolve_env(content: String, options: Option<ResolveOptions>) -> Result<Value> {
+    let expand = options.and_then(|opts| opts.expand).unwrap_or(true);
+    native::resolve_env(&content, expand)
+        .map(|resolved| to_value(&resolved))
+        .map_err(to_napi)
+}
+
+#[napi(object)]
+pub struct OverlayInput {
+    pub arrays: Option<String>,
+    pub array_key: Option<String>,
+}
+
+#[napi(js_name = "overlay")]
+pub fn overlay(
+    base: String,
+    overlays: Vec<String>,
+    options: Option<OverlayInput>,
+) -> Result<String> {
+    let opts = options
+        .and_then(|input| {
+            let key = input.array_key.unwrap_or_else(|| "id".into());
+            native::ArrayStrategy::parse(input.arrays.as_deref()?, &key)
+        })
+        .map(|arrays| native::OverlayOptions { arrays })
+        .unwrap_or_default();
+    native::overlay(&base, &overlays, &opts).map_err(to_napi)
+}
+
+#[napi(object)]
+pub struct ConvertInput {
+    pub separator: Option<String>,
+    pub root: Option<String>,
+    pub infer_types: Option<bool>,
+    pub uppercase: Option<bool>,
+}
+
+fn convert_options(options: Option<ConvertInput>) -> native::ConvertOptions {
+    let mut opts = native::ConvertOptions::default();
+    if let Some(input) = options {
+        if let Some(separator) = input.separator {
+            opts.separator = separator;
+        }
+        if let Some(root) = input.root {
+            opts.root = root;
+        }
+        opts.infer_types = input.infer_types.unwrap_or(false);
+        opts.uppercase = input.uppercase.unwrap_or(false);
+    }
+    opts
+}
+
+#[napi(js_name = "convert")]
+pub fn convert(
+    from_type: String,
+    to_type: String,
+    content: String,
+    options: Option<ConvertInput>,
+) -> Result<String> {
+    native::convert(&from_type, &to_type, &content, &convert_options(options)).map_err(to_napi)
+}
+
+#[napi(js_name = "to_env")]
+pub fn to_env(content: String, options: Option<ConvertInput>) -> Result<String> {
+    native::to_env(&content, &convert_options(options)).map_err(to_napi)
+}
+
+#[napi(js_name = "env_to_json")]
+pub fn env_to_json(content: String, options: Option<ConvertInput>) -> Result<String> {
+    native::env_to_json(&content, &convert_options(options)).map_err(to_napi)
+}
+
+#[napi(object)]
+pub struct TemplateInput {
+    pub syntax: Option<String>,
+}
+
+fn template_options(options: Option<TemplateInput>) -> native::TemplateOptions {
+    let syntax = options
+        .and_then(|input| input.syntax)
+        .and_then(|label| native::PlaceholderSyntax::parse(&label))
+        .unwrap_or_default();
+    native::TemplateOptions { syntax }
+}
+
+#[napi(js_name = "render")]
+pub fn render(
+    file_type: String,
+    content: String,
+    variables: HashMap<String, String>,
+    options: Option<TemplateInput>,
+) -> Result<String> {
+    native::render(&file_type, &content, &variables, &template_options(options)).map_err(to_napi)
+}
+
+#[napi(js_name = "list_placeholders")]
+pub fn list_placeholders(
+    file_type: String,
+    content: String,
+    options: Option<TemplateInput>,
+) -> Result<Value> {
+    native::list_placeholders(&file_type, &content, &template_options(options))
+        .map(|found| to_value(&found))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "scan_secrets")]
+pub fn scan_secrets(file_type: String, content: String) -> Result<Value> {
+    native::scan_secrets(&file_type, &content)
+        .map(|found| to_value(&found))
+        .map_err(to_napi)
+}
+
+#[napi(object)]
+pub struct RedactInput {
+    pub placeholder: Option<String>,
+}
+
+#[napi(js_name = "redact")]
+pub fn redact(
+    file_type: String,
+    content: String,
+    paths: Vec<Vec<String>>,
+    options: Option<RedactInput>,
+) -> Result<String> {
+    let placeholder = options
+        .and_then(|input| input.placeholder)
+        .unwrap_or_else(|| native::REDACTED.into());
+    native::redact(&file_type, &content, &paths, &placeholder).map_err(to_napi)
+}
+
+#[napi(js_name = "sensitive_values")]
+pub fn sensitive_values(
+    file_type: String,
+    content: String,
+    paths: Vec<Vec<String>>,
+) -> Result<Value> {
+    native::sensitive_values(&file_type, &content, &paths)
+        .map(|values| to_value(&values))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "encrypted_values")]
+pub fn encrypted_values(file_type: String, content: String) -> Result<Value> {
+    native::encrypted_values(&file_type, &content)
+        .map(|values| to_value(&values))
+        .map_err(to_napi)
+}
+
+/// `script` is the steps array, or its JSON text.
+#[napi(js_name = "migrate")]
+pub fn migrate(file_type: String, content: String, script: Value) -> Result<Value> {
+    let script = match script {
+        Value::String(text) => text,
+        steps => steps.to_string(),
+    };
+    native::migrate(&file_type, &content, &script)
+        .map(|migration| to_value(&migration))
+        .map_err(to_napi)
+}
+
+/// One document of `find_duplicates`.
+#[napi(object)]
+pub struct ConfigFileInput {
+    pub name: String,
+    pub file_type: String,
+    pub content: String,
+}
+
+#[napi(js_name = "find_duplicates")]
+pub fn find_duplicates(files: Vec<ConfigFileInput>) -> Result<Value> {
+    let files: Vec<native::ConfigFile> = files
+        .into_iter()
+        .map(|file| native::ConfigFile {
+            name: file.name,
+            file_type: file.file_type,
+            content: file.content,
+        })
+        .collect();
+    native::find_duplicates(&files)
+        .map(|clusters| to_value(&clusters))
+        .map_err(to_napi)
+}
+
+/// One column of `env_matrix`.
+#[napi(object)]
+pub struct EnvironmentFileInput {
+    pub env: String,
+    pub content: String,
+}
+
+#[napi(js_name = "env_matrix")]
+pub fn env_matrix(file_type: String, files: Vec<EnvironmentFileInput>) -> Result<Value> {
+    let files: Vec<native::EnvironmentFile> = files
+        .into_iter()
+        .map(|file| native::EnvironmentFile {
+            env: file.env,
+            content: file.content,
+        })
+        .collect();
+    native::env_matrix(&file_type, &files)
+        .map(|matrix| to_value(&matrix))
+        .map_err(to_napi)
+}
+
+#[napi(object)]
+pub struct LintInput {
+    /// Rule id to `"off"`, `"warning"`, `"error"` or `[level, option]`.
+    pub rules: Option<Value>,
+}
+
+#[napi(js_name = "lint")]
+pub fn lint(file_type: String, content: String, config: Option<LintInput>) -> Result<Value> {
+    let rules = config
+        .and_then(|input| input.rules)
+        .unwrap_or_else(|| json!({}));
+    let config =
+        native::LintConfig::from_rules(&rules).map_err(|e| to_napi(Error::InvalidArgument(e)))?;
+    native::lint(&file_type, &content, &config)
+        .map(|result| to_value(&result))
+        .map_err(to_napi)
+}
+
+/// Runs the host's cipher on one value. Node's `crypto` is synchronous, so
+/// unlike the wasm exports the callback must return a string, not a promise.
+fn call_cipher(
+    env: &Env,
+    cipher: &JsFunction,
+    item: &native::SensitiveValue,
+) -> std::result::Result<String, Error> {
+    let run = || -> napi::Result<String> {
+        let value = env.create_string(&item.value)?.into_unknown();
+        let path = env.to_js_value(&item.path)?;
+        let result = cipher.call(None, &[value, path])?;
+        JsString::try_from(result)?.into_utf8()?.into_owned()
+    };
+    run().map_err(|e| Error::InvalidArgument(format!("Cipher callback failed: {}", e.reason)))
+}
+
+#[napi(js_name = "encrypt_values")]
+pub fn encrypt_values(
+    env: Env,
+    file_type: String,
+    content: String,
+    paths: Vec<Vec<String>>,
+    encrypt: JsFunction,
+) -> Result<String> {
+    native::encrypt_values(&file_type, &content, &paths, |item| {
+        call_cipher(&env, &encrypt, item)
+    })
+    .map_err(to_napi)
+}
+
+#[napi(js_name = "decrypt_values")]
+pub fn decrypt_values(
+    env: Env,
+    file_type: String,
+    content: String,
+    decrypt: JsFunction,
+) -> Result<String> {
+    native::decrypt_values(&file_type, &content, |item| {
+        call_cipher(&env, &decrypt, item)
+    })
+    .map_err(to_napi)
+}
+
+#[napi(js_name = "diff_env")]
+pub fn diff_env(old: String, new: String) -> Result<Value> {
+    native::diff_env(&old, &new)
+        .map(|entries| to_value(&entries))
+        .map_err(to_napi)
+}
+
+/// `{ groupByPrefix, blankLineBetweenGroups }` of `organize_env`; both
+/// default to true.
+#[napi(object)]
+pub struct OrganizeEnvInput {
+    pub group_by_prefix: Option<bool>,
+    pub blank_line_between_groups: Option<bool>,
+}
+
+#[napi(js_name = "organize_env")]
+pub fn organize_env(content: String, options: Option<OrganizeEnvInput>) -> Result<String> {
+    let mut opts = native::OrganizeEnvOptions::default();
+    if let Some(input) = options {
+        opts.group_by_prefix = input.group_by_prefix.unwrap_or(opts.group_by_prefix);
+        opts.blank_line_between_groups = input
+            .blank_line_between_groups
+            .unwrap_or(opts.blank_line_between_groups);
+    }
+    native::organize_env(&content, &opts).map_err(to_napi)
+}
+
+/// One layer of `merge_env`.
+#[napi(object)]
+pub struct EnvFileInput {
+    pub name: String,
+    pub content: String,
+}
+
+fn env_files(files: Vec<EnvFileInput>) -> Vec<native::EnvFile> {
+    files
+        .into_iter()
+        .map(|file| native::EnvFile {
+            name: file.name,
+            content: file.content,
+        })
+        .collect()
+}
+
+#[napi(js_name = "merge_env")]
+pub fn merge_env(files: Vec<EnvFileInput>) -> Result<Value> {
+    native::merge_env(&env_files(files))
+        .map(|merged| to_value(&merged))
+        .map_err(to_napi)
+}
+
+/// `{ files }` of `rename_env_key`: other files to rename in.
+#[napi(object)]
+pub struct RenameInput {
+    pub files: Option<Vec<EnvFileInput>>,
+}
+
+#[napi(js_name = "rename_env_key")]
+pub fn rename_env_key(
+    content: String,
+    old: String,
+    new: String,
+    options: Option<RenameInput>,
+) -> Result<Value> {
+    let files = env_files(options.and_then(|input| input.files).unwrap_or_default());
+    native::rename_env_key(&content, &old, &new, &files)
+        .map(|renamed| to_value(&renamed))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "escape_segment")]
+pub fn escape_segment(key: String) -> String {
+    native::escape_segment(&key)
+}
+
+#[napi(js_name = "key_segment")]
+pub fn key_segment(key: String) -> String {
+    native::key_segment(&key)
+}
+
+#[napi(js_name = "index_segment")]
+pub fn index_segment(index: u32) -> String {
+    native::index_segment(index as usize)
+}
+
+#[napi(js_name = "join_path")]
+pub fn join_path(path: Vec<String>) -> String {
+    native::join_path(&path)
+}
+
+#[napi(js_name = "split_path")]
+pub fn split_path(text: String) -> Vec<String> {
+    native::split_path(&text)
+}
+
+#[napi(js_name = "to_pointer")]
+pub fn to_pointer(path: Vec<String>) -> String {
+    native::to_pointer(&path)
+}
+
+#[napi(js_name = "from_pointer")]
+pub fn from_pointer(pointer: String) -> Result<Vec<String>> {
+    native::from_pointer(&pointer).map_err(to_napi)
+}
+
+#[napi(js_name = "path_at_offset")]
+pub fn path_at_offset(file_type: String, content: String, offset: u32) -> Result<Value> {
+    native::path_at_offset(&file_type, &content, offset as usize)
+        .map(|found| to_value(&found))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "suggest_keys")]
+pub fn suggest_keys(content: String, parent_path: Vec<String>) -> Result<Value> {
+    native::suggest_keys(&content, &parent_path)
+        .map(|suggestions| to_value(&suggestions))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "fuzzy_find")]
+pub fn fuzzy_find(file_type: String, content: String, query: String) -> Result<Value> {
+    native::fuzzy_find(&file_type, &content, &query)
+        .map(|matches| to_value(&matches))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "find_item")]
+pub fn find_item(
+    content: String,
+    array_path: Vec<String>,
+    field: String,
+    value: Value,
+) -> Result<Value> {
+    native::find_item(&content, &array_path, &field, &value)
+        .map(|found| to_value(&found))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "find_value_spans")]
+pub fn find_value_spans(
+    file_type: String,
+    content: String,
+    paths: Vec<Vec<String>>,
+) -> Result<Value> {
+    native::find_value_spans(&file_type, &content, &paths)
+        .map(span_lookups)
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "set_limits")]
+pub fn set_limits(options: Option<LimitOptions>) {
+    let limits = options.map_or_else(Limits::default, |options| Limits {
+        max_bytes: positive(options.max_bytes),
+        max_tokens: positive(options.max_tokens),
+        max_errors: positive(options.max_errors),
+    });
+    native::set_limits(limits);
+}
+
+#[napi(js_name = "clear_parse_cache")]
+pub fn clear_parse_cache() {
+    native::clear_parse_cache();
+}
+
+#[napi(js_name = "capabilities")]
+pub fn capabilities() -> Value {
+    to_value(&native::capabilities())
+}
+
+#[napi(js_name = "decode_bytes")]
+pub fn decode_bytes(bytes: Buffer) -> Result<Value> {
+    native::decode_bytes(&bytes)
+        .map(|decoded| to_value(&decoded))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "encode_text")]
+pub fn encode_text(content: String, encoding: String, bom: bool) -> Result<Buffer> {
+    let parsed = native::TextEncoding::parse(&encoding).ok_or_else(|| {
+        to_napi(Error::InvalidArgument(format!(
+            "Unsupported encoding: {encoding}"
+        )))
+    })?;
+    Ok(native::encode_text(&content, parsed, bom).into())
+}
+
+#[napi(js_name = "register_schema")]
+pub fn register_schema(schema_id: String, schema: String) -> Result<()> {
+    native::register_schema(&schema_id, &schema).map_err(to_napi)
+}
+
+#[napi(js_name = "export_schema")]
+pub fn export_schema(schema_id: String) -> Result<Buffer> {
+    native::export_schema(&schema_id)
+        .map(Buffer::from)
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "import_schema")]
+pub fn import_schema(schema_id: String, bytes: Buffer) -> Result<()> {
+    native::import_schema(&schema_id, &bytes).map_err(to_napi)
+}
+
+#[napi(object)]
+pub struct SchemaDocsInput {
+    /// `"json"` (default) or `"markdown"`.
+    pub format: Option<String>,
+}
+
+#[napi(js_name = "schema_docs")]
+pub fn schema_docs(schema_id: String, options: Option<SchemaDocsInput>) -> Result<Value> {
+    match options.and_then(|options| options.format).as_deref() {
+        None | Some("json") => native::schema_docs(&schema_id)
+            .map(|docs| to_value(&docs))
+            .map_err(to_napi),
+        Some("markdown") => native::schema_docs_markdown(&schema_id)
+            .map(Value::String)
+            .map_err(to_napi),
+        Some(other) => Err(to_napi(native::Error::InvalidArgument(format!(
+            "Unsupported docs format: {other}"
+        )))),
+    }
+}
+
+#[napi(js_name = "unused_keys")]
+pub fn unused_keys(content: String, schema_id: String) -> Result<Value> {
+    native::unused_keys(&content, &schema_id)
+        .map(|keys| to_value(&keys))
+        .map_err(to_napi)
+}
+
+#[napi(object)]
+pub struct InstantiateInput {
+    pub file_type: Option<String>,
+    pub include_optional: Option<bool>,
+    pub separator: Option<String>,
+    pub root: Option<String>,
+    pub uppercase: Option<bool>,
+}
+
+#[napi(js_name = "instantiate_schema")]
+pub fn instantiate_schema(schema_id: String, options: Option<InstantiateInput>) -> Result<String> {
+    let mut opts = native::InstantiateOptions::default();
+    if let Some(input) = options {
+        if let Some(file_type) = input.file_type {
+            opts.file_type = file_type;
+        }
+        opts.include_optional = input.include_optional.unwrap_or(false);
+        opts.convert = convert_options(Some(ConvertInput {
+            separator: input.separator,
+            root: input.root,
+            infer_types: None,
+            uppercase: input.uppercase,
+        }));
+    }
+    native::instantiate_schema(&schema_id, &opts).map_err(to_napi)
+}
+
+#[napi(js_name = "validate_schema")]
+pub fn validate_schema(
+    content: String,
+    schema: String,
+    options: Option<SchemaOptions>,
+) -> Result<Value> {
+    native::validate_schema(&content, &schema, &schema_options(options))
+        .map(|outcome| to_value(&outcome))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "validate_schema_with_id")]
+pub fn validate_schema_with_id(
+    content: String,
+    schema_id: String,
+    options: Option<SchemaOptions>,
+) -> Result<Value> {
+    native::validate_schema_with_id(&content, &schema_id, &schema_options(options))
+        .map(|outcome| to_value(&outcome))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "validate_env_schema")]
+pub fn validate_env_schema(
+    content: String,
+    schema: String,
+    options: Option<SchemaOptions>,
+) -> Result<Value> {
+    native::validate_env_schema(&content, &schema, &schema_options(options))
+        .map(|outcome| to_value(&outcome))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "validate_env_schema_with_id")]
+pub fn validate_env_schema_with_id(
+    content: String,
+    schema_id: String,
+    options: Option<SchemaOptions>,
+) -> Result<Value> {
+    native::validate_env_schema_with_id(&content, &schema_id, &schema_options(options))
+        .map(|outcome| to_value(&outcome))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "validate_schema_all")]
+pub fn validate_schema_all(
+    content: String,
+    schema_ids: Vec<String>,
+    options: Option<SchemaOptions>,
+) -> Result<Value> {
+    native::validate_schema_all(&content, &schema_ids, &schema_options(options))
+        .map(|outcome| to_value(&outcome))
+        .map_err(to_napi)
+}
+
+#[napi(js_name = "format")]
+pub fn format(file_type: String, content: String) -> Result<String> {
+    native::format(&file_type, &content).map_err(to_napi)
+}
+
+/// Parse-once handle, as the wasm `Document`.
+#[napi]
+pub struct Document {
+    inner: native::Document,
+}
+
+#[napi]
+impl Document {
+    #[napi(constructor)]
+    pub fn new(file_type: String, content: String) -> Result<Self> {
+        native::Document::new(&file_type, &content)
+            .map(|inner| Self { inner })
+            .map_err(to_napi)
+    }
+
+    #[napi(getter)]
+    pub fn content(&self) -> String {
+        self.inner.content().to_string()
+    }
+
+    #[napi(js_name = "set_content")]
+    pub fn set_content(&mut self, content: String) -> Result<()> {
+        self.inner.set_content(&content).map_err(to_napi)
+    }
+
+    #[napi(js_name = "snapshot")]
+    pub fn snapshot(&mut self) -> u32 {
+        self.inner.snapshot()
+    }
+
+    #[napi(js_name = "diff_since")]
+    pub fn diff_since(&self, id: u32) -> Result<Value> {
+        self.inner
+            .diff_since(id)
+            .map(|entries| to_value(&entries))
+            .map_err(to_napi)
+    }
+
+    #[napi(js_name = "restore")]
+    pub fn restore(&mut self, id: u32) -> Result<()> {
+        self.inner.restore(id).map_err(to_napi)
+    }
+
+    #[napi(js_name = "feed")]
+    pub fn feed(&mut self, chunk: String) -> Result<()> {
+        self.inner.feed(&chunk).map_err(to_napi)
+    }
+
+    #[napi(js_name = "finish")]
+    pub fn finish(&mut self) -> Result<Value> {
+        self.inner
+            .finish()
+            .map(|result| to_value(&result))
+            .map_err(to_napi)
+    }
+
+    #[napi(js_name = "validate")]
+    pub fn validate(&mut self, options: Option<PositionOptions>) -> Result<Value> {
+        let mut result = self.inner.validate().map_err(to_napi)?;
+        if let Some(unit) = position_unit(options) {
+            self.inner.apply_column_unit(&mut result, unit);
+        }
+        Ok(to_value(&result))
+    }
+
+    #[napi(js_name = "validate_multi")]
+    pub fn validate_multi(
+        &mut self,
+        max_errors: Option<u32>,
+        options: Option<MultiOptions>,
+    ) -> Result<Value> {
+        let (unit, group, opts) = multi_options(options);
+        let mut result = self
+            .inner
+            .validate_multi_with(max_errors, &opts)
+            .map_err(to_napi)?;
+        if let Some(unit) = unit {
+            self.inner.apply_column_unit(&mut result, unit);
+        }
+        if group {
+            result = result.group_by_line();
+        }
+        Ok(to_value(&result))
+    }
+
+    #[napi(js_name = "find_value_span")]
+    pub fn find_value_span(
+        &mut self,
+        path: Vec<String>,
+        options: Option<LookupInput>,
+    ) -> Result<Value> {
+        self.inner
+            .find_value_span_with(&path, &lookup(options))
+            .map(|span| to_value(&span))
+            .map_err(to_napi)
+    }
+
+    #[napi(js_name = "find_value_spans")]
+    pub fn find_value_spans(&mut self, paths: Vec<Vec<String>>) -> Result<Value> {
+        self.inner
+            .find_value_spans(&paths)
+            .map(span_lookups)
+            .map_err(to_napi)
+    }
+
+    #[napi(js_name = "get_string")]
+    pub fn get_string(&mut self, path: Vec<String>) -> Result<String> {
+        self.inner.get_string(&path).map_err(to_napi)
+    }
+
+    #[napi(js_name = "get_value")]
+    pub fn get_value(&mut self, path: Vec<String>, options: Option<ReadOptions>) -> Result<String> {
+        let (decode, lookup) = read_options(options);
+        self.inner
+            .get_value_with(&path, decode, &lookup)
+            .map_err(to_napi)
+    }
+
+    #[napi(js_name = "update_value")]
+    pub fn update_value(
+        &mut self,
+        path: Vec<String>,
+        new_val: String,
+        options: Option<UpdateInput>,
+    ) -> Result<String> {
+        self.inner
+            .update_value_with(&path, &new_val, &update_options(options))
+            .map_err(to_napi)
+    }
+
+    #[napi(js_name = "validate_schema_with_id")]
+    pub fn validate_schema_with_id(
+        &mut self,
+        schema_id: String,
+        options: Option<SchemaOptions>,
+    ) -> Result<Value> {
+        self.inner
+            .validate_schema_with_id(&schema_id, &schema_options(options))
+            .map(|outcome| to_value(&outcome))
+            .map_err(to_napi)
+    }
+
+    #[napi(js_name = "next_tokens")]
+    pub fn next_tokens(&mut self, max: u32) -> Result<Value> {
+        self.inner
+            .next_tokens(max as usize)
+            .map(|batch| to_value(&batch))
+            .map_err(to_napi)
+    }
+
+    #[napi(js_name = "reset_tokens")]
+    pub fn reset_tokens(&self) {
+        self.inner.reset_tokens();
+    }
+
+    #[napi(js_name = "offset_to_position")]
+    pub fn offset_to_position(&self, offset: u32) -> Value {
+        let (line, column) = self.inner.offset_to_position(offset as usize);
+        json!({ "line": line, "column": column })
+    }
+
+    #[napi(js_name = "position_to_offset")]
+    pub fn position_to_offset(&self, line: u32, column: u32) -> u32 {
+        self.inner
+            .position_to_offset(line as usize, column as usize) as u32
+    }
+}
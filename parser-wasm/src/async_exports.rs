@@ -0,0 +1,202 @@
+//! Promise-returning variants of the long-running exports.
+//!
+//! wasm runs on the calling thread, so these are cooperative: the work is
+//! split into steps and each step ends with a macrotask yield
+//! (`setTimeout(0)`), which lets the page paint and handle input. Each export
+//! accepts a `signal` (an `AbortSignal`, or any object with an `aborted`
+//! flag) that is checked between steps. An aborted call rejects with an
+//! `Error` whose `name` is `"AbortError"` and `code` is `"aborted"`.
+
+use js_sys::{Array, Function, Object, Promise, Reflect};
+use parser_core::native::{self, Error, ValidationStep};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+
+use crate::{
+    coded_error, column_unit_from_js, field, invalid_argument, multi_result,
+    schema_options_from_js, to_js, to_js_error, unsupported_validation_result,
+};
+
+/// Resolves on the next macrotask, or on the next microtask when the host
+/// has no `setTimeout`.
+async fn yield_now() -> Result<(), JsValue> {
+    let promise = Promise::new(&mut |resolve, _reject| {
+        let set_timeout = Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))
+            .ok()
+            .and_then(|f| f.dyn_into::<Function>().ok());
+        let scheduled = set_timeout.is_some_and(|set_timeout| {
+            set_timeout
+                .call2(&JsValue::NULL, &resolve, &JsValue::from_f64(0.0))
+                .is_ok()
+        });
+        if !scheduled {
+            let _ = resolve.call0(&JsValue::NULL);
+        }
+    });
+    JsFuture::from(promise).await.map(drop)
+}
+
+/// The `signal` option of an async export.
+struct AbortToken(Option<JsValue>);
+
+impl AbortToken {
+    fn from_options(options: Option<&Object>) -> Self {
+        Self(
+            options
+                .map(|obj| field(obj, "signal"))
+                .filter(JsValue::is_object),
+        )
+    }
+
+    fn check(&self) -> Result<(), JsValue> {
+        let aborted = self.0.as_ref().is_some_and(|signal| {
+            Reflect::get(signal, &JsValue::from_str("aborted"))
+                .map(|flag| flag.is_truthy())
+                .unwrap_or(false)
+        });
+        if aborted {
+            return Err(abort_error());
+        }
+        Ok(())
+    }
+}
+
+fn abort_error() -> JsValue {
+    let err = coded_error("aborted", "The operation was aborted");
+    err.set_name("AbortError");
+    err.into()
+}
+
+fn options_object(options: Option<JsValue>) -> Option<Object> {
+    options.filter(JsValue::is_object).map(Object::from)
+}
+
+/// `onProgress(processed, total)`; a throwing callback rejects the call.
+fn progress_callback(options: Option<&Object>) -> Option<Function> {
+    options.and_then(|obj| field(obj, "onProgress").dyn_into::<Function>().ok())
+}
+
+fn report(callback: Option<&Function>, processed: usize, total: usize) -> Result<(), JsValue> {
+    if let Some(callback) = callback {
+        callback.call2(
+            &JsValue::NULL,
+            &JsValue::from_f64(processed as f64),
+            &JsValue::from_f64(total as f64),
+        )?;
+    }
+    Ok(())
+}
+
+/// `validate` for large files: `{ chunkSize, signal, onProgress, columnUnit }`.
+/// Scans `chunkSize` bytes (64 KiB when 0 or missing) per step; `onProgress`
+/// receives bytes scanned and the content length.
+#[wasm_bindgen(unchecked_return_type = "Promise<ValidationResult>")]
+pub fn validate_async(file_type: String, content: String, options: Option<JsValue>) -> Promise {
+    future_to_promise(async move {
+        let options = options_object(options);
+        let token = AbortToken::from_options(options.as_ref());
+        let on_progress = progress_callback(options.as_ref());
+        let chunk = options
+            .as_ref()
+            .and_then(|obj| field(obj, "chunkSize").as_f64())
+            .filter(|size| size.is_finite() && *size >= 1.0)
+            .map_or(0, |size| size as usize);
+        let unit = column_unit_from_js(options.as_deref());
+
+        yield_now().await?;
+        token.check()?;
+        let mut doc = match native::Document::new(&file_type, &content) {
+            Ok(doc) => doc,
+            Err(Error::UnsupportedFileType(raw)) => {
+                return Ok(to_js(&unsupported_validation_result(&raw)))
+            }
+            Err(err) => return Err(to_js_error(err)),
+        };
+        let total = content.len();
+        loop {
+            match doc.validate_step(chunk).map_err(to_js_error)? {
+                ValidationStep::Done(mut summary) => {
+                    if let Some(unit) = unit {
+                        doc.apply_column_unit(&mut summary, unit);
+                    }
+                    report(on_progress.as_ref(), total, total)?;
+                    return Ok(to_js(&summary));
+                }
+                ValidationStep::Pending { processed } => {
+                    report(on_progress.as_ref(), processed, total)?;
+                    yield_now().await?;
+                    token.check()?;
+                }
+            }
+        }
+    })
+}
+
+/// `validate_multi` over `[{ fileType, content }]`, one file per step:
+/// `{ maxErrors, signal, onProgress, columnUnit, groupByLine, xmlResync }`.
+/// `onProgress` receives files done and the file count. Results keep input
+/// order.
+#[wasm_bindgen(unchecked_return_type = "Promise<MultiValidationResult[]>")]
+pub fn validate_batch_async(files: JsValue, options: Option<JsValue>) -> Promise {
+    future_to_promise(async move {
+        let files = files
+            .dyn_into::<Array>()
+            .map_err(|_| invalid_argument("Invalid files: must be an array"))?;
+        let options = options_object(options);
+        let token = AbortToken::from_options(options.as_ref());
+        let on_progress = progress_callback(options.as_ref());
+        let max_errors = options
+            .as_ref()
+            .and_then(|obj| field(obj, "maxErrors").as_f64())
+            .map(|max| max as u32);
+
+        let total = files.length() as usize;
+        let results = Array::new();
+        for (done, file) in files.iter().enumerate() {
+            yield_now().await?;
+            token.check()?;
+            let entry = file.dyn_into::<Object>().map_err(|_| {
+                invalid_argument("Invalid files: entries must be { fileType, content }")
+            })?;
+            let file_type = field(&entry, "fileType").as_string().unwrap_or_default();
+            let content = field(&entry, "content").as_string().unwrap_or_default();
+            let result = multi_result(&file_type, &content, max_errors, options.as_deref())?;
+            results.push(&to_js(&result));
+            report(on_progress.as_ref(), done + 1, total)?;
+        }
+        Ok(results.into())
+    })
+}
+
+/// `register_schema` without blocking the caller's frame: the schema is
+/// compiled after a yield, unless `{ signal }` was aborted by then.
+#[wasm_bindgen(unchecked_return_type = "Promise<void>")]
+pub fn register_schema_async(
+    schema_id: String,
+    schema: String,
+    options: Option<JsValue>,
+) -> Promise {
+    future_to_promise(async move {
+        let options = options_object(options);
+        let token = AbortToken::from_options(options.as_ref());
+        yield_now().await?;
+        token.check()?;
+        native::register_schema(&schema_id, &schema).map_err(to_js_error)?;
+        Ok(JsValue::UNDEFINED)
+    })
+}
+
+/// `validate_schema` (inline schema, compiled per call) after a yield;
+/// takes the schema options plus `signal`.
+#[wasm_bindgen(unchecked_return_type = "Promise<SchemaValidationResult>")]
+pub fn validate_schema_async(content: String, schema: String, options: Option<JsValue>) -> Promise {
+    future_to_promise(async move {
+        let token = AbortToken::from_options(options_object(options.clone()).as_ref());
+        let opts = schema_options_from_js(options);
+        yield_now().await?;
+        token.check()?;
+        native::validate_schema(&content, &schema, &opts)
+            .map(|outcome| to_js(&outcome))
+            .map_err(to_js_error)
+    })
+}
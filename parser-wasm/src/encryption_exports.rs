@@ -0,0 +1,71 @@
+//! Value encryption through a host-provided cipher.
+//!
+//! The cipher callback receives `(value, path)` and returns the ciphertext
+//! (or, for decryption, the plain text), either directly or as a
+//! `Promise`, so WebCrypto's async API can be used as is. The exports
+//! always return a `Promise<string>` with the new content. A callback that
+//! throws or rejects rejects the export with the same value.
+
+use js_sys::{Array, Function, Promise};
+use parser_core::native::{self, SensitiveValue};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+
+use crate::{invalid_argument, paths_from_js, to_js_error};
+
+/// Runs `cipher` on one value, awaiting it when it returns a promise.
+async fn call_cipher(cipher: &Function, item: &SensitiveValue) -> Result<String, JsValue> {
+    let path: Array = item.path.iter().map(|s| JsValue::from_str(s)).collect();
+    let mut result = cipher.call2(&JsValue::NULL, &JsValue::from_str(&item.value), &path)?;
+    if let Some(promise) = result.dyn_ref::<Promise>() {
+        result = JsFuture::from(promise.clone()).await?;
+    }
+    result
+        .as_string()
+        .ok_or_else(|| invalid_argument("The cipher callback must return a string"))
+}
+
+/// Encrypts the values at `paths` in place, storing each as
+/// `ENC[<ciphertext>]`; values already encrypted are skipped.
+#[wasm_bindgen(unchecked_return_type = "Promise<string>")]
+pub fn encrypt_values(
+    file_type: String,
+    content: String,
+    paths: JsValue,
+    encrypt: Function,
+) -> Result<Promise, JsValue> {
+    let paths = paths_from_js(paths)
+        .ok_or_else(|| invalid_argument("Invalid paths: must be an array of string arrays"))?;
+    let pending = native::sensitive_values(&file_type, &content, &paths).map_err(to_js_error)?;
+    Ok(future_to_promise(async move {
+        let mut sealed = Vec::with_capacity(pending.len());
+        for item in pending {
+            let value = native::seal(&call_cipher(&encrypt, &item).await?);
+            sealed.push(SensitiveValue { value, ..item });
+        }
+        native::store_values(&file_type, &content, &sealed)
+            .map(JsValue::from)
+            .map_err(to_js_error)
+    }))
+}
+
+/// Decrypts every `ENC[…]` value in place; `decrypt` receives the
+/// ciphertext without the marker.
+#[wasm_bindgen(unchecked_return_type = "Promise<string>")]
+pub fn decrypt_values(
+    file_type: String,
+    content: String,
+    decrypt: Function,
+) -> Result<Promise, JsValue> {
+    let pending = native::encrypted_values(&file_type, &content).map_err(to_js_error)?;
+    Ok(future_to_promise(async move {
+        let mut plain = Vec::with_capacity(pending.len());
+        for item in pending {
+            let value = call_cipher(&decrypt, &item).await?;
+            plain.push(SensitiveValue { value, ..item });
+        }
+        native::store_values(&file_type, &content, &plain)
+            .map(JsValue::from)
+            .map_err(to_js_error)
+    }))
+}
@@ -0,0 +1,155 @@
+//! `*_json` variants of the exports whose results can be large.
+//!
+//! They return the result serialized as one JSON string instead of a tree of
+//! JS objects: a single string crosses the wasm boundary in one copy, and a
+//! worker can `postMessage` it without a structured clone of every node.
+//! `JSON.parse` of the string gives exactly what the object-returning export
+//! returns; errors are thrown the same way.
+//!
+//! The `*_buffer` variants return the same JSON as UTF-8 in a fresh
+//! `ArrayBuffer`, which a worker can list as a transferable so the UI thread
+//! receives it without a copy.
+
+use js_sys::{ArrayBuffer, Uint8Array};
+use parser_core::native;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    column_unit_from_js, invalid_argument, multi_result, paths_from_js, schema_options_from_js,
+    string_array_from_js, to_js_error, validation_result, SpanLookup,
+};
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn to_buffer(json: String) -> ArrayBuffer {
+    Uint8Array::from(json.as_bytes()).buffer()
+}
+
+/// `validate` as a JSON string.
+#[wasm_bindgen]
+pub fn validate_json(
+    file_type: &str,
+    content: &str,
+    options: Option<JsValue>,
+) -> Result<String, JsValue> {
+    validation_result(file_type, content, column_unit_from_js(options.as_ref()))
+        .map(|summary| to_json(&summary))
+}
+
+/// `validate_multi` as a JSON string.
+#[wasm_bindgen]
+pub fn validate_multi_json(
+    file_type: &str,
+    content: &str,
+    max_errors: Option<u32>,
+    options: Option<JsValue>,
+) -> Result<String, JsValue> {
+    multi_result(file_type, content, max_errors, options.as_ref()).map(|result| to_json(&result))
+}
+
+/// `find_value_spans` as a JSON string.
+#[wasm_bindgen]
+pub fn find_value_spans_json(
+    file_type: &str,
+    content: &str,
+    paths: JsValue,
+) -> Result<String, JsValue> {
+    let paths = paths_from_js(paths)
+        .ok_or_else(|| invalid_argument("Invalid paths: must be an array of string arrays"))?;
+    let spans = native::find_value_spans(file_type, content, &paths).map_err(to_js_error)?;
+    Ok(to_json(&SpanLookup::collect(spans)))
+}
+
+/// `validate_schema` as a JSON string.
+#[wasm_bindgen]
+pub fn validate_schema_json(
+    content: &str,
+    schema: &str,
+    options: Option<JsValue>,
+) -> Result<String, JsValue> {
+    let opts = schema_options_from_js(options);
+    native::validate_schema(content, schema, &opts)
+        .map(|outcome| to_json(&outcome))
+        .map_err(to_js_error)
+}
+
+/// `validate_schema_with_id` as a JSON string.
+#[wasm_bindgen]
+pub fn validate_schema_with_id_json(
+    content: &str,
+    schema_id: &str,
+    options: Option<JsValue>,
+) -> Result<String, JsValue> {
+    let opts = schema_options_from_js(options);
+    native::validate_schema_with_id(content, schema_id, &opts)
+        .map(|outcome| to_json(&outcome))
+        .map_err(to_js_error)
+}
+
+/// `validate_schema_all` as a JSON string.
+#[wasm_bindgen]
+pub fn validate_schema_all_json(
+    content: &str,
+    schema_ids: JsValue,
+    options: Option<JsValue>,
+) -> Result<String, JsValue> {
+    let ids = string_array_from_js(schema_ids).unwrap_or_default();
+    let opts = schema_options_from_js(options);
+    native::validate_schema_all(content, &ids, &opts)
+        .map(|outcome| to_json(&outcome))
+        .map_err(to_js_error)
+}
+
+/// `validate_multi_json` as a transferable UTF-8 buffer.
+#[wasm_bindgen]
+pub fn validate_multi_buffer(
+    file_type: &str,
+    content: &str,
+    max_errors: Option<u32>,
+    options: Option<JsValue>,
+) -> Result<ArrayBuffer, JsValue> {
+    validate_multi_json(file_type, content, max_errors, options).map(to_buffer)
+}
+
+/// `find_value_spans_json` as a transferable UTF-8 buffer.
+#[wasm_bindgen]
+pub fn find_value_spans_buffer(
+    file_type: &str,
+    content: &str,
+    paths: JsValue,
+) -> Result<ArrayBuffer, JsValue> {
+    find_value_spans_json(file_type, content, paths).map(to_buffer)
+}
+
+/// `validate_schema_json` as a transferable UTF-8 buffer.
+#[wasm_bindgen]
+pub fn validate_schema_buffer(
+    content: &str,
+    schema: &str,
+    options: Option<JsValue>,
+) -> Result<ArrayBuffer, JsValue> {
+    validate_schema_json(content, schema, options).map(to_buffer)
+}
+
+/// `validate_schema_with_id_json` as a transferable UTF-8 buffer.
+#[wasm_bindgen]
+pub fn validate_schema_with_id_buffer(
+    content: &str,
+    schema_id: &str,
+    options: Option<JsValue>,
+) -> Result<ArrayBuffer, JsValue> {
+    validate_schema_with_id_json(content, schema_id, options).map(to_buffer)
+}
+
+/// `validate_schema_all_json` as a transferable UTF-8 buffer.
+#[wasm_bindgen]
+pub fn validate_schema_all_buffer(
+    content: &str,
+    schema_ids: JsValue,
+    options: Option<JsValue>,
+) -> Result<ArrayBuffer, JsValue> {
+    validate_schema_all_json(content, schema_ids, options).map(to_buffer)
+}
@@ -1,542 +1,1579 @@
-use js_sys::Array;
-use serde_json::Value;
-use wasm_bindgen::prelude::*;
-use xmlparser::{Error as XmlError, Tokenizer};
-
-#[global_allocator]
-static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
-
-mod env_parser;
-mod json_lexer;
-mod json_parser;
-mod multi_validation;
-mod schema;
-mod xml_parser;
-
-#[cfg(test)]
-mod tests;
-
-pub use env_parser::EnvParser;
-pub use json_parser::JsonParser;
-use multi_validation::{
-    infer_json_span, validate_json_multi, validate_xml_multi, DetailedError, MultiValidationResult,
-    MAX_MULTI_ERRORS,
+//! wasm-bindgen adapter over `parser_core::native`.
+//!
+//! Everything here is JS glue: argument decoding, `to_js` serialization of
+//! the core result types, and mapping [`Error`] to thrown values. Parsing,
+//! span resolution, limits and schema validation live in `parser-core`.
+
+use std::collections::HashMap;
+
+use js_sys::{Array, Object, Reflect};
+use parser_core::native::{
+    self, ColumnUnit, CommentSpacing, DetailedError, Error, LimitExceeded, Limits, LookupOptions,
+    MixedContent, MultiValidationResult, Occurrence, SchemaValidationOptions, Span, UpdateOptions,
+    ValidationResult,
 };
-pub use xml_parser::XmlParser;
+use serde::Serialize;
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+mod async_exports;
+mod encryption_exports;
+mod json_exports;
+
+// tsify without its wasm-bindgen feature only builds the declarations; emit
+// them into the generated `.d.ts` so `unchecked_return_type` names resolve.
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SPAN: &str = <native::Span as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_VALIDATION_RESULT: &str = <native::ValidationResult as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_DETAILED_ERROR: &str = <native::DetailedError as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_RELATED_LOCATION: &str = <native::RelatedLocation as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_ERROR_COUNTS: &str = <native::ErrorCounts as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_MULTI_VALIDATION_RESULT: &str = <native::MultiValidationResult as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SEVERITY: &str = <native::Severity as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SCHEMA_ERROR: &str = <native::SchemaErrorDescriptor as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SCHEMA_RESULT: &str = <native::SchemaOutcome as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PROFILE_TIMINGS: &str = <native::Timings as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_CAPABILITIES: &str = <native::Capabilities as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TOKEN_KIND: &str = <native::TokenKind as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TOKEN: &str = <native::Token as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TOKEN_BATCH: &str = <native::TokenBatch as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TEXT_ENCODING: &str = <native::TextEncoding as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_DECODED_TEXT: &str = <native::DecodedText as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SPAN_LOOKUP: &str = <SpanLookup as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_CHUNKED_RESULT: &str = <ChunkedResult as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_POSITION: &str = <Position as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_CHANGE_KIND: &str = <native::ChangeKind as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_DIFF_SIDE: &str = <native::DiffSide as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_DIFF_ENTRY: &str = <native::DiffEntry as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PROPERTY_DOC: &str = <native::PropertyDoc as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_UNUSED_KEY: &str = <native::UnusedKey as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_MATRIX_ROW: &str = <native::MatrixRow as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_ENV_MATRIX: &str = <native::EnvMatrix as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_EQUALITY: &str = <native::Equality as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PLACEHOLDER: &str = <native::Placeholder as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SECRET_KIND: &str = <native::SecretKind as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SECRET_FINDING: &str = <native::SecretFinding as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SENSITIVE_VALUE: &str = <native::SensitiveValue as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_DUPLICATE_OCCURRENCE: &str = <native::DuplicateOccurrence as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_DUPLICATE_CLUSTER: &str = <native::DuplicateCluster as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_STEP_STATUS: &str = <native::StepStatus as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_MIGRATION_STEP: &str = <native::MigrationStep as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_MIGRATION: &str = <native::Migration as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_LINT_DIAGNOSTIC: &str = <native::LintDiagnostic as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_LINT_FIX: &str = <native::LintFix as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_LINT_RESULT: &str = <native::LintResult as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PATH_AT_OFFSET: &str = <native::PathAtOffset as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PATH_ANCESTOR: &str = <native::Ancestor as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_ANCESTOR_KIND: &str = <native::AncestorKind as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_KEY_SUGGESTION: &str = <native::KeySuggestion as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_KEY_MATCH: &str = <native::KeyMatch as Tsify>::DECL;
+#[wasm_bindgen(typescript_custom_section)]
+const TS_FOUND_ITEM: &str = <native::FoundItem as Tsify>::DECL;
+#[cfg(feature = "xml")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_COMMENT_PLACEMENT: &str = <native::CommentPlacement as Tsify>::DECL;
+#[cfg(feature = "xml")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_XML_COMMENT: &str = <native::XmlComment as Tsify>::DECL;
+#[cfg(feature = "xml")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_XML_ATTRIBUTE: &str = <native::XmlAttribute as Tsify>::DECL;
+#[cfg(feature = "xml")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PSEUDO_ATTRIBUTE: &str = <native::PseudoAttribute as Tsify>::DECL;
+#[cfg(feature = "xml")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_XML_INSTRUCTION: &str = <native::XmlInstruction as Tsify>::DECL;
+#[cfg(feature = "env")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_ENV_DEFINITION: &str = <native::EnvDefinition as Tsify>::DECL;
+#[cfg(feature = "env")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_MERGED_ENTRY: &str = <native::MergedEntry as Tsify>::DECL;
+#[cfg(feature = "env")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_MERGED_ENV: &str = <native::MergedEnv as Tsify>::DECL;
+#[cfg(feature = "env")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_RESOLVED_ENTRY: &str = <native::ResolvedEntry as Tsify>::DECL;
+#[cfg(feature = "env")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_RESOLVED_ENV: &str = <native::ResolvedEnv as Tsify>::DECL;
+#[cfg(feature = "env")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_RENAME_KIND: &str = <native::RenameKind as Tsify>::DECL;
+#[cfg(feature = "env")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_RENAME_EDIT: &str = <native::RenameEdit as Tsify>::DECL;
+#[cfg(feature = "env")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_RENAMED_ENV_FILE: &str = <native::RenamedEnvFile as Tsify>::DECL;
+#[cfg(feature = "env")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_RENAMED_ENV: &str = <native::RenamedEnv as Tsify>::DECL;
+
+/// Converts a result struct into a plain JS object (maps become objects,
+/// numbers stay numbers) in one serialization pass.
+fn to_js<T: Serialize + ?Sized>(value: &T) -> JsValue {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .unwrap_or(JsValue::UNDEFINED)
+}
+
+/// Every export throws an `Error` whose `code` is [`Error::code`] and whose
+/// `message` is the core message, plus `span: { start, end }` when the error
+/// points at a byte range. Limit overruns also carry `limit`, `max` and
+/// `actual`.
+fn to_js_error(err: Error) -> JsValue {
+    if let Error::LimitExceeded(exceeded) = err {
+        return limit_error(exceeded);
+    }
+    let js = coded_error(err.code(), &err.to_string());
+    if let Some(span) = err.span() {
+        let _ = Reflect::set(&js, &JsValue::from_str("span"), &to_js(&span));
+    }
+    js.into()
+}
 
-/// Span represents a byte range in the original content
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Span {
-    pub start: usize,
-    pub end: usize,
+fn invalid_argument(message: &str) -> JsValue {
+    to_js_error(Error::InvalidArgument(message.to_string()))
 }
 
-impl Span {
-    pub fn new(start: usize, end: usize) -> Self {
-        Self { start, end }
+fn coded_error(code: &str, message: &str) -> js_sys::Error {
+    let err = js_sys::Error::new(message);
+    let _ = Reflect::set(&err, &JsValue::from_str("code"), &JsValue::from_str(code));
+    err
+}
+
+fn limit_error(exceeded: LimitExceeded) -> JsValue {
+    let err = coded_error("limit_exceeded", &exceeded.message());
+    let fields = [
+        ("limit", JsValue::from_str(exceeded.kind.as_str())),
+        ("max", JsValue::from_f64(exceeded.max as f64)),
+        ("actual", JsValue::from_f64(exceeded.actual as f64)),
+    ];
+    for (key, value) in fields {
+        let _ = Reflect::set(&err, &JsValue::from_str(key), &value);
+    }
+    err.into()
+}
+
+fn paths_from_js(value: JsValue) -> Option<Vec<Vec<String>>> {
+    let js_array = value.dyn_into::<Array>().ok()?;
+    js_array.iter().map(segments_from_js).collect()
+}
+
+/// A path array whose entries are strings or typed `{ key }` / `{ index }`
+/// segments; typed ones are encoded the way `key_segment` / `index_segment`
+/// spell them.
+fn segments_from_js(value: JsValue) -> Option<Vec<String>> {
+    let js_array = value.dyn_into::<Array>().ok()?;
+    js_array.iter().map(segment_from_js).collect()
+}
+
+fn segment_from_js(value: JsValue) -> Option<String> {
+    if let Some(text) = value.as_string() {
+        return Some(text);
+    }
+    if !value.is_object() {
+        return Some(String::new());
+    }
+    let obj = Object::from(value);
+    if let Some(key) = field(&obj, "key").as_string() {
+        return Some(native::key_segment(&key));
+    }
+    let index = field(&obj, "index").as_f64()?;
+    (index.is_finite() && index >= 0.0 && index.fract() == 0.0)
+        .then(|| native::index_segment(index as usize))
+}
+
+fn string_array_from_js(value: JsValue) -> Option<Vec<String>> {
+    let js_array = value.dyn_into::<Array>().ok()?;
+    Some(
+        js_array
+            .iter()
+            .map(|val| val.as_string().unwrap_or_default())
+            .collect(),
+    )
+}
+
+fn path_from_js(value: JsValue) -> Result<Vec<String>, JsValue> {
+    segments_from_js(value).ok_or_else(|| {
+        invalid_argument(
+            "Invalid path: must be an array of strings or { key } / { index } segments",
+        )
+    })
+}
+
+fn field(obj: &Object, key: &str) -> JsValue {
+    Reflect::get(obj, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+}
+
+fn positive_field(obj: &Object, key: &str) -> Option<usize> {
+    let num = field(obj, key).as_f64()?;
+    (num.is_finite() && num >= 1.0).then_some(num as usize)
+}
+
+fn limits_from_js(value: &JsValue) -> Limits {
+    let mut limits = Limits::default();
+    if value.is_object() {
+        let obj = Object::from(value.clone());
+        limits.max_bytes = positive_field(&obj, "maxBytes");
+        limits.max_tokens = positive_field(&obj, "maxTokens");
+        limits.max_errors = positive_field(&obj, "maxErrors");
+    }
+    limits
+}
+
+/// `columnUnit` of an options object; unknown units are ignored.
+fn column_unit_from_js(value: Option<&JsValue>) -> Option<ColumnUnit> {
+    let obj = value
+        .filter(|v| v.is_object())
+        .map(|v| Object::from(v.clone()))?;
+    field(&obj, "columnUnit")
+        .as_string()
+        .and_then(|label| ColumnUnit::parse(&label))
+}
+
+/// `{ mixedContent, occurrence, lenient, rawText }` of a lookup options
+/// object: `"first"`, `"concat"` or `"error"`, `"first"`, `"last"` or a
+/// 0-based number, `true` to read `NaN` / `Infinity`, and `true` to keep the
+/// indentation around XML text. Unknown values keep the default.
+fn lookup_options_from_js(value: Option<&JsValue>) -> LookupOptions {
+    let mut opts = LookupOptions::default();
+    let Some(obj) = value
+        .filter(|v| v.is_object())
+        .map(|v| Object::from(v.clone()))
+    else {
+        return opts;
+    };
+    if let Some(mixed) = field(&obj, "mixedContent")
+        .as_string()
+        .and_then(|label| MixedContent::parse(&label))
+    {
+        opts.mixed_content = mixed;
+    }
+    let occurrence = field(&obj, "occurrence");
+    if let Some(label) = occurrence.as_string() {
+        opts.occurrence = Occurrence::parse(&label).unwrap_or_default();
+    } else if let Some(n) = occurrence.as_f64() {
+        if n.is_finite() && n >= 0.0 && n.fract() == 0.0 {
+            opts.occurrence = Occurrence::Nth(n as usize);
+        }
+    }
+    opts.lenient = field(&obj, "lenient").as_bool().unwrap_or(false);
+    opts.raw_text = field(&obj, "rawText").as_bool().unwrap_or(false);
+    opts
+}
+
+/// Lookup options plus `commentSpacing` (`"preserve"`, `"align"` or
+/// `"single"`) and `skipValidation` of an `update_value` options object.
+fn update_options_from_js(value: Option<&JsValue>) -> UpdateOptions {
+    let mut opts = UpdateOptions::from(lookup_options_from_js(value));
+    let Some(obj) = value
+        .filter(|v| v.is_object())
+        .map(|v| Object::from(v.clone()))
+    else {
+        return opts;
+    };
+    if let Some(spacing) = field(&obj, "commentSpacing")
+        .as_string()
+        .and_then(|label| CommentSpacing::parse(&label))
+    {
+        opts.comment_spacing = spacing;
+    }
+    opts.skip_validation = field(&obj, "skipValidation").as_bool().unwrap_or(false);
+    opts
+}
+
+/// `keyAttributes` of a `diff` options object; non-string entries are
+/// skipped.
+fn diff_options_from_js(value: Option<JsValue>) -> native::DiffOptions {
+    let key_attributes = value
+        .filter(JsValue::is_object)
+        .map(|v| field(&Object::from(v), "keyAttributes"))
+        .and_then(|keys| keys.dyn_into::<Array>().ok())
+        .map(|keys| keys.iter().filter_map(|key| key.as_string()).collect())
+        .unwrap_or_default();
+    native::DiffOptions { key_attributes }
+}
+
+/// `{ arrays, arrayKey }` of an `overlay` options object; `arrayKey`
+/// defaults to `"id"`, unknown strategies to `"replace"`.
+fn overlay_options_from_js(value: Option<JsValue>) -> native::OverlayOptions {
+    let Some(obj) = value.filter(JsValue::is_object).map(Object::from) else {
+        return native::OverlayOptions::default();
+    };
+    let key = field(&obj, "arrayKey")
+        .as_string()
+        .unwrap_or_else(|| "id".into());
+    native::OverlayOptions {
+        arrays: field(&obj, "arrays")
+            .as_string()
+            .and_then(|label| native::ArrayStrategy::parse(&label, &key))
+            .unwrap_or_default(),
+    }
+}
+
+/// `{ separator, root, inferTypes, uppercase }` of `convert`, `to_env` and
+/// `env_to_json`; missing fields keep
+/// their defaults.
+fn convert_options_from_js(value: Option<JsValue>) -> native::ConvertOptions {
+    let mut opts = native::ConvertOptions::default();
+    let Some(obj) = value.filter(JsValue::is_object).map(Object::from) else {
+        return opts;
+    };
+    if let Some(separator) = field(&obj, "separator").as_string() {
+        opts.separator = separator;
+    }
+    if let Some(root) = field(&obj, "root").as_string() {
+        opts.root = root;
+    }
+    opts.infer_types = field(&obj, "inferTypes").as_bool() == Some(true);
+    opts.uppercase = field(&obj, "uppercase").as_bool() == Some(true);
+    opts
+}
+
+/// `{ syntax }` of `render` and `list_placeholders`: `"${}"` (default) or
+/// `"{{}}"`.
+fn template_options_from_js(value: Option<JsValue>) -> native::TemplateOptions {
+    let syntax = value
+        .filter(JsValue::is_object)
+        .map(|v| field(&Object::from(v), "syntax"))
+        .and_then(|label| label.as_string())
+        .and_then(|label| native::PlaceholderSyntax::parse(&label))
+        .unwrap_or_default();
+    native::TemplateOptions { syntax }
+}
+
+/// `{ xmlResync }` of `validate_multi`: `"markup"` (default) or `"nextTag"`.
+fn multi_options_from_js(value: Option<&JsValue>) -> native::MultiValidationOptions {
+    let xml_resync = value
+        .filter(|v| v.is_object())
+        .map(|v| field(&Object::from(v.clone()), "xmlResync"))
+        .and_then(|label| label.as_string())
+        .and_then(|label| native::XmlResync::parse(&label))
+        .unwrap_or_default();
+    native::MultiValidationOptions { xml_resync }
+}
+
+/// The string properties of a `{ name: value }` object.
+fn variables_from_js(value: JsValue) -> Option<HashMap<String, String>> {
+    if !value.is_object() {
+        return None;
+    }
+    Object::entries(&Object::from(value))
+        .iter()
+        .map(|pair| {
+            let pair = Array::from(&pair);
+            Some((pair.get(0).as_string()?, pair.get(1).as_string()?))
+        })
+        .collect()
+}
+
+/// `[{ name, content }]` ENV files.
+#[cfg(feature = "env")]
+fn env_files_from_js(files: JsValue) -> Result<Vec<native::EnvFile>, JsValue> {
+    let invalid = || invalid_argument("Invalid files: must be an array of { name, content }");
+    files
+        .dyn_into::<Array>()
+        .map_err(|_| invalid())?
+        .iter()
+        .map(|file| {
+            let obj = file.dyn_into::<Object>().ok()?;
+            Some(native::EnvFile {
+                name: field(&obj, "name").as_string()?,
+                content: field(&obj, "content").as_string()?,
+            })
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(invalid)
+}
+
+/// `decode` of a `get_value` options object; off unless `true`.
+fn decode_from_js(value: Option<JsValue>) -> bool {
+    value
+        .filter(JsValue::is_object)
+        .map(Object::from)
+        .is_some_and(|obj| field(&obj, "decode").as_bool() == Some(true))
+}
+
+/// `{ expand }` of `resolve_env`; references are expanded unless
+/// `expand: false`.
+#[cfg(feature = "env")]
+fn expand_from_js(value: Option<JsValue>) -> bool {
+    value
+        .filter(JsValue::is_object)
+        .map(Object::from)
+        .is_none_or(|obj| field(&obj, "expand").as_bool() != Some(false))
+}
+
+/// `{ maxErrors, collectPositions, draft, reportDeprecated, profile,
+/// columnUnit, formatAssertion, disabledVocabularies, maxMessageValues,
+/// maxMessageLength }`; the core clamps `maxErrors`. Unknown vocabulary
+/// names are ignored.
+fn schema_options_from_js(value: Option<JsValue>) -> SchemaValidationOptions {
+    let mut opts = SchemaValidationOptions::default();
+    let Some(obj) = value.filter(JsValue::is_object).map(Object::from) else {
+        return opts;
+    };
+    if let Some(num) = field(&obj, "maxErrors").as_f64() {
+        if num.is_finite() && num > 0.0 {
+            opts.max_errors = num as usize;
+        }
+    }
+    if let Some(flag) = field(&obj, "collectPositions").as_bool() {
+        opts.collect_positions = flag;
+    }
+    if let Some(label) = field(&obj, "draft").as_string() {
+        opts.draft = native::parse_draft(&label);
+    }
+    if let Some(flag) = field(&obj, "reportDeprecated").as_bool() {
+        opts.report_deprecated = flag;
+    }
+    opts.profile = field(&obj, "profile").as_bool().unwrap_or(false);
+    opts.column_unit = column_unit_from_js(Some(&obj));
+    opts.format_assertion = field(&obj, "formatAssertion").as_bool();
+    let vocabularies = field(&obj, "disabledVocabularies");
+    if Array::is_array(&vocabularies) {
+        opts.disabled_vocabularies = Array::from(&vocabularies)
+            .iter()
+            .filter_map(|label| native::Vocabulary::parse(&label.as_string()?))
+            .collect();
+    }
+    let count = |key: &str| {
+        field(&obj, key)
+            .as_f64()
+            .filter(|num| num.is_finite() && *num >= 0.0)
+            .map(|num| num as usize)
+    };
+    opts.max_message_values = count("maxMessageValues");
+    opts.max_message_length = count("maxMessageLength");
+    opts
+}
+
+/// One entry of a batched span lookup.
+#[derive(Debug, Serialize, Tsify)]
+#[serde(untagged)]
+enum SpanLookup {
+    Found(Span),
+    Missing { error: String },
+}
+
+impl SpanLookup {
+    fn collect(spans: Vec<Result<Span, String>>) -> Vec<Self> {
+        spans
+            .into_iter()
+            .map(|span| match span {
+                Ok(span) => SpanLookup::Found(span),
+                Err(error) => SpanLookup::Missing { error },
+            })
+            .collect()
     }
+}
+
+#[derive(Serialize, Tsify)]
+#[serde(untagged)]
+enum ChunkedResult {
+    Done {
+        #[serde(flatten)]
+        result: ValidationResult,
+        cancelled: bool,
+    },
+    Cancelled {
+        cancelled: bool,
+        processed: usize,
+        total: usize,
+    },
+}
+
+#[derive(Serialize, Tsify)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+fn unsupported_message(file_type: &str) -> String {
+    format!("Unsupported file type: {}", file_type.to_lowercase())
+}
 
-    pub fn len(&self) -> usize {
-        self.end - self.start
+/// `validate` reports an unknown file type as an invalid result, not a throw.
+fn unsupported_validation_result(file_type: &str) -> ValidationResult {
+    ValidationResult {
+        valid: false,
+        message: Some(unsupported_message(file_type)),
+        line: None,
+        column: None,
+        span: None,
     }
 }
 
+fn unsupported_multi_result(file_type: &str) -> MultiValidationResult {
+    let summary = DetailedError {
+        message: unsupported_message(file_type),
+        code: None,
+        line: 1,
+        column: 1,
+        span: Span::new(0, 0),
+        sub_errors: Vec::new(),
+        related: Vec::new(),
+    };
+    MultiValidationResult::single(summary)
+}
+
+/// `options`: `{ mixedContent, occurrence, lenient, rawText }`, as for
+/// `get_value`, and `commentSpacing`: the gap kept before an ENV value's
+/// trailing `# comment`, `"preserve"` (default), `"align"` or `"single"`.
+/// The comment itself is always kept. `skipValidation` skips the structural
+/// check for content the caller has just validated.
 #[wasm_bindgen]
 pub fn update_value(
     file_type: &str,
     content: &str,
     path: JsValue,
     new_val: &str,
+    options: Option<JsValue>,
 ) -> Result<String, JsValue> {
-    let path: Vec<String> = if let Ok(js_array) = path.dyn_into::<Array>() {
-        js_array
-            .iter()
-            .map(|val| val.as_string().unwrap_or_default())
-            .collect()
-    } else {
-        return Err(JsValue::from_str(
-            "Invalid path: must be an array of strings",
-        ));
+    let path = path_from_js(path)?;
+    let update = update_options_from_js(options.as_ref());
+    native::update_value_with(file_type, content, &path, new_val, &update).map_err(to_js_error)
+}
+
+/// The JSON value at `path` with string escapes decoded, for showing the
+/// text a user edits; non-string values are returned as written.
+#[wasm_bindgen]
+pub fn get_string(content: &str, path: JsValue) -> Result<String, JsValue> {
+    let path = path_from_js(path)?;
+    native::get_string(content, &path).map_err(to_js_error)
+}
+
+/// The text of the value at `path`: as written, or with `{ decode: true }`
+/// unescaped (JSON escapes, XML entity and character references, ENV quotes)
+/// so that writing it back through `update_value` does not double-escape it.
+/// `mixedContent` picks the text of an XML element that also has child
+/// elements: `"first"` (default), `"concat"` or `"error"`. `occurrence`
+/// picks among a JSON object's repeated keys: `"first"` (default), `"last"`
+/// or a 0-based number. `lenient` reads JSON with `NaN` or `Infinity`
+/// values, which `validate` still reports. XML text spans leave out the
+/// indentation around a value; `rawText` keeps it.
+#[wasm_bindgen]
+pub fn get_value(
+    file_type: &str,
+    content: &str,
+    path: JsValue,
+    options: Option<JsValue>,
+) -> Result<String, JsValue> {
+    let path = path_from_js(path)?;
+    let lookup = lookup_options_from_js(options.as_ref());
+    native::get_value_with(file_type, content, &path, decode_from_js(options), &lookup)
+        .map_err(to_js_error)
+}
+
+/// Paths added, removed or changed from `old` to `new`, with the value text
+/// and span in each version, e.g. to show what changed on disk. XML
+/// siblings are paired by `keyAttributes` (`{ keyAttributes: ["id"] }`).
+#[wasm_bindgen(unchecked_return_type = "DiffEntry[]")]
+pub fn diff(
+    file_type: &str,
+    old: &str,
+    new: &str,
+    options: Option<JsValue>,
+) -> Result<JsValue, JsValue> {
+    native::diff_with(file_type, old, new, &diff_options_from_js(options))
+        .map(|entries| to_js(&entries))
+        .map_err(to_js_error)
+}
+
+/// Whether `a` and `b` hold the same configuration regardless of formatting,
+/// with the first path where they differ; `{ ignoreKeyOrder: true }` also
+/// accepts reordered keys.
+#[wasm_bindgen(unchecked_return_type = "Equality")]
+pub fn equals(
+    file_type: &str,
+    a: &str,
+    b: &str,
+    options: Option<JsValue>,
+) -> Result<JsValue, JsValue> {
+    let ignore_key_order = options
+        .filter(JsValue::is_object)
+        .map(|v| field(&Object::from(v), "ignoreKeyOrder").as_bool() == Some(true))
+        .unwrap_or(false);
+    let opts = native::EqualsOptions { ignore_key_order };
+    native::equals(file_type, a, b, &opts)
+        .map(|result| to_js(&result))
+        .map_err(to_js_error)
+}
+
+/// A 16-hex-digit hash of what `content` means, unchanged by formatting,
+/// key order or quoting; compare it across reloads to spot real changes.
+#[wasm_bindgen]
+pub fn semantic_hash(file_type: &str, content: &str) -> Result<String, JsValue> {
+    native::semantic_hash(file_type, content).map_err(to_js_error)
+}
+
+/// Deep-merges JSON `overlays` (`string[]`, later ones winning) into `base`,
+/// keeping the base's formatting wherever a value is unchanged. Arrays are
+/// replaced unless `{ arrays: "concat" }` or `{ arrays: "mergeByKey",
+/// arrayKey: "name" }` is passed.
+#[wasm_bindgen]
+pub fn overlay(base: &str, overlays: JsValue, options: Option<JsValue>) -> Result<String, JsValue> {
+    let overlays = string_array_from_js(overlays)
+        .ok_or_else(|| invalid_argument("Invalid overlays: must be an array of strings"))?;
+    native::overlay(base, &overlays, &overlay_options_from_js(options)).map_err(to_js_error)
+}
+
+/// `content` rewritten from `from_type` to `to_type` (`"json"`, `"xml"` or
+/// `"env"`). ENV keys nest on `separator` (default `"__"`), JSON that is
+/// not a single-key object is wrapped in a `root` element, and
+/// `inferTypes: true` reads numbers and booleans out of ENV and XML text.
+#[wasm_bindgen]
+pub fn convert(
+    from_type: &str,
+    to_type: &str,
+    content: &str,
+    options: Option<JsValue>,
+) -> Result<String, JsValue> {
+    native::convert(
+        from_type,
+        to_type,
+        content,
+        &convert_options_from_js(options),
+    )
+    .map_err(to_js_error)
+}
+
+/// A JSON config as `.env` lines, nested keys joined with `separator`
+/// (default `"__"`) and upper-cased with `uppercase: true`.
+#[cfg(feature = "env")]
+#[wasm_bindgen]
+pub fn to_env(content: &str, options: Option<JsValue>) -> Result<String, JsValue> {
+    native::to_env(content, &convert_options_from_js(options)).map_err(to_js_error)
+}
+
+/// The nested JSON document an ENV file's `separator`-delimited keys
+/// (default `"__"`) describe; `inferTypes: true` turns numbers and booleans
+/// into JSON ones.
+#[cfg(feature = "env")]
+#[wasm_bindgen]
+pub fn env_to_json(content: &str, options: Option<JsValue>) -> Result<String, JsValue> {
+    native::env_to_json(content, &convert_options_from_js(options)).map_err(to_js_error)
+}
+
+/// `content` with each placeholder named in `variables` (`{ NAME: "value" }`)
+/// replaced by its value, escaped for the value it sits in. Other bytes,
+/// and placeholders without a variable, stay as written.
+#[wasm_bindgen]
+pub fn render(
+    file_type: &str,
+    content: &str,
+    variables: JsValue,
+    options: Option<JsValue>,
+) -> Result<String, JsValue> {
+    let variables = variables_from_js(variables)
+        .ok_or_else(|| invalid_argument("Invalid variables: must be an object of string values"))?;
+    native::render(
+        file_type,
+        content,
+        &variables,
+        &template_options_from_js(options),
+    )
+    .map_err(to_js_error)
+}
+
+/// Placeholders in the values of `content`, each with its variable name,
+/// the path of its value and its own span.
+#[wasm_bindgen(unchecked_return_type = "Placeholder[]")]
+pub fn list_placeholders(
+    file_type: &str,
+    content: &str,
+    options: Option<JsValue>,
+) -> Result<JsValue, JsValue> {
+    native::list_placeholders(file_type, content, &template_options_from_js(options))
+        .map(|found| to_js(&found))
+        .map_err(to_js_error)
+}
+
+/// Likely secrets in the values of `content` (AWS keys, private key
+/// blocks, known token prefixes, high-entropy strings), located by path,
+/// span and position without echoing them.
+#[wasm_bindgen(unchecked_return_type = "SecretFinding[]")]
+pub fn scan_secrets(file_type: &str, content: &str) -> Result<JsValue, JsValue> {
+    native::scan_secrets(file_type, content)
+        .map(|found| to_js(&found))
+        .map_err(to_js_error)
+}
+
+/// `content` with the value at each of `paths` replaced by `"[REDACTED]"`,
+/// or `{ placeholder }`.
+#[wasm_bindgen]
+pub fn redact(
+    file_type: &str,
+    content: &str,
+    paths: JsValue,
+    options: Option<JsValue>,
+) -> Result<String, JsValue> {
+    let paths = paths_from_js(paths)
+        .ok_or_else(|| invalid_argument("Invalid paths: must be an array of string arrays"))?;
+    let placeholder = options
+        .filter(JsValue::is_object)
+        .and_then(|v| field(&Object::from(v), "placeholder").as_string())
+        .unwrap_or_else(|| native::REDACTED.into());
+    native::redact(file_type, content, &paths, &placeholder).map_err(to_js_error)
+}
+
+/// Plain text of the values at `paths` that are not encrypted yet, for
+/// hosts that run their cipher outside `encrypt_values`.
+#[wasm_bindgen(unchecked_return_type = "SensitiveValue[]")]
+pub fn sensitive_values(
+    file_type: &str,
+    content: &str,
+    paths: JsValue,
+) -> Result<JsValue, JsValue> {
+    let paths = paths_from_js(paths)
+        .ok_or_else(|| invalid_argument("Invalid paths: must be an array of string arrays"))?;
+    native::sensitive_values(file_type, content, &paths)
+        .map(|values| to_js(&values))
+        .map_err(to_js_error)
+}
+
+/// Every `ENC[…]` value with its path and ciphertext.
+#[wasm_bindgen(unchecked_return_type = "SensitiveValue[]")]
+pub fn encrypted_values(file_type: &str, content: &str) -> Result<JsValue, JsValue> {
+    native::encrypted_values(file_type, content)
+        .map(|values| to_js(&values))
+        .map_err(to_js_error)
+}
+
+/// Applies a migration script (a JSON array of steps, as text or as an
+/// array) to `content`, reporting each step.
+#[wasm_bindgen(unchecked_return_type = "Migration")]
+pub fn migrate(file_type: &str, content: &str, script: JsValue) -> Result<JsValue, JsValue> {
+    let script = match script.as_string() {
+        Some(text) => text,
+        None => js_sys::JSON::stringify(&script)
+            .ok()
+            .and_then(|text| text.as_string())
+            .ok_or_else(|| {
+                invalid_argument("Invalid migration script: must be an array of steps")
+            })?,
     };
+    native::migrate(file_type, content, &script)
+        .map(|migration| to_js(&migration))
+        .map_err(to_js_error)
+}
 
-    if path.is_empty() {
-        return Err(JsValue::from_str("Path cannot be empty"));
-    }
+/// String values set under the same key in two or more of `files`
+/// (`[{ name, fileType, content }]`), clustered with every file, path and
+/// span they occur at.
+#[wasm_bindgen(unchecked_return_type = "DuplicateCluster[]")]
+pub fn find_duplicates(files: JsValue) -> Result<JsValue, JsValue> {
+    let invalid =
+        || invalid_argument("Invalid files: must be an array of { name, fileType, content }");
+    let files = files
+        .dyn_into::<Array>()
+        .map_err(|_| invalid())?
+        .iter()
+        .map(|file| {
+            let obj = file.dyn_into::<Object>().ok()?;
+            Some(native::ConfigFile {
+                name: field(&obj, "name").as_string()?,
+                file_type: field(&obj, "fileType").as_string()?,
+                content: field(&obj, "content").as_string()?,
+            })
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(invalid)?;
+    native::find_duplicates(&files)
+        .map(|clusters| to_js(&clusters))
+        .map_err(to_js_error)
+}
 
-    let result = match file_type.to_lowercase().as_str() {
-        "json" => {
-            let parser = JsonParser::new();
-            parser
-                .validate_syntax(content)
-                .map_err(|e| JsValue::from_str(&e))?;
-            let span = parser
-                .find_value_span(content, &path)
-                .map_err(|e| JsValue::from_str(&e))?;
+/// Each leaf path of the per-environment `files` (`[{ env, content }]`)
+/// with its value in every environment, which environments lack it and
+/// whether it is the same everywhere.
+#[wasm_bindgen(unchecked_return_type = "EnvMatrix")]
+pub fn env_matrix(file_type: &str, files: JsValue) -> Result<JsValue, JsValue> {
+    let invalid = || invalid_argument("Invalid files: must be an array of { env, content }");
+    let files = files
+        .dyn_into::<Array>()
+        .map_err(|_| invalid())?
+        .iter()
+        .map(|file| {
+            let obj = file.dyn_into::<Object>().ok()?;
+            Some(native::EnvironmentFile {
+                env: field(&obj, "env").as_string()?,
+                content: field(&obj, "content").as_string()?,
+            })
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(invalid)?;
+    native::env_matrix(file_type, &files)
+        .map(|matrix| to_js(&matrix))
+        .map_err(to_js_error)
+}
 
-            let escaped_value = if is_json_literal(new_val) {
-                new_val.to_string()
-            } else {
-                format!("\"{}\"", escape_json_string(new_val))
-            };
+/// Runs the lint rules `config.rules` enables, ESLint style: each rule id
+/// maps to `"off"`, `"warning"` or `"error"`, or to `[level, option]`.
+#[wasm_bindgen(unchecked_return_type = "LintResult")]
+pub fn lint(file_type: &str, content: &str, config: JsValue) -> Result<JsValue, JsValue> {
+    let rules = if config.is_object() {
+        field(&Object::from(config), "rules")
+    } else {
+        JsValue::UNDEFINED
+    };
+    let rules: serde_json::Value = if rules.is_undefined() {
+        serde_json::Value::Object(Default::default())
+    } else {
+        serde_wasm_bindgen::from_value(rules)
+            .map_err(|_| invalid_argument("Invalid lint rules: must be an object"))?
+    };
+    let config = native::LintConfig::from_rules(&rules).map_err(|e| invalid_argument(&e))?;
+    native::lint(file_type, content, &config)
+        .map(|result| to_js(&result))
+        .map_err(to_js_error)
+}
 
-            Ok(parser.replace_value(content, span, &escaped_value))
-        }
+/// The XML declaration (target `"xml"`) and processing instructions;
+/// update them through `["?target", "@name"]` paths.
+#[cfg(feature = "xml")]
+#[wasm_bindgen(unchecked_return_type = "XmlInstruction[]")]
+pub fn xml_instructions(content: &str) -> Result<JsValue, JsValue> {
+    native::xml_instructions(content)
+        .map(|instructions| to_js(&instructions))
+        .map_err(to_js_error)
+}
 
-        "xml" | "config" => {
-            let parser = XmlParser::new();
-            parser
-                .validate_syntax(content)
-                .map_err(|e| JsValue::from_str(&e))?;
-            let span = parser
-                .find_value_span(content, &path)
-                .map_err(|e| JsValue::from_str(&e))?;
-            Ok(parser.replace_value(content, span, &escape_xml_string(new_val)))
-        }
+/// Attributes of the XML element at `path` with both their raw text and
+/// span, for replacing, and their decoded value, for display. A trailing
+/// `@name` segment selects one attribute, `@*` all of them.
+#[cfg(feature = "xml")]
+#[wasm_bindgen(unchecked_return_type = "XmlAttribute[]")]
+pub fn xml_attributes(content: &str, path: JsValue) -> Result<JsValue, JsValue> {
+    let path = path_from_js(path)?;
+    native::xml_attributes(content, &path)
+        .map(|attributes| to_js(&attributes))
+        .map_err(to_js_error)
+}
 
-        "env" => {
-            let parser = EnvParser::new();
-            parser
-                .validate_syntax(content)
-                .map_err(|e| JsValue::from_str(&e))?;
-            let span = parser
-                .find_value_span(content, &path)
-                .map_err(|e| JsValue::from_str(&e))?;
-
-            let needs_quotes = new_val.contains([' ', '#', '\n', '\t']);
-            let val = if needs_quotes {
-                format!("\"{}\"", escape_env_string(new_val))
-            } else {
-                new_val.to_string()
-            };
-
-            Ok(parser.replace_value(content, span, &val))
-        }
+/// Comments above, inside and trailing the XML element at `path` (the
+/// document level for an empty path).
+#[cfg(feature = "xml")]
+#[wasm_bindgen(unchecked_return_type = "XmlComment[]")]
+pub fn xml_comments(content: &str, path: JsValue) -> Result<JsValue, JsValue> {
+    let path = path_from_js(path)?;
+    native::xml_comments(content, &path)
+        .map(|comments| to_js(&comments))
+        .map_err(to_js_error)
+}
 
-        other => Err(JsValue::from_str(&format!(
-            "Unsupported file type: {}",
-            other
-        ))),
-    }?;
+/// `${VAR}` / `$VAR` references in an ENV file resolved against its own
+/// keys, with the reference graph and undefined / circular reference
+/// diagnostics.
+#[cfg(feature = "env")]
+#[wasm_bindgen(unchecked_return_type = "ResolvedEnv")]
+pub fn resolve_env(content: &str, options: Option<JsValue>) -> Result<JsValue, JsValue> {
+    native::resolve_env(content, expand_from_js(options))
+        .map(|resolved| to_js(&resolved))
+        .map_err(to_js_error)
+}
 
-    Ok(result)
+/// `diff` of two ENV files: keys added, removed or changed, plus `quoting`
+/// and `export` entries for values written with other quotes or prefix.
+#[cfg(feature = "env")]
+#[wasm_bindgen(unchecked_return_type = "DiffEntry[]")]
+pub fn diff_env(old: &str, new: &str) -> Result<JsValue, JsValue> {
+    native::diff_env(old, new)
+        .map(|entries| to_js(&entries))
+        .map_err(to_js_error)
+}
+
+/// Renames the ENV variable `old` to `new` and its `${old}` / `$old`
+/// references, also in `options.files` (`[{ name, content }]`), returning
+/// the new contents with every edit for previewing.
+#[cfg(feature = "env")]
+#[wasm_bindgen(unchecked_return_type = "RenamedEnv")]
+pub fn rename_env_key(
+    content: &str,
+    old: &str,
+    new: &str,
+    options: Option<JsValue>,
+) -> Result<JsValue, JsValue> {
+    let files = options
+        .filter(JsValue::is_object)
+        .map(|v| field(&Object::from(v), "files"))
+        .filter(|files| !files.is_undefined())
+        .map(env_files_from_js)
+        .transpose()?
+        .unwrap_or_default();
+    native::rename_env_key(content, old, new, &files)
+        .map(|renamed| to_js(&renamed))
+        .map_err(to_js_error)
 }
 
+/// ENV entries sorted by key in prefix groups (`DB_`, `REDIS_`), each with
+/// its comments. `options`: `{ groupByPrefix, blankLineBetweenGroups }`,
+/// both on by default.
+#[cfg(feature = "env")]
 #[wasm_bindgen]
-pub fn validate(file_type: &str, content: &str) -> JsValue {
-    let ty = file_type.to_lowercase();
-    let obj = js_sys::Object::new();
-
-    // Default: assume valid=false until proven valid
-    let _ = js_sys::Reflect::set(
-        &obj,
-        &JsValue::from_str("valid"),
-        &JsValue::from_bool(false),
-    );
-
-    match ty.as_str() {
-        "json" => match serde_json::from_str::<serde_json::Value>(content) {
-            Ok(_) => {
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("valid"),
-                    &JsValue::from_bool(true),
-                );
-            }
-            Err(e) => {
-                let msg = e.to_string();
-                let line = e.line();
-                let column = e.column();
-                let start = compute_offset_from_line_col(content, line as usize, column as usize);
-                let span = infer_json_span(content, start);
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("message"),
-                    &JsValue::from_str(&msg),
-                );
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("line"),
-                    &JsValue::from_f64(line as f64),
-                );
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("column"),
-                    &JsValue::from_f64(column as f64),
-                );
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("start"),
-                    &JsValue::from_f64(span.start as f64),
-                );
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("end"),
-                    &JsValue::from_f64(span.end as f64),
-                );
-            }
-        },
-        "xml" | "config" => {
-            // Iterate tokens and stop at first error to get precise position
-            let mut err: Option<XmlError> = None;
-            for tok in Tokenizer::from(content) {
-                if let Err(e) = tok {
-                    err = Some(e);
-                    break;
-                }
-            }
-            if let Some(e) = err {
-                let msg = e.to_string();
-                let pos = e.pos();
-                let line = pos.row;
-                let column = pos.col;
-                let start = compute_offset_from_line_col(content, line as usize, column as usize);
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("message"),
-                    &JsValue::from_str(&msg),
-                );
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("line"),
-                    &JsValue::from_f64(line as f64),
-                );
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("column"),
-                    &JsValue::from_f64(column as f64),
-                );
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("start"),
-                    &JsValue::from_f64(start as f64),
-                );
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("end"),
-                    &JsValue::from_f64(start as f64),
-                );
-            } else {
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("valid"),
-                    &JsValue::from_bool(true),
-                );
-            }
+pub fn organize_env(content: &str, options: Option<JsValue>) -> Result<String, JsValue> {
+    let mut opts = native::OrganizeEnvOptions::default();
+    if let Some(obj) = options.filter(JsValue::is_object).map(Object::from) {
+        if let Some(group) = field(&obj, "groupByPrefix").as_bool() {
+            opts.group_by_prefix = group;
         }
-        "env" => match env_parser::validate_with_pos(content) {
-            Ok(_) => {
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("valid"),
-                    &JsValue::from_bool(true),
-                );
-            }
-            Err(e) => {
-                let start =
-                    compute_offset_from_line_col(content, e.line as usize, e.column as usize);
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("message"),
-                    &JsValue::from_str(&e.msg),
-                );
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("line"),
-                    &JsValue::from_f64(e.line as f64),
-                );
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("column"),
-                    &JsValue::from_f64(e.column as f64),
-                );
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("start"),
-                    &JsValue::from_f64(start as f64),
-                );
-                let _ = js_sys::Reflect::set(
-                    &obj,
-                    &JsValue::from_str("end"),
-                    &JsValue::from_f64(start as f64),
-                );
-            }
-        },
-        other => {
-            let _ = js_sys::Reflect::set(
-                &obj,
-                &JsValue::from_str("message"),
-                &JsValue::from_str(&format!("Unsupported file type: {}", other)),
-            );
+        if let Some(blank) = field(&obj, "blankLineBetweenGroups").as_bool() {
+            opts.blank_line_between_groups = blank;
         }
     }
+    native::organize_env(content, &opts).map_err(to_js_error)
+}
+
+/// Layers ENV files (`[{ name, content }]`), later files overriding earlier
+/// ones, and reports which file and line set each key.
+#[cfg(feature = "env")]
+#[wasm_bindgen(unchecked_return_type = "MergedEnv")]
+pub fn merge_env(files: JsValue) -> Result<JsValue, JsValue> {
+    let files = env_files_from_js(files)?;
+    native::merge_env(&files)
+        .map(|merged| to_js(&merged))
+        .map_err(to_js_error)
+}
+
+/// The path segment that addresses the literal key `key`: a leading `@` or
+/// `\`, or an `[n]` spelling, is escaped with `\`; anything else is
+/// returned unchanged.
+#[wasm_bindgen]
+pub fn escape_segment(key: &str) -> String {
+    native::escape_segment(key)
+}
+
+/// The segment that matches only the object key `key`, never an array index.
+#[wasm_bindgen]
+pub fn key_segment(key: &str) -> String {
+    native::key_segment(key)
+}
 
-    obj.into()
+/// The segment (`[n]`) that matches only array index `index`.
+#[wasm_bindgen]
+pub fn index_segment(index: u32) -> String {
+    native::index_segment(index as usize)
 }
 
+/// `path` as one dotted string (`\.` and `\\` inside segments), for form
+/// field names.
 #[wasm_bindgen]
-pub fn validate_multi(file_type: &str, content: &str, max_errors: Option<u32>) -> JsValue {
-    let ty = file_type.to_lowercase();
-    let cap = max_errors.unwrap_or(3).clamp(1, MAX_MULTI_ERRORS as u32) as usize;
-    let result = match ty.as_str() {
-        "json" => validate_json_multi(content, cap),
-        "xml" | "config" => validate_xml_multi(content, cap),
-        "env" => env_multi_result(content),
-        other => unsupported_multi_result(other),
+pub fn join_path(path: JsValue) -> Result<String, JsValue> {
+    Ok(native::join_path(&path_from_js(path)?))
+}
+
+#[wasm_bindgen(unchecked_return_type = "string[]")]
+pub fn split_path(text: &str) -> JsValue {
+    to_js(&native::split_path(text))
+}
+
+#[wasm_bindgen]
+pub fn to_pointer(path: JsValue) -> Result<String, JsValue> {
+    Ok(native::to_pointer(&path_from_js(path)?))
+}
+
+#[wasm_bindgen(unchecked_return_type = "string[]")]
+pub fn from_pointer(pointer: &str) -> Result<JsValue, JsValue> {
+    native::from_pointer(pointer)
+        .map(|path| to_js(&path))
+        .map_err(to_js_error)
+}
+
+/// The innermost value around byte `offset`: `{ path, keySpan?, valueSpan,
+/// ancestors }`, or `null` when the offset is outside every value. Throws on
+/// syntax errors and offsets past the end.
+#[wasm_bindgen(unchecked_return_type = "PathAtOffset | null")]
+pub fn path_at_offset(file_type: &str, content: &str, offset: u32) -> Result<JsValue, JsValue> {
+    native::path_at_offset(file_type, content, offset as usize)
+        .map(|found| to_js(&found))
+        .map_err(to_js_error)
+}
+
+/// Keys the other objects of the array holding the JSON object at
+/// `parentPath` have and it lacks, most common first, for completion without
+/// a schema. A path to the array suggests keys for a new item.
+#[wasm_bindgen(unchecked_return_type = "KeySuggestion[]")]
+pub fn suggest_keys(content: &str, parent_path: JsValue) -> Result<JsValue, JsValue> {
+    let parent_path = path_from_js(parent_path)?;
+    native::suggest_keys(content, &parent_path)
+        .map(|suggestions| to_js(&suggestions))
+        .map_err(to_js_error)
+}
+
+/// Keys whose dotted path matches `query` (substring, else the characters
+/// in order; case ignored), best first, each with `{ path, label, score,
+/// keySpan, valueSpan }` for a "go to setting" palette.
+#[wasm_bindgen(unchecked_return_type = "KeyMatch[]")]
+pub fn fuzzy_find(file_type: &str, content: &str, query: &str) -> Result<JsValue, JsValue> {
+    native::fuzzy_find(file_type, content, query)
+        .map(|matches| to_js(&matches))
+        .map_err(to_js_error)
+}
+
+/// The first object in the JSON array at `arrayPath` whose `field` equals
+/// `value` (any JSON value, compared as parsed): `{ index, path, start, end }`,
+/// or `null` when none matches.
+#[wasm_bindgen(unchecked_return_type = "FoundItem | null")]
+pub fn find_item(
+    content: &str,
+    array_path: JsValue,
+    field: &str,
+    value: JsValue,
+) -> Result<JsValue, JsValue> {
+    let array_path = path_from_js(array_path)?;
+    let value: serde_json::Value = serde_wasm_bindgen::from_value(value)
+        .map_err(|_| invalid_argument("Invalid value: must be a JSON value"))?;
+    native::find_item(content, &array_path, field, &value)
+        .map(|found| to_js(&found))
+        .map_err(to_js_error)
+}
+
+/// Resolves many paths from a single parse, so form rendering needs one call
+/// instead of one per field. Returns one entry per path: `{ start, end }`,
+/// or `{ error }` when that path cannot be resolved. Throws on syntax errors.
+#[wasm_bindgen(unchecked_return_type = "SpanLookup[]")]
+pub fn find_value_spans(
+    file_type: &str,
+    content: &str,
+    paths: JsValue,
+) -> Result<JsValue, JsValue> {
+    let paths = paths_from_js(paths)
+        .ok_or_else(|| invalid_argument("Invalid paths: must be an array of string arrays"))?;
+    let spans = native::find_value_spans(file_type, content, &paths).map_err(to_js_error)?;
+    Ok(to_js(&SpanLookup::collect(spans)))
+}
+
+fn validation_result(
+    file_type: &str,
+    content: &str,
+    unit: Option<ColumnUnit>,
+) -> Result<ValidationResult, JsValue> {
+    let mut summary = match native::validate(file_type, content) {
+        Ok(summary) => summary,
+        Err(Error::UnsupportedFileType(raw)) => unsupported_validation_result(&raw),
+        Err(err) => return Err(to_js_error(err)),
     };
-    multi_result_to_js(result.with_limit(cap))
+    if let Some(unit) = unit {
+        native::apply_column_unit(&mut summary, content, unit);
+    }
+    Ok(summary)
 }
 
+/// `options`: `{ columnUnit }` (`"char"`, `"utf16"` or `"byte"`), which
+/// recomputes `line`/`column` from the error span in that unit.
+#[wasm_bindgen(unchecked_return_type = "ValidationResult")]
+pub fn validate(
+    file_type: &str,
+    content: &str,
+    options: Option<JsValue>,
+) -> Result<JsValue, JsValue> {
+    validation_result(file_type, content, column_unit_from_js(options.as_ref()))
+        .map(|summary| to_js(&summary))
+}
+
+/// Sets process-wide input guards: `{ maxBytes, maxTokens, maxErrors }`.
+/// Missing or non-positive fields disable the corresponding limit; entry
+/// points throw an `Error` with `code: "limit_exceeded"` on overrun.
 #[wasm_bindgen]
-pub fn validate_schema(content: &str, schema: &str, options: Option<JsValue>) -> JsValue {
-    schema::validate_schema_inline(content, schema, options)
+pub fn set_limits(options: JsValue) {
+    native::set_limits(limits_from_js(&options));
 }
 
+/// Drops the recent-parse cache behind the stateless exports, for hosts that
+/// need the memory back (e.g. after closing large files).
 #[wasm_bindgen]
+pub fn clear_parse_cache() {
+    native::clear_parse_cache();
+}
+
+/// `{ version, formats, features }` for hosts that feature-detect a minimal
+/// build (e.g. one compiled without `xml`) at runtime.
+#[wasm_bindgen(unchecked_return_type = "Capabilities")]
+pub fn capabilities() -> JsValue {
+    to_js(&native::capabilities())
+}
+
+fn multi_result(
+    file_type: &str,
+    content: &str,
+    max_errors: Option<u32>,
+    options: Option<&JsValue>,
+) -> Result<MultiValidationResult, JsValue> {
+    let opts = multi_options_from_js(options);
+    let mut result = match native::validate_multi_with(file_type, content, max_errors, &opts) {
+        Ok(result) => result,
+        Err(Error::UnsupportedFileType(raw)) => unsupported_multi_result(&raw),
+        Err(err) => return Err(to_js_error(err)),
+    };
+    if let Some(unit) = column_unit_from_js(options) {
+        native::apply_column_unit(&mut result, content, unit);
+    }
+    Ok(grouped_by_line(result, options))
+}
+
+/// `result` with `group_by_line` applied when `options` has
+/// `groupByLine: true`.
+fn grouped_by_line(
+    result: MultiValidationResult,
+    options: Option<&JsValue>,
+) -> MultiValidationResult {
+    let group = options
+        .filter(|v| v.is_object())
+        .is_some_and(|v| field(&Object::from(v.clone()), "groupByLine").as_bool() == Some(true));
+    if group {
+        result.group_by_line()
+    } else {
+        result
+    }
+}
+
+/// `options`: `{ columnUnit }`, as for `validate`, `groupByLine` to fold
+/// the errors on one line into the first one's `subErrors`, and `xmlResync`
+/// to pick how XML checking carries on after an error.
+#[wasm_bindgen(unchecked_return_type = "MultiValidationResult")]
+pub fn validate_multi(
+    file_type: &str,
+    content: &str,
+    max_errors: Option<u32>,
+    options: Option<JsValue>,
+) -> Result<JsValue, JsValue> {
+    multi_result(file_type, content, max_errors, options.as_ref()).map(|result| to_js(&result))
+}
+
+#[wasm_bindgen(unchecked_return_type = "SchemaValidationResult")]
+pub fn validate_schema(
+    content: &str,
+    schema: &str,
+    options: Option<JsValue>,
+) -> Result<JsValue, JsValue> {
+    let opts = schema_options_from_js(options);
+    native::validate_schema(content, schema, &opts)
+        .map(|outcome| to_js(&outcome))
+        .map_err(to_js_error)
+}
+
+#[wasm_bindgen(unchecked_return_type = "SchemaValidationResult")]
 pub fn validate_schema_with_id(
     content: &str,
     schema_id: &str,
     options: Option<JsValue>,
-) -> JsValue {
-    schema::validate_schema_with_id(content, schema_id, options)
+) -> Result<JsValue, JsValue> {
+    let opts = schema_options_from_js(options);
+    native::validate_schema_with_id(content, schema_id, &opts)
+        .map(|outcome| to_js(&outcome))
+        .map_err(to_js_error)
+}
+
+/// `validate_schema` for a `.env` file, its values coerced to the types the
+/// schema declares.
+#[cfg(feature = "env")]
+#[wasm_bindgen(unchecked_return_type = "SchemaValidationResult")]
+pub fn validate_env_schema(
+    content: &str,
+    schema: &str,
+    options: Option<JsValue>,
+) -> Result<JsValue, JsValue> {
+    let opts = schema_options_from_js(options);
+    native::validate_env_schema(content, schema, &opts)
+        .map(|outcome| to_js(&outcome))
+        .map_err(to_js_error)
+}
+
+#[cfg(feature = "env")]
+#[wasm_bindgen(unchecked_return_type = "SchemaValidationResult")]
+pub fn validate_env_schema_with_id(
+    content: &str,
+    schema_id: &str,
+    options: Option<JsValue>,
+) -> Result<JsValue, JsValue> {
+    let opts = schema_options_from_js(options);
+    native::validate_env_schema_with_id(content, schema_id, &opts)
+        .map(|outcome| to_js(&outcome))
+        .map_err(to_js_error)
+}
+
+/// Decodes a `Uint8Array` read from disk: UTF-8, or UTF-16 LE/BE with or
+/// without a BOM. Returns `{ content, encoding, bom }`; pass `encoding` and
+/// `bom` to `encode_text` when saving.
+#[wasm_bindgen(unchecked_return_type = "DecodedText")]
+pub fn decode_bytes(bytes: &[u8]) -> Result<JsValue, JsValue> {
+    native::decode_bytes(bytes)
+        .map(|decoded| to_js(&decoded))
+        .map_err(to_js_error)
+}
+
+/// `content` as bytes in `encoding` (`"utf-8"`, `"utf-16le"`, `"utf-16be"`),
+/// with a BOM when `bom` is true.
+#[wasm_bindgen]
+pub fn encode_text(content: &str, encoding: &str, bom: bool) -> Result<Vec<u8>, JsValue> {
+    let encoding = native::TextEncoding::parse(encoding)
+        .ok_or_else(|| invalid_argument(&format!("Unsupported encoding: {encoding}")))?;
+    Ok(native::encode_text(content, encoding, bom))
 }
 
 #[wasm_bindgen]
 pub fn register_schema(schema_id: &str, schema: &str) -> Result<(), JsValue> {
-    schema::register_schema(schema_id, schema)
-}
-
-fn multi_result_to_js(result: MultiValidationResult) -> JsValue {
-    let obj = js_sys::Object::new();
-    let _ = js_sys::Reflect::set(
-        &obj,
-        &JsValue::from_str("valid"),
-        &JsValue::from_bool(result.valid),
-    );
-
-    let errors = Array::new();
-    for err in &result.errors {
-        errors.push(&detailed_error_to_js(err));
-    }
-    let _ = js_sys::Reflect::set(&obj, &JsValue::from_str("errors"), &errors);
-
-    if let Some(summary) = &result.summary {
-        let summary_obj = js_sys::Object::new();
-        set_summary_fields(&summary_obj, summary);
-        let _ = js_sys::Reflect::set(&obj, &JsValue::from_str("summary"), &summary_obj);
-    }
-
-    obj.into()
-}
-
-fn detailed_error_to_js(err: &DetailedError) -> JsValue {
-    let obj = js_sys::Object::new();
-    let _ = js_sys::Reflect::set(
-        &obj,
-        &JsValue::from_str("message"),
-        &JsValue::from_str(&err.message),
-    );
-    if let Some(code) = err.code {
-        let _ = js_sys::Reflect::set(&obj, &JsValue::from_str("code"), &JsValue::from_str(code));
-    }
-    let _ = js_sys::Reflect::set(
-        &obj,
-        &JsValue::from_str("line"),
-        &JsValue::from_f64(err.line as f64),
-    );
-    let _ = js_sys::Reflect::set(
-        &obj,
-        &JsValue::from_str("column"),
-        &JsValue::from_f64(err.column as f64),
-    );
-    let _ = js_sys::Reflect::set(
-        &obj,
-        &JsValue::from_str("start"),
-        &JsValue::from_f64(err.span.start as f64),
-    );
-    let _ = js_sys::Reflect::set(
-        &obj,
-        &JsValue::from_str("end"),
-        &JsValue::from_f64(err.span.end as f64),
-    );
-    obj.into()
-}
-
-fn set_summary_fields(obj: &js_sys::Object, summary: &DetailedError) {
-    let _ = js_sys::Reflect::set(
-        obj,
-        &JsValue::from_str("message"),
-        &JsValue::from_str(&summary.message),
-    );
-    let _ = js_sys::Reflect::set(
-        obj,
-        &JsValue::from_str("line"),
-        &JsValue::from_f64(summary.line as f64),
-    );
-    let _ = js_sys::Reflect::set(
-        obj,
-        &JsValue::from_str("column"),
-        &JsValue::from_f64(summary.column as f64),
-    );
-    let _ = js_sys::Reflect::set(
-        obj,
-        &JsValue::from_str("start"),
-        &JsValue::from_f64(summary.span.start as f64),
-    );
-    let _ = js_sys::Reflect::set(
-        obj,
-        &JsValue::from_str("end"),
-        &JsValue::from_f64(summary.span.end as f64),
-    );
-}
-
-fn env_multi_result(content: &str) -> MultiValidationResult {
-    match env_parser::validate_with_pos(content) {
-        Ok(_) => MultiValidationResult::success(),
-        Err(e) => {
-            let start = compute_offset_from_line_col(content, e.line as usize, e.column as usize);
-            let summary = DetailedError {
-                message: e.msg,
-                code: None,
-                line: e.line as usize,
-                column: e.column as usize,
-                span: Span::new(start, start),
-            };
-            invalid_summary_result(summary)
-        }
-    }
+    native::register_schema(schema_id, schema).map_err(to_js_error)
 }
 
-fn unsupported_multi_result(file_type: &str) -> MultiValidationResult {
-    let summary = DetailedError {
-        message: format!("Unsupported file type: {}", file_type),
-        code: None,
-        line: 1,
-        column: 1,
-        span: Span::new(0, 0),
-    };
-    invalid_summary_result(summary)
+/// Serializes a registered schema so a later session can `import_schema` it
+/// without re-parsing the source.
+#[wasm_bindgen]
+pub fn export_schema(schema_id: &str) -> Result<Vec<u8>, JsValue> {
+    native::export_schema(schema_id).map_err(to_js_error)
 }
 
-fn invalid_summary_result(summary: DetailedError) -> MultiValidationResult {
-    MultiValidationResult {
-        valid: false,
-        summary: Some(summary.clone()),
-        errors: vec![summary],
-    }
-}
-
-pub(crate) fn compute_offset_from_line_col(content: &str, line: usize, column: usize) -> usize {
-    // Lines/columns are 1-based per serde_json/xmlparser conventions
-    let mut current_line = 1usize;
-    let mut offset = 0usize;
-    for (idx, ch) in content.char_indices() {
-        if current_line == line {
-            // column indicates the character position within the line (1-based)
-            // Convert to byte offset: find the byte index at given column
-            let mut col = 1usize;
-            let mut i = idx;
-            // Walk forward within this line to the requested column
-            while i < content.len() {
-                if col == column {
-                    return i;
-                }
-                let c = content[i..].chars().next().unwrap();
-                if c == '\n' || c == '\r' {
-                    // End of line reached before desired column
-                    return i;
-                }
-                i += c.len_utf8();
-                col += 1;
-            }
-            return i;
-        }
-        if ch == '\n' {
-            current_line += 1;
-            offset = idx + 1;
-            if current_line > line {
-                break;
-            }
+#[wasm_bindgen]
+pub fn import_schema(schema_id: &str, bytes: &[u8]) -> Result<(), JsValue> {
+    native::import_schema(schema_id, bytes).map_err(to_js_error)
+}
+
+/// Members of the JSON `content` that the registered schema neither
+/// describes nor constrains, with the span of each member for pruning.
+#[wasm_bindgen(unchecked_return_type = "UnusedKey[]")]
+pub fn unused_keys(content: &str, schema_id: &str) -> Result<JsValue, JsValue> {
+    native::unused_keys(content, schema_id)
+        .map(|keys| to_js(&keys))
+        .map_err(to_js_error)
+}
+
+/// Property reference for a registered schema: `PropertyDoc[]`, or with
+/// `{ format: "markdown" }` the same as a Markdown table.
+#[wasm_bindgen(unchecked_return_type = "PropertyDoc[] | string")]
+pub fn schema_docs(schema_id: &str, options: Option<JsValue>) -> Result<JsValue, JsValue> {
+    let format = options
+        .filter(JsValue::is_object)
+        .and_then(|v| field(&Object::from(v), "format").as_string());
+    match format.as_deref() {
+        None | Some("json") => native::schema_docs(schema_id)
+            .map(|docs| to_js(&docs))
+            .map_err(to_js_error),
+        Some("markdown") => native::schema_docs_markdown(schema_id)
+            .map(|table| JsValue::from_str(&table))
+            .map_err(to_js_error),
+        Some(other) => Err(invalid_argument(&format!(
+            "Unsupported docs format: {other}"
+        ))),
+    }
+}
+
+/// A new document from a registered schema. `options`: `{ fileType,
+/// includeOptional }`, plus `convert`'s `separator`, `root` and
+/// `uppercase` for XML and ENV output.
+#[wasm_bindgen]
+pub fn instantiate_schema(schema_id: &str, options: Option<JsValue>) -> Result<String, JsValue> {
+    let mut opts = native::InstantiateOptions {
+        convert: convert_options_from_js(options.clone()),
+        ..Default::default()
+    };
+    if let Some(obj) = options.filter(JsValue::is_object).map(Object::from) {
+        if let Some(file_type) = field(&obj, "fileType").as_string() {
+            opts.file_type = file_type;
         }
+        opts.include_optional = field(&obj, "includeOptional").as_bool() == Some(true);
     }
-    // Fallback to last known offset
-    offset
+    native::instantiate_schema(schema_id, &opts).map_err(to_js_error)
+}
+
+#[wasm_bindgen(unchecked_return_type = "SchemaValidationResult")]
+pub fn validate_schema_all(
+    content: &str,
+    schema_ids: JsValue,
+    options: Option<JsValue>,
+) -> Result<JsValue, JsValue> {
+    let ids = string_array_from_js(schema_ids).unwrap_or_default();
+    let opts = schema_options_from_js(options);
+    native::validate_schema_all(content, &ids, &opts)
+        .map(|outcome| to_js(&outcome))
+        .map_err(to_js_error)
 }
 
-pub(crate) fn compute_line_col_from_offset(content: &str, offset: usize) -> (usize, usize) {
-    let clamped = offset.min(content.len());
-    let mut line = 1usize;
-    let mut column = 1usize;
-    for (idx, ch) in content.char_indices() {
-        if idx >= clamped {
-            return (line, column);
+/// Parse-once handle: validating and then editing the same content lexes it
+/// once. Any edit replaces the content and drops the cache.
+#[wasm_bindgen]
+pub struct Document(native::Document);
+
+#[wasm_bindgen]
+impl Document {
+    #[wasm_bindgen(constructor)]
+    pub fn new(file_type: &str, content: &str) -> Result<Document, JsValue> {
+        native::Document::new(file_type, content)
+            .map(Document)
+            .map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn content(&self) -> String {
+        self.0.content().to_string()
+    }
+
+    pub fn set_content(&mut self, content: &str) -> Result<(), JsValue> {
+        self.0.set_content(content).map_err(to_js_error)
+    }
+
+    /// Saves the current content in the handle and returns its id; the 64
+    /// most recent are kept.
+    pub fn snapshot(&mut self) -> u32 {
+        self.0.snapshot()
+    }
+
+    /// `diff` from snapshot `id` to the current content.
+    #[wasm_bindgen(unchecked_return_type = "DiffEntry[]")]
+    pub fn diff_since(&self, id: u32) -> Result<JsValue, JsValue> {
+        self.0
+            .diff_since(id)
+            .map(|entries| to_js(&entries))
+            .map_err(to_js_error)
+    }
+
+    /// Puts snapshot `id` back as the content.
+    pub fn restore(&mut self, id: u32) -> Result<(), JsValue> {
+        self.0.restore(id).map_err(to_js_error)
+    }
+
+    /// Appends a chunk of streamed input (e.g. from a `TextDecoderStream`),
+    /// lexing JSON as it arrives instead of after the file is concatenated.
+    pub fn feed(&mut self, chunk: &str) -> Result<(), JsValue> {
+        self.0.feed(chunk).map_err(to_js_error)
+    }
+
+    /// Ends a `feed` sequence and returns `validate()` of the full content.
+    #[wasm_bindgen(unchecked_return_type = "ValidationResult")]
+    pub fn finish(&mut self) -> Result<JsValue, JsValue> {
+        self.0
+            .finish()
+            .map(|summary| to_js(&summary))
+            .map_err(to_js_error)
+    }
+
+    /// `options`: `{ columnUnit }`, as for the `validate` export.
+    #[wasm_bindgen(unchecked_return_type = "ValidationResult")]
+    pub fn validate(&mut self, options: Option<JsValue>) -> Result<JsValue, JsValue> {
+        let mut summary = self.0.validate().map_err(to_js_error)?;
+        if let Some(unit) = column_unit_from_js(options.as_ref()) {
+            self.0.apply_column_unit(&mut summary, unit);
         }
-        if ch == '\n' {
-            line += 1;
-            column = 1;
-        } else {
-            column += 1;
+        Ok(to_js(&summary))
+    }
+
+    #[wasm_bindgen(unchecked_return_type = "MultiValidationResult")]
+    pub fn validate_multi(
+        &mut self,
+        max_errors: Option<u32>,
+        options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let opts = multi_options_from_js(options.as_ref());
+        let mut result = self
+            .0
+            .validate_multi_with(max_errors, &opts)
+            .map_err(to_js_error)?;
+        if let Some(unit) = column_unit_from_js(options.as_ref()) {
+            self.0.apply_column_unit(&mut result, unit);
         }
+        Ok(to_js(&grouped_by_line(result, options.as_ref())))
     }
-    (line, column)
-}
 
-pub fn is_json_literal(s: &str) -> bool {
-    // Check for basic JSON literals
-    if matches!(s, "true" | "false" | "null") {
-        return true;
+    #[wasm_bindgen(unchecked_return_type = "Span")]
+    pub fn find_value_span(
+        &mut self,
+        path: JsValue,
+        options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let path = path_from_js(path)?;
+        self.0
+            .find_value_span_with(&path, &lookup_options_from_js(options.as_ref()))
+            .map(|span| to_js(&span))
+            .map_err(to_js_error)
     }
 
-    if let Ok(value) = serde_json::from_str::<Value>(s) {
-        return matches!(value, Value::Number(_) | Value::Array(_) | Value::Object(_));
+    /// Resolves every path in `paths` against this document's single parse;
+    /// see the `find_value_spans` export for the result shape.
+    #[wasm_bindgen(unchecked_return_type = "SpanLookup[]")]
+    pub fn find_value_spans(&mut self, paths: JsValue) -> Result<JsValue, JsValue> {
+        let paths = paths_from_js(paths)
+            .ok_or_else(|| invalid_argument("Invalid paths: must be an array of string arrays"))?;
+        let spans = self.0.find_value_spans(&paths).map_err(to_js_error)?;
+        Ok(to_js(&SpanLookup::collect(spans)))
     }
 
-    false
-}
+    pub fn get_string(&mut self, path: JsValue) -> Result<String, JsValue> {
+        let path = path_from_js(path)?;
+        self.0.get_string(&path).map_err(to_js_error)
+    }
 
-fn escape_json_string(s: &str) -> String {
-    s.chars()
-        .map(|c| match c {
-            '"' => "\\\"".to_string(),
-            '\\' => "\\\\".to_string(),
-            '\n' => "\\n".to_string(),
-            '\r' => "\\r".to_string(),
-            '\t' => "\\t".to_string(),
-            c if c.is_control() => format!("\\u{:04x}", c as u32),
-            c => c.to_string(),
-        })
-        .collect()
-}
+    pub fn get_value(
+        &mut self,
+        path: JsValue,
+        options: Option<JsValue>,
+    ) -> Result<String, JsValue> {
+        let path = path_from_js(path)?;
+        let lookup = lookup_options_from_js(options.as_ref());
+        self.0
+            .get_value_with(&path, decode_from_js(options), &lookup)
+            .map_err(to_js_error)
+    }
 
-fn escape_xml_string(s: &str) -> String {
-    s.chars()
-        .map(|c| match c {
-            '&' => "&amp;".to_string(),
-            '<' => "&lt;".to_string(),
-            '>' => "&gt;".to_string(),
-            '"' => "&quot;".to_string(),
-            '\'' => "&apos;".to_string(),
-            c => c.to_string(),
-        })
-        .collect()
+    pub fn update_value(
+        &mut self,
+        path: JsValue,
+        new_val: &str,
+        options: Option<JsValue>,
+    ) -> Result<String, JsValue> {
+        let path = path_from_js(path)?;
+        self.0
+            .update_value_with(&path, new_val, &update_options_from_js(options.as_ref()))
+            .map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(unchecked_return_type = "SchemaValidationResult")]
+    pub fn validate_schema_with_id(
+        &mut self,
+        schema_id: &str,
+        options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let opts = schema_options_from_js(options);
+        self.0
+            .validate_schema_with_id(schema_id, &opts)
+            .map(|outcome| to_js(&outcome))
+            .map_err(to_js_error)
+    }
+
+    /// Next `max` tokens as `{ tokens: [{ kind, start, end }], done }`.
+    /// JSON only; call `reset_tokens` to start over.
+    #[wasm_bindgen(unchecked_return_type = "TokenBatch")]
+    pub fn next_tokens(&mut self, max: u32) -> Result<JsValue, JsValue> {
+        self.0
+            .next_tokens(max as usize)
+            .map(|batch| to_js(&batch))
+            .map_err(to_js_error)
+    }
+
+    /// Chunked `validate()`: `on_progress(processed, total)` runs between
+    /// slices of `chunk_size` bytes and may return `false` to cancel. An
+    /// exception thrown by the callback cancels and is rethrown.
+    #[wasm_bindgen(unchecked_return_type = "ChunkedResult")]
+    pub fn validate_chunked(
+        &mut self,
+        chunk_size: u32,
+        on_progress: &js_sys::Function,
+    ) -> Result<JsValue, JsValue> {
+        let total = self.0.content().len();
+        let mut thrown = None;
+        let outcome = self
+            .0
+            .validate_chunked(chunk_size as usize, |processed, total| {
+                match on_progress.call2(
+                    &JsValue::NULL,
+                    &JsValue::from_f64(processed as f64),
+                    &JsValue::from_f64(total as f64),
+                ) {
+                    Ok(ret) => ret != JsValue::FALSE,
+                    Err(e) => {
+                        thrown = Some(e);
+                        false
+                    }
+                }
+            })
+            .map_err(to_js_error)?;
+        if let Some(e) = thrown {
+            return Err(e);
+        }
+        Ok(to_js(&match outcome {
+            native::ChunkedOutcome::Done(result) => ChunkedResult::Done {
+                result,
+                cancelled: false,
+            },
+            native::ChunkedOutcome::Cancelled { processed } => ChunkedResult::Cancelled {
+                cancelled: true,
+                processed,
+                total,
+            },
+        }))
+    }
+
+    pub fn reset_tokens(&self) {
+        self.0.reset_tokens();
+    }
+
+    /// 1-based `{ line, column }` (character columns) for a byte offset.
+    #[wasm_bindgen(unchecked_return_type = "Position")]
+    pub fn offset_to_position(&self, offset: usize) -> JsValue {
+        let (line, column) = self.0.offset_to_position(offset);
+        to_js(&Position { line, column })
+    }
+
+    /// Byte offset for a 1-based line and character column.
+    pub fn position_to_offset(&self, line: usize, column: usize) -> usize {
+        self.0.position_to_offset(line, column)
+    }
 }
 
-fn escape_env_string(s: &str) -> String {
-    s.chars()
-        .map(|c| match c {
-            '"' => "\\\"".to_string(),
-            '\\' => "\\\\".to_string(),
-            '\n' => "\\n".to_string(),
-            '\r' => "\\r".to_string(),
-            '\t' => "\\t".to_string(),
-            c => c.to_string(),
-        })
-        .collect()
+/// `performance.now()` where the host has it (browsers, workers, Node),
+/// `Date.now()` otherwise.
+fn performance_now() -> f64 {
+    let performance = Reflect::get(&js_sys::global(), &JsValue::from_str("performance"))
+        .ok()
+        .filter(|p| p.is_object());
+    if let Some(performance) = performance {
+        if let Ok(now) = Reflect::get(&performance, &JsValue::from_str("now")) {
+            if let Ok(now) = now.dyn_into::<js_sys::Function>() {
+                if let Some(ms) = now.call0(&performance).ok().and_then(|v| v.as_f64()) {
+                    return ms;
+                }
+            }
+        }
+    }
+    js_sys::Date::now()
 }
 
 #[cfg_attr(not(test), wasm_bindgen(start))]
 pub fn main() {
-    // WASM init hook
+    native::set_profile_clock(performance_now);
 }
-
-// Ensure the trait is imported at the top of the file so methods are in scope
-use crate::env_parser::BytePreservingParser;
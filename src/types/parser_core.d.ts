@@ -2,23 +2,586 @@ declare module "../parser-wasm/pkg/parser_core.js" {
 	export default function init(...args: unknown[]): Promise<void>;
 	export type SchemaValidationError = {
 		message: string;
+		severity: "error" | "warning";
 		keyword?: string;
 		instancePath: string;
+		/** `instancePath` as a path for `find_value_span` and the other path APIs. */
+		instancePathSegments: string[];
 		schemaPath?: string;
+		/** `<schema $id>#<pointer>` of the failing keyword, through `$ref`s into other registered schemas. */
+		absoluteKeywordLocation?: string;
 		line?: number;
 		column?: number;
 		start?: number;
 		end?: number;
+		suggestedValue?: unknown;
+		/** Every allowed `enum` value, when the message lists only some. */
+		allowedValues?: unknown[];
+	};
+	export type ParserErrorCode =
+		| "unsupported_file_type"
+		| "invalid_argument"
+		| "syntax_error"
+		| "path_not_found"
+		| "unsupported_operation"
+		| "schema_error"
+		| "limit_exceeded"
+		| "aborted";
+	/** What every export throws (or an async export rejects with). */
+	export type ParserError = Error & {
+		code: ParserErrorCode;
+		/** Byte range of the first syntax error, when known. */
+		span?: { start: number; end: number };
+	};
+	export type LimitExceededError = ParserError & {
+		code: "limit_exceeded";
+		limit: "maxBytes" | "maxTokens";
+		max: number;
+		actual: number;
+	};
+	/** Exports throw `LimitExceededError` when a limit is exceeded. */
+	export function set_limits(limits: {
+		maxBytes?: number;
+		maxTokens?: number;
+		maxErrors?: number;
+	}): void;
+	/** Formats and optional cargo features compiled into this build. */
+	export function capabilities(): {
+		version: string;
+		formats: Array<"json" | "xml" | "env">;
+		features: Array<"threads" | "wee_alloc">;
+	};
+	/** Drops the cached parses reused by the stateless exports. */
+	export function clear_parse_cache(): void;
+	export type SchemaValidationOptions = {
+		maxErrors?: number;
+		collectPositions?: boolean;
+		draft?: string;
+		reportDeprecated?: boolean;
+		profile?: boolean;
+		columnUnit?: ColumnUnit;
+		/** Make `format` fail validation (`true`) or only annotate (`false`); omitted, the draft decides. */
+		formatAssertion?: boolean;
+		/** Vocabularies whose keywords are ignored. */
+		disabledVocabularies?: SchemaVocabulary[];
+		/** List at most this many values in an `enum` message; the error's `allowedValues` then holds them all. */
+		maxMessageValues?: number;
+		/** Cut longer messages to this many characters, ending them with `…`. */
+		maxMessageLength?: number;
+	};
+	export type SchemaVocabulary = "applicator" | "unevaluated" | "validation" | "format" | "content";
+	/** Unit of reported columns; editors built on UTF-16 strings want `"utf16"`. */
+	export type ColumnUnit = "char" | "utf16" | "byte";
+	export type PositionOptions = { columnUnit?: ColumnUnit };
+	/**
+	 * `groupByLine` folds later errors on a line into the first one's `subErrors`;
+	 * `xmlResync` picks how XML checking carries on after an error.
+	 */
+	export type MultiOptions = PositionOptions & {
+		groupByLine?: boolean;
+		xmlResync?: "markup" | "nextTag";
+	};
+	export type RelatedLocation = {
+		message: string;
+		line: number;
+		column: number;
+		start: number;
+		end: number;
+	};
+	/** Milliseconds per phase; phases that did not run are omitted. */
+	export type ProfileTimings = {
+		lex?: number;
+		structuralCheck?: number;
+		schemaCompile?: number;
+		schemaValidate?: number;
+		total: number;
+	};
+	export type SchemaValidationResult = {
+		valid: boolean;
+		errors?: SchemaValidationError[];
+		profile?: ProfileTimings;
+	};
+	/**
+	 * A key or array index. A plain string matches either, `{ key }` only an
+	 * object key and `{ index }` only an array element.
+	 */
+	export type PathSegment = string | { key: string } | { index: number };
+	export type LookupOptions = {
+		/**
+		 * Text of an XML element that also has child elements: the first text
+		 * node (default), the run from the first to the last non-blank text
+		 * node with the inline children, or an error.
+		 */
+		mixedContent?: "first" | "concat" | "error";
+		/**
+		 * Match to use when a JSON object repeats a key: the first (default),
+		 * the last (the one most parsers keep), or a 0-based index.
+		 */
+		occurrence?: "first" | "last" | number;
+		/**
+		 * Read JSON that holds `NaN`, `Infinity` or `-Infinity` values.
+		 * `validate` still reports them.
+		 */
+		lenient?: boolean;
+		/**
+		 * Keep the line breaks and indentation around XML element text in
+		 * its span; by default only the value itself is covered.
+		 */
+		rawText?: boolean;
+	};
+	export type UpdateOptions = LookupOptions & {
+		/**
+		 * Gap before an ENV value's trailing `# comment`, which is always kept:
+		 * as written (default), padded to keep the `#` column, or one space.
+		 */
+		commentSpacing?: "preserve" | "align" | "single";
+		/**
+		 * Skip the structural check before editing, for content that was just
+		 * validated. Content that does not tokenize still throws.
+		 */
+		skipValidation?: boolean;
 	};
 	export function update_value(
 		fileType: string,
 		originalContent: string,
-		path: string[],
-		newValue: string
+		path: PathSegment[],
+		newValue: string,
+		options?: UpdateOptions
 	): string;
+	/** JSON value at `path`, string escapes decoded; other values as written. */
+	export function get_string(content: string, path: PathSegment[]): string;
+	/** Value text at `path`; `decode` undoes the escaping `update_value` applies. */
+	export function get_value(
+		fileType: string,
+		content: string,
+		path: PathSegment[],
+		options?: { decode?: boolean } & LookupOptions
+	): string;
+	export type DiffSide = {
+		/** Value text as written in that version. */
+		value: string;
+		start: number;
+		end: number;
+	};
+	export type DiffEntry = {
+		/**
+		 * `moved`: an XML element now elsewhere among its siblings. `quoting` /
+		 * `export`: an ENV value's quotes or `export` prefix changed; the sides
+		 * of `export` cover the whole assignment.
+		 */
+		kind: "added" | "removed" | "changed" | "moved" | "quoting" | "export";
+		path: string[];
+		/** Absent for `added`. */
+		old?: DiffSide;
+		/** Absent for `removed`. */
+		new?: DiffSide;
+	};
+	/** What changed from `oldContent` to `newContent`; formatting and key order are ignored. */
+	export function diff(
+		fileType: string,
+		oldContent: string,
+		newContent: string,
+		/** Attributes that pair repeated XML siblings, tried in order. */
+		options?: { keyAttributes?: string[] }
+	): DiffEntry[];
+	export type Equality = {
+		equal: boolean;
+		/** The first path where the documents differ; absent when equal. */
+		path?: string[];
+	};
+	/** Structural comparison: formatting, escapes and ENV quoting are not differences. */
+	export function equals(
+		fileType: string,
+		a: string,
+		b: string,
+		options?: { ignoreKeyOrder?: boolean }
+	): Equality;
+	/** 16 hex digits over the parsed structure; stable across releases. */
+	export function semantic_hash(fileType: string, content: string): string;
+	/**
+	 * Deep-merges JSON overlays into `base`, later overlays winning. Unchanged values keep
+	 * the base's bytes. Arrays are replaced, concatenated, or merged item by item where
+	 * `arrayKey` (default `"id"`) matches.
+	 */
+	export function overlay(
+		base: string,
+		overlays: string[],
+		options?: { arrays?: "replace" | "concat" | "mergeByKey"; arrayKey?: string }
+	): string;
+	/**
+	 * Rewrites `content` from one format to another (`"json"`, `"xml"` or `"env"`). ENV keys
+	 * nest on `separator` (default `"__"`); JSON that is not a single-key object is wrapped in
+	 * a `root` element (default `"root"`); `inferTypes` reads numbers and booleans out of ENV
+	 * values and XML text.
+	 */
+	export function convert(
+		fromType: string,
+		toType: string,
+		content: string,
+		options?: { separator?: string; root?: string; inferTypes?: boolean; uppercase?: boolean }
+	): string;
+	/**
+	 * A JSON config as `.env` lines: nested keys joined with `separator` (default `"__"`), array
+	 * items by index, in document order; `uppercase` gives `SERVER__PORT=8080`.
+	 */
+	export function to_env(
+		content: string,
+		options?: { separator?: string; uppercase?: boolean }
+	): string;
+	/**
+	 * The nested JSON an ENV file's `separator`-delimited keys describe (default `"__"`); keys
+	 * `0`, `1`, … become arrays. `inferTypes` turns numbers and booleans into JSON ones.
+	 */
+	export function env_to_json(
+		content: string,
+		options?: { separator?: string; inferTypes?: boolean }
+	): string;
+	export type Placeholder = {
+		/** The variable name, spaces around it trimmed. */
+		name: string;
+		/** The value holding the placeholder. */
+		path: string[];
+		/** The placeholder, delimiters included. */
+		start: number;
+		end: number;
+	};
+	/**
+	 * Replaces each placeholder (`${NAME}`, or `{{ NAME }}` with `syntax: "{{}}"`) in the values
+	 * of `content` that names one of `variables`, escaped for where it lands. Other bytes stay.
+	 */
+	export function render(
+		fileType: string,
+		content: string,
+		variables: Record<string, string>,
+		options?: { syntax?: "${}" | "{{}}" }
+	): string;
+	export function list_placeholders(
+		fileType: string,
+		content: string,
+		options?: { syntax?: "${}" | "{{}}" }
+	): Placeholder[];
+	export type SecretKind = "awsAccessKey" | "privateKey" | "token" | "highEntropy";
+	export type SecretFinding = {
+		kind: SecretKind;
+		/** The value holding the secret; pass it to `redact`. */
+		path: string[];
+		line: number;
+		column: number;
+		/** The secret itself, which may be part of the value. */
+		start: number;
+		end: number;
+	};
+	/** Likely secrets in the values of `content`, located without echoing them. */
+	export function scan_secrets(fileType: string, content: string): SecretFinding[];
+	/** Replaces the value at each path with `placeholder` (default `"[REDACTED]"`). */
+	export function redact(
+		fileType: string,
+		content: string,
+		paths: string[][],
+		options?: { placeholder?: string }
+	): string;
+	export type SensitiveValue = {
+		path: string[];
+		/** Plain text or ciphertext (without the `ENC[…]` marker). */
+		value: string;
+	};
+	/** `(value, path)` to ciphertext when encrypting, or back to plain text when decrypting. */
+	export type CipherCallback = (value: string, path: string[]) => string | Promise<string>;
+	/** Encrypts the values at `paths` in place as `ENC[<ciphertext>]`, skipping encrypted ones. */
+	export function encrypt_values(
+		fileType: string,
+		content: string,
+		paths: string[][],
+		encrypt: CipherCallback
+	): Promise<string>;
+	/** Decrypts every `ENC[…]` value in place. */
+	export function decrypt_values(
+		fileType: string,
+		content: string,
+		decrypt: CipherCallback
+	): Promise<string>;
+	export function sensitive_values(
+		fileType: string,
+		content: string,
+		paths: string[][]
+	): SensitiveValue[];
+	export function encrypted_values(fileType: string, content: string): SensitiveValue[];
+	export type MigrationOp =
+		| { op: "rename"; path: PathSegment[] | string; to: string }
+		| { op: "move"; from: PathSegment[] | string; to: PathSegment[] | string }
+		| { op: "setDefault"; path: PathSegment[] | string; value: unknown }
+		| { op: "delete"; path: PathSegment[] | string }
+		| { op: "changeType"; path: PathSegment[] | string; type: "string" | "number" | "boolean" };
+	export type MigrationStep = {
+		op: MigrationOp["op"];
+		/** `from` for a move. */
+		path: string[];
+		status: "applied" | "skipped" | "failed";
+		/** Why the step was skipped or failed. */
+		message?: string;
+	};
+	/** Applies the steps in order as in-place edits (JSON and ENV); the script may be JSON text. */
+	export function migrate(
+		fileType: string,
+		content: string,
+		script: MigrationOp[] | string
+	): { content: string; steps: MigrationStep[] };
+	export type DuplicateOccurrence = {
+		file: string;
+		path: string[];
+		line: number;
+		column: number;
+		/** The value, between any quotes. */
+		start: number;
+		end: number;
+	};
+	export type DuplicateCluster = {
+		/** The last key of the path, without an XML attribute's `@`. */
+		key: string;
+		value: string;
+		occurrences: DuplicateOccurrence[];
+	};
+	/** String values set under the same key in two or more of `files`. */
+	export function find_duplicates(
+		files: { name: string; fileType: string; content: string }[]
+	): DuplicateCluster[];
+	export type MatrixRow = {
+		path: string[];
+		/** Per environment, in `envs` order; `null` where the path is missing. */
+		values: (string | null)[];
+		/** Environments without the path. */
+		missing: string[];
+		/** Set in every environment, to the same value. */
+		identical: boolean;
+	};
+	export type EnvMatrix = { envs: string[]; rows: MatrixRow[] };
+	/** One row per leaf path of the per-environment files, with its value in each. */
+	export function env_matrix(
+		fileType: string,
+		files: { env: string; content: string }[]
+	): EnvMatrix;
+	export type LintLevel = "off" | "warning" | "error";
+	export type LintRules = {
+		"key-naming"?:
+			| LintLevel
+			| [LintLevel, ("camelCase" | "PascalCase" | "snake_case" | "kebab-case" | "SCREAMING_SNAKE_CASE")?];
+		/** Default length 120. */
+		"max-line-length"?: LintLevel | [LintLevel, number?];
+		/** Not checked in JSON; the option is text the comment must contain. */
+		"header-comment"?: LintLevel | [LintLevel, string?];
+		"no-tabs"?: LintLevel | [LintLevel];
+		"no-empty-values"?: LintLevel | [LintLevel];
+		"eol-last"?: LintLevel | [LintLevel];
+		/** Quoted ENV values are left alone. */
+		"no-trailing-spaces"?: LintLevel | [LintLevel];
+		/** The option is the tab width the fix expands tabs with; 4 by default. */
+		"no-mixed-spaces-and-tabs"?: LintLevel | [LintLevel, number?];
+		/** JSON only; `"asc"` by default, or the `properties` order of a schema given as text. */
+		"sort-keys"?: LintLevel | [LintLevel, ("asc" | { schemaId: string })?];
+		/** Boolean-like strings where the registered schema expects a boolean. */
+		"no-boolean-strings"?: LintLevel | [LintLevel, { schemaId: string }];
+	};
+	export type LintDiagnostic = {
+		message: string;
+		/** The rule id, e.g. `"key-naming"`. */
+		code: string;
+		severity: "error" | "warning";
+		line: number;
+		column: number;
+		start: number;
+		end: number;
+		/** Replacing `start..end` of the content with `text` resolves the diagnostic. */
+		fix?: LintFix;
+	};
+	export type LintFix = { start: number; end: number; text: string };
+	/** Shaped like `validate_multi`; `valid` unless a diagnostic has `error` severity. */
+	export type LintResult = {
+		valid: boolean;
+		errors: LintDiagnostic[];
+		summary?: LintDiagnostic;
+	};
+	/** Runs the enabled rules; all rules are off unless listed. */
+	export function lint(fileType: string, content: string, config: { rules?: LintRules }): LintResult;
+	export type XmlInstruction = {
+		/** `"xml"` for the declaration. */
+		target: string;
+		content: string;
+		/** `name="value"` pairs; spans cover the value inside its quotes. */
+		attributes: Array<{ name: string; value: string; start: number; end: number }>;
+		start: number;
+		end: number;
+	};
+	/** Declaration and processing instructions; edit via `["?xml", "@encoding"]` paths. */
+	export function xml_instructions(content: string): XmlInstruction[];
+	export type XmlAttribute = {
+		name: string;
+		/** Value text as written; the span covers it, inside the quotes. */
+		raw: string;
+		/** Entity and character references decoded. */
+		value: string;
+		start: number;
+		end: number;
+	};
+	/** Attributes of the element at `path`; a trailing `@name` selects one. */
+	/** All attributes of the element at `path`; a trailing `"@name"` selects one, `"@*"` all. */
+	export function xml_attributes(content: string, path: PathSegment[]): XmlAttribute[];
+	export type XmlComment = {
+		placement: "before" | "inside" | "after";
+		/** Text between `<!--` and `-->`; the span covers the delimiters. */
+		text: string;
+		start: number;
+		end: number;
+	};
+	/** Comments above, inside and trailing the element at `path` (`[]` = document level). */
+	export function xml_comments(content: string, path: PathSegment[]): XmlComment[];
+	export type ResolvedEntry = {
+		key: string;
+		/** Value text as written, quotes included. */
+		raw: string;
+		/** Unquoted and unescaped; references expanded unless `expand: false`. */
+		value: string;
+		start: number;
+		end: number;
+	};
+	export type ResolvedEnv = {
+		entries: ResolvedEntry[];
+		/** Keys each entry references, in order of first use. */
+		dependencies: Record<string, string[]>;
+		/** `env.undefined_reference` / `env.reference_cycle`, in file order. */
+		diagnostics: Array<{
+			message: string;
+			code: string;
+			line: number;
+			column: number;
+			start: number;
+			end: number;
+		}>;
+	};
+	/** `${VAR}` / `$VAR` references in an ENV file, resolved against its own keys. */
+	export function resolve_env(
+		content: string,
+		options?: { expand?: boolean }
+	): ResolvedEnv;
+	/** `diff("env", ...)`: ignores order and spacing; reports quoting and `export` changes. */
+	export function diff_env(oldContent: string, newContent: string): DiffEntry[];
+	/** ENV entries sorted by key in prefix groups, each moved with the comments above it. */
+	export function organize_env(
+		content: string,
+		options?: { groupByPrefix?: boolean; blankLineBetweenGroups?: boolean }
+	): string;
+	export type EnvDefinition = {
+		file: string;
+		line: number;
+		raw: string;
+		value: string;
+		start: number;
+		end: number;
+	};
+	/** The winning definition, plus the earlier ones it overrides. */
+	export type MergedEntry = EnvDefinition & {
+		key: string;
+		overridden: EnvDefinition[];
+	};
+	/** Later files override earlier ones (`.env`, `.env.local`, `.env.production`). */
+	export function merge_env(
+		files: Array<{ name: string; content: string }>
+	): { entries: MergedEntry[] };
+	export type RenameEdit = {
+		kind: "key" | "reference";
+		line: number;
+		column: number;
+		/** In the content before the rename. */
+		start: number;
+		end: number;
+		text: string;
+	};
+	export type RenamedEnvFile = { name: string; content: string; edits: RenameEdit[] };
+	/** Renames an ENV variable and its `${OLD}` / `$OLD` references, also in `options.files`. */
+	export function rename_env_key(
+		content: string,
+		oldName: string,
+		newName: string,
+		options?: { files?: Array<{ name: string; content: string }> }
+	): { content: string; edits: RenameEdit[]; files: RenamedEnvFile[] };
+	/** Segment for a literal key: escapes a leading `@`, `\` or `[n]` with `\`. */
+	export function escape_segment(key: string): string;
+	/** String form of `{ key }`: `\` + key. */
+	export function key_segment(key: string): string;
+	/** String form of `{ index }`: `[n]`. */
+	export function index_segment(index: number): string;
+	/** Dotted form with `\.` / `\\` inside segments; `split_path` reverses it. */
+	export function join_path(path: PathSegment[]): string;
+	export function split_path(text: string): string[];
+	/** RFC 6901 JSON Pointer for `path`; `from_pointer` reverses it. */
+	export function to_pointer(path: PathSegment[]): string;
+	export function from_pointer(pointer: string): string[];
+	export type PathAtOffset = {
+		/**
+		 * Repeated XML elements carry an attribute predicate when one sets them apart.
+		 * Lookups cannot reach the JSON root, objects or arrays that are array items,
+		 * or XML elements without text of their own.
+		 */
+		path: string[];
+		/** 0-based match of `path` for the `occurrence` lookup option, when a repeated JSON key makes it match several values. */
+		occurrence?: number;
+		/** The path resolves to an earlier XML element of the same name that no attribute tells apart. */
+		ambiguous: boolean;
+		/** The key, element or attribute name, or variable name; absent for array items and the JSON root. */
+		keySpan?: { start: number; end: number };
+		valueSpan: { start: number; end: number };
+		/** Containers around the value, outermost first; empty for ENV. */
+		ancestors: Ancestor[];
+	};
+	export type Ancestor = {
+		path: string[];
+		/** As for `PathAtOffset`. */
+		occurrence?: number;
+		ambiguous: boolean;
+		kind: AncestorKind;
+		start: number;
+		end: number;
+	};
+	export type AncestorKind = "object" | "array" | "element";
+	/** The innermost value around byte `offset`, or `null` outside every value. */
+	export function path_at_offset(
+		fileType: string,
+		content: string,
+		offset: number
+	): PathAtOffset | null;
+	/** `count` is how many sibling objects have `key`. */
+	export type KeySuggestion = { key: string; count: number };
+	/** Keys from the other objects of the same array, for JSON without a schema. */
+	export function suggest_keys(content: string, parentPath: PathSegment[]): KeySuggestion[];
+	export type KeyMatch = {
+		path: string[];
+		/** Names on the path joined with `.`, what the query matched. */
+		label: string;
+		/** Higher is better; only comparable within one search. */
+		score: number;
+		keySpan: { start: number; end: number };
+		valueSpan: { start: number; end: number };
+	};
+	/** Keys matching `query` as a substring or in-order characters, best first. */
+	export function fuzzy_find(fileType: string, content: string, query: string): KeyMatch[];
+	/** An array item; `path` is the array path plus `index`, the span the whole item. */
+	export type FoundItem = { index: number; path: string[]; start: number; end: number };
+	/** The first object in the array at `arrayPath` whose `field` equals `value`. */
+	export function find_item(
+		content: string,
+		arrayPath: PathSegment[],
+		field: string,
+		value: unknown
+	): FoundItem | null;
+	export type SpanLookup = { start: number; end: number } | { error: string };
+	export function find_value_spans(
+		fileType: string,
+		content: string,
+		paths: PathSegment[][]
+	): SpanLookup[];
 	export function validate(
 		fileType: string,
-		content: string
+		content: string,
+		options?: PositionOptions
 	): {
 		valid: boolean;
 		message?: string;
@@ -30,7 +593,8 @@ declare module "../parser-wasm/pkg/parser_core.js" {
 	export function validate_multi(
 		fileType: string,
 		content: string,
-		maxErrors?: number
+		maxErrors?: number,
+		options?: MultiOptions
 	): {
 		valid: boolean;
 		errors: Array<{
@@ -40,6 +604,18 @@ declare module "../parser-wasm/pkg/parser_core.js" {
 			column: number;
 			start: number;
 			end: number;
+			/** Other places that explain the error, e.g. where an unclosed `{` opened. */
+			related?: RelatedLocation[];
+			/** With `groupByLine`: later errors on the same line. */
+			subErrors?: Array<{
+				message: string;
+				code?: string;
+				line: number;
+				column: number;
+				start: number;
+				end: number;
+				related?: RelatedLocation[];
+			}>;
 		}>;
 		summary?: {
 			message: string;
@@ -48,30 +624,257 @@ declare module "../parser-wasm/pkg/parser_core.js" {
 			start: number;
 			end: number;
 		};
+		/** Everything found, including what `maxErrors` left out of `errors`. */
+		counts: {
+			totalFound: number;
+			truncated: boolean;
+			byCode: Record<string, number>;
+		};
 	};
 	export function validate_schema(
 		content: string,
 		schema: string,
-		options?: {
-			maxErrors?: number;
-			collectPositions?: boolean;
-			draft?: string;
-		}
-	): {
-		valid: boolean;
-		errors?: SchemaValidationError[];
-	};
+		options?: SchemaValidationOptions
+	): SchemaValidationResult;
 	export function validate_schema_with_id(
 		content: string,
+		schemaId: string,
+		options?: SchemaValidationOptions
+	): SchemaValidationResult;
+	/** A `.env` file as an object of its variables, values coerced to the declared types. */
+	export function validate_env_schema(
+		content: string,
+		schema: string,
+		options?: SchemaValidationOptions
+	): SchemaValidationResult;
+	export function validate_env_schema_with_id(
+		content: string,
+		schemaId: string,
+		options?: SchemaValidationOptions
+	): SchemaValidationResult;
+	export function register_schema(schemaId: string, schema: string): void;
+	export type TextEncoding = "utf-8" | "utf-16le" | "utf-16be";
+	/** BOM-sniffed (or UTF-16-sniffed) text; `content` excludes the BOM. */
+	export function decode_bytes(bytes: Uint8Array): {
+		content: string;
+		encoding: TextEncoding;
+		bom: boolean;
+	};
+	/** Bytes to save `content` in the encoding `decode_bytes` reported. */
+	export function encode_text(
+		content: string,
+		encoding: TextEncoding,
+		bom: boolean
+	): Uint8Array;
+	/** Parsed schema plus draft; restore with `import_schema` in a later session. */
+	export function export_schema(schemaId: string): Uint8Array;
+	export function import_schema(schemaId: string, bytes: Uint8Array): void;
+	export type PropertyDoc = {
+		/** `[]` for array items, `*` for `additionalProperties`, `/pattern/` for `patternProperties`. */
+		path: string[];
+		types: string[];
+		required: boolean;
+		default?: unknown;
+		/** `description`, else `title`. */
+		description?: string;
+		/** `keyword: value`, the value as JSON. */
+		constraints: string[];
+		deprecated: boolean;
+	};
+	/** Property reference for a registered schema, as entries or a Markdown table. */
+	export function schema_docs(schemaId: string, options?: { format?: "json" }): PropertyDoc[];
+	export function schema_docs(schemaId: string, options: { format: "markdown" }): string;
+	export type UnusedKey = {
+		path: string[];
+		line: number;
+		column: number;
+		/** The member, key through value. */
+		start: number;
+		end: number;
+	};
+	/** JSON members the registered schema neither describes nor constrains. */
+	export function unused_keys(content: string, schemaId: string): UnusedKey[];
+	/**
+	 * A new document from a registered schema: defaults, examples or first enum
+	 * options, else empty values, for each required property.
+	 */
+	export function instantiate_schema(
 		schemaId: string,
 		options?: {
+			/** `"json"` by default. */
+			fileType?: string;
+			/** Fill in properties the schema does not require too. */
+			includeOptional?: boolean;
+			separator?: string;
+			root?: string;
+			uppercase?: boolean;
+		}
+	): string;
+	export class Document {
+		constructor(fileType: string, content: string);
+		readonly content: string;
+		set_content(content: string): void;
+		/** Saves the content in the handle; returns the id. The 64 most recent are kept. */
+		snapshot(): number;
+		/** `diff` from snapshot `id` to the current content. */
+		diff_since(id: number): DiffEntry[];
+		/** Puts snapshot `id` back as the content; snapshots stay available. */
+		restore(id: number): void;
+		/** Appends streamed input; start from `new Document(type, "")`. */
+		feed(chunk: string): void;
+		/** Ends a `feed` sequence; same result as `validate()`. */
+		finish(): ReturnType<typeof validate>;
+		validate(options?: PositionOptions): ReturnType<typeof validate>;
+		validate_multi(
+			maxErrors?: number,
+			options?: MultiOptions
+		): ReturnType<typeof validate_multi>;
+		find_value_span(
+			path: PathSegment[],
+			options?: LookupOptions
+		): { start: number; end: number };
+		find_value_spans(paths: PathSegment[][]): SpanLookup[];
+		/** JSON documents only; see `get_string`. */
+		get_string(path: PathSegment[]): string;
+		get_value(path: PathSegment[], options?: { decode?: boolean } & LookupOptions): string;
+		update_value(path: PathSegment[], newValue: string, options?: UpdateOptions): string;
+		validate_schema_with_id(
+			schemaId: string,
+			options?: Parameters<typeof validate_schema_with_id>[2]
+		): ReturnType<typeof validate_schema_with_id>;
+		offset_to_position(offset: number): { line: number; column: number };
+		position_to_offset(line: number, column: number): number;
+		next_tokens(max: number): {
+			tokens: Array<{
+				kind:
+					| "lbrace"
+					| "rbrace"
+					| "lbrack"
+					| "rbrack"
+					| "colon"
+					| "comma"
+					| "string"
+					| "number"
+					| "true"
+					| "false"
+					| "null";
+				start: number;
+				end: number;
+			}>;
+			done: boolean;
+		};
+		reset_tokens(): void;
+		validate_chunked(
+			chunkSize: number,
+			onProgress: (processed: number, total: number) => boolean | void
+		):
+			| (ReturnType<typeof validate> & { cancelled: false })
+			| { cancelled: true; processed: number; total: number };
+		free(): void;
+	}
+	export function validate_schema_all(
+		content: string,
+		schemaIds: string[],
+		options?: SchemaValidationOptions
+	): SchemaValidationResult;
+	/** `AbortSignal` or any object with an `aborted` flag. */
+	export type AbortToken = { readonly aborted: boolean };
+	/** Rejection of an aborted `*_async` call. */
+	export type AbortedError = ParserError & { name: "AbortError"; code: "aborted" };
+	export type AsyncProgress = (processed: number, total: number) => void;
+	/** Yields to the event loop every `chunkSize` bytes (64 KiB by default). */
+	export function validate_async(
+		fileType: string,
+		content: string,
+		options?: PositionOptions & {
+			chunkSize?: number;
+			signal?: AbortToken;
+			onProgress?: AsyncProgress;
+		}
+	): Promise<ReturnType<typeof validate>>;
+	/** One file per step; results keep input order. */
+	export function validate_batch_async(
+		files: Array<{ fileType: string; content: string }>,
+		options?: MultiOptions & {
 			maxErrors?: number;
-			collectPositions?: boolean;
-			draft?: string;
+			signal?: AbortToken;
+			onProgress?: AsyncProgress;
 		}
-	): {
-		valid: boolean;
-		errors?: SchemaValidationError[];
-	};
-	export function register_schema(schemaId: string, schema: string): void;
+	): Promise<Array<ReturnType<typeof validate_multi>>>;
+	export function register_schema_async(
+		schemaId: string,
+		schema: string,
+		options?: { signal?: AbortToken }
+	): Promise<void>;
+	export function validate_schema_async(
+		content: string,
+		schema: string,
+		options?: SchemaValidationOptions & { signal?: AbortToken }
+	): Promise<SchemaValidationResult>;
+	/**
+	 * JSON-string variants: `JSON.parse(result)` matches the object-returning
+	 * export. Cheaper to transfer for large results and to `postMessage`.
+	 */
+	export function validate_json(
+		fileType: string,
+		content: string,
+		options?: PositionOptions
+	): string;
+	export function validate_multi_json(
+		fileType: string,
+		content: string,
+		maxErrors?: number,
+		options?: MultiOptions
+	): string;
+	export function find_value_spans_json(
+		fileType: string,
+		content: string,
+		paths: string[][]
+	): string;
+	export function validate_schema_json(
+		content: string,
+		schema: string,
+		options?: SchemaValidationOptions
+	): string;
+	export function validate_schema_with_id_json(
+		content: string,
+		schemaId: string,
+		options?: SchemaValidationOptions
+	): string;
+	export function validate_schema_all_json(
+		content: string,
+		schemaIds: string[],
+		options?: SchemaValidationOptions
+	): string;
+	/**
+	 * `*_json` results as UTF-8 in a fresh `ArrayBuffer`: list it as a
+	 * transferable in `postMessage`, then
+	 * `JSON.parse(new TextDecoder().decode(buffer))` on the receiving side.
+	 */
+	export function validate_multi_buffer(
+		fileType: string,
+		content: string,
+		maxErrors?: number,
+		options?: MultiOptions
+	): ArrayBuffer;
+	export function find_value_spans_buffer(
+		fileType: string,
+		content: string,
+		paths: PathSegment[][]
+	): ArrayBuffer;
+	export function validate_schema_buffer(
+		content: string,
+		schema: string,
+		options?: SchemaValidationOptions
+	): ArrayBuffer;
+	export function validate_schema_with_id_buffer(
+		content: string,
+		schemaId: string,
+		options?: SchemaValidationOptions
+	): ArrayBuffer;
+	export function validate_schema_all_buffer(
+		content: string,
+		schemaIds: string[],
+		options?: SchemaValidationOptions
+	): ArrayBuffer;
 }